@@ -0,0 +1,73 @@
+/*
+ * C bindings for lsp-cli.
+ *
+ * Extraction and query functions return JSON strings in the same schema as the CLI output
+ * (see llms.md). The library runs `lsp-cli serve --stdio` as a child process and talks
+ * JSON-RPC to it, so the language servers stay warm between calls; hosts only see this API.
+ *
+ * All returned strings are heap-allocated and must be released with lsp_cli_free_string().
+ * A NULL return means the call failed; lsp_cli_last_error() describes why.
+ * A handle must not be used from several threads at the same time.
+ * If the lsp-cli process dies, calls fail with an error; SIGPIPE is blocked while writing to it, so it never
+ * reaches the host.
+ */
+#ifndef LSP_CLI_H
+#define LSP_CLI_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct lsp_cli lsp_cli_t;
+
+/*
+ * Starts an lsp-cli session.
+ *
+ * executable: path of the lsp-cli executable, or NULL to look up "lsp-cli" on PATH.
+ * Returns NULL if the process could not be started.
+ */
+lsp_cli_t *lsp_cli_open(const char *executable);
+
+/*
+ * Extracts all symbols of a workspace.
+ *
+ * Returns {"snapshotId", "language", "directory", "symbols"}; pass snapshotId to lsp_cli_query().
+ */
+char *lsp_cli_analyze(lsp_cli_t *cli, const char *directory, const char *language);
+
+/*
+ * Queries a snapshot. name, kind and file are optional filters (NULL to skip) that must all match.
+ *
+ * Returns {"language", "directory", "symbols"}.
+ */
+char *lsp_cli_query(lsp_cli_t *cli, const char *snapshot_id, const char *name, const char *kind, const char *file);
+
+/*
+ * Resolves the definition of the symbol at a 0-based position. file may be relative to directory.
+ *
+ * Returns {"locations": [{"file", "range", "preview"}]}.
+ */
+char *lsp_cli_definition(lsp_cli_t *cli, const char *directory, const char *language, const char *file,
+                         unsigned int line, unsigned int character);
+
+/*
+ * Finds all references to the symbol at a 0-based position. file may be relative to directory.
+ *
+ * Returns {"locations": [{"file", "range", "preview"}]}.
+ */
+char *lsp_cli_references(lsp_cli_t *cli, const char *directory, const char *language, const char *file,
+                         unsigned int line, unsigned int character, int include_declaration);
+
+/* Message of the last failed call on this handle, or "" if none failed. Owned by the handle. */
+const char *lsp_cli_last_error(const lsp_cli_t *cli);
+
+void lsp_cli_free_string(char *str);
+
+/* Shuts down the language servers and the lsp-cli process, then frees the handle. */
+void lsp_cli_close(lsp_cli_t *cli);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LSP_CLI_H */
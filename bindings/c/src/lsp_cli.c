@@ -0,0 +1,477 @@
+/*
+ * C bindings for lsp-cli: a JSON-RPC client for `lsp-cli serve --stdio`.
+ * See include/lsp_cli.h for the API contract.
+ */
+#define _POSIX_C_SOURCE 200809L
+
+#include "lsp_cli.h"
+
+#include <errno.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/types.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+struct lsp_cli {
+    pid_t pid;
+    int to_child;
+    FILE *from_child;
+    long next_id;
+    char error[512];
+};
+
+struct buffer {
+    char *data;
+    size_t len;
+    size_t cap;
+};
+
+static void set_error(lsp_cli_t *cli, const char *format, ...) {
+    va_list args;
+    va_start(args, format);
+    vsnprintf(cli->error, sizeof(cli->error), format, args);
+    va_end(args);
+}
+
+static int buffer_append(struct buffer *buf, const char *data, size_t len) {
+    if (buf->len + len + 1 > buf->cap) {
+        size_t cap = buf->cap ? buf->cap : 256;
+        while (buf->len + len + 1 > cap) {
+            cap *= 2;
+        }
+        char *grown = realloc(buf->data, cap);
+        if (!grown) {
+            return -1;
+        }
+        buf->data = grown;
+        buf->cap = cap;
+    }
+    memcpy(buf->data + buf->len, data, len);
+    buf->len += len;
+    buf->data[buf->len] = '\0';
+    return 0;
+}
+
+static int buffer_append_str(struct buffer *buf, const char *str) {
+    return buffer_append(buf, str, strlen(str));
+}
+
+/* Appends "key": "value" (with a leading comma unless first), skipping NULL values. */
+static int buffer_append_member(struct buffer *buf, const char *key, const char *value) {
+    if (!value) {
+        return 0;
+    }
+    if (buf->len > 1 && buffer_append_str(buf, ",") != 0) {
+        return -1;
+    }
+    if (buffer_append_str(buf, "\"") != 0 || buffer_append_str(buf, key) != 0 || buffer_append_str(buf, "\":\"") != 0) {
+        return -1;
+    }
+    for (const unsigned char *p = (const unsigned char *)value; *p; p++) {
+        char escaped[8];
+        if (*p == '"' || *p == '\\') {
+            snprintf(escaped, sizeof(escaped), "\\%c", *p);
+        } else if (*p < 0x20) {
+            snprintf(escaped, sizeof(escaped), "\\u%04x", *p);
+        } else {
+            escaped[0] = (char)*p;
+            escaped[1] = '\0';
+        }
+        if (buffer_append_str(buf, escaped) != 0) {
+            return -1;
+        }
+    }
+    return buffer_append_str(buf, "\"");
+}
+
+static int buffer_append_number(struct buffer *buf, const char *key, long value) {
+    char member[96];
+    snprintf(member, sizeof(member), "%s\"%s\":%ld", buf->len > 1 ? "," : "", key, value);
+    return buffer_append_str(buf, member);
+}
+
+/* --- Minimal JSON scanning, enough to pick members out of a response ---------------------------- */
+
+static const char *skip_ws(const char *p) {
+    while (*p == ' ' || *p == '\t' || *p == '\n' || *p == '\r') {
+        p++;
+    }
+    return p;
+}
+
+static const char *skip_string(const char *p) {
+    for (p++; *p; p++) {
+        if (*p == '\\' && p[1]) {
+            p++;
+        } else if (*p == '"') {
+            return p + 1;
+        }
+    }
+    return NULL;
+}
+
+/* Returns the position right after the JSON value starting at p, or NULL if malformed. */
+static const char *skip_value(const char *p) {
+    p = skip_ws(p);
+    if (*p == '"') {
+        return skip_string(p);
+    }
+    if (*p == '{' || *p == '[') {
+        int depth = 0;
+        while (*p) {
+            if (*p == '"') {
+                p = skip_string(p);
+                if (!p) {
+                    return NULL;
+                }
+                continue;
+            }
+            if (*p == '{' || *p == '[') {
+                depth++;
+            } else if (*p == '}' || *p == ']') {
+                if (--depth == 0) {
+                    return p + 1;
+                }
+            }
+            p++;
+        }
+        return NULL;
+    }
+    while (*p && *p != ',' && *p != '}' && *p != ']' && *p != ' ' && *p != '\n' && *p != '\r' && *p != '\t') {
+        p++;
+    }
+    return p;
+}
+
+/* Finds a member of the JSON object at p; sets *end to the end of its value. */
+static const char *find_member(const char *p, const char *key, const char **end) {
+    size_t key_len = strlen(key);
+    p = skip_ws(p);
+    if (*p != '{') {
+        return NULL;
+    }
+    p++;
+    while (1) {
+        p = skip_ws(p);
+        if (*p != '"') {
+            return NULL;
+        }
+        const char *key_start = p + 1;
+        const char *key_end = skip_string(p);
+        if (!key_end) {
+            return NULL;
+        }
+        p = skip_ws(key_end);
+        if (*p != ':') {
+            return NULL;
+        }
+        const char *value = skip_ws(p + 1);
+        const char *value_end = skip_value(value);
+        if (!value_end) {
+            return NULL;
+        }
+        if ((size_t)(key_end - 1 - key_start) == key_len && strncmp(key_start, key, key_len) == 0) {
+            *end = value_end;
+            return value;
+        }
+        p = skip_ws(value_end);
+        if (*p != ',') {
+            return NULL;
+        }
+        p++;
+    }
+}
+
+/* --- Transport ----------------------------------------------------------------------------------- */
+
+/*
+ * Writes all of data to the child. SIGPIPE is blocked meanwhile, so a dead child fails the write with
+ * EPIPE instead of killing the host; a SIGPIPE raised by the write is consumed before unblocking.
+ */
+static int write_all(lsp_cli_t *cli, const char *data, size_t len) {
+    sigset_t pipe_set;
+    sigset_t old_set;
+    sigset_t pending;
+    sigemptyset(&pipe_set);
+    sigaddset(&pipe_set, SIGPIPE);
+    pthread_sigmask(SIG_BLOCK, &pipe_set, &old_set);
+    sigpending(&pending);
+    int was_pending = sigismember(&pending, SIGPIPE);
+
+    int result = 0;
+    while (len > 0) {
+        ssize_t written = write(cli->to_child, data, len);
+        if (written < 0) {
+            if (errno == EINTR) {
+                continue;
+            }
+            result = errno;
+            break;
+        }
+        data += written;
+        len -= (size_t)written;
+    }
+
+    if (result == EPIPE && !was_pending) {
+        const struct timespec no_wait = {0, 0};
+        while (sigtimedwait(&pipe_set, NULL, &no_wait) < 0 && errno == EINTR) {
+        }
+    }
+    pthread_sigmask(SIG_SETMASK, &old_set, NULL);
+    errno = result;
+    return result == 0 ? 0 : -1;
+}
+
+static char *read_message(lsp_cli_t *cli) {
+    char line[256];
+    long length = -1;
+
+    while (fgets(line, sizeof(line), cli->from_child)) {
+        if (strcmp(line, "\r\n") == 0) {
+            break;
+        }
+        if (strncmp(line, "Content-Length:", 15) == 0) {
+            length = strtol(line + 15, NULL, 10);
+        }
+    }
+
+    if (length < 0) {
+        set_error(cli, "lsp-cli closed the connection");
+        return NULL;
+    }
+
+    char *body = malloc((size_t)length + 1);
+    if (!body) {
+        set_error(cli, "Out of memory");
+        return NULL;
+    }
+    if (fread(body, 1, (size_t)length, cli->from_child) != (size_t)length) {
+        free(body);
+        set_error(cli, "Truncated response from lsp-cli");
+        return NULL;
+    }
+    body[length] = '\0';
+    return body;
+}
+
+/* Reads messages until the response with the given id, skipping notifications and other responses. */
+static char *read_response(lsp_cli_t *cli, long id) {
+    while (1) {
+        char *message = read_message(cli);
+        if (!message) {
+            return NULL;
+        }
+        const char *end = NULL;
+        const char *value = find_member(message, "id", &end);
+        /* Only one request is in flight, so an error without id (a null id) is about it too */
+        if (value && (*value == 'n' || strtol(value, NULL, 10) == id)) {
+            return message;
+        }
+        free(message);
+    }
+}
+
+/* Sends a request with the given params object and returns a copy of its "result" member. */
+static char *request(lsp_cli_t *cli, const char *method, const char *params) {
+    cli->error[0] = '\0';
+    long id = cli->next_id++;
+
+    struct buffer message = {0};
+    char header[128];
+    snprintf(header, sizeof(header), "{\"jsonrpc\":\"2.0\",\"id\":%ld,\"method\":\"%s\",\"params\":", id, method);
+    if (buffer_append_str(&message, header) != 0 || buffer_append_str(&message, params) != 0 ||
+        buffer_append_str(&message, "}") != 0) {
+        free(message.data);
+        set_error(cli, "Out of memory");
+        return NULL;
+    }
+
+    char framing[64];
+    snprintf(framing, sizeof(framing), "Content-Length: %zu\r\n\r\n", message.len);
+    int failed = write_all(cli, framing, strlen(framing)) != 0 || write_all(cli, message.data, message.len) != 0;
+    free(message.data);
+    if (failed) {
+        set_error(cli, "Failed to write to lsp-cli: %s", strerror(errno));
+        return NULL;
+    }
+
+    char *response = read_response(cli, id);
+    if (!response) {
+        return NULL;
+    }
+
+    const char *end = NULL;
+    const char *error = find_member(response, "error", &end);
+    if (error) {
+        const char *message_end = NULL;
+        const char *text = find_member(error, "message", &message_end);
+        if (text && *text == '"') {
+            set_error(cli, "%.*s", (int)(message_end - text - 2), text + 1);
+        } else {
+            set_error(cli, "%.*s", (int)(end - error), error);
+        }
+        free(response);
+        return NULL;
+    }
+
+    const char *result = find_member(response, "result", &end);
+    if (!result) {
+        set_error(cli, "Malformed response from lsp-cli");
+        free(response);
+        return NULL;
+    }
+
+    size_t len = (size_t)(end - result);
+    char *copy = malloc(len + 1);
+    if (copy) {
+        memcpy(copy, result, len);
+        copy[len] = '\0';
+    } else {
+        set_error(cli, "Out of memory");
+    }
+    free(response);
+    return copy;
+}
+
+/* Builds the params object with the given members and sends the request. */
+static char *request_with(lsp_cli_t *cli, const char *method, struct buffer *params) {
+    if (!params->data || buffer_append_str(params, "}") != 0) {
+        free(params->data);
+        set_error(cli, "Out of memory");
+        return NULL;
+    }
+    char *result = request(cli, method, params->data);
+    free(params->data);
+    return result;
+}
+
+/* --- Public API ---------------------------------------------------------------------------------- */
+
+lsp_cli_t *lsp_cli_open(const char *executable) {
+    int to_child[2];
+    int from_child[2];
+
+    if (pipe(to_child) != 0) {
+        return NULL;
+    }
+    if (pipe(from_child) != 0) {
+        close(to_child[0]);
+        close(to_child[1]);
+        return NULL;
+    }
+
+    pid_t pid = fork();
+    if (pid < 0) {
+        close(to_child[0]);
+        close(to_child[1]);
+        close(from_child[0]);
+        close(from_child[1]);
+        return NULL;
+    }
+
+    if (pid == 0) {
+        dup2(to_child[0], STDIN_FILENO);
+        dup2(from_child[1], STDOUT_FILENO);
+        close(to_child[0]);
+        close(to_child[1]);
+        close(from_child[0]);
+        close(from_child[1]);
+        const char *program = executable ? executable : "lsp-cli";
+        execlp(program, program, "serve", "--stdio", (char *)NULL);
+        _exit(127);
+    }
+
+    close(to_child[0]);
+    close(from_child[1]);
+
+    lsp_cli_t *cli = calloc(1, sizeof(lsp_cli_t));
+    if (cli) {
+        cli->from_child = fdopen(from_child[0], "r");
+    }
+    if (!cli || !cli->from_child) {
+        close(to_child[1]);
+        if (cli && cli->from_child) {
+            fclose(cli->from_child);
+        } else {
+            close(from_child[0]);
+        }
+        free(cli);
+        kill(pid, SIGTERM);
+        waitpid(pid, NULL, 0);
+        return NULL;
+    }
+
+    cli->pid = pid;
+    cli->to_child = to_child[1];
+    cli->next_id = 1;
+    return cli;
+}
+
+char *lsp_cli_analyze(lsp_cli_t *cli, const char *directory, const char *language) {
+    struct buffer params = {0};
+    buffer_append_str(&params, "{");
+    buffer_append_member(&params, "directory", directory);
+    buffer_append_member(&params, "language", language);
+    return request_with(cli, "analyze", &params);
+}
+
+char *lsp_cli_query(lsp_cli_t *cli, const char *snapshot_id, const char *name, const char *kind, const char *file) {
+    struct buffer params = {0};
+    buffer_append_str(&params, "{");
+    buffer_append_member(&params, "snapshotId", snapshot_id);
+    buffer_append_member(&params, "name", name);
+    buffer_append_member(&params, "kind", kind);
+    buffer_append_member(&params, "file", file);
+    return request_with(cli, "query", &params);
+}
+
+static void append_position_params(struct buffer *params, const char *directory, const char *language,
+                                   const char *file, unsigned int line, unsigned int character) {
+    buffer_append_str(params, "{");
+    buffer_append_member(params, "directory", directory);
+    buffer_append_member(params, "language", language);
+    buffer_append_member(params, "file", file);
+    buffer_append_number(params, "line", (long)line);
+    buffer_append_number(params, "character", (long)character);
+}
+
+char *lsp_cli_definition(lsp_cli_t *cli, const char *directory, const char *language, const char *file,
+                         unsigned int line, unsigned int character) {
+    struct buffer params = {0};
+    append_position_params(&params, directory, language, file, line, character);
+    return request_with(cli, "definition", &params);
+}
+
+char *lsp_cli_references(lsp_cli_t *cli, const char *directory, const char *language, const char *file,
+                         unsigned int line, unsigned int character, int include_declaration) {
+    struct buffer params = {0};
+    append_position_params(&params, directory, language, file, line, character);
+    buffer_append_str(&params, include_declaration ? ",\"includeDeclaration\":true" : ",\"includeDeclaration\":false");
+    return request_with(cli, "references", &params);
+}
+
+const char *lsp_cli_last_error(const lsp_cli_t *cli) {
+    return cli->error;
+}
+
+void lsp_cli_free_string(char *str) {
+    free(str);
+}
+
+void lsp_cli_close(lsp_cli_t *cli) {
+    if (!cli) {
+        return;
+    }
+    /* Fails without harm if lsp-cli already exited */
+    free(request(cli, "shutdown", "{}"));
+    close(cli->to_child);
+    fclose(cli->from_child);
+    waitpid(cli->pid, NULL, 0);
+    free(cli);
+}
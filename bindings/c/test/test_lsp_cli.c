@@ -0,0 +1,133 @@
+/*
+ * Tests of the C bindings against a fake `lsp-cli serve --stdio`: this program itself, which acts as the
+ * server when started with "serve". FAKE_LSP_CLI=exit makes the server exit before reading anything.
+ */
+#define _POSIX_C_SOURCE 200809L
+
+#include "lsp_cli.h"
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+
+static int failures = 0;
+
+#define CHECK(condition)                                                                   \
+    do {                                                                                   \
+        if (!(condition)) {                                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            failures++;                                                                    \
+        }                                                                                  \
+    } while (0)
+
+/* --- Fake server ---------------------------------------------------------------------------------- */
+
+static void send_message(const char *body) {
+    printf("Content-Length: %zu\r\n\r\n%s", strlen(body), body);
+    fflush(stdout);
+}
+
+/* Answers each request after a notification and a response to another request, as a busy server may. */
+static int serve(void) {
+    const char *mode = getenv("FAKE_LSP_CLI");
+    if (mode && strcmp(mode, "exit") == 0) {
+        return 0;
+    }
+
+    char line[256];
+    while (1) {
+        long length = -1;
+        while (fgets(line, sizeof(line), stdin)) {
+            if (strcmp(line, "\r\n") == 0) {
+                break;
+            }
+            if (strncmp(line, "Content-Length:", 15) == 0) {
+                length = strtol(line + 15, NULL, 10);
+            }
+        }
+        if (length < 0) {
+            return 0;
+        }
+        char *body = calloc((size_t)length + 1, 1);
+        if (!body || fread(body, 1, (size_t)length, stdin) != (size_t)length) {
+            return 1;
+        }
+
+        long id = strtol(strstr(body, "\"id\":") + 5, NULL, 10);
+        char response[512];
+        send_message("{\"jsonrpc\":\"2.0\",\"method\":\"$/progress\",\"params\":{\"message\":\"indexing\"}}");
+        snprintf(response, sizeof(response), "{\"jsonrpc\":\"2.0\",\"id\":%ld,\"result\":\"stale\"}", id + 100);
+        send_message(response);
+
+        if (strstr(body, "\"method\":\"definition\"")) {
+            snprintf(response, sizeof(response),
+                     "{\"jsonrpc\":\"2.0\",\"id\":%ld,\"error\":{\"code\":-32602,\"message\":\"No such file\"}}", id);
+        } else if (strstr(body, "\"method\":\"shutdown\"")) {
+            snprintf(response, sizeof(response), "{\"jsonrpc\":\"2.0\",\"id\":%ld,\"result\":null}", id);
+        } else {
+            snprintf(response, sizeof(response),
+                     "{\"jsonrpc\":\"2.0\",\"id\":%ld,\"result\":{\"snapshotId\":\"snap-%ld\",\"symbols\":[]}}", id, id);
+        }
+        send_message(response);
+
+        int shutdown = strstr(body, "\"method\":\"shutdown\"") != NULL;
+        free(body);
+        if (shutdown) {
+            return 0;
+        }
+    }
+}
+
+/* --- Tests ---------------------------------------------------------------------------------------- */
+
+static void test_matches_responses_by_id(const char *executable) {
+    setenv("FAKE_LSP_CLI", "serve", 1);
+    lsp_cli_t *cli = lsp_cli_open(executable);
+    CHECK(cli != NULL);
+
+    char *first = lsp_cli_analyze(cli, "/project", "c");
+    CHECK(first != NULL && strcmp(first, "{\"snapshotId\":\"snap-1\",\"symbols\":[]}") == 0);
+    lsp_cli_free_string(first);
+
+    char *second = lsp_cli_query(cli, "snap-1", "main", NULL, NULL);
+    CHECK(second != NULL && strstr(second, "snap-2") != NULL);
+    lsp_cli_free_string(second);
+
+    CHECK(lsp_cli_definition(cli, "/project", "c", "missing.c", 0, 0) == NULL);
+    CHECK(strcmp(lsp_cli_last_error(cli), "No such file") == 0);
+
+    lsp_cli_close(cli);
+}
+
+static void test_survives_exited_server(const char *executable) {
+    setenv("FAKE_LSP_CLI", "exit", 1);
+    lsp_cli_t *cli = lsp_cli_open(executable);
+    CHECK(cli != NULL);
+
+    /* The first write may still fit in the pipe; the read then sees the closed connection */
+    CHECK(lsp_cli_analyze(cli, "/project", "c") == NULL);
+    /* Give the server time to exit fully; then the write fails with EPIPE instead of raising SIGPIPE */
+    const struct timespec exit_time = {0, 100 * 1000 * 1000};
+    nanosleep(&exit_time, NULL);
+    CHECK(lsp_cli_analyze(cli, "/project", "c") == NULL);
+    CHECK(strlen(lsp_cli_last_error(cli)) > 0);
+
+    lsp_cli_close(cli);
+}
+
+int main(int argc, char **argv) {
+    if (argc > 1 && strcmp(argv[1], "serve") == 0) {
+        return serve();
+    }
+
+    test_matches_responses_by_id(argv[0]);
+    test_survives_exited_server(argv[0]);
+
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("All C binding tests passed\n");
+    return 0;
+}
@@ -0,0 +1,20 @@
+"""
+Python bindings for lsp-cli.
+
+Runs `lsp-cli serve --stdio` as a subprocess and exposes its JSON-RPC methods as typed calls.
+The lsp-cli executable must be installed (npm install -g @mariozechner/lsp-cli).
+"""
+
+from ._client import Client, LspCliError, analyze
+from ._types import AnalysisResult, Location, Position, Range, Symbol
+
+__all__ = [
+    "AnalysisResult",
+    "Client",
+    "Location",
+    "LspCliError",
+    "Position",
+    "Range",
+    "Symbol",
+    "analyze",
+]
@@ -0,0 +1,170 @@
+"""JSON-RPC client for `lsp-cli serve --stdio`."""
+
+import json
+import subprocess
+import threading
+from typing import Any, Dict, Iterator, List, Optional
+
+from ._types import AnalysisResult, Location, Symbol
+
+
+class LspCliError(Exception):
+    """Raised when lsp-cli rejects a request or the process goes away."""
+
+    def __init__(self, message: str, code: Optional[int] = None) -> None:
+        super().__init__(message)
+        self.code = code
+
+
+class Client:
+    """
+    A persistent lsp-cli session.
+
+    Starts `lsp-cli serve --stdio` once and keeps its language servers warm, so repeated
+    analyses and position queries on the same workspace are fast. Use as a context manager
+    or call close() when done. Calls are serialized; a client may be shared between threads.
+    """
+
+    def __init__(self, executable: str = "lsp-cli", verbose: bool = False) -> None:
+        """
+        :param executable: lsp-cli executable name or path
+        :param verbose: pass -v to lsp-cli (debug logs go to this process' stderr)
+        """
+        args = [executable, "serve", "--stdio"] + (["-v"] if verbose else [])
+        self._process = subprocess.Popen(args, stdin=subprocess.PIPE, stdout=subprocess.PIPE)
+        self._lock = threading.Lock()
+        self._next_id = 1
+
+    def analyze(self, path: str, language: str) -> AnalysisResult:
+        """
+        Extracts all symbols of a workspace.
+
+        :param path: workspace root directory
+        :param language: one of java, cpp, c, csharp, haxe, typescript, dart, rust, python
+        :return: the analysis result; its snapshot_id can be passed to query()
+        """
+        return AnalysisResult.from_json(self._request("analyze", {"directory": path, "language": language}))
+
+    def iter_symbols(self, path: str, language: str) -> Iterator[Symbol]:
+        """
+        Analyzes a workspace and yields every symbol, nested children included, depth-first.
+
+        Symbols are converted to Python objects one at a time, so large results don't have to be
+        materialized as a tree of objects at once.
+        """
+        result = self._request("analyze", {"directory": path, "language": language})
+        stack = list(reversed(result["symbols"]))
+        while stack:
+            data = stack.pop()
+            symbol = Symbol.from_json({key: value for key, value in data.items() if key != "children"})
+            stack.extend(reversed(data.get("children") or []))
+            yield symbol
+
+    def query(
+        self,
+        snapshot_id: str,
+        name: Optional[str] = None,
+        kind: Optional[str] = None,
+        file: Optional[str] = None,
+    ) -> AnalysisResult:
+        """Returns the symbols of a snapshot matching all given filters (nested symbols included)."""
+        params = {"snapshotId": snapshot_id, "name": name, "kind": kind, "file": file}
+        return AnalysisResult.from_json(
+            self._request("query", {key: value for key, value in params.items() if value is not None})
+        )
+
+    def definition(self, path: str, language: str, file: str, line: int, character: int) -> List[Location]:
+        """Resolves the definition of the symbol at a 0-based position; file may be relative to path."""
+        result = self._request(
+            "definition",
+            {"directory": path, "language": language, "file": file, "line": line, "character": character},
+        )
+        return [Location.from_json(location) for location in result["locations"]]
+
+    def references(
+        self,
+        path: str,
+        language: str,
+        file: str,
+        line: int,
+        character: int,
+        include_declaration: bool = True,
+    ) -> List[Location]:
+        """Finds all references to the symbol at a 0-based position; file may be relative to path."""
+        result = self._request(
+            "references",
+            {
+                "directory": path,
+                "language": language,
+                "file": file,
+                "line": line,
+                "character": character,
+                "includeDeclaration": include_declaration,
+            },
+        )
+        return [Location.from_json(location) for location in result["locations"]]
+
+    def close(self) -> None:
+        """Shuts down the language servers and the lsp-cli process."""
+        if self._process.poll() is None:
+            try:
+                self._request("shutdown", {})
+            except LspCliError:
+                pass
+        try:
+            self._process.stdin.close()
+        except BrokenPipeError:
+            # Unsent data of a request to a process that had already exited
+            pass
+        self._process.wait()
+        self._process.stdout.close()
+
+    def __enter__(self) -> "Client":
+        return self
+
+    def __exit__(self, *exc_info: Any) -> None:
+        self.close()
+
+    def _request(self, method: str, params: Dict[str, Any]) -> Any:
+        with self._lock:
+            request_id = self._next_id
+            self._next_id += 1
+
+            body = json.dumps({"jsonrpc": "2.0", "id": request_id, "method": method, "params": params}).encode()
+            try:
+                self._process.stdin.write(b"Content-Length: %d\r\n\r\n" % len(body) + body)
+                self._process.stdin.flush()
+            except BrokenPipeError as error:
+                raise LspCliError("lsp-cli process is not running") from error
+
+            # Skip notifications and responses to other requests, e.g. progress reports
+            response = self._read_message()
+            while response.get("id") not in (request_id, None) or "method" in response:
+                response = self._read_message()
+            if "error" in response:
+                raise LspCliError(response["error"].get("message", "Unknown error"), response["error"].get("code"))
+            return response.get("result")
+
+    def _read_message(self) -> Dict[str, Any]:
+        length = None
+        while True:
+            line = self._process.stdout.readline()
+            if not line:
+                raise LspCliError("lsp-cli closed the connection")
+            if line == b"\r\n":
+                break
+            if line.lower().startswith(b"content-length:"):
+                length = int(line.split(b":", 1)[1])
+        if length is None:
+            raise LspCliError("Malformed response from lsp-cli: missing Content-Length")
+        return json.loads(self._process.stdout.read(length))
+
+
+def analyze(path: str, language: str, executable: str = "lsp-cli") -> AnalysisResult:
+    """
+    One-shot extraction: starts lsp-cli, analyzes the workspace and shuts everything down again.
+
+    Use Client directly to keep language servers running across several calls.
+    """
+    with Client(executable) as client:
+        return client.analyze(path, language)
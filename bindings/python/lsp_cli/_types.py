@@ -0,0 +1,112 @@
+"""Typed views of the lsp-cli output schema (see llms.md)."""
+
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterator, List, Optional
+
+
+@dataclass(frozen=True)
+class Position:
+    """0-based line/character position."""
+
+    line: int
+    character: int
+
+    @classmethod
+    def from_json(cls, data: Dict[str, Any]) -> "Position":
+        return cls(line=data["line"], character=data["character"])
+
+
+@dataclass(frozen=True)
+class Range:
+    start: Position
+    end: Position
+
+    @classmethod
+    def from_json(cls, data: Dict[str, Any]) -> "Range":
+        return cls(start=Position.from_json(data["start"]), end=Position.from_json(data["end"]))
+
+
+@dataclass(frozen=True)
+class Location:
+    file: str
+    range: Range
+    preview: Optional[str] = None
+
+    @classmethod
+    def from_json(cls, data: Dict[str, Any]) -> "Location":
+        return cls(file=data["file"], range=Range.from_json(data["range"]), preview=data.get("preview"))
+
+
+_TYPED_FIELDS = {
+    "name",
+    "kind",
+    "file",
+    "range",
+    "preview",
+    "documentation",
+    "comments",
+    "supertypes",
+    "children",
+    "definition",
+}
+
+
+@dataclass
+class Symbol:
+    name: str
+    kind: str
+    file: str
+    range: Range
+    preview: str
+    documentation: Optional[str] = None
+    comments: List[str] = field(default_factory=list)
+    supertypes: List[str] = field(default_factory=list)
+    children: List["Symbol"] = field(default_factory=list)
+    definition: Optional[Location] = None
+    # Output fields without a typed attribute here, keyed by their JSON name
+    extra: Dict[str, Any] = field(default_factory=dict)
+
+    @classmethod
+    def from_json(cls, data: Dict[str, Any]) -> "Symbol":
+        return cls(
+            name=data["name"],
+            kind=data["kind"],
+            file=data["file"],
+            range=Range.from_json(data["range"]),
+            preview=data.get("preview", ""),
+            documentation=data.get("documentation"),
+            comments=list(data.get("comments") or []),
+            supertypes=list(data.get("supertypes") or []),
+            children=[Symbol.from_json(child) for child in data.get("children") or []],
+            definition=Location.from_json(data["definition"]) if data.get("definition") else None,
+            extra={key: value for key, value in data.items() if key not in _TYPED_FIELDS},
+        )
+
+    def walk(self) -> Iterator["Symbol"]:
+        """Yields this symbol and all nested children, depth-first in document order."""
+        yield self
+        for child in self.children:
+            yield from child.walk()
+
+
+@dataclass
+class AnalysisResult:
+    language: str
+    directory: str
+    symbols: List[Symbol]
+    # Set for results of Client.analyze(); pass it to Client.query()
+    snapshot_id: Optional[str] = None
+
+    @classmethod
+    def from_json(cls, data: Dict[str, Any]) -> "AnalysisResult":
+        return cls(
+            language=data["language"],
+            directory=data["directory"],
+            symbols=[Symbol.from_json(symbol) for symbol in data["symbols"]],
+            snapshot_id=data.get("snapshotId"),
+        )
+
+    def walk(self) -> Iterator[Symbol]:
+        """Yields every symbol, including nested children."""
+        for symbol in self.symbols:
+            yield from symbol.walk()
@@ -0,0 +1,106 @@
+#!/usr/bin/env python3
+"""
+Stands in for `lsp-cli serve --stdio` in the tests. Answers each request after a notification and a
+response to another request, as a busy server may. FAKE_LSP_CLI=exit makes it exit before reading anything.
+"""
+
+import json
+import os
+import sys
+
+SYMBOLS = [
+    {
+        "name": "Greeter",
+        "kind": "class",
+        "file": "/project/src/greeter.py",
+        "range": {"start": {"line": 0, "character": 0}, "end": {"line": 4, "character": 0}},
+        "preview": "class Greeter:",
+        "documentation": "Greets people.",
+        "visibility": "public",
+        "children": [
+            {
+                "name": "greet",
+                "kind": "method",
+                "file": "/project/src/greeter.py",
+                "range": {"start": {"line": 2, "character": 4}, "end": {"line": 3, "character": 20}},
+                "preview": "def greet(self):",
+                "definition": {
+                    "file": "/project/src/greeter.py",
+                    "range": {"start": {"line": 2, "character": 8}, "end": {"line": 2, "character": 13}},
+                },
+            }
+        ],
+    },
+    {
+        "name": "main",
+        "kind": "function",
+        "file": "/project/src/main.py",
+        "range": {"start": {"line": 0, "character": 0}, "end": {"line": 1, "character": 0}},
+        "preview": "def main():",
+    },
+]
+
+
+def read_message():
+    """Reads one Content-Length framed message from stdin; returns None at the end of input."""
+    length = None
+    while True:
+        line = sys.stdin.buffer.readline()
+        if not line:
+            return None
+        if line == b"\r\n":
+            break
+        if line.lower().startswith(b"content-length:"):
+            length = int(line.split(b":", 1)[1])
+    return json.loads(sys.stdin.buffer.read(length))
+
+
+def send_message(message):
+    body = json.dumps(message).encode()
+    sys.stdout.buffer.write(b"Content-Length: %d\r\n\r\n" % len(body) + body)
+    sys.stdout.buffer.flush()
+
+
+def answer(method, params):
+    """Returns the result of a request, or raises ValueError with the error message."""
+    if any(value is None for value in params.values()):
+        raise ValueError("Unexpected null parameter")
+    if method == "analyze":
+        return {
+            "snapshotId": "snap-1",
+            "language": params["language"],
+            "directory": params["directory"],
+            "symbols": SYMBOLS,
+        }
+    if method == "query":
+        nested = SYMBOLS + SYMBOLS[0]["children"]
+        matches = [symbol for symbol in nested if params.get("kind") in (None, symbol["kind"])]
+        return {"language": "python", "directory": "/project", "symbols": matches}
+    if method == "references":
+        location = SYMBOLS[0]["children"][0]["definition"]
+        return {"locations": [location] if params["includeDeclaration"] else []}
+    if method == "shutdown":
+        return None
+    raise ValueError("No such file")
+
+
+def main():
+    if os.environ.get("FAKE_LSP_CLI") == "exit":
+        return
+    while True:
+        request = read_message()
+        if request is None:
+            return
+        send_message({"jsonrpc": "2.0", "method": "$/progress", "params": {"message": "indexing"}})
+        send_message({"jsonrpc": "2.0", "id": request["id"] + 100, "result": "stale"})
+        try:
+            result = answer(request["method"], request["params"])
+            send_message({"jsonrpc": "2.0", "id": request["id"], "result": result})
+        except ValueError as error:
+            send_message({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32602, "message": str(error)}})
+        if request["method"] == "shutdown":
+            return
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,85 @@
+"""Tests of the Python bindings against a fake `lsp-cli serve --stdio` (fake_lsp_cli.py)."""
+
+import os
+import stat
+import unittest
+from pathlib import Path
+from unittest import mock
+
+import lsp_cli
+
+FAKE_LSP_CLI = Path(__file__).with_name("fake_lsp_cli.py")
+
+
+class ClientTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls) -> None:
+        FAKE_LSP_CLI.chmod(FAKE_LSP_CLI.stat().st_mode | stat.S_IXUSR)
+
+    def setUp(self) -> None:
+        self.client = lsp_cli.Client(executable=str(FAKE_LSP_CLI))
+        self.addCleanup(self.client.close)
+
+    def test_analyze_returns_typed_symbols(self) -> None:
+        result = self.client.analyze("/project", "python")
+
+        self.assertEqual(result.snapshot_id, "snap-1")
+        self.assertEqual([symbol.name for symbol in result.walk()], ["Greeter", "greet", "main"])
+        greeter = result.symbols[0]
+        self.assertEqual(greeter.range.end, lsp_cli.Position(line=4, character=0))
+        self.assertEqual(greeter.documentation, "Greets people.")
+        self.assertEqual(greeter.extra, {"visibility": "public"})
+        self.assertEqual(greeter.children[0].definition.range.start, lsp_cli.Position(line=2, character=8))
+        self.assertIsNone(result.symbols[1].definition)
+
+    def test_iter_symbols_yields_depth_first_without_children(self) -> None:
+        symbols = list(self.client.iter_symbols("/project", "python"))
+
+        self.assertEqual([symbol.name for symbol in symbols], ["Greeter", "greet", "main"])
+        self.assertTrue(all(symbol.children == [] for symbol in symbols))
+
+    def test_query_sends_only_given_filters(self) -> None:
+        result = self.client.query("snap-1", kind="method")
+
+        self.assertEqual([symbol.name for symbol in result.symbols], ["greet"])
+        self.assertIsNone(result.snapshot_id)
+
+    def test_references_pass_include_declaration(self) -> None:
+        with_declaration = self.client.references("/project", "python", "src/greeter.py", 2, 8)
+        without = self.client.references("/project", "python", "src/greeter.py", 2, 8, include_declaration=False)
+
+        self.assertEqual([location.file for location in with_declaration], ["/project/src/greeter.py"])
+        self.assertEqual(without, [])
+
+    def test_errors_raise_with_code_and_keep_the_client_usable(self) -> None:
+        with self.assertRaises(lsp_cli.LspCliError) as raised:
+            self.client.definition("/project", "python", "missing.py", 0, 0)
+
+        self.assertEqual(str(raised.exception), "No such file")
+        self.assertEqual(raised.exception.code, -32602)
+        self.assertEqual(self.client.analyze("/project", "python").snapshot_id, "snap-1")
+
+    def test_close_shuts_the_process_down(self) -> None:
+        self.client.close()
+
+        self.assertEqual(self.client._process.returncode, 0)
+
+
+class ProcessTest(unittest.TestCase):
+    def test_one_shot_analyze(self) -> None:
+        result = lsp_cli.analyze("/project", "python", executable=str(FAKE_LSP_CLI))
+
+        self.assertEqual(len(result.symbols), 2)
+
+    def test_exited_process_raises(self) -> None:
+        with mock.patch.dict(os.environ, {"FAKE_LSP_CLI": "exit"}):
+            client = lsp_cli.Client(executable=str(FAKE_LSP_CLI))
+        client._process.wait()
+
+        with self.assertRaises(lsp_cli.LspCliError):
+            client.analyze("/project", "python")
+        client.close()
+
+
+if __name__ == "__main__":
+    unittest.main()
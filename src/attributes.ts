@@ -0,0 +1,189 @@
+import type { AttributeInfo, SupportedLanguage } from './types';
+
+// How far above a declaration attributes are searched, to bound the scan for huge multi-line attributes
+const MAX_ATTRIBUTE_LINES = 50;
+
+const CLOSING: Record<string, string> = { '(': ')', '[': ']', '{': '}' };
+
+/**
+ * Extracts the attributes, annotations or decorators of a declaration: Rust `#[...]`, C# `[...]`,
+ * C/C++ `[[...]]` and `__attribute__((...))`, and `@name(...)` in Python, Java, TypeScript,
+ * Dart and Haxe. They are read from the lines directly above the declaration (comments in
+ * between are skipped) and from the start of the declaration line itself.
+ *
+ * @param lines - Source lines of the file
+ * @param declarationLine - 0-based line of the symbol's name
+ * @param language - Language of the file
+ * @returns Attributes in source order, or undefined if there are none
+ */
+export function extractAttributes(
+    lines: string[],
+    declarationLine: number,
+    language: SupportedLanguage
+): AttributeInfo[] | undefined {
+    let top = declarationLine;
+    let depth = 0;
+    for (let line = declarationLine - 1; line >= 0 && declarationLine - line <= MAX_ATTRIBUTE_LINES; line--) {
+        const text = stripStrings(lines[line], language).trim();
+        // Walking upwards, closing brackets open a multi-line attribute and opening ones close it;
+        // the lines only count once the bracket is closed by a line starting an attribute
+        depth += count(text, /[)\]}]/g) - count(text, /[([{]/g);
+        if (depth > 0) {
+            continue;
+        }
+        if (depth < 0 || !(startsAttribute(text, language) || isComment(text, language))) {
+            break;
+        }
+        top = line;
+    }
+
+    const attributes = parseAttributes(lines.slice(top, declarationLine + 1).join('\n'), language);
+    return attributes.length > 0 ? attributes : undefined;
+}
+
+function startsAttribute(text: string, language: SupportedLanguage): boolean {
+    switch (language) {
+        case 'rust':
+            return text.startsWith('#[');
+        case 'csharp':
+            return text.startsWith('[');
+        case 'c':
+        case 'cpp':
+            return text.startsWith('[[') || text.startsWith('__attribute__');
+        default:
+            return text.startsWith('@');
+    }
+}
+
+function isComment(text: string, language: SupportedLanguage): boolean {
+    if (language === 'python') {
+        return text.startsWith('#');
+    }
+    return text.startsWith('//') || text.startsWith('/*') || text.startsWith('*');
+}
+
+function count(text: string, pattern: RegExp): number {
+    return text.match(pattern)?.length ?? 0;
+}
+
+/** Replaces string literal contents, so brackets inside them aren't counted */
+function stripStrings(text: string, language: SupportedLanguage): string {
+    // Rust lifetimes and char literals make single quotes ambiguous
+    const pattern = language === 'rust' ? /"(?:\\.|[^"\\])*"/g : /"(?:\\.|[^"\\])*"|'(?:\\.|[^'\\])*'/g;
+    return text.replace(pattern, '""');
+}
+
+/** Parses consecutive attributes at the start of some text, stopping at the first non-attribute */
+function parseAttributes(text: string, language: SupportedLanguage): AttributeInfo[] {
+    const attributes: AttributeInfo[] = [];
+    let index = 0;
+
+    while (index < text.length) {
+        const rest = text.slice(index);
+        const whitespace = /^\s+/.exec(rest);
+        if (whitespace) {
+            index += whitespace[0].length;
+            continue;
+        }
+
+        if (rest.startsWith('//') || (language === 'python' && rest.startsWith('#'))) {
+            const newline = rest.indexOf('\n');
+            index = newline === -1 ? text.length : index + newline;
+        } else if (rest.startsWith('/*')) {
+            const end = rest.indexOf('*/');
+            index = end === -1 ? text.length : index + end + 2;
+        } else if (language === 'rust' && rest.startsWith('#[')) {
+            const end = matchingBracket(text, index + 1, language);
+            attributes.push(parseCall(text.slice(index + 2, end)));
+            index = end + 1;
+        } else if ((language === 'c' || language === 'cpp') && rest.startsWith('[[')) {
+            const end = matchingBracket(text, index, language);
+            attributes.push(...splitTopLevel(text.slice(index + 2, end - 1), language).map(parseCall));
+            index = end + 1;
+        } else if ((language === 'c' || language === 'cpp') && /^__attribute__\s*\(/.test(rest)) {
+            const open = text.indexOf('(', index);
+            const end = matchingBracket(text, open, language);
+            attributes.push(...splitTopLevel(text.slice(open + 2, end - 1), language).map(parseCall));
+            index = end + 1;
+        } else if (language === 'csharp' && rest.startsWith('[')) {
+            const end = matchingBracket(text, index, language);
+            attributes.push(...splitTopLevel(text.slice(index + 1, end), language).map(parseCall));
+            index = end + 1;
+        } else if (!['rust', 'csharp', 'c', 'cpp'].includes(language) && /^@:?[\w.]+/.test(rest)) {
+            // Haxe metadata is written @:name
+            const name = /^@(:?[\w.]+)/.exec(rest)![1];
+            const afterName = index + 1 + name.length;
+            if (text[afterName] === '(') {
+                const end = matchingBracket(text, afterName, language);
+                attributes.push({ name, arguments: text.slice(afterName + 1, end).trim() });
+                index = end + 1;
+            } else {
+                attributes.push({ name });
+                index = afterName;
+            }
+        } else {
+            break;
+        }
+    }
+
+    return attributes;
+}
+
+/** `name`, `name(arguments)` or `name = value`, as inside `#[...]` or `[...]` */
+function parseCall(attribute: string): AttributeInfo {
+    const match = /^([\w.:]+)\s*(?:\(([\s\S]*)\)|=\s*([\s\S]*))?$/.exec(attribute.trim());
+    if (!match) {
+        return { name: attribute.trim() };
+    }
+    const args = (match[2] ?? match[3])?.trim();
+    return { name: match[1], ...(args !== undefined && { arguments: args }) };
+}
+
+/** Index of the bracket closing the one at `open`, or the last index if it is never closed */
+function matchingBracket(text: string, open: number, language: SupportedLanguage): number {
+    const stack: string[] = [];
+    for (let index = open; index < text.length; index++) {
+        const char = text[index];
+        if (char === '"' || (char === "'" && language !== 'rust')) {
+            // Skip string literals
+            for (index++; index < text.length && text[index] !== char; index++) {
+                if (text[index] === '\\') {
+                    index++;
+                }
+            }
+        } else if (CLOSING[char]) {
+            stack.push(CLOSING[char]);
+        } else if (char === stack[stack.length - 1]) {
+            stack.pop();
+            if (stack.length === 0) {
+                return index;
+            }
+        }
+    }
+    return text.length - 1;
+}
+
+/** Splits a list like `Serializable, Obsolete("a, b")` at commas outside brackets and strings */
+function splitTopLevel(text: string, language: SupportedLanguage): string[] {
+    const parts: string[] = [];
+    let start = 0;
+    for (let index = 0; index < text.length; index++) {
+        const char = text[index];
+        if (CLOSING[char] || char === '"' || (char === "'" && language !== 'rust')) {
+            index = char in CLOSING ? matchingBracket(text, index, language) : skipString(text, index);
+        } else if (char === ',') {
+            parts.push(text.slice(start, index));
+            start = index + 1;
+        }
+    }
+    parts.push(text.slice(start));
+    return parts.map((part) => part.trim()).filter((part) => part !== '');
+}
+
+function skipString(text: string, open: number): number {
+    let index = open + 1;
+    while (index < text.length && text[index] !== text[open]) {
+        index += text[index] === '\\' ? 2 : 1;
+    }
+    return index;
+}
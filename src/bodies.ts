@@ -0,0 +1,32 @@
+import type { Position, SymbolInfo } from './types';
+
+/**
+ * Sets `body` on symbols and their children to their source text, sliced from the file by the
+ * full range (doc comments and attributes included) or by `range` where the server reports no
+ * full range. Long bodies can be cut to their first lines, in which case `bodyTruncated` is set.
+ *
+ * @param symbols - Symbols of one file, with position offsets already added; updated in place
+ * @param lines - Source lines of the file
+ * @param maxLines - Number of lines to keep per body; all of them if omitted
+ */
+export function addBodies(symbols: SymbolInfo[], lines: string[], maxLines?: number): void {
+    const column = (position: Position) => position.utf16Character ?? position.character;
+
+    const visit = (symbol: SymbolInfo) => {
+        const { start, end } = symbol.fullRange ?? symbol.range;
+        const body = lines.slice(start.line, end.line + 1);
+        if (body.length > 0) {
+            // Cut the end first, it is on the same line as the start for one-line symbols
+            body[body.length - 1] = body[body.length - 1].slice(0, column(end));
+            body[0] = body[0].slice(column(start));
+        }
+        if (maxLines !== undefined && body.length > maxLines) {
+            symbol.body = body.slice(0, maxLines).join('\n');
+            symbol.bodyTruncated = true;
+        } else {
+            symbol.body = body.join('\n');
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
@@ -0,0 +1,237 @@
+import { createHash } from 'node:crypto';
+import { existsSync, mkdirSync, readFileSync, renameSync, rmSync, writeFileSync } from 'node:fs';
+import { homedir } from 'node:os';
+import { dirname, join } from 'node:path';
+import type { Logger } from './logger';
+import type { OrphanedDoc, PositionEncoding, SourceEncoding, SupportedLanguage, SymbolInfo } from './types';
+
+// Layout of the cache files; bump when what is stored per file changes, so old caches are ignored
+const CACHE_FORMAT = 2;
+
+/** Dependency on files that can't be told, e.g. of a type from hover: any change in the workspace counts */
+export const ANY_FILE = '*';
+
+/** What the analysis of one file produced, before the passes over the whole workspace */
+export interface CachedFile {
+    /** SHA-256 of the file's bytes */
+    hash: string;
+    symbols: SymbolInfo[];
+    encoding: SourceEncoding;
+    /** With `--trailing-docs orphan` */
+    orphanedDocs?: OrphanedDoc[];
+    /** Hashes of the other files the symbols took data from, e.g. the target of a re-export */
+    dependencies?: Record<string, string>;
+    /** Fingerprint of all analyzed files, for symbols with data from files that can't be told */
+    workspace?: string;
+}
+
+interface CacheContents {
+    format: number;
+    positionEncoding?: PositionEncoding;
+    /** Cached files by absolute path */
+    files: Record<string, CachedFile>;
+}
+
+/**
+ * @param workspaceRoot - Absolute path of the analyzed directory
+ * @returns Directory below `~/.lsp-cli/cache` holding the directory's caches, one per language and settings
+ */
+export function cacheDirectory(workspaceRoot: string): string {
+    const project = createHash('sha256').update(workspaceRoot).digest('hex').slice(0, 16);
+    return join(homedir(), '.lsp-cli', 'cache', project);
+}
+
+/**
+ * Removes the caches of a directory, for all languages and settings.
+ *
+ * @param workspaceRoot - Absolute path of the analyzed directory
+ * @returns Whether there was a cache to remove
+ */
+export function clearCache(workspaceRoot: string): boolean {
+    const directory = cacheDirectory(workspaceRoot);
+    const existed = existsSync(directory);
+    rmSync(directory, { recursive: true, force: true });
+    return existed;
+}
+
+/**
+ * Symbols of previously analyzed files, keyed on the files' content hashes. A cache belongs to one
+ * directory, language and set of settings (lsp-cli and server version, extraction options); other
+ * settings get a cache of their own. Symbols with data from other files, like a re-export's target or
+ * a type from hover, are only reused while those files are unchanged too.
+ */
+export class AnalysisCache {
+    // Hashes of the files read in this run, so a file is hashed once and stored with the content analyzed
+    private hashes = new Map<string, string | undefined>();
+    private fingerprint?: string;
+    private changed = false;
+
+    private constructor(
+        private path: string,
+        private contents: CacheContents
+    ) {}
+
+    /**
+     * Loads the cache for a directory, language and settings; an unreadable cache is started over.
+     *
+     * @param workspaceRoot - Absolute path of the analyzed directory
+     * @param language - Analyzed language
+     * @param settings - Everything besides the file content the symbols depend on, e.g. the server version
+     * @param logger - Logger for an unreadable cache
+     * @returns The cache, empty if there was none
+     */
+    static open(
+        workspaceRoot: string,
+        language: SupportedLanguage,
+        settings: Record<string, unknown>,
+        logger: Logger
+    ): AnalysisCache {
+        const key = createHash('sha256')
+            .update(JSON.stringify({ format: CACHE_FORMAT, ...settings }))
+            .digest('hex')
+            .slice(0, 16);
+        const path = join(cacheDirectory(workspaceRoot), `${language}-${key}.json`);
+        let contents: CacheContents = { format: CACHE_FORMAT, files: {} };
+        if (existsSync(path)) {
+            try {
+                const stored = JSON.parse(readFileSync(path, 'utf8')) as CacheContents;
+                if (stored.format === CACHE_FORMAT) {
+                    contents = stored;
+                }
+            } catch (error) {
+                const reason = error instanceof Error ? error.message : String(error);
+                logger.warn(`Ignoring unreadable cache ${path}: ${reason}`);
+            }
+        }
+        logger.debug(`Using cache ${path} with ${Object.keys(contents.files).length} files`);
+        return new AnalysisCache(path, contents);
+    }
+
+    /**
+     * @returns The position encoding the cached symbols were extracted with, if any were
+     */
+    get positionEncoding(): PositionEncoding | undefined {
+        return this.contents.positionEncoding;
+    }
+
+    /**
+     * Hashes all files of the run, for the fingerprint that symbols with data from unknown files are
+     * checked against.
+     *
+     * @param files - Absolute paths of all files analyzed in this run
+     */
+    scan(files: string[]): void {
+        const fingerprint = createHash('sha256');
+        for (const file of [...files].sort()) {
+            fingerprint.update(`${file}\0${this.hashOf(file) ?? ''}\n`);
+        }
+        this.fingerprint = fingerprint.digest('hex');
+    }
+
+    /**
+     * @param file - Absolute path
+     * @returns A copy of the file's cached analysis if neither the file nor the files its symbols took
+     * data from changed since, otherwise undefined
+     */
+    lookup(file: string): CachedFile | undefined {
+        const hash = this.hashOf(file);
+        const entry = this.contents.files[file];
+        if (hash === undefined || entry?.hash !== hash) {
+            return undefined;
+        }
+        const dependencies = Object.entries(entry.dependencies ?? {});
+        if (
+            dependencies.some(([dependency, dependencyHash]) => this.hashOf(dependency) !== dependencyHash) ||
+            (entry.workspace !== undefined && entry.workspace !== this.fingerprint)
+        ) {
+            return undefined;
+        }
+        // The workspace passes change the symbols they are given
+        return JSON.parse(JSON.stringify(entry)) as CachedFile;
+    }
+
+    /**
+     * Records the analysis of a file. The symbols are copied, so later changes to them, e.g. by
+     * enrichment, don't end up in the cache.
+     *
+     * @param file - Absolute path
+     * @param analysis - What the analysis of the file produced
+     * @param dependencies - Other files the symbols took data from, `ANY_FILE` for files that can't be told
+     */
+    store(
+        file: string,
+        analysis: Omit<CachedFile, 'hash' | 'dependencies' | 'workspace'>,
+        dependencies: Iterable<string> = []
+    ): void {
+        const hash = this.hashOf(file);
+        if (hash === undefined) {
+            return;
+        }
+        const hashes: Record<string, string> = {};
+        let workspace: string | undefined;
+        for (const dependency of dependencies) {
+            const dependencyHash = dependency === ANY_FILE ? undefined : this.hashOf(dependency);
+            if (dependencyHash !== undefined) {
+                hashes[dependency] = dependencyHash;
+            } else if (this.fingerprint === undefined) {
+                // Without a fingerprint such symbols could never be checked
+                return;
+            } else {
+                workspace = this.fingerprint;
+            }
+        }
+        this.contents.files[file] = {
+            hash,
+            ...JSON.parse(JSON.stringify(analysis)),
+            ...(Object.keys(hashes).length > 0 && { dependencies: hashes }),
+            ...(workspace !== undefined && { workspace })
+        };
+        this.changed = true;
+    }
+
+    /**
+     * Writes the cache if it changed, dropping files that no longer exist. The file is replaced
+     * atomically, so concurrent runs never read a partial cache.
+     *
+     * @param positionEncoding - Position encoding of the stored symbols
+     */
+    save(positionEncoding: PositionEncoding): void {
+        for (const file of Object.keys(this.contents.files)) {
+            if (!existsSync(file)) {
+                delete this.contents.files[file];
+                this.changed = true;
+            }
+        }
+        if (!this.changed && this.contents.positionEncoding === positionEncoding) {
+            return;
+        }
+        this.contents.positionEncoding = positionEncoding;
+
+        mkdirSync(dirname(this.path), { recursive: true });
+        const temporary = `${this.path}.${process.pid}.tmp`;
+        try {
+            writeFileSync(temporary, JSON.stringify(this.contents));
+            renameSync(temporary, this.path);
+        } catch (error) {
+            rmSync(temporary, { force: true });
+            throw error;
+        }
+        this.changed = false;
+    }
+
+    /**
+     * @param file - Absolute path
+     * @returns SHA-256 of the file's content when first read in this run, or undefined if it can't be read
+     */
+    private hashOf(file: string): string | undefined {
+        if (!this.hashes.has(file)) {
+            try {
+                this.hashes.set(file, createHash('sha256').update(readFileSync(file)).digest('hex'));
+            } catch (_error) {
+                // Left to the analysis to report
+                this.hashes.set(file, undefined);
+            }
+        }
+        return this.hashes.get(file);
+    }
+}
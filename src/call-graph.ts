@@ -0,0 +1,117 @@
+import type { CallGraphEdge, CallGraphNode, Position, Range, SymbolInfo } from './types';
+
+// Kinds of the symbols whose calls and references make up the call graph
+export const CALL_GRAPH_KINDS = new Set(['function', 'method', 'constructor']);
+
+/** Extracted symbols by file, nested ones included, for finding the symbol at or around a position */
+export type SymbolIndex = Map<string, SymbolInfo[]>;
+
+/**
+ * Indexes symbols by file. Notebook symbols are left out: their ranges are relative to a cell, not the
+ * document the server sees.
+ *
+ * @param symbols - Extracted symbols
+ * @returns The symbols of each file, containers before their children
+ */
+export function indexSymbols(symbols: SymbolInfo[]): SymbolIndex {
+    const index: SymbolIndex = new Map();
+    const visit = (symbol: SymbolInfo) => {
+        if (symbol.cell !== undefined) {
+            return;
+        }
+        index.set(symbol.file, [...(index.get(symbol.file) ?? []), symbol]);
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+    return index;
+}
+
+/**
+ * @param index - Symbols by file
+ * @param file - Absolute path
+ * @param position - Start of a symbol's name, e.g. a call hierarchy item's selection range
+ * @returns The symbol named at the position, or the innermost symbol containing it
+ */
+export function findSymbolAt(index: SymbolIndex, file: string, position: Position): SymbolInfo | undefined {
+    const symbols = index.get(file) ?? [];
+    return (
+        symbols.find((symbol) => symbol.selectionRange && samePosition(symbol.selectionRange.start, position)) ??
+        findEnclosingSymbol(index, file, position)
+    );
+}
+
+/**
+ * @param index - Symbols by file
+ * @param file - Absolute path
+ * @param position - Position in the file, e.g. of a reference
+ * @returns The innermost symbol whose definition contains the position
+ */
+export function findEnclosingSymbol(index: SymbolIndex, file: string, position: Position): SymbolInfo | undefined {
+    let enclosing: SymbolInfo | undefined;
+    for (const symbol of index.get(file) ?? []) {
+        // Children come after their containers, so the last match is the innermost
+        if (contains(symbol.fullRange ?? symbol.range, position)) {
+            enclosing = symbol;
+        }
+    }
+    return enclosing;
+}
+
+/**
+ * @param symbol - Extracted symbol
+ * @returns The symbol as an end of an edge
+ */
+export function symbolNode(symbol: SymbolInfo): CallGraphNode {
+    return {
+        ...(symbol.id !== undefined && { id: symbol.id }),
+        name: symbol.name,
+        kind: symbol.kind,
+        file: symbol.file,
+        range: symbol.selectionRange ?? symbol.range
+    };
+}
+
+/**
+ * Adds an edge, merging it into an existing edge of the same kind between the same symbols.
+ *
+ * @param edges - Edges by key; updated in place
+ * @param edge - Edge to add
+ */
+export function addEdge(edges: Map<string, CallGraphEdge>, edge: CallGraphEdge): void {
+    const node = (end: CallGraphNode) => end.id ?? `${end.file}:${end.range.start.line}:${end.range.start.character}`;
+    const key = `${edge.kind}\0${node(edge.from)}\0${node(edge.to)}`;
+    const existing = edges.get(key);
+    if (!existing) {
+        edges.set(key, { ...edge, ranges: [...edge.ranges] });
+        return;
+    }
+    for (const range of edge.ranges) {
+        if (!existing.ranges.some((known) => samePosition(known.start, range.start))) {
+            existing.ranges.push(range);
+        }
+    }
+}
+
+/**
+ * @param range - Range
+ * @param position - Position
+ * @returns Whether the position is inside the range, ends included
+ */
+export function contains(range: Range, position: Position): boolean {
+    const afterStart =
+        position.line > range.start.line ||
+        (position.line === range.start.line && position.character >= range.start.character);
+    const beforeEnd =
+        position.line < range.end.line ||
+        (position.line === range.end.line && position.character <= range.end.character);
+    return afterStart && beforeEnd;
+}
+
+/**
+ * @param a - Position
+ * @param b - Position
+ * @returns Whether both are on the same line and column
+ */
+function samePosition(a: Position, b: Position): boolean {
+    return a.line === b.line && a.character === b.character;
+}
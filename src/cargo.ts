@@ -0,0 +1,286 @@
+import { execFile } from 'node:child_process';
+import { existsSync, readFileSync } from 'node:fs';
+import { basename, dirname, join, sep } from 'node:path';
+import { promisify } from 'node:util';
+import type { CargoPackageInfo, CargoWorkspaceInfo, CrateInfo, SymbolInfo } from './types';
+
+const execFileAsync = promisify(execFile);
+
+// Files that own their directory's modules, like target entry points, instead of a directory named after the file
+const MODULE_ROOTS = new Set(['lib.rs', 'main.rs', 'mod.rs']);
+
+/**
+ * Reads the members of the Cargo workspace containing a directory via `cargo metadata`.
+ *
+ * @param directory - Any directory inside the workspace
+ * @returns Workspace root and members; dependencies are not resolved
+ */
+export async function readCargoMetadata(directory: string): Promise<CargoWorkspaceInfo> {
+    const { stdout } = await execFileAsync('cargo', ['metadata', '--format-version', '1', '--no-deps'], {
+        cwd: directory,
+        maxBuffer: 64 * 1024 * 1024
+    });
+    const metadata = JSON.parse(stdout);
+    const members = new Set<string>(metadata.workspace_members);
+
+    return {
+        workspaceRoot: metadata.workspace_root,
+        members: metadata.packages
+            .filter((pkg: { id: string }) => members.has(pkg.id))
+            .map(
+                (pkg: any): CargoPackageInfo => ({
+                    name: pkg.name,
+                    version: pkg.version,
+                    manifestPath: pkg.manifest_path,
+                    features: pkg.features ?? {},
+                    targets: pkg.targets.map((target: any) => ({
+                        name: target.name,
+                        kinds: target.kind,
+                        srcPath: target.src_path,
+                        ...(target['required-features']?.length
+                            ? { requiredFeatures: target['required-features'] }
+                            : {})
+                    }))
+                })
+            )
+    };
+}
+
+/**
+ * Groups source files by the workspace member owning them: the member with the deepest manifest
+ * directory containing the file.
+ *
+ * @param files - Absolute paths of source files
+ * @param workspace - Workspace from `readCargoMetadata`
+ * @returns Files per member, in member order; files outside every member are keyed by `undefined`
+ */
+export function groupFilesByMember(
+    files: string[],
+    workspace: CargoWorkspaceInfo
+): Map<CargoPackageInfo | undefined, string[]> {
+    const groups = new Map<CargoPackageInfo | undefined, string[]>(workspace.members.map((member) => [member, []]));
+    for (const file of files) {
+        const member = findMember(file, workspace);
+        groups.set(member, [...(groups.get(member) ?? []), file]);
+    }
+    return groups;
+}
+
+/**
+ * Tags symbols with their owning crate and target, and with the Cargo features referenced by
+ * `#[cfg(feature = ...)]` attributes on the symbol, its parents, the `mod` declarations leading to
+ * its file, `#![cfg(...)]` at the top of the file, or the target's `required-features`.
+ *
+ * @param symbols - Symbols to enrich in place; their files are read from disk
+ * @param workspace - Workspace from `readCargoMetadata`
+ */
+export function addCargoInfo(symbols: SymbolInfo[], workspace: CargoWorkspaceInfo): void {
+    const fileLines = new Map<string, string[]>();
+    const linesOf = (file: string) => {
+        let lines = fileLines.get(file);
+        if (!lines) {
+            lines = existsSync(file) ? readFileSync(file, 'utf-8').split('\n') : [];
+            fileLines.set(file, lines);
+        }
+        return lines;
+    };
+    const moduleFeatures = collectModuleFeatures(workspace, linesOf);
+    const fileInfo = new Map<string, { crate?: CrateInfo; features: string[] }>();
+    const infoFor = (file: string) => {
+        let info = fileInfo.get(file);
+        if (!info) {
+            const member = findMember(file, workspace);
+            const target = member && findTarget(file, member);
+            info = {
+                crate: member && {
+                    name: member.name,
+                    version: member.version,
+                    ...(target ? { target: { name: target.name, kind: target.kinds[0] } } : {})
+                },
+                features: unique([...(moduleFeatures.get(file) ?? []), ...(target?.requiredFeatures ?? [])])
+            };
+            fileInfo.set(file, info);
+        }
+        return info;
+    };
+
+    const annotate = (symbol: SymbolInfo, inherited: string[]) => {
+        const { crate } = infoFor(symbol.file);
+        const features = unique([...inherited, ...cfgFeatures(attributesOf(linesOf(symbol.file), symbol))]);
+        if (crate) {
+            symbol.crate = crate;
+        }
+        if (features.length > 0) {
+            symbol.features = features;
+        }
+        symbol.children?.forEach((child) => annotate(child, features));
+    };
+
+    for (const symbol of symbols) {
+        annotate(symbol, infoFor(symbol.file).features);
+    }
+}
+
+function findMember(file: string, workspace: CargoWorkspaceInfo): CargoPackageInfo | undefined {
+    let best: CargoPackageInfo | undefined;
+    for (const member of workspace.members) {
+        const root = dirname(member.manifestPath);
+        if (isInside(file, root) && (!best || root.length > dirname(best.manifestPath).length)) {
+            best = member;
+        }
+    }
+    return best;
+}
+
+/** The target whose entry point is the file, or else whose entry point directory most closely contains it */
+function findTarget(file: string, member: CargoPackageInfo): CargoPackageInfo['targets'][number] | undefined {
+    const exact = member.targets.find((target) => target.srcPath === file);
+    if (exact) {
+        return exact;
+    }
+    let best: CargoPackageInfo['targets'][number] | undefined;
+    for (const target of member.targets) {
+        const root = dirname(target.srcPath);
+        if (!isInside(file, root)) {
+            continue;
+        }
+        const bestRoot = best ? dirname(best.srcPath) : '';
+        // Library and binary usually share src/; modules there belong to the library
+        if (!best || root.length > bestRoot.length || (root === bestRoot && target.kinds.includes('lib'))) {
+            best = target;
+        }
+    }
+    return best;
+}
+
+/**
+ * Follows `mod name;` declarations from each target's entry point and records the features
+ * required by cfg attributes on the declarations and by `#![cfg(...)]` in the module files.
+ */
+function collectModuleFeatures(
+    workspace: CargoWorkspaceInfo,
+    linesOf: (file: string) => string[]
+): Map<string, string[]> {
+    const features = new Map<string, string[]>();
+    const entryPoints = new Set(workspace.members.flatMap((member) => member.targets.map((target) => target.srcPath)));
+
+    const visit = (file: string, inherited: string[]) => {
+        if (features.has(file) || !existsSync(file)) {
+            return;
+        }
+        const lines = linesOf(file);
+        const own = unique([...inherited, ...cfgFeatures(innerAttributes(lines))]);
+        features.set(file, own);
+
+        const isModuleRoot = entryPoints.has(file) || MODULE_ROOTS.has(basename(file));
+        const moduleDir = isModuleRoot ? dirname(file) : file.replace(/\.rs$/, '');
+        lines.forEach((line, index) => {
+            const declaration = /^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;/.exec(line);
+            if (!declaration) {
+                return;
+            }
+            const pathAttribute = precedingAttributes(lines, index).match(/#\[path\s*=\s*"([^"]+)"\]/);
+            const candidates = pathAttribute
+                ? [join(dirname(file), pathAttribute[1])]
+                : [join(moduleDir, `${declaration[1]}.rs`), join(moduleDir, declaration[1], 'mod.rs')];
+            const moduleFile = candidates.find((candidate) => existsSync(candidate));
+            if (moduleFile) {
+                visit(moduleFile, unique([...own, ...cfgFeatures(precedingAttributes(lines, index))]));
+            }
+        });
+    };
+
+    for (const member of workspace.members) {
+        for (const target of member.targets) {
+            visit(target.srcPath, []);
+        }
+    }
+    return features;
+}
+
+/** Attribute lines directly above and at the start of a symbol's range */
+function attributesOf(lines: string[], symbol: SymbolInfo): string {
+    const leading: string[] = [];
+    let depth = 0;
+    for (let line = symbol.range.start.line; line < lines.length && line <= symbol.range.end.line; line++) {
+        const text = lines[line].trim();
+        if (depth === 0 && !text.startsWith('#[') && !text.startsWith('//')) {
+            break;
+        }
+        leading.push(text);
+        if (!text.startsWith('//')) {
+            depth += text.split('[').length - text.split(']').length;
+        }
+    }
+    return `${precedingAttributes(lines, symbol.range.start.line)}\n${leading.join('\n')}`;
+}
+
+/** Attributes and comments directly above a line, including attributes spanning several lines */
+function precedingAttributes(lines: string[], index: number): string {
+    const collected: string[] = [];
+    let inAttribute = false;
+    for (let line = index - 1; line >= 0; line--) {
+        const text = lines[line].trim();
+        if (inAttribute) {
+            collected.unshift(text);
+            inAttribute = !text.startsWith('#[');
+        } else if (text.startsWith('#[') || text.startsWith('//')) {
+            collected.unshift(text);
+        } else if (text.endsWith(']')) {
+            // Last line of a multi-line attribute; collect up to its `#[`
+            collected.unshift(text);
+            inAttribute = true;
+        } else {
+            break;
+        }
+    }
+    return collected.join('\n');
+}
+
+/** `#![...]` attributes at the top of a file, before the first item */
+function innerAttributes(lines: string[]): string {
+    const collected: string[] = [];
+    for (const line of lines) {
+        const text = line.trim();
+        if (text.startsWith('#![')) {
+            collected.push(text);
+        } else if (text !== '' && !text.startsWith('//')) {
+            break;
+        }
+    }
+    return collected.join('\n');
+}
+
+/** Feature names required by the `cfg` attributes in some source text; negated features are ignored */
+function cfgFeatures(attributes: string): string[] {
+    const features: string[] = [];
+    for (const match of attributes.matchAll(/#!?\[\s*cfg\s*\(/g)) {
+        let depth = 1;
+        let end = match.index + match[0].length;
+        while (end < attributes.length && depth > 0) {
+            if (attributes[end] === '(') {
+                depth++;
+            } else if (attributes[end] === ')') {
+                depth--;
+            }
+            end++;
+        }
+        let predicate = attributes.slice(match.index + match[0].length, end - 1);
+        // Strip innermost not(...) groups until none are left
+        while (/not\s*\([^()]*\)/.test(predicate)) {
+            predicate = predicate.replace(/not\s*\([^()]*\)/g, '');
+        }
+        for (const [, feature] of predicate.matchAll(/feature\s*=\s*"([^"]+)"/g)) {
+            features.push(feature);
+        }
+    }
+    return features;
+}
+
+function isInside(file: string, directory: string): boolean {
+    return file.startsWith(directory.endsWith(sep) ? directory : `${directory}${sep}`);
+}
+
+function unique(values: string[]): string[] {
+    return [...new Set(values)];
+}
@@ -0,0 +1,119 @@
+import { existsSync, readFileSync } from 'node:fs';
+import { relative, resolve } from 'node:path';
+import { identifySymbols } from './ci';
+import type { Session } from './session';
+import type { DiagnosticInfo, SymbolInfo } from './types';
+
+/** Two versions of a file; a missing side means the file was added or deleted */
+export interface FileVersions {
+    /** Path, absolute or relative to the workspace root */
+    file: string;
+    before?: string;
+    after?: string;
+}
+
+export interface SignatureChange {
+    symbol: string;
+    before: string;
+    after: string;
+}
+
+export interface ChangeReport {
+    files: string[];
+    /** Diagnostics of the new versions that the old versions don't have (matched by file, severity and message) */
+    newDiagnostics: DiagnosticInfo[];
+    addedSymbols: string[];
+    removedSymbols: string[];
+    changedSignatures: SignatureChange[];
+}
+
+/**
+ * Compares the symbols and diagnostics of two versions of a set of files, using the
+ * session's running language server. Files missing from a side are closed for it, and
+ * afterwards the server is switched back to the on-disk content of each file, or the
+ * file is closed if it doesn't exist on disk.
+ *
+ * @param session - Session for the workspace the files belong to
+ * @param files - Old and new content per file
+ * @returns API-surface changes and diagnostics introduced by the new versions
+ */
+export async function compareVersions(session: Session, files: FileVersions[]): Promise<ChangeReport> {
+    const analyze = async (side: 'before' | 'after') => {
+        const symbols: SymbolInfo[] = [];
+        for (const file of files) {
+            const content = file[side];
+            if (content !== undefined) {
+                symbols.push(...(await session.analyzeContent(file.file, content)));
+            } else {
+                // A deleted or not yet added file must not keep the other side's content open
+                await session.closeFile(file.file);
+            }
+        }
+        // `./src/a.ts` and absolute paths name the same file as `src/a.ts`
+        const present = new Set(
+            files.filter((file) => file[side] !== undefined).map((file) => resolve(session.directory, file.file))
+        );
+        const diagnostics = (await session.diagnostics()).filter((diagnostic) =>
+            present.has(resolve(session.directory, diagnostic.file))
+        );
+        const output = { language: session.language, directory: session.directory, symbols };
+        return { symbols: identifySymbols(output), diagnostics };
+    };
+
+    const before = await analyze('before');
+    const after = await analyze('after');
+
+    for (const { file, after: content } of files) {
+        const path = resolve(session.directory, file);
+        if (!existsSync(path)) {
+            await session.closeFile(file);
+            continue;
+        }
+        const onDisk = readFileSync(path, 'utf-8');
+        if (onDisk !== content) {
+            await session.analyzeContent(file, onDisk);
+        }
+    }
+
+    const beforeById = new Map(before.symbols.map(({ id, symbol }) => [id, symbol]));
+    const afterById = new Map(after.symbols.map(({ id, symbol }) => [id, symbol]));
+    const changedSignatures: SignatureChange[] = [];
+    for (const [id, symbol] of afterById) {
+        const previous = beforeById.get(id);
+        if (previous && signature(previous) !== signature(symbol)) {
+            changedSignatures.push({ symbol: id, before: signature(previous), after: signature(symbol) });
+        }
+    }
+
+    return {
+        files: files.map((file) => file.file),
+        newDiagnostics: subtractDiagnostics(session.directory, after.diagnostics, before.diagnostics),
+        addedSymbols: [...afterById.keys()].filter((id) => !beforeById.has(id)),
+        removedSymbols: [...beforeById.keys()].filter((id) => !afterById.has(id)),
+        changedSignatures
+    };
+}
+
+/** The declaration header, or the declaration line if there is none, ignoring whitespace differences */
+function signature(symbol: SymbolInfo): string {
+    return (symbol.signature ?? symbol.preview.split('\n')[0]).trim().replace(/\s+/g, ' ');
+}
+
+function subtractDiagnostics(
+    directory: string,
+    current: DiagnosticInfo[],
+    previous: DiagnosticInfo[]
+): DiagnosticInfo[] {
+    const key = (diagnostic: DiagnosticInfo) =>
+        `${relative(directory, diagnostic.file)}\0${diagnostic.severity}\0${diagnostic.message}`;
+
+    const known = new Map<string, number>();
+    for (const diagnostic of previous) {
+        known.set(key(diagnostic), (known.get(key(diagnostic)) ?? 0) + 1);
+    }
+    return current.filter((diagnostic) => {
+        const count = known.get(key(diagnostic)) ?? 0;
+        known.set(key(diagnostic), count - 1);
+        return count <= 0;
+    });
+}
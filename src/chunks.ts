@@ -0,0 +1,179 @@
+import { readFileSync } from 'node:fs';
+import { relative } from 'node:path';
+import { estimateTokenizer } from './tokenizer';
+import type { AnalysisOutput, Range, SymbolInfo } from './types';
+
+/** A retrieval unit: one symbol, a part of a large symbol, or several small symbols of one file */
+export interface Chunk {
+    /** Stable identifier, e.g. `src/user.ts:UserService.save:42` */
+    id: string;
+    language: string;
+    /** Path relative to the analyzed directory */
+    file: string;
+    /** Lines covered by the chunk */
+    range: Range;
+    symbols: Array<{ name: string; qualifiedName: string; kind: string }>;
+    /** Declaration line; single-symbol chunks only */
+    signature?: string;
+    documentation?: string;
+    /** 1-based part number if a symbol was split to fit the token budget */
+    part?: number;
+    text: string;
+    tokens: number;
+}
+
+export interface ChunkOptions {
+    /** Group small symbols of a file and split large ones so chunks stay within this many tokens */
+    maxTokens?: number;
+    /** Counts the tokens of a chunk text; defaults to an estimate of 4 characters per token (see `loadTokenizer`) */
+    countTokens?: (text: string) => number;
+}
+
+interface SymbolEntry {
+    symbol: SymbolInfo;
+    qualifiedName: string;
+}
+
+/**
+ * Turns an analysis into retrieval-ready chunks, one per symbol in document order.
+ *
+ * Symbols without children contribute their full source. Symbols with children (classes,
+ * namespaces, ...) contribute an outline of their own declaration and their members' signatures,
+ * so member bodies are not indexed twice. With `maxTokens`, consecutive symbols of a file are
+ * merged up to the budget and larger symbols are split at line boundaries.
+ *
+ * @param output - Analysis result; source files are read from disk
+ * @param options - Token budget and counter
+ * @returns Chunks in file and document order
+ */
+export function createChunks(output: AnalysisOutput, options: ChunkOptions = {}): Chunk[] {
+    const countTokens = options.countTokens ?? estimateTokenizer.count;
+    const fileLines = new Map<string, string[]>();
+    const linesOf = (file: string) => {
+        let lines = fileLines.get(file);
+        if (!lines) {
+            lines = readFileSync(file, 'utf-8').split('\n');
+            fileLines.set(file, lines);
+        }
+        return lines;
+    };
+
+    const entries: SymbolEntry[] = [];
+    const visit = (symbol: SymbolInfo, parents: string[]) => {
+        const path = [...parents, symbol.name];
+        entries.push({ symbol, qualifiedName: path.join('.') });
+        symbol.children?.forEach((child) => visit(child, path));
+    };
+    output.symbols.forEach((symbol) => visit(symbol, []));
+
+    const chunks = entries.map((entry) => symbolChunk(output, entry, linesOf(entry.symbol.file), countTokens));
+    return options.maxTokens ? fitToBudget(chunks, options.maxTokens, countTokens) : chunks;
+}
+
+function symbolChunk(
+    output: AnalysisOutput,
+    { symbol, qualifiedName }: SymbolEntry,
+    lines: string[],
+    countTokens: (text: string) => number
+): Chunk {
+    const file = relative(output.directory, symbol.file);
+    const { start, end } = symbol.range;
+
+    const source = symbol.children?.length
+        ? [symbol.preview, ...symbol.children.map((child) => `    ${child.preview}`)].join('\n')
+        : lines.slice(start.line, end.line + 1).join('\n');
+    const header = `${qualifiedName} (${symbol.kind}) in ${file}`;
+    const text = [header, symbol.documentation, source].filter(Boolean).join('\n\n');
+
+    return {
+        id: `${file}:${qualifiedName}:${start.line + 1}`,
+        language: output.language,
+        file,
+        range: { start: { line: start.line, character: 0 }, end: { line: end.line, character: 0 } },
+        symbols: [{ name: symbol.name, qualifiedName, kind: symbol.kind }],
+        signature: symbol.preview,
+        ...(symbol.documentation ? { documentation: symbol.documentation } : {}),
+        text,
+        tokens: countTokens(text)
+    };
+}
+
+function fitToBudget(chunks: Chunk[], maxTokens: number, countTokens: (text: string) => number): Chunk[] {
+    const result: Chunk[] = [];
+    let group: Chunk[] = [];
+
+    const flush = () => {
+        if (group.length === 1) {
+            result.push(group[0]);
+        } else if (group.length > 1) {
+            result.push(mergeChunks(group, countTokens));
+        }
+        group = [];
+    };
+
+    for (const chunk of chunks) {
+        if (chunk.tokens > maxTokens) {
+            flush();
+            result.push(...splitChunk(chunk, maxTokens, countTokens));
+            continue;
+        }
+        const groupTokens = group.reduce((sum, member) => sum + member.tokens, 0);
+        if (group.length > 0 && (group[0].file !== chunk.file || groupTokens + chunk.tokens > maxTokens)) {
+            flush();
+        }
+        group.push(chunk);
+    }
+    flush();
+
+    return result;
+}
+
+function mergeChunks(group: Chunk[], countTokens: (text: string) => number): Chunk {
+    const first = group[0];
+    const text = group.map((chunk) => chunk.text).join('\n\n---\n\n');
+    // Nested symbols end before their parent, so the last chunk doesn't necessarily end last
+    const endLine = Math.max(...group.map((chunk) => chunk.range.end.line));
+    return {
+        id: `${first.id}+${group.length - 1}`,
+        language: first.language,
+        file: first.file,
+        range: { start: first.range.start, end: { line: endLine, character: 0 } },
+        symbols: group.flatMap((chunk) => chunk.symbols),
+        text,
+        tokens: countTokens(text)
+    };
+}
+
+/** Splits a chunk's text at line boundaries; the first line (symbol header) is repeated in every part */
+function splitChunk(chunk: Chunk, maxTokens: number, countTokens: (text: string) => number): Chunk[] {
+    const [header, ...body] = chunk.text.split('\n');
+    const headerTokens = countTokens(`${header}\n`);
+    const parts: Chunk[] = [];
+    let current: string[] = [];
+    let currentTokens = headerTokens;
+
+    const flush = () => {
+        if (current.length === 0) {
+            return;
+        }
+        const text = [header, ...current].join('\n');
+        const part = parts.length + 1;
+        parts.push({ ...chunk, id: `${chunk.id}#${part}`, part, text, tokens: countTokens(text) });
+        current = [];
+        currentTokens = headerTokens;
+    };
+
+    for (const line of body) {
+        // Summing per-line counts is approximate but avoids re-tokenizing the part for every line.
+        // A single line longer than the budget becomes its own part rather than being cut.
+        const lineTokens = countTokens(`${line}\n`);
+        if (current.length > 0 && currentTokens + lineTokens > maxTokens) {
+            flush();
+        }
+        current.push(line);
+        currentTokens += lineTokens;
+    }
+    flush();
+
+    return parts;
+}
@@ -0,0 +1,180 @@
+import { relative } from 'node:path';
+import type { AnalysisOutput, DiagnosticInfo, SymbolInfo } from './types';
+
+/** Symbol kinds expected to carry documentation when measuring doc coverage */
+const DOCUMENTABLE_KINDS = new Set(['class', 'interface', 'enum', 'struct', 'function', 'method', 'constructor']);
+
+export interface CiThresholds {
+    /** Minimum share of documentable symbols with documentation, in percent */
+    minDocCoverage?: number;
+    /** Maximum number of error-level diagnostics */
+    maxErrors?: number;
+    /** Maximum number of error-level diagnostics not present in the baseline */
+    maxNewErrors?: number;
+    /** Maximum number of baseline symbols missing from the current analysis */
+    maxRemovedSymbols?: number;
+}
+
+export interface CiCheck {
+    name: 'docCoverage' | 'errors' | 'newErrors' | 'removedSymbols';
+    value: number;
+    threshold: number;
+    passed: boolean;
+    /** Offending items, e.g. removed symbol identifiers */
+    details?: string[];
+}
+
+export interface CiSummary {
+    passed: boolean;
+    language: string;
+    directory: string;
+    stats: {
+        symbols: number;
+        documentable: number;
+        documented: number;
+        docCoverage: number;
+        errors: number;
+        warnings: number;
+    };
+    checks: CiCheck[];
+}
+
+/**
+ * Evaluates an analysis against quality thresholds. Only checks with a configured threshold are run;
+ * the baseline-relative checks require a baseline.
+ *
+ * @param output - Current analysis, including diagnostics
+ * @param thresholds - Limits to enforce
+ * @param baseline - Earlier analysis (e.g. of the target branch) to compare against
+ * @returns The summary; `passed` is false if any check failed
+ */
+export function evaluateCi(output: AnalysisOutput, thresholds: CiThresholds, baseline?: AnalysisOutput): CiSummary {
+    const symbols = flatten(output.symbols);
+    const documentable = symbols.filter((symbol) => DOCUMENTABLE_KINDS.has(symbol.kind));
+    const documented = documentable.length - findUndocumented(output).length;
+    const docCoverage = documentable.length > 0 ? round((documented / documentable.length) * 100) : 100;
+    const diagnostics = output.diagnostics ?? [];
+    const errors = diagnostics.filter((diagnostic) => diagnostic.severity === 'error');
+
+    const checks: CiCheck[] = [];
+    const check = (name: CiCheck['name'], value: number, threshold: number, passed: boolean, details?: string[]) => {
+        checks.push({ name, value, threshold, passed, ...(details?.length ? { details } : {}) });
+    };
+
+    if (thresholds.minDocCoverage !== undefined) {
+        check('docCoverage', docCoverage, thresholds.minDocCoverage, docCoverage >= thresholds.minDocCoverage);
+    }
+    if (thresholds.maxErrors !== undefined) {
+        const details = errors.map((error) => formatDiagnostic(output.directory, error));
+        check('errors', errors.length, thresholds.maxErrors, errors.length <= thresholds.maxErrors, details);
+    }
+    if (baseline && thresholds.maxNewErrors !== undefined) {
+        const added = findNewErrors(output, baseline);
+        const details = added.map((error) => formatDiagnostic(output.directory, error));
+        check('newErrors', added.length, thresholds.maxNewErrors, added.length <= thresholds.maxNewErrors, details);
+    }
+    if (baseline && thresholds.maxRemovedSymbols !== undefined) {
+        const removed = findRemovedSymbols(output, baseline).map((symbol) => symbol.id);
+        const limit = thresholds.maxRemovedSymbols;
+        check('removedSymbols', removed.length, limit, removed.length <= limit, removed);
+    }
+
+    return {
+        passed: checks.every((result) => result.passed),
+        language: output.language,
+        directory: output.directory,
+        stats: {
+            symbols: symbols.length,
+            documentable: documentable.length,
+            documented,
+            docCoverage,
+            errors: errors.length,
+            warnings: diagnostics.filter((diagnostic) => diagnostic.severity === 'warning').length
+        },
+        checks
+    };
+}
+
+/**
+ * @returns Documentable symbols (classes, functions, methods, ...) without documentation
+ */
+export function findUndocumented(output: AnalysisOutput): SymbolInfo[] {
+    return flatten(output.symbols).filter(
+        (symbol) => DOCUMENTABLE_KINDS.has(symbol.kind) && !symbol.documentation?.trim()
+    );
+}
+
+/**
+ * Finds baseline symbols missing from the current analysis. Symbols are identified by
+ * workspace-relative file, enclosing symbols and kind, so analyses of the same code in
+ * different checkout directories compare equal.
+ *
+ * @returns The removed symbols as they appear in the baseline, with their identifier
+ */
+export function findRemovedSymbols(
+    output: AnalysisOutput,
+    baseline: AnalysisOutput
+): Array<{ id: string; symbol: SymbolInfo }> {
+    const current = new Set(identifySymbols(output).map(({ id }) => id));
+    return identifySymbols(baseline).filter(({ id }) => !current.has(id));
+}
+
+/**
+ * Errors are matched by file and message only, as unrelated edits shift line numbers.
+ * Each baseline error absorbs at most one current error with the same key.
+ *
+ * @returns Error-level diagnostics of the current analysis that the baseline doesn't have
+ */
+export function findNewErrors(output: AnalysisOutput, baseline: AnalysisOutput): DiagnosticInfo[] {
+    const key = (directory: string, diagnostic: DiagnosticInfo) =>
+        `${relative(directory, diagnostic.file)}\0${diagnostic.message}`;
+
+    const known = new Map<string, number>();
+    for (const diagnostic of baseline.diagnostics ?? []) {
+        if (diagnostic.severity === 'error') {
+            const id = key(baseline.directory, diagnostic);
+            known.set(id, (known.get(id) ?? 0) + 1);
+        }
+    }
+
+    return (output.diagnostics ?? []).filter((diagnostic) => {
+        if (diagnostic.severity !== 'error') {
+            return false;
+        }
+        const id = key(output.directory, diagnostic);
+        const count = known.get(id) ?? 0;
+        if (count > 0) {
+            known.set(id, count - 1);
+            return false;
+        }
+        return true;
+    });
+}
+
+function flatten(symbols: SymbolInfo[]): SymbolInfo[] {
+    return symbols.flatMap((symbol) => [symbol, ...flatten(symbol.children ?? [])]);
+}
+
+/**
+ * @returns Every symbol with an identifier of workspace-relative file, enclosing symbols and kind,
+ * e.g. `src/user.ts:UserService.save (method)`
+ */
+export function identifySymbols(output: AnalysisOutput): Array<{ id: string; symbol: SymbolInfo }> {
+    const ids: Array<{ id: string; symbol: SymbolInfo }> = [];
+    const visit = (symbol: SymbolInfo, parents: string[]) => {
+        const path = [...parents, symbol.name];
+        ids.push({ id: `${relative(output.directory, symbol.file)}:${path.join('.')} (${symbol.kind})`, symbol });
+        symbol.children?.forEach((child) => visit(child, path));
+    };
+    output.symbols.forEach((symbol) => visit(symbol, []));
+    return ids;
+}
+
+function formatDiagnostic(directory: string, diagnostic: DiagnosticInfo): string {
+    const { line, character } = diagnostic.range.start;
+    return `${relative(directory, diagnostic.file)}:${line + 1}:${character + 1}: ${diagnostic.message}`;
+}
+
+function round(value: number): number {
+    return Math.round(value * 10) / 10;
+}
@@ -0,0 +1,75 @@
+import type { SupportedLanguage } from './types';
+
+// Lines between the comments and the declaration that don't separate them: attributes and decorators
+const ATTRIBUTE_LINE = /^(#!?\[|@|\[)/;
+
+/**
+ * Extracts the regular comments directly above a declaration: `//` and `/* *\/` comments, or `#`
+ * comments in Python. Doc comments (`///`, `//!`, `/** *\/`, `/*! *\/`), attributes and decorators
+ * between them and the declaration are skipped; a blank line or code ends the comments.
+ *
+ * @param lines - Source lines of the file
+ * @param line - 0-based line of the declaration
+ * @param language - Language of the file
+ * @returns The comment text without comment markers, or undefined if there is none
+ */
+export function extractLeadingComments(
+    lines: string[],
+    line: number,
+    language: SupportedLanguage
+): string | undefined {
+    const comments: string[] = [];
+    let current = line - 1;
+    while (current >= 0) {
+        const text = lines[current].trim();
+        if (text === '') {
+            break;
+        }
+        if (text.endsWith('*/') && language !== 'python') {
+            const start = blockCommentStart(lines, current);
+            if (start === undefined) {
+                break;
+            }
+            const opening = lines[start].trim();
+            if (!opening.startsWith('/**') && !opening.startsWith('/*!')) {
+                comments.unshift(...cleanBlockComment(lines.slice(start, current + 1)));
+            } else if (comments.length > 0) {
+                break;
+            }
+            current = start - 1;
+        } else if (language === 'python' ? /^#(?!!)/.test(text) : /^\/\/(?![/!])/.test(text)) {
+            comments.unshift(text.replace(language === 'python' ? /^#\s?/ : /^\/\/\s?/, ''));
+            current--;
+        } else if (/^(\/\/\/|\/\/!)/.test(text) || ATTRIBUTE_LINE.test(text)) {
+            // Doc comments and attributes belong to the declaration; plain comments may sit above them
+            if (comments.length > 0) {
+                break;
+            }
+            current--;
+        } else {
+            break;
+        }
+    }
+    const text = comments.join('\n').trim();
+    return text === '' ? undefined : text;
+}
+
+/** Line of the `/*` opening the block comment that ends on the given line */
+function blockCommentStart(lines: string[], end: number): number | undefined {
+    for (let line = end; line >= 0 && end - line < 50; line--) {
+        if (lines[line].includes('/*')) {
+            return line;
+        }
+    }
+    return undefined;
+}
+
+function cleanBlockComment(lines: string[]): string[] {
+    return lines.map((line) =>
+        line
+            .trim()
+            .replace(/^\/\*+\s?/, '')
+            .replace(/\s*\*+\/$/, '')
+            .replace(/^\*\s?/, '')
+    );
+}
@@ -0,0 +1,80 @@
+import { exec } from 'node:child_process';
+import { createHash } from 'node:crypto';
+import { existsSync, mkdirSync, readdirSync, statSync } from 'node:fs';
+import { homedir } from 'node:os';
+import { dirname, join } from 'node:path';
+import { promisify } from 'node:util';
+import type { Logger } from './logger';
+
+const execAsync = promisify(exec);
+
+// Build directories where CMake, Meson and IDEs commonly put compile_commands.json
+const BUILD_DIRECTORIES = ['.', 'build', 'out', 'builddir', 'build/debug', 'build/release', 'out/build'];
+
+/**
+ * Finds an existing `compile_commands.json` in a project root or one of its usual build directories,
+ * including CLion's `cmake-build-*`.
+ *
+ * @param directory - Project root
+ * @returns Directory containing the compilation database, for clangd's `--compile-commands-dir`
+ */
+export function findCompileCommands(directory: string): string | undefined {
+    const candidates = [
+        ...BUILD_DIRECTORIES,
+        ...readdirSync(directory).filter((entry) => entry.startsWith('cmake-build-'))
+    ].map((candidate) => join(directory, candidate));
+    return candidates.find((candidate) => existsSync(join(candidate, 'compile_commands.json')));
+}
+
+/**
+ * Resolves a user-supplied compilation database path.
+ *
+ * @param path - A `compile_commands.json` file or the directory containing it
+ * @returns Directory containing the compilation database
+ */
+export function resolveCompileCommands(path: string): string {
+    const directory = existsSync(path) && statSync(path).isFile() ? dirname(path) : path;
+    if (!existsSync(join(directory, 'compile_commands.json'))) {
+        throw new Error(`No compile_commands.json found at ${path}`);
+    }
+    return directory;
+}
+
+/**
+ * Generates a compilation database with the project's build system: CMake and Meson configure a
+ * build directory under ~/.lsp-cli/compile-commands without building; for Makefile projects,
+ * Bear records the compiler calls of a full `make` run.
+ *
+ * @param directory - Project root
+ * @param logger - Logger for progress
+ * @returns Directory containing the generated `compile_commands.json`
+ */
+export async function generateCompileCommands(directory: string, logger: Logger): Promise<string> {
+    const hash = createHash('sha256').update(directory).digest('hex').slice(0, 16);
+    const buildDir = join(homedir(), '.lsp-cli', 'compile-commands', hash);
+    mkdirSync(buildDir, { recursive: true });
+
+    let command: string;
+    if (existsSync(join(directory, 'CMakeLists.txt'))) {
+        command = `cmake -S "${directory}" -B "${buildDir}" -DCMAKE_EXPORT_COMPILE_COMMANDS=ON`;
+    } else if (existsSync(join(directory, 'meson.build'))) {
+        command = existsSync(join(buildDir, 'build.ninja'))
+            ? `meson setup --reconfigure "${buildDir}" "${directory}"`
+            : `meson setup "${buildDir}" "${directory}"`;
+    } else if (existsSync(join(directory, 'Makefile')) || existsSync(join(directory, 'makefile'))) {
+        // -B rebuilds everything so Bear sees every compiler call, not just outdated targets
+        command = `bear --output "${join(buildDir, 'compile_commands.json')}" -- make -B`;
+    } else {
+        throw new Error('Cannot generate compile_commands.json: no CMakeLists.txt, meson.build or Makefile found');
+    }
+
+    logger.info(`Generating compile_commands.json: ${command}`);
+    try {
+        await execAsync(command, { cwd: directory, maxBuffer: 64 * 1024 * 1024 });
+    } catch (error) {
+        const stderr = (error as { stderr?: string }).stderr?.trim();
+        throw new Error(`${command} failed${stderr ? `:\n${stderr}` : ''}`);
+    }
+
+    return resolveCompileCommands(buildDir);
+}
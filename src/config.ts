@@ -0,0 +1,418 @@
+import { existsSync, readFileSync } from 'node:fs';
+import { homedir } from 'node:os';
+import { dirname, join, resolve } from 'node:path';
+import type { Command, Option } from 'commander';
+import type { Logger } from './logger';
+
+export type ConfigValue = string | number | boolean | ConfigValue[] | ConfigTable;
+
+export interface ConfigTable {
+    [key: string]: ConfigValue;
+}
+
+/** Name of the project configuration file, looked up in the analyzed directory and its parents */
+export const PROJECT_CONFIG_FILE = '.lsp-cli.toml';
+
+// Decimal integers and floats; TOML allows underscores only between digits and no leading zeros
+const TOML_NUMBER = /^[+-]?(?:0|[1-9](?:_?\d)*)(?:\.\d(?:_?\d)*)?(?:[eE][+-]?\d(?:_?\d)*)?(?![\w.])/;
+
+/**
+ * @returns Path of the user configuration file, `$XDG_CONFIG_HOME/lsp-cli/config.toml`
+ * (by default `~/.config/lsp-cli/config.toml`)
+ */
+export function userConfigPath(): string {
+    return join(process.env.XDG_CONFIG_HOME || join(homedir(), '.config'), 'lsp-cli', 'config.toml');
+}
+
+/**
+ * Loads the user configuration and the nearest project configuration, the project's values taking
+ * precedence over the user's. Tables are merged key by key, arrays are replaced as a whole.
+ *
+ * @param directory - Analyzed directory; `.lsp-cli.toml` is searched from here upwards
+ * @returns Merged configuration, and the files it was read from, user file first
+ */
+export function loadConfig(directory: string): { config: ConfigTable; files: string[] } {
+    const files = [userConfigPath(), findProjectConfig(directory)].filter(
+        (file): file is string => file !== undefined && existsSync(file)
+    );
+    const config = files
+        .map((file) => parseToml(readFileSync(file, 'utf-8'), file))
+        .reduce((merged, layer) => mergeTables(merged, layer), {} as ConfigTable);
+    return { config, files };
+}
+
+/**
+ * Sets the options of a command that weren't given on the command line from the configuration:
+ * top-level keys set the root command's options, keys of a table named after a subcommand (e.g.
+ * `[ci]`) that subcommand's options. Keys are long flag names without dashes, e.g.
+ * `include-body = 40` or `cache = false` for `--no-cache`. With `--profile <name>`, the keys of
+ * `[profile.<name>]` are applied on top, to the running subcommand's options or else the root's.
+ * Options given on the command line win.
+ *
+ * @param command - Command about to run
+ * @param config - Configuration as returned by {@link loadConfig}
+ * @param logger - Warns about keys that match no option
+ * @throws Error for values of the wrong type and unknown profiles
+ */
+export function applyConfig(command: Command, config: ConfigTable, logger: Logger): void {
+    let root = command;
+    while (root.parent) {
+        root = root.parent;
+    }
+    const isSubcommand = command !== root;
+
+    applyTable([root], config, '', logger);
+    const commandTable = config[command.name()];
+    if (isSubcommand && isTable(commandTable)) {
+        applyTable([command], commandTable, ` in [${command.name()}]`, logger);
+    }
+
+    const name = root.getOptionValue('profile') as string | undefined;
+    if (name === undefined) {
+        return;
+    }
+    const profiles = isTable(config.profile) ? config.profile : {};
+    const profile = profiles[name];
+    if (!isTable(profile)) {
+        const defined = profileNames(config).join(', ') || 'none';
+        throw new Error(`Unknown profile '${name}' (defined: ${defined})`);
+    }
+    applyTable(isSubcommand ? [command, root] : [root], profile, ` in [profile.${name}]`, logger);
+    const profileCommandTable = profile[command.name()];
+    if (isSubcommand && isTable(profileCommandTable)) {
+        applyTable([command], profileCommandTable, ` in [profile.${name}.${command.name()}]`, logger);
+    }
+}
+
+/**
+ * @param config - Configuration as returned by {@link loadConfig}
+ * @returns Names of the profiles defined in the configuration
+ */
+export function profileNames(config: ConfigTable): string[] {
+    return isTable(config.profile) ? Object.keys(config.profile) : [];
+}
+
+/** Sets each key on the first of the commands that has a matching option, unless given on the command line */
+function applyTable(commands: Command[], values: ConfigTable, scope: string, logger: Logger): void {
+    for (const [key, value] of Object.entries(values)) {
+        if (isTable(value)) {
+            // Tables of subcommands and profiles
+            continue;
+        }
+        let target: Command | undefined;
+        let option: Option | undefined;
+        for (const candidate of commands) {
+            option = candidate.options.find((defined) =>
+                defined.negate ? defined.long === `--no-${key}` : defined.long === `--${key}`
+            );
+            if (option) {
+                target = candidate;
+                break;
+            }
+        }
+        if (!target || !option) {
+            logger.warn(`Ignoring unknown configuration key '${key}'${scope}`);
+            continue;
+        }
+        const attribute = option.attributeName();
+        if (target.getOptionValueSource(attribute) !== 'cli') {
+            target.setOptionValueWithSource(attribute, optionValue(option, key, value), 'config');
+        }
+    }
+}
+
+/**
+ * Parses the subset of TOML used by configuration files: tables, dotted keys, basic and literal
+ * strings, numbers, booleans, arrays and inline tables. Multi-line strings, dates and arrays of
+ * tables are not supported.
+ *
+ * @param text - Content of the file
+ * @param source - File name for error messages
+ * @returns The parsed document
+ * @throws Error naming the file and line of a syntax error
+ */
+export function parseToml(text: string, source: string): ConfigTable {
+    const root: ConfigTable = {};
+    let table = root;
+    let pos = 0;
+    // Tables with a [header] so far, and inline tables, which can't be added to later
+    const headers = new Set<string>();
+    const inlineTables = new WeakSet<ConfigTable>();
+
+    const fail = (message: string): never => {
+        throw new Error(`${source}:${text.slice(0, pos).split('\n').length}: ${message}`);
+    };
+    const skipSpace = (newlines: boolean) => {
+        while (pos < text.length) {
+            const char = text[pos];
+            if (char === ' ' || char === '\t' || char === '\r' || (newlines && char === '\n')) {
+                pos++;
+            } else if (char === '#') {
+                while (pos < text.length && text[pos] !== '\n') {
+                    pos++;
+                }
+            } else {
+                break;
+            }
+        }
+    };
+
+    const parseString = (): string => {
+        const quote = text[pos];
+        if (text.startsWith(quote.repeat(3), pos)) {
+            fail('Multi-line strings are not supported');
+        }
+        let value = '';
+        for (pos++; pos < text.length && text[pos] !== quote; pos++) {
+            if (text[pos] === '\n') {
+                fail('Unterminated string');
+            }
+            if (quote === "'" || text[pos] !== '\\') {
+                value += text[pos];
+                continue;
+            }
+            const escape = text[++pos];
+            const simple: Record<string, string> = {
+                b: '\b',
+                t: '\t',
+                n: '\n',
+                f: '\f',
+                r: '\r',
+                '"': '"',
+                '\\': '\\'
+            };
+            const digits = escape === 'u' ? 4 : escape === 'U' ? 8 : 0;
+            const hex = text.slice(pos + 1, pos + 1 + digits);
+            if (escape in simple) {
+                value += simple[escape];
+            } else if (digits > 0 && hex.length === digits && /^[0-9a-fA-F]+$/.test(hex)) {
+                const code = Number.parseInt(hex, 16);
+                // Surrogates alone would make invalid UTF-16
+                if ((code >= 0xd800 && code <= 0xdfff) || code > 0x10ffff) {
+                    fail(`Escape \\${escape}${hex} is not a Unicode scalar value`);
+                }
+                value += String.fromCodePoint(code);
+                pos += digits;
+            } else {
+                fail(`Invalid escape \\${escape}`);
+            }
+        }
+        if (pos >= text.length) {
+            fail('Unterminated string');
+        }
+        pos++;
+        return value;
+    };
+
+    const parseKey = (): string[] => {
+        const parts: string[] = [];
+        for (;;) {
+            skipSpace(false);
+            if (text[pos] === '"' || text[pos] === "'") {
+                parts.push(parseString());
+            } else {
+                const bare = /^[A-Za-z0-9_-]+/.exec(text.slice(pos));
+                if (!bare) {
+                    return fail('Expected a key');
+                }
+                parts.push(bare[0]);
+                pos += bare[0].length;
+            }
+            skipSpace(false);
+            if (text[pos] !== '.') {
+                return parts;
+            }
+            pos++;
+        }
+    };
+
+    const parseValue = (): ConfigValue => {
+        skipSpace(false);
+        const char = text[pos];
+        if (char === '"' || char === "'") {
+            return parseString();
+        }
+        if (char === '[') {
+            const values: ConfigValue[] = [];
+            pos++;
+            for (;;) {
+                skipSpace(true);
+                if (text[pos] === ']') {
+                    pos++;
+                    return values;
+                }
+                values.push(parseValue());
+                skipSpace(true);
+                if (text[pos] === ',') {
+                    pos++;
+                } else if (text[pos] !== ']') {
+                    fail("Expected ',' or ']' in array");
+                }
+            }
+        }
+        if (char === '{') {
+            const inline: ConfigTable = {};
+            pos++;
+            skipSpace(false);
+            while (text[pos] !== '}') {
+                const key = parseKey();
+                if (text[pos] !== '=') {
+                    fail("Expected '=' after key");
+                }
+                pos++;
+                assign(inline, key, parseValue());
+                skipSpace(false);
+                if (text[pos] === ',') {
+                    pos++;
+                    skipSpace(false);
+                } else if (text[pos] !== '}') {
+                    fail("Expected ',' or '}' in inline table");
+                }
+            }
+            pos++;
+            inlineTables.add(inline);
+            return inline;
+        }
+        const rest = text.slice(pos);
+        const boolean = /^(?:true|false)(?![\w.])/.exec(rest);
+        if (boolean) {
+            pos += boolean[0].length;
+            return boolean[0] === 'true';
+        }
+        const number = TOML_NUMBER.exec(rest);
+        if (number) {
+            pos += number[0].length;
+            return Number(number[0].replace(/_/g, ''));
+        }
+        if (/^[+-]?0[\d_]/.test(rest)) {
+            return fail('Numbers must not have leading zeros');
+        }
+        if (/^[+-]?[\d_][\w.+-]*_/.test(rest)) {
+            return fail('Underscores in numbers must be between digits');
+        }
+        return fail('Expected a value');
+    };
+
+    const assign = (target: ConfigTable, key: string[], value: ConfigValue) => {
+        const parent = tableAt(target, key.slice(0, -1));
+        const name = key[key.length - 1];
+        if (name in parent) {
+            fail(`Duplicate key '${key.join('.')}'`);
+        }
+        parent[name] = value;
+    };
+    const tableAt = (target: ConfigTable, path: string[]): ConfigTable => {
+        let current = target;
+        for (const part of path) {
+            const next = current[part] ?? {};
+            if (!isTable(next)) {
+                return fail(`'${part}' is not a table`);
+            }
+            if (inlineTables.has(next)) {
+                return fail(`Inline table '${part}' cannot be extended`);
+            }
+            current[part] = next;
+            current = next;
+        }
+        return current;
+    };
+
+    for (;;) {
+        skipSpace(true);
+        if (pos >= text.length) {
+            return root;
+        }
+        if (text[pos] === '[') {
+            if (text[pos + 1] === '[') {
+                fail('Arrays of tables are not supported');
+            }
+            pos++;
+            const path = parseKey();
+            if (text[pos] !== ']') {
+                fail("Expected ']' after table name");
+            }
+            pos++;
+            const name = JSON.stringify(path);
+            if (headers.has(name)) {
+                fail(`Table '${path.join('.')}' is defined twice`);
+            }
+            headers.add(name);
+            table = tableAt(root, path);
+        } else {
+            const key = parseKey();
+            if (text[pos] !== '=') {
+                fail("Expected '=' after key");
+            }
+            pos++;
+            assign(table, key, parseValue());
+        }
+        skipSpace(false);
+        if (pos < text.length && text[pos] !== '\n') {
+            fail('Expected a new line');
+        }
+    }
+}
+
+/**
+ * @param value - A configuration value
+ * @returns Whether the value is a table
+ */
+export function isTable(value: ConfigValue | undefined): value is ConfigTable {
+    return typeof value === 'object' && !Array.isArray(value);
+}
+
+function findProjectConfig(directory: string): string | undefined {
+    let current = resolve(directory);
+    for (;;) {
+        const candidate = join(current, PROJECT_CONFIG_FILE);
+        if (existsSync(candidate)) {
+            return candidate;
+        }
+        if (dirname(current) === current) {
+            return undefined;
+        }
+        current = dirname(current);
+    }
+}
+
+function mergeTables(base: ConfigTable, override: ConfigTable): ConfigTable {
+    const merged: ConfigTable = { ...base };
+    for (const [key, value] of Object.entries(override)) {
+        const existing = merged[key];
+        merged[key] = isTable(existing) && isTable(value) ? mergeTables(existing, value) : value;
+    }
+    return merged;
+}
+
+/** Converts a configuration value the way the option's argument would be parsed on the command line */
+function optionValue(option: Option, key: string, value: ConfigValue): unknown {
+    const parse = (text: string, previous: unknown) => {
+        try {
+            return option.parseArg ? option.parseArg(text, previous) : text;
+        } catch (error) {
+            throw new Error(`Configuration key '${key}': ${error instanceof Error ? error.message : String(error)}`);
+        }
+    };
+    const isFlag = !option.required && !option.optional;
+
+    if (isTable(value)) {
+        throw new Error(`Configuration key '${key}' expects a value, not a table`);
+    }
+    if (typeof value === 'boolean') {
+        if (!isFlag && !(option.optional && value)) {
+            throw new Error(`Configuration key '${key}' expects a value, not a boolean`);
+        }
+        return value;
+    }
+    if (isFlag) {
+        throw new Error(`Configuration key '${key}' expects true or false`);
+    }
+    // Repeatable options collect each array element
+    if (Array.isArray(option.defaultValue)) {
+        return (Array.isArray(value) ? value : [value]).reduce<unknown>(
+            (collected, item) => parse(String(item), collected),
+            []
+        );
+    }
+    return parse(Array.isArray(value) ? value.join(',') : String(value), undefined);
+}
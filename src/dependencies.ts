@@ -0,0 +1,388 @@
+import { existsSync, readFileSync } from 'node:fs';
+import { basename, dirname, join, relative } from 'node:path';
+import type { Logger } from './logger';
+import type { AnalysisOutput, DependencyUsage, SymbolInfo } from './types';
+import { getAllFiles, SOURCE_EXTENSIONS } from './utils';
+
+/** A third-party package declared by the project */
+interface DeclaredDependency {
+    /** Package name in the registry */
+    name: string;
+    /** Locked or installed version; the declared requirement if neither is known */
+    version?: string;
+    ecosystem: DependencyUsage['ecosystem'];
+}
+
+/** What a file imports: local identifiers and path roots that refer to dependencies */
+interface FileImports {
+    /** Identifier bound by an import (or usable as a path root, for crates) to the package it comes from */
+    bindings: Map<string, DeclaredDependency>;
+    /** Packages imported without bindings, e.g. side-effect imports or glob imports */
+    imported: Set<DeclaredDependency>;
+}
+
+// Cargo.toml tables declaring dependencies, including platform-specific ones
+const CARGO_DEPENDENCY_TABLE = /^(?:workspace\.|target\..+\.)?(?:dev-|build-)?dependencies$/;
+
+/**
+ * Maps the analyzed code to the third-party packages it uses: Cargo dependencies for Rust (versions
+ * from `Cargo.lock`) and npm dependencies for TypeScript/JavaScript (versions from `node_modules`).
+ * Sets `dependencies` on every symbol whose source references an imported package, and
+ * `output.dependencies` to one entry per used package with the files importing it.
+ *
+ * @param output - Analysis to enrich in place; source files are read from disk
+ * @param logger - Logger for unsupported languages
+ */
+export function addDependencyInfo(output: AnalysisOutput, logger: Logger): void {
+    const declared =
+        output.language === 'rust'
+            ? readCargoDependencies(output.directory)
+            : output.language === 'typescript'
+              ? readNpmDependencies(output.directory)
+              : undefined;
+    if (!declared) {
+        logger.warn('Dependency mapping supports Rust (Cargo) and TypeScript/JavaScript (npm) projects only');
+        return;
+    }
+
+    const imports = new Map<string, FileImports>();
+    const importsOf = (file: string) => {
+        let fileImports = imports.get(file);
+        if (!fileImports) {
+            const content = existsSync(file) ? readFileSync(file, 'utf-8') : '';
+            fileImports =
+                output.language === 'rust'
+                    ? parseRustImports(content, declared)
+                    : parseModuleImports(content, declared);
+            imports.set(file, fileImports);
+        }
+        return fileImports;
+    };
+
+    const usage = new Map<DeclaredDependency, { files: Set<string>; symbols: number }>();
+    const usageOf = (dependency: DeclaredDependency) => {
+        let entry = usage.get(dependency);
+        if (!entry) {
+            entry = { files: new Set(), symbols: 0 };
+            usage.set(dependency, entry);
+        }
+        return entry;
+    };
+
+    // Files without symbols (entry points, scripts) still count as importing a package
+    for (const file of getAllFiles(output.directory, SOURCE_EXTENSIONS[output.language])) {
+        const fileImports = importsOf(file);
+        for (const dependency of [...fileImports.bindings.values(), ...fileImports.imported]) {
+            usageOf(dependency).files.add(relative(output.directory, file));
+        }
+    }
+
+    const lines = new Map<string, string[]>();
+    const annotate = (symbol: SymbolInfo) => {
+        const fileImports = importsOf(symbol.file);
+        if (fileImports.bindings.size > 0) {
+            if (!lines.has(symbol.file)) {
+                lines.set(symbol.file, existsSync(symbol.file) ? readFileSync(symbol.file, 'utf-8').split('\n') : []);
+            }
+            const text = lines
+                .get(symbol.file)!
+                .slice(symbol.range.start.line, symbol.range.end.line + 1)
+                .join('\n');
+            const used = referencedDependencies(text, fileImports.bindings);
+            if (used.length > 0) {
+                symbol.dependencies = used.map((dependency) => dependency.name).sort();
+                for (const dependency of used) {
+                    usageOf(dependency).symbols++;
+                }
+            }
+        }
+        symbol.children?.forEach(annotate);
+    };
+    output.symbols.forEach(annotate);
+
+    output.dependencies = [...usage]
+        .map(([dependency, { files, symbols }]) => ({
+            name: dependency.name,
+            ...(dependency.version && { version: dependency.version }),
+            ecosystem: dependency.ecosystem,
+            files: [...files].sort(),
+            symbols
+        }))
+        .sort((a, b) => a.name.localeCompare(b.name));
+}
+
+/**
+ * Renders which code uses which third-party packages, for upgrade planning.
+ *
+ * @param output - Analysis enriched by `addDependencyInfo`
+ * @param name - Package to list the using files and symbols of; without it, all packages with counts
+ * @returns Plain-text report
+ */
+export function formatDependencyReport(output: AnalysisOutput, name?: string): string {
+    const dependencies = output.dependencies ?? [];
+    const heading = (dependency: DependencyUsage) =>
+        `${dependency.name}${dependency.version ? ` ${dependency.version}` : ''} (${dependency.ecosystem})`;
+
+    if (!name) {
+        return dependencies
+            .map((dependency) => {
+                const counts = `${dependency.files.length} files, ${dependency.symbols} symbols`;
+                return `${heading(dependency)}: ${counts}`;
+            })
+            .join('\n');
+    }
+
+    const dependency = dependencies.find((candidate) => candidate.name === name);
+    if (!dependency) {
+        throw new Error(`Package '${name}' is not used by the analyzed code`);
+    }
+
+    // List the innermost symbols using the package; their parents add no information
+    const symbolsByFile = new Map<string, SymbolInfo[]>();
+    const collect = (symbol: SymbolInfo) => {
+        if (!symbol.dependencies?.includes(name)) {
+            return;
+        }
+        if (!symbol.children?.some((child) => child.dependencies?.includes(name))) {
+            const file = relative(output.directory, symbol.file);
+            symbolsByFile.set(file, [...(symbolsByFile.get(file) ?? []), symbol]);
+        }
+        symbol.children?.forEach(collect);
+    };
+    output.symbols.forEach(collect);
+
+    const lines = [heading(dependency)];
+    for (const file of dependency.files) {
+        lines.push(`  ${file}`);
+        for (const symbol of symbolsByFile.get(file) ?? []) {
+            lines.push(`    ${symbol.range.start.line + 1}: ${symbol.name} (${symbol.kind})`);
+        }
+    }
+    return lines.join('\n');
+}
+
+/** Dependencies whose bound identifiers occur as whole words in some source text */
+function referencedDependencies(text: string, bindings: Map<string, DeclaredDependency>): DeclaredDependency[] {
+    const used = new Set<DeclaredDependency>();
+    const identifiers = [...bindings.keys()].map((identifier) => identifier.replace(/\$/g, '\\$'));
+    const pattern = new RegExp(`(?<![\\w$])(?:${identifiers.join('|')})(?![\\w$])`, 'g');
+    for (const [identifier] of text.matchAll(pattern)) {
+        used.add(bindings.get(identifier)!);
+    }
+    return [...used];
+}
+
+/**
+ * Reads the registry and git dependencies of all Cargo manifests below a directory, keyed by the
+ * name they are imported as (renames applied, `-` replaced by `_`). Path dependencies are local code
+ * and skipped.
+ */
+function readCargoDependencies(directory: string): Map<string, DeclaredDependency> {
+    const locked = readCargoLock(directory);
+    const dependencies = new Map<string, DeclaredDependency>();
+
+    for (const manifest of getAllFiles(directory, ['.toml']).filter((file) => basename(file) === 'Cargo.toml')) {
+        for (const { key, packageName, local } of cargoManifestDependencies(readFileSync(manifest, 'utf-8'))) {
+            const crateName = key.replace(/-/g, '_');
+            if (local || dependencies.has(crateName)) {
+                continue;
+            }
+            dependencies.set(crateName, {
+                name: packageName,
+                version: locked.get(packageName)?.join(', '),
+                ecosystem: 'cargo'
+            });
+        }
+    }
+    return dependencies;
+}
+
+/** Versions of the non-local packages in the nearest Cargo.lock at or above a directory */
+function readCargoLock(directory: string): Map<string, string[]> {
+    const versions = new Map<string, string[]>();
+    for (let dir = directory; ; dir = dirname(dir)) {
+        const lockFile = join(dir, 'Cargo.lock');
+        if (existsSync(lockFile)) {
+            // Every [[package]] block has name and version; only external packages have a source
+            for (const block of readFileSync(lockFile, 'utf-8').split('[[package]]').slice(1)) {
+                const name = /^name\s*=\s*"([^"]+)"/m.exec(block)?.[1];
+                const version = /^version\s*=\s*"([^"]+)"/m.exec(block)?.[1];
+                if (name && version && /^source\s*=/m.test(block)) {
+                    versions.set(name, [...(versions.get(name) ?? []), version]);
+                }
+            }
+            return versions;
+        }
+        if (dirname(dir) === dir) {
+            return versions;
+        }
+    }
+}
+
+/**
+ * Extracts the entries of the dependency tables of a Cargo.toml, both inline
+ * (`serde = { version = "1", features = [...] }`) and as subtables (`[dependencies.serde]`).
+ */
+function cargoManifestDependencies(manifest: string): Array<{ key: string; packageName: string; local: boolean }> {
+    const entries: Array<{ key: string; packageName: string; local: boolean }> = [];
+    let table = '';
+    let subtable: { key: string; packageName: string; local: boolean } | undefined;
+
+    for (const rawLine of manifest.split('\n')) {
+        const line = rawLine.replace(/\s#.*$/, '').trim();
+        const header = /^\[\[?([^\]]+)\]\]?$/.exec(line);
+        if (header) {
+            table = header[1].replace(/["\s]/g, '');
+            const parent = table.slice(0, table.lastIndexOf('.'));
+            subtable = undefined;
+            if (CARGO_DEPENDENCY_TABLE.test(parent)) {
+                const key = table.slice(parent.length + 1);
+                subtable = { key, packageName: key, local: false };
+                entries.push(subtable);
+            }
+            continue;
+        }
+
+        const assignment = /^"?([\w-]+)"?\s*=\s*(.+)$/.exec(line);
+        if (!assignment) {
+            continue;
+        }
+        const [, key, value] = assignment;
+        if (subtable) {
+            if (key === 'package') {
+                subtable.packageName = value.replace(/"/g, '');
+            } else if (key === 'path') {
+                subtable.local = true;
+            }
+        } else if (CARGO_DEPENDENCY_TABLE.test(table)) {
+            entries.push({
+                key,
+                packageName: /\bpackage\s*=\s*"([^"]+)"/.exec(value)?.[1] ?? key,
+                local: /\bpath\s*=/.test(value)
+            });
+        }
+    }
+    return entries;
+}
+
+/**
+ * Reads the dependencies of a directory's package.json, keyed by package name. Workspace, file and
+ * link dependencies are local code and skipped.
+ */
+function readNpmDependencies(directory: string): Map<string, DeclaredDependency> {
+    const dependencies = new Map<string, DeclaredDependency>();
+    const manifestPath = join(directory, 'package.json');
+    if (!existsSync(manifestPath)) {
+        return dependencies;
+    }
+
+    const manifest = JSON.parse(readFileSync(manifestPath, 'utf-8'));
+    for (const field of ['dependencies', 'devDependencies', 'peerDependencies', 'optionalDependencies']) {
+        for (const [name, requirement] of Object.entries<string>(manifest[field] ?? {})) {
+            if (dependencies.has(name) || /^(workspace|file|link):/.test(requirement)) {
+                continue;
+            }
+            const installed = join(directory, 'node_modules', name, 'package.json');
+            const version = existsSync(installed) ? JSON.parse(readFileSync(installed, 'utf-8')).version : requirement;
+            dependencies.set(name, { name, version, ecosystem: 'npm' });
+        }
+    }
+    return dependencies;
+}
+
+/**
+ * Finds the crates a Rust file refers to: identifiers bound by `use` and `extern crate`, and crate
+ * names used as path roots (`serde_json::to_string`).
+ */
+function parseRustImports(content: string, crates: Map<string, DeclaredDependency>): FileImports {
+    const imports: FileImports = { bindings: new Map(), imported: new Set() };
+
+    for (const [, root] of content.matchAll(/(?<![\w:])(\w+)::/g)) {
+        const dependency = crates.get(root);
+        if (dependency) {
+            imports.bindings.set(root, dependency);
+        }
+    }
+    for (const [, name, alias] of content.matchAll(/\bextern\s+crate\s+(\w+)(?:\s+as\s+(\w+))?\s*;/g)) {
+        const dependency = crates.get(name);
+        if (dependency) {
+            imports.bindings.set(alias ?? name, dependency);
+        }
+    }
+    for (const [, root, tree] of content.matchAll(/\buse\s+(?:::)?(\w+)((?:\s*::[^;]*)?);/g)) {
+        const dependency = crates.get(root);
+        if (!dependency) {
+            continue;
+        }
+        if (tree.includes('*')) {
+            // Glob imports bind names we can't see
+            imports.imported.add(dependency);
+        }
+        // The last segment of each path in the tree is bound, unless renamed with `as`
+        const leaves = tree
+            .replace(/\w+\s+as\s+(\w+)/g, '$1')
+            .replace(/\w+\s*::/g, '')
+            .match(/\w+/g);
+        for (const leaf of leaves ?? [root]) {
+            if (leaf !== 'self' && leaf !== '_') {
+                imports.bindings.set(leaf, dependency);
+            }
+        }
+    }
+    return imports;
+}
+
+/**
+ * Finds the npm packages a TypeScript/JavaScript file imports: ES imports and re-exports,
+ * `require` calls and dynamic `import()`, with the identifiers they bind.
+ */
+function parseModuleImports(content: string, packages: Map<string, DeclaredDependency>): FileImports {
+    const imports: FileImports = { bindings: new Map(), imported: new Set() };
+    const packageOf = (specifier: string) => {
+        const segments = specifier.split('/');
+        return packages.get(specifier.startsWith('@') ? segments.slice(0, 2).join('/') : segments[0]);
+    };
+
+    // Any import, re-export, require or import() marks the file as using the package
+    const specifierPattern = /(?:\bfrom|\bimport|\brequire\s*\(|\bimport\s*\()\s*['"]([^'"]+)['"]/g;
+    for (const [, specifier] of content.matchAll(specifierPattern)) {
+        const dependency = packageOf(specifier);
+        if (dependency) {
+            imports.imported.add(dependency);
+        }
+    }
+
+    const bind = (clause: string, dependency: DeclaredDependency) => {
+        const named = /\{([^}]*)\}/.exec(clause);
+        for (const specifier of named?.[1].split(',') ?? []) {
+            const local = specifier.replace(/^\s*type\s+/, '').split(/\s+as\s+/).pop()?.trim();
+            if (local) {
+                imports.bindings.set(local, dependency);
+            }
+        }
+        const rest = clause.replace(/\{[^}]*\}/, '');
+        const namespace = /\*\s*as\s+([\w$]+)/.exec(rest);
+        if (namespace) {
+            imports.bindings.set(namespace[1], dependency);
+        }
+        const defaultImport = /^\s*(?:type\s+)?([\w$]+)\s*(?:,|$)/.exec(rest.replace(/\*\s*as\s+[\w$]+/, ''));
+        if (defaultImport) {
+            imports.bindings.set(defaultImport[1], dependency);
+        }
+    };
+    const importPattern = /\bimport\s+(?:type\s+)?([^'";]+?)\s+from\s*['"]([^'"]+)['"]/g;
+    for (const [, clause, specifier] of content.matchAll(importPattern)) {
+        const dependency = packageOf(specifier);
+        if (dependency) {
+            bind(clause, dependency);
+        }
+    }
+    const requirePattern = /\b(?:const|let|var)\s+([\w$]+|\{[^}]*\})\s*=\s*require\s*\(\s*['"]([^'"]+)['"]\s*\)/g;
+    for (const [, binding, specifier] of content.matchAll(requirePattern)) {
+        const dependency = packageOf(specifier);
+        if (dependency) {
+            bind(binding.replace(/:/g, ' as '), dependency);
+        }
+    }
+    return imports;
+}
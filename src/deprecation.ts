@@ -0,0 +1,94 @@
+import { stripMarkers } from './documentation';
+import type { AttributeInfo, DeprecationInfo, SymbolInfo } from './types';
+
+// Attribute names marking a declaration deprecated, without path and lowercased: Rust, C++, Python
+// and Haxe `deprecated`, Java and Dart `Deprecated`, C# `Obsolete`
+const DEPRECATION_ATTRIBUTES = new Set(['deprecated', 'obsolete', 'obsoleteattribute']);
+
+/**
+ * Completes `deprecated` on symbols and their children from their attributes and documentation:
+ * `#[deprecated(since = ..., note = ...)]`, `@Deprecated(since = ...)`, `[Obsolete("...")]`,
+ * `[[deprecated("...")]]`, `@deprecated("...")` decorators, and `@deprecated`/`\deprecated` tags or
+ * Sphinx `.. deprecated::` directives in doc comments. Symbols the server tagged deprecated already
+ * carry an empty `deprecated`; an attribute's message takes precedence over the documentation's.
+ *
+ * @param symbols - Symbols of one file, with attributes and raw documentation set; updated in place
+ */
+export function addDeprecation(symbols: SymbolInfo[]): void {
+    const visit = (symbol: SymbolInfo) => {
+        const attribute = symbol.attributes?.find((candidate) =>
+            // `System.Obsolete`, `gnu::deprecated`, Haxe `:deprecated`
+            DEPRECATION_ATTRIBUTES.has(candidate.name.replace(/^.*[.:]/, '').toLowerCase())
+        );
+        const fromAttribute = attribute && fromAttributeArguments(attribute);
+        // The raw comment still has the blank lines ending a tag's description
+        const fromDocs = symbol.rawDocumentation
+            ? fromDocumentation(stripMarkers(symbol.rawDocumentation.split('\n')))
+            : undefined;
+        if (symbol.deprecated || fromAttribute || fromDocs) {
+            symbol.deprecated = deprecation(
+                fromAttribute?.since ?? fromDocs?.since,
+                fromAttribute?.message ?? fromDocs?.message
+            );
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
+
+function fromAttributeArguments(attribute: AttributeInfo): DeprecationInfo {
+    const args = attribute.arguments ?? '';
+    const since = named(args, 'since');
+    // Rust `note = "..."`, Python `message`; otherwise the first string argument, as in `[Obsolete("...")]`
+    const message =
+        named(args, 'note') ??
+        named(args, 'message') ??
+        /^\s*(?:r#*)?(?:"((?:\\.|[^"\\])*)"|'((?:\\.|[^'\\])*)')/.exec(args)?.slice(1).find((value) => value);
+    return deprecation(since, message);
+}
+
+/** Leaves out empty values */
+function deprecation(since: string | undefined, message: string | undefined): DeprecationInfo {
+    return { ...(since ? { since } : {}), ...(message ? { message } : {}) };
+}
+
+function named(args: string, key: string): string | undefined {
+    const match = new RegExp(`\\b${key}\\s*[=:]\\s*(?:"((?:\\\\.|[^"\\\\])*)"|'((?:\\\\.|[^'\\\\])*)')`).exec(args);
+    return match?.slice(1).find((value) => value !== undefined);
+}
+
+/**
+ * @param lines - Text lines of a doc comment, without comment markers
+ * @returns The deprecation from `@deprecated message` or `\deprecated message` tags, or
+ * `.. deprecated:: 1.2` followed by the message, if there is one
+ */
+function fromDocumentation(lines: string[]): DeprecationInfo | undefined {
+    for (let index = 0; index < lines.length; index++) {
+        const sphinx = /^\s*\.\.\s+deprecated::\s*(\S*)\s*(.*)$/.exec(lines[index]);
+        if (sphinx) {
+            const message = [sphinx[2], ...continuation(lines, index + 1)].join(' ').trim();
+            return deprecation(sphinx[1], message);
+        }
+        const tag = /^\s*[@\\]deprecated\b\s*(.*)$/.exec(lines[index]);
+        if (tag) {
+            const message = [tag[1], ...continuation(lines, index + 1)].join(' ').trim();
+            // `@deprecated since 2.0, use bar() instead`
+            const since = /^(?:since|as of)\s+v?([\w.-]+)[,:]?\s*/i.exec(message);
+            return deprecation(since?.[1], since ? message.slice(since[0].length) : message);
+        }
+    }
+    return undefined;
+}
+
+/** Lines continuing a tag's description, up to the next tag or a blank line */
+function continuation(lines: string[], start: number): string[] {
+    const result: string[] = [];
+    for (let index = start; index < lines.length; index++) {
+        const line = lines[index].trim();
+        if (line === '' || /^[@\\]\w+/.test(line) || line.startsWith('..')) {
+            break;
+        }
+        result.push(line);
+    }
+    return result;
+}
@@ -0,0 +1,453 @@
+import { exec } from 'node:child_process';
+import { existsSync, mkdirSync, mkdtempSync, realpathSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { delimiter, dirname, join } from 'node:path';
+import { promisify } from 'node:util';
+import { LanguageClient } from './language-client';
+import type { Logger } from './logger';
+import { SERVER_NAMES, ServerManager } from './server-manager';
+import type { SupportedLanguage, SymbolInfo } from './types';
+import { checkProjectFiles, checkToolchain, detectLanguages } from './utils';
+
+const execAsync = promisify(exec);
+
+/** How long the sample extraction may take, including the server's startup */
+const SAMPLE_TIMEOUT_MS = 120_000;
+
+export type DoctorStatus = 'ok' | 'warn' | 'fail' | 'skip';
+
+export interface DoctorCheck {
+    /** Language the check is about; workspace-wide checks have none */
+    language?: SupportedLanguage;
+    name: 'workspace' | 'toolchain' | 'path' | 'server' | 'project' | 'sample';
+    status: DoctorStatus;
+    detail: string;
+    /** What to do about a warning or failure */
+    fix?: string;
+}
+
+export interface DoctorReport {
+    /** False if any check failed; warnings don't count */
+    passed: boolean;
+    directory: string;
+    checks: DoctorCheck[];
+}
+
+export interface DoctorOptions {
+    /** Languages to check; without them, the languages detected in `directory` */
+    languages?: SupportedLanguage[];
+    /** Workspace to detect languages and project files in */
+    directory: string;
+    /** Install missing language servers instead of reporting them */
+    install?: boolean;
+    /** Run the end-to-end extraction of the built-in sample; on by default */
+    sample?: boolean;
+}
+
+/** Commands printing the toolchain's version; alternatives are tried in order */
+const TOOLCHAIN_VERSIONS: { [key in SupportedLanguage]: string[] } = {
+    java: ['java --version'],
+    cpp: ['clang --version', 'gcc --version'],
+    c: ['clang --version', 'gcc --version'],
+    csharp: ['dotnet --version'],
+    haxe: ['haxe --version'],
+    typescript: ['node --version'],
+    dart: ['dart --version'],
+    rust: ['rustc --version'],
+    python: ['python3 --version', 'python --version']
+};
+
+/** Programs each language needs on PATH, to run or to install its server; inner lists are alternatives */
+const REQUIRED_PROGRAMS: { [key in SupportedLanguage]: string[][] } = {
+    java: [['java']],
+    cpp: [['clang', 'gcc']],
+    c: [['clang', 'gcc']],
+    csharp: [['dotnet']],
+    haxe: [['haxe'], ['node'], ['npm'], ['git']],
+    typescript: [['node'], ['npm']],
+    dart: [['dart']],
+    rust: [['rustc'], ['cargo'], ['rust-analyzer']],
+    python: [['python3', 'python'], ['node'], ['npm']]
+};
+
+const PROGRAM_FIXES: Record<string, string> = {
+    java: 'Install a JDK 11 or later, e.g. from https://adoptium.net',
+    clang: 'Install Clang or GCC, e.g. `sudo apt install clang` or `xcode-select --install`',
+    dotnet: 'Install the .NET SDK from https://dotnet.microsoft.com/download',
+    haxe: 'Install Haxe from https://haxe.org/download',
+    node: 'Install Node.js from https://nodejs.org',
+    npm: 'Install npm, which comes with Node.js from https://nodejs.org',
+    git: 'Install git from https://git-scm.com',
+    dart: 'Install the Dart SDK from https://dart.dev/get-dart',
+    rustc: 'Install Rust with rustup from https://rustup.rs',
+    cargo: 'Install Rust with rustup from https://rustup.rs',
+    'rust-analyzer': 'Run `rustup component add rust-analyzer`',
+    python3: 'Install Python 3.7 or later with pip from https://www.python.org/downloads'
+};
+
+/** Servers whose version comes from running them, so a missing version means they don't run */
+const EXECUTED_SERVERS = new Set<SupportedLanguage>(['c', 'cpp', 'rust']);
+
+/** A project declaring `greet`, the symbol the sample extraction must find */
+const SAMPLES: { [key in SupportedLanguage]: Record<string, string> } = {
+    java: {
+        'pom.xml':
+            '<project><modelVersion>4.0.0</modelVersion><groupId>doctor</groupId>' +
+            '<artifactId>sample</artifactId><version>1</version></project>\n',
+        'src/main/java/Sample.java': 'public class Sample {\n    public static String greet() {\n        return "hi";\n    }\n}\n'
+    },
+    cpp: { 'sample.cpp': 'int greet() {\n    return 0;\n}\n', 'compile_flags.txt': '-std=c++17\n' },
+    c: { 'sample.c': 'int greet(void) {\n    return 0;\n}\n', 'compile_flags.txt': '-std=c11\n' },
+    csharp: {
+        'Sample.csproj':
+            '<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup><TargetFramework>net6.0</TargetFramework>' +
+            '</PropertyGroup></Project>\n',
+        'Sample.cs': 'public class Sample\n{\n    public static string Greet() => "hi";\n}\n'
+    },
+    haxe: {
+        'build.hxml': '-cp src\n-main Sample\n--interp\n',
+        'src/Sample.hx': 'class Sample {\n    static function main() {}\n\n    public static function greet() {\n        return "hi";\n    }\n}\n'
+    },
+    typescript: {
+        'tsconfig.json': '{ "compilerOptions": { "strict": true } }\n',
+        'sample.ts': 'export function greet(): string {\n    return "hi";\n}\n'
+    },
+    dart: {
+        'pubspec.yaml': "name: sample\nenvironment:\n  sdk: '>=2.12.0 <4.0.0'\n",
+        'lib/sample.dart': "String greet() {\n  return 'hi';\n}\n"
+    },
+    rust: {
+        'Cargo.toml': '[package]\nname = "sample"\nversion = "0.1.0"\nedition = "2021"\n',
+        'src/lib.rs': 'pub fn greet() -> &\'static str {\n    "hi"\n}\n'
+    },
+    python: {
+        'pyproject.toml': '[project]\nname = "sample"\nversion = "0.1.0"\n',
+        'sample.py': 'def greet():\n    return "hi"\n'
+    }
+};
+
+/**
+ * Diagnoses the environment: toolchains and their versions, programs on PATH, language server
+ * installs, the workspace's languages and project files, and an end-to-end extraction of a small
+ * built-in sample per language. Failed and suspicious checks come with a fix.
+ *
+ * @param options - Languages and workspace to check
+ * @param logger - Logger for progress and for the sample extraction's messages
+ * @returns The checks in the order they ran
+ */
+export async function runDoctor(options: DoctorOptions, logger: Logger): Promise<DoctorReport> {
+    const checks: DoctorCheck[] = [];
+    const add = (check: DoctorCheck) => {
+        checks.push(check);
+        logger.debug(`doctor: ${check.language ?? 'workspace'} ${check.name}: ${check.status} - ${check.detail}`);
+    };
+
+    let languages = options.languages ?? [];
+    let detected: Array<{ language: SupportedLanguage; files: number }> = [];
+    if (existsSync(options.directory)) {
+        detected = detectLanguages(options.directory);
+        add({
+            name: 'workspace',
+            status: detected.length > 0 || languages.length > 0 ? 'ok' : 'warn',
+            detail:
+                detected.length > 0
+                    ? `${options.directory}: ${detected.map((entry) => `${entry.language} (${entry.files} files)`).join(', ')}`
+                    : `${options.directory}: no source files of a supported language`,
+            fix:
+                detected.length > 0 || languages.length > 0
+                    ? undefined
+                    : 'Run doctor in the project to analyze, or pass a language, e.g. `lsp-cli doctor rust`'
+        });
+        if (languages.length === 0) {
+            languages = detected.map((entry) => entry.language);
+        }
+    } else {
+        add({
+            name: 'workspace',
+            status: 'fail',
+            detail: `Directory '${options.directory}' does not exist`,
+            fix: 'Pass an existing directory with --directory'
+        });
+    }
+
+    const serverManager = new ServerManager(logger.scoped('install'));
+    for (const language of languages) {
+        logger.stage(`Checking ${language}`);
+        const toolchain = await checkLanguageToolchain(language);
+        add(toolchain);
+        for (const check of checkPath(language)) {
+            add(check);
+        }
+        const server = await checkServer(language, serverManager, options.install ?? false);
+        add(server);
+        // Project files only matter where the workspace has code in the language
+        if (detected.some((entry) => entry.language === language)) {
+            const project = await checkProjectFiles(options.directory, language);
+            add({
+                language,
+                name: 'project',
+                status: project.found ? 'ok' : 'warn',
+                detail: project.found ? project.message : `No ${language} project files in ${options.directory}`,
+                fix: project.found ? undefined : project.message
+            });
+        }
+        if (options.sample === false) {
+            logger.stageDone();
+            continue;
+        }
+        if (toolchain.status === 'fail' || server.status === 'fail') {
+            add({
+                language,
+                name: 'sample',
+                status: 'skip',
+                detail: `Skipped: the ${toolchain.status === 'fail' ? 'toolchain' : 'language server'} is missing`
+            });
+        } else {
+            add(await checkSample(language, logger));
+        }
+        logger.stageDone();
+    }
+
+    return { passed: !checks.some((check) => check.status === 'fail'), directory: options.directory, checks };
+}
+
+/**
+ * Renders a doctor report as one line per check, with the fix below warnings and failures.
+ *
+ * @param report - Report of `runDoctor`
+ * @returns Plain-text report
+ */
+export function formatDoctorReport(report: DoctorReport): string {
+    const icons: Record<DoctorStatus, string> = { ok: '✓', warn: '!', fail: '✗', skip: '-' };
+    const lines = report.checks.flatMap((check) => {
+        const label = check.language ? `${check.language} ${check.name}` : check.name;
+        const line = `${icons[check.status]} ${label}: ${check.detail}`;
+        return check.fix ? [line, ...check.fix.split('\n').map((fix) => `    → ${fix}`)] : [line];
+    });
+    const failed = report.checks.filter((check) => check.status === 'fail').length;
+    const warnings = report.checks.filter((check) => check.status === 'warn').length;
+    lines.push('', report.passed ? `No problems found (${warnings} warnings)` : `${failed} problems, ${warnings} warnings`);
+    return lines.join('\n');
+}
+
+/**
+ * @param language - Language to check
+ * @returns Whether the toolchain is installed, with its version
+ */
+async function checkLanguageToolchain(language: SupportedLanguage): Promise<DoctorCheck> {
+    const result = await checkToolchain(language);
+    if (!result.installed) {
+        return { language, name: 'toolchain', status: 'fail', detail: 'Not found', fix: result.message };
+    }
+    const version = await firstVersion(TOOLCHAIN_VERSIONS[language]);
+    return { language, name: 'toolchain', status: 'ok', detail: version ?? result.message };
+}
+
+/**
+ * Looks up the programs a language needs on PATH. Missing programs fail; programs found in several
+ * PATH directories warn, since an earlier, older copy is a common reason for confusing errors.
+ *
+ * @param language - Language to check
+ * @returns One check per missing or shadowed program, or a single passing check
+ */
+function checkPath(language: SupportedLanguage): DoctorCheck[] {
+    const checks: DoctorCheck[] = [];
+    const found: string[] = [];
+    for (const alternatives of REQUIRED_PROGRAMS[language]) {
+        const match = alternatives
+            .map((program) => ({ program, paths: findOnPath(program) }))
+            .find((candidate) => candidate.paths.length > 0);
+        if (!match) {
+            checks.push({
+                language,
+                name: 'path',
+                status: 'fail',
+                detail: `${alternatives.join(' or ')} not found on PATH`,
+                fix: PROGRAM_FIXES[alternatives[0]]
+            });
+            continue;
+        }
+        found.push(match.program);
+        const copies = [...new Set(match.paths.map((path) => realpathSync(path)))];
+        if (copies.length > 1) {
+            checks.push({
+                language,
+                name: 'path',
+                status: 'warn',
+                detail: `${match.program} found ${copies.length} times on PATH; ${match.paths[0]} is used`,
+                fix: `If that is not the intended ${match.program}, move ${dirname(
+                    match.paths.find((path) => realpathSync(path) === copies[1]) ?? copies[1]
+                )} earlier in PATH or remove the other copy`
+            });
+        }
+    }
+    if (checks.length === 0) {
+        checks.push({ language, name: 'path', status: 'ok', detail: `Found ${found.join(', ')}` });
+    }
+    return checks;
+}
+
+/**
+ * @param language - Language to check
+ * @param serverManager - Manager of the installed servers
+ * @param install - Install the server if it is missing
+ * @returns Whether the language server is installed and runs, with its version where it reports one
+ */
+async function checkServer(
+    language: SupportedLanguage,
+    serverManager: ServerManager,
+    install: boolean
+): Promise<DoctorCheck> {
+    let validation = serverManager.validateServer(language);
+    if (!validation.valid && install) {
+        try {
+            await serverManager.ensureServer(language);
+            validation = serverManager.validateServer(language);
+        } catch (error) {
+            const message = error instanceof Error ? error.message : String(error);
+            return {
+                language,
+                name: 'server',
+                status: 'fail',
+                detail: message,
+                fix: 'Check your network connection and the toolchain checks above, then retry'
+            };
+        }
+    }
+    if (!validation.valid) {
+        return {
+            language,
+            name: 'server',
+            status: 'fail',
+            detail: validation.error ?? 'Not installed',
+            fix: `Run \`lsp-cli doctor ${language} --install\` to install it`
+        };
+    }
+
+    const executable = serverManager.getServerCommand(language)[0];
+    const version = await serverManager.getServerVersion(language);
+    if (!version && EXECUTED_SERVERS.has(language)) {
+        return {
+            language,
+            name: 'server',
+            status: 'fail',
+            detail: `${executable} does not run`,
+            fix:
+                language === 'rust'
+                    ? 'Run `rustup component add rust-analyzer`'
+                    : `Remove ${dirname(executable)} and run \`lsp-cli doctor ${language} --install\``
+        };
+    }
+    const name = `${SERVER_NAMES[language]}${version ? ` ${version}` : ''}`;
+    return { language, name: 'server', status: 'ok', detail: `${name} (${executable})` };
+}
+
+/**
+ * Extracts the symbols of the built-in sample project in a temporary directory, which exercises
+ * the server's startup, the LSP handshake and symbol extraction without the user's code.
+ *
+ * @param language - Language to check
+ * @param logger - Logger for the extraction
+ * @returns Whether the sample's `greet` function was found
+ */
+async function checkSample(language: SupportedLanguage, logger: Logger): Promise<DoctorCheck> {
+    const directory = mkdtempSync(join(tmpdir(), 'lsp-cli-doctor-'));
+    let timer: NodeJS.Timeout | undefined;
+    let closed: (message: string) => void = () => {};
+    // Ends the extraction when the server dies or hangs, instead of ending the whole run
+    const timeout = new Promise<never>((_, reject) => {
+        closed = (message) => reject(new Error(message));
+        timer = setTimeout(() => reject(new Error(`No result within ${SAMPLE_TIMEOUT_MS / 1000}s`)), SAMPLE_TIMEOUT_MS);
+    });
+    const client = new LanguageClient(language, directory, logger, { onClose: (message) => closed(message) });
+    const startedAt = Date.now();
+    try {
+        for (const [path, content] of Object.entries(SAMPLES[language])) {
+            mkdirSync(dirname(join(directory, path)), { recursive: true });
+            writeFileSync(join(directory, path), content);
+        }
+        const symbols = await Promise.race([
+            (async () => {
+                await client.start();
+                return client.analyzeDirectory();
+            })(),
+            timeout
+        ]);
+        const seconds = ((Date.now() - startedAt) / 1000).toFixed(1);
+        if (!containsGreet(symbols)) {
+            return {
+                language,
+                name: 'sample',
+                status: 'fail',
+                detail: `The server answered in ${seconds}s but returned no symbol for the sample's greet function`,
+                fix: 'Rerun with -vv to see the LSP requests and responses, and check the server version above'
+            };
+        }
+        return { language, name: 'sample', status: 'ok', detail: `Extracted the sample in ${seconds}s` };
+    } catch (error) {
+        const lines = (error instanceof Error ? error.message : String(error)).split('\n');
+        // Connection errors spread their diagnosis over several lines; the status line says what happened
+        const status = lines.find((line) => line.trim().startsWith('Status:'))?.trim().slice('Status:'.length);
+        return {
+            language,
+            name: 'sample',
+            status: 'fail',
+            detail: status?.trim() ?? lines[0],
+            fix: 'Rerun with -vv to see the server output and LSP requests'
+        };
+    } finally {
+        clearTimeout(timer);
+        await client.stop().catch(() => {});
+        rmSync(directory, { recursive: true, force: true });
+    }
+}
+
+/**
+ * @param symbols - Extracted symbols
+ * @returns Whether a symbol named greet (any case) is among them or their children
+ */
+function containsGreet(symbols: SymbolInfo[]): boolean {
+    return symbols.some((symbol) => /^greet\b/i.test(symbol.name) || containsGreet(symbol.children ?? []));
+}
+
+/**
+ * @param commands - Commands printing a version, tried in order
+ * @returns First line of the first command that succeeds, or undefined if none does
+ */
+async function firstVersion(commands: string[]): Promise<string | undefined> {
+    for (const command of commands) {
+        try {
+            const { stdout, stderr } = await execAsync(command, { timeout: 10_000 });
+            // Java prints its version to stderr
+            const line = (stdout || stderr).trim().split('\n')[0];
+            if (line) {
+                return line;
+            }
+        } catch (_error) {
+            // Try the next alternative
+        }
+    }
+    return undefined;
+}
+
+/**
+ * @param program - Program name, without extension
+ * @returns Paths of the program in each PATH directory that has it, in PATH order
+ */
+function findOnPath(program: string): string[] {
+    const extensions = process.platform === 'win32' ? (process.env.PATHEXT ?? '.EXE;.CMD;.BAT').split(';') : [''];
+    const paths: string[] = [];
+    for (const directory of (process.env.PATH ?? '').split(delimiter)) {
+        if (!directory) {
+            continue;
+        }
+        for (const extension of extensions) {
+            const candidate = join(directory, program + extension);
+            if (existsSync(candidate) && !paths.includes(candidate)) {
+                paths.push(candidate);
+                break;
+            }
+        }
+    }
+    return paths;
+}
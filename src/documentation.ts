@@ -0,0 +1,161 @@
+import type { SupportedLanguage } from './types';
+
+// Block tags grouped into a list under one heading, with the tag's first word as the item name
+const LIST_TAGS: Record<string, string> = {
+    param: 'Parameters',
+    tparam: 'Type parameters',
+    throws: 'Throws',
+    exception: 'Throws',
+    raises: 'Throws'
+};
+
+// Block tags rendered as a labelled paragraph
+const PARAGRAPH_TAGS: Record<string, string> = {
+    return: 'Returns',
+    returns: 'Returns',
+    retval: 'Returns',
+    deprecated: 'Deprecated',
+    since: 'Since',
+    see: 'See',
+    author: 'Author',
+    version: 'Version',
+    note: 'Note',
+    warning: 'Warning',
+    example: 'Example'
+};
+
+/**
+ * Renders a doc comment as markdown: comment markers (`///`, `//!`, `/** *\/`, `"""`) and the
+ * common indentation are removed, JavaDoc/JSDoc/Doxygen tags (`@param`, `{@link ...}`, `\return`)
+ * and C# XML documentation (`<summary>`, `<param>`, `<see cref>`) become markdown, and blank lines
+ * between paragraphs are kept.
+ *
+ * @param docLines - Source lines of the doc comment or docstring, markers included
+ * @param language - Language of the file
+ * @returns The documentation as markdown
+ */
+export function renderDocumentationMarkdown(docLines: string[], language: SupportedLanguage): string {
+    let text = dedent(stripMarkers(docLines)).join('\n');
+    if (language === 'csharp' || /^\s*<(summary|remarks|param|returns)\b/.test(text)) {
+        text = convertXmlDoc(text);
+    }
+    if (language !== 'python' && language !== 'rust') {
+        text = convertTags(text);
+    }
+    return text.replace(/\n{3,}/g, '\n\n').trim();
+}
+
+/**
+ * Removes comment markers and docstring quotes, keeping blank lines.
+ *
+ * @param docLines - Source lines of the doc comment or docstring, markers included
+ * @returns The comment's text lines
+ */
+export function stripMarkers(docLines: string[]): string[] {
+    const first = docLines[0]?.trim() ?? '';
+    if (first.startsWith('"""') || first.startsWith("'''")) {
+        const quote = first.slice(0, 3);
+        const stripped = docLines.map((line, index) => {
+            let text = index === 0 ? line.trim().slice(3) : line;
+            if (index === docLines.length - 1 && text.trimEnd().endsWith(quote)) {
+                text = text.trimEnd().slice(0, -3);
+            }
+            return text;
+        });
+        // The first line starts right after the quotes, the others carry the body's indentation
+        return [stripped[0], ...dedent(stripped.slice(1))];
+    }
+    if (first.startsWith('/*')) {
+        return docLines.map((line, index) => {
+            let text = line;
+            if (index === 0) {
+                text = text.replace(/^\s*\/\*[*!]?/, '');
+            }
+            if (index === docLines.length - 1) {
+                text = text.replace(/\*+\/\s*$/, '');
+            }
+            return index === 0 ? text : text.replace(/^\s*\*(?!\*)\s?/, '');
+        });
+    }
+    return docLines.map((line) => line.replace(/^\s*\/\/[/!]\s?/, ''));
+}
+
+/** Removes the indentation shared by all non-blank lines, keeping relative indentation of code blocks */
+function dedent(lines: string[]): string[] {
+    const indents = lines.filter((line) => line.trim() !== '').map((line) => /^\s*/.exec(line)?.[0].length ?? 0);
+    const indent = indents.length > 0 ? Math.min(...indents) : 0;
+    return lines.map((line) => (line.trim() === '' ? '' : line.slice(indent).trimEnd()));
+}
+
+function convertXmlDoc(text: string): string {
+    return text
+        .replace(/<\/?(summary|remarks)>/g, '\n')
+        .replace(/<para>|<\/para>/g, '\n\n')
+        .replace(/<param\s+name="(\w+)">([\s\S]*?)<\/param>/g, '@param $1 $2')
+        .replace(/<typeparam\s+name="(\w+)">([\s\S]*?)<\/typeparam>/g, '@tparam $1 $2')
+        .replace(/<returns>([\s\S]*?)<\/returns>/g, '@returns $1')
+        .replace(/<exception\s+cref="([^"]+)">([\s\S]*?)<\/exception>/g, '@throws $1 $2')
+        .replace(/<see\s+(?:c|lang)ref="([^"]+)"\s*\/>/g, '`$1`')
+        .replace(/<(?:param|typeparam)ref\s+name="([^"]+)"\s*\/>/g, '`$1`')
+        .replace(/<c>([\s\S]*?)<\/c>/g, '`$1`')
+        .replace(/<\/?code>/g, '\n```\n')
+        .split('\n')
+        .map((line) => line.trim())
+        .join('\n');
+}
+
+function convertTags(text: string): string {
+    const body: string[] = [];
+    const lists = new Map<string, string[]>();
+    const paragraphs: string[] = [];
+    let current: string[] | undefined;
+    let inCode = false;
+
+    for (const line of text.split('\n')) {
+        if (/^\s*```/.test(line)) {
+            inCode = !inCode;
+        }
+        const tag = inCode ? null : /^\s*[@\\](\w+)\s*(.*)$/.exec(line);
+        if (tag && tag[1] === 'brief') {
+            body.push(inlineTags(tag[2]));
+            current = undefined;
+        } else if (tag && LIST_TAGS[tag[1]]) {
+            const heading = LIST_TAGS[tag[1]];
+            // `@param {Type} name description`, `@param[in] name description`
+            const [, type, name, description] =
+                /^(?:\{([^}]*)\}\s*)?\[?([\w.$]+)\]?\s*(?:-\s*)?(.*)$/.exec(tag[2].replace(/^\[\w+\]\s*/, '')) ?? [];
+            const items = lists.get(heading) ?? [];
+            items.push(
+                `- \`${name ?? tag[2]}\`${type ? ` (\`${type}\`)` : ''}${description ? `: ${description}` : ''}`
+            );
+            lists.set(heading, items);
+            current = items;
+        } else if (tag && PARAGRAPH_TAGS[tag[1]]) {
+            const description = tag[2].replace(/^\{[^}]*\}\s*/, '');
+            paragraphs.push(`**${PARAGRAPH_TAGS[tag[1]]}:** ${description}`.trimEnd());
+            current = paragraphs;
+        } else if (current && line.trim() !== '' && !inCode) {
+            // Continuation of the previous tag's description
+            current[current.length - 1] += ` ${line.trim()}`;
+        } else {
+            body.push(line);
+            current = undefined;
+        }
+    }
+
+    const sections = [body.join('\n').trim()];
+    for (const [heading, items] of lists) {
+        sections.push(`**${heading}:**\n${items.join('\n')}`);
+    }
+    sections.push(...paragraphs);
+    return inlineTags(sections.filter(Boolean).join('\n\n'));
+}
+
+/** `{@code x}` and `{@literal x}` as code, `{@link Type#member label}` as the label or code */
+function inlineTags(text: string): string {
+    return text
+        .replace(/\{@(?:code|literal)\s+([^}]*)\}/g, '`$1`')
+        .replace(/\{@link(?:plain)?\s+([^\s}]+)\s+([^}]+)\}/g, '$2')
+        .replace(/\{@link(?:plain)?\s+([^}]+)\}/g, (_, target: string) => `\`${target.replace(/^#/, '')}\``)
+        .replace(/\{@inheritDoc\}/g, '');
+}
@@ -0,0 +1,164 @@
+import type { Chunk } from './chunks';
+import type { Logger } from './logger';
+import { fetchWithRetry, type RetryOptions, writeOutput } from './utils';
+
+export interface EmbeddingOptions {
+    /** Base URL of an OpenAI-compatible API, e.g. https://api.openai.com/v1 or http://localhost:11434/v1 */
+    endpoint: string;
+    model: string;
+    apiKey?: string;
+    /** Texts per request */
+    batchSize?: number;
+    /** Upper bound for requests started per minute */
+    requestsPerMinute?: number;
+    /** Retries per batch on network errors, 429 and 5xx responses */
+    maxRetries?: number;
+}
+
+export type EmbeddedChunk = Chunk & { embedding: number[] };
+
+const DEFAULT_BATCH_SIZE = 64;
+const DEFAULT_MAX_RETRIES = 5;
+
+/**
+ * Computes embeddings for chunks through an OpenAI-compatible `/embeddings` endpoint.
+ * Batches are sent sequentially; failed batches are retried with exponential backoff,
+ * honoring `Retry-After` on rate-limit responses.
+ *
+ * @param chunks - Chunks whose `text` is embedded
+ * @param options - Endpoint, model and limits
+ * @param logger - Logger for progress and retries
+ * @returns The chunks with an `embedding` vector each, in input order
+ */
+export async function embedChunks(
+    chunks: Chunk[],
+    options: EmbeddingOptions,
+    logger: Logger
+): Promise<EmbeddedChunk[]> {
+    const batchSize = options.batchSize ?? DEFAULT_BATCH_SIZE;
+    const minInterval = options.requestsPerMinute ? 60000 / options.requestsPerMinute : 0;
+    const result: EmbeddedChunk[] = [];
+    let lastRequestAt = 0;
+
+    const throttle = async () => {
+        const wait = lastRequestAt + minInterval - Date.now();
+        if (wait > 0) {
+            await new Promise((resolve) => setTimeout(resolve, wait));
+        }
+        lastRequestAt = Date.now();
+    };
+
+    for (let start = 0; start < chunks.length; start += batchSize) {
+        const batch = chunks.slice(start, start + batchSize);
+        logger.progress(Math.min(start + batchSize, chunks.length), chunks.length);
+
+        const vectors = await requestEmbeddings(batch, options, {
+            maxRetries: options.maxRetries ?? DEFAULT_MAX_RETRIES,
+            beforeAttempt: throttle,
+            onRetry: (reason, delay) => logger.debug(`Embedding request failed (${reason}), retrying in ${delay}ms`)
+        });
+        batch.forEach((chunk, index) => result.push({ ...chunk, embedding: vectors[index] }));
+    }
+
+    logger.clearLine();
+    return result;
+}
+
+async function requestEmbeddings(batch: Chunk[], options: EmbeddingOptions, retry: RetryOptions): Promise<number[][]> {
+    const response = await fetchWithRetry(
+        `${options.endpoint.replace(/\/+$/, '')}/embeddings`,
+        {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/json',
+                ...(options.apiKey ? { Authorization: `Bearer ${options.apiKey}` } : {})
+            },
+            body: JSON.stringify({ model: options.model, input: batch.map((chunk) => chunk.text) })
+        },
+        retry
+    );
+    if (!response.ok) {
+        throw new Error(`Embedding endpoint returned HTTP ${response.status}: ${await response.text()}`);
+    }
+
+    const body = (await response.json()) as { data?: Array<{ index: number; embedding: number[] }> };
+    if (!Array.isArray(body.data) || body.data.length !== batch.length) {
+        throw new Error(`Embedding endpoint returned ${body.data?.length ?? 'no'} vectors for ${batch.length} inputs`);
+    }
+    // Entries carry their input index; don't rely on response order
+    return [...body.data].sort((a, b) => a.index - b.index).map((entry) => entry.embedding);
+}
+
+/**
+ * Writes embedded chunks to a JSON file (`{ model, dimensions, chunks }`) or, for `.sqlite`/`.db`
+ * paths, to a SQLite database with vectors stored as little-endian float32 blobs.
+ * SQLite output uses the built-in `node:sqlite` module (Node.js 22.5+).
+ *
+ * @param path - Output file
+ * @param model - Embedding model, recorded in the output
+ * @param chunks - Embedded chunks
+ */
+export async function writeEmbeddings(path: string, model: string, chunks: EmbeddedChunk[]): Promise<void> {
+    const dimensions = chunks[0]?.embedding.length ?? 0;
+
+    if (!/\.(sqlite3?|db)$/.test(path)) {
+        await writeOutput(path, JSON.stringify({ model, dimensions, chunks }));
+        return;
+    }
+
+    let sqlite: { DatabaseSync: new (path: string) => SqliteDatabase };
+    try {
+        // Not in the typings of the supported Node.js versions, hence the computed specifier
+        const specifier = 'node:sqlite';
+        sqlite = await import(specifier);
+    } catch (_error) {
+        throw new Error(`SQLite output requires Node.js 22.5 or newer (running ${process.version}); use a .json file`);
+    }
+
+    const db = new sqlite.DatabaseSync(path);
+    try {
+        db.exec(`
+            CREATE TABLE IF NOT EXISTS metadata (key TEXT PRIMARY KEY, value TEXT);
+            CREATE TABLE IF NOT EXISTS chunks (
+                id TEXT PRIMARY KEY,
+                file TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                symbols TEXT NOT NULL,
+                text TEXT NOT NULL,
+                tokens INTEGER NOT NULL,
+                embedding BLOB NOT NULL
+            );
+        `);
+        db.prepare('INSERT OR REPLACE INTO metadata (key, value) VALUES (?, ?)').run('model', model);
+        db.prepare('INSERT OR REPLACE INTO metadata (key, value) VALUES (?, ?)').run('dimensions', String(dimensions));
+
+        const insert = db.prepare(
+            'INSERT OR REPLACE INTO chunks (id, file, start_line, end_line, symbols, text, tokens, embedding) ' +
+                'VALUES (?, ?, ?, ?, ?, ?, ?, ?)'
+        );
+        db.exec('BEGIN');
+        for (const chunk of chunks) {
+            const vector = new Float32Array(chunk.embedding);
+            insert.run(
+                chunk.id,
+                chunk.file,
+                chunk.range.start.line,
+                chunk.range.end.line,
+                JSON.stringify(chunk.symbols),
+                chunk.text,
+                chunk.tokens,
+                Buffer.from(vector.buffer, vector.byteOffset, vector.byteLength)
+            );
+        }
+        db.exec('COMMIT');
+    } finally {
+        db.close();
+    }
+}
+
+interface SqliteDatabase {
+    exec(sql: string): void;
+    prepare(sql: string): { run(...values: unknown[]): unknown };
+    close(): void;
+}
@@ -0,0 +1,118 @@
+import { readFileSync } from 'node:fs';
+import type { SourceEncoding } from './types';
+
+/**
+ * Reads a source file and decodes it to a string, whatever encoding it is in.
+ *
+ * @param path - Path of the file
+ * @returns The content without byte order mark, and the encoding it was decoded from
+ */
+export function readSourceFile(path: string): { content: string; encoding: SourceEncoding } {
+    const buffer = readFileSync(path);
+    const encoding = detectEncoding(buffer);
+    return { content: decode(buffer, encoding), encoding };
+}
+
+/**
+ * Detects the encoding of a file's bytes: a byte order mark decides for UTF-8 and UTF-16; otherwise
+ * valid UTF-8 is UTF-8, and other text is Shift-JIS if all its non-ASCII bytes form Shift-JIS
+ * characters, or else Windows-1252 (a Latin-1 superset, so any byte sequence decodes).
+ *
+ * @param buffer - Content of the file
+ * @returns The detected encoding
+ */
+export function detectEncoding(buffer: Buffer): SourceEncoding {
+    if (buffer[0] === 0xef && buffer[1] === 0xbb && buffer[2] === 0xbf) {
+        return 'utf-8-bom';
+    }
+    if (buffer[0] === 0xff && buffer[1] === 0xfe) {
+        return 'utf-16le';
+    }
+    if (buffer[0] === 0xfe && buffer[1] === 0xff) {
+        return 'utf-16be';
+    }
+
+    try {
+        new TextDecoder('utf-8', { fatal: true }).decode(buffer);
+        return 'utf-8';
+    } catch {
+        return isShiftJis(buffer) ? 'shift_jis' : 'windows-1252';
+    }
+}
+
+/**
+ * @param buffer - Content of the file
+ * @param encoding - Encoding of the content
+ * @returns The content as a string, without byte order mark
+ */
+export function decode(buffer: Buffer, encoding: SourceEncoding): string {
+    switch (encoding) {
+        case 'utf-8':
+            return buffer.toString('utf-8');
+        case 'utf-8-bom':
+            return buffer.subarray(3).toString('utf-8');
+        default:
+            // TextDecoder drops the byte order mark of UTF-16 itself
+            return new TextDecoder(encoding).decode(buffer);
+    }
+}
+
+/**
+ * Length of a piece of decoded text in the file's own encoding, to map string positions back to
+ * offsets in the file on disk.
+ *
+ * @param text - Decoded text
+ * @param encoding - Encoding of the file
+ * @returns Number of bytes the text takes in the file
+ */
+export function encodedLength(text: string, encoding: SourceEncoding): number {
+    switch (encoding) {
+        case 'utf-8':
+        case 'utf-8-bom':
+            return Buffer.byteLength(text);
+        case 'utf-16le':
+        case 'utf-16be':
+            return text.length * 2;
+        case 'windows-1252':
+            return text.length;
+        case 'shift_jis': {
+            let length = 0;
+            for (const char of text) {
+                const code = char.codePointAt(0) ?? 0;
+                // ASCII and halfwidth katakana are single bytes
+                length += code < 0x80 || (code >= 0xff61 && code <= 0xff9f) ? 1 : 2;
+            }
+            return length;
+        }
+    }
+}
+
+/**
+ * @param encoding - Encoding of the file
+ * @returns Length of the byte order mark the file starts with
+ */
+export function byteOrderMarkLength(encoding: SourceEncoding): number {
+    if (encoding === 'utf-8-bom') {
+        return 3;
+    }
+    return encoding === 'utf-16le' || encoding === 'utf-16be' ? 2 : 0;
+}
+
+/** Whether all bytes above ASCII form Shift-JIS characters, with at least one double-byte character */
+function isShiftJis(buffer: Buffer): boolean {
+    let doubleByte = 0;
+    for (let index = 0; index < buffer.length; index++) {
+        const byte = buffer[index];
+        if (byte < 0x80 || (byte >= 0xa1 && byte <= 0xdf)) {
+            continue;
+        }
+        const trail = buffer[index + 1];
+        const lead = (byte >= 0x81 && byte <= 0x9f) || (byte >= 0xe0 && byte <= 0xfc);
+        if (!lead || trail === undefined || trail < 0x40 || trail === 0x7f || trail > 0xfc) {
+            return false;
+        }
+        doubleByte++;
+        index++;
+    }
+    return doubleByte > 0;
+}
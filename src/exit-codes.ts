@@ -0,0 +1,49 @@
+/**
+ * Exit codes of the CLI. They are a stable contract for scripts: codes keep their meaning across
+ * releases and new failure types get new codes. 1 is left for failures without a code of their own.
+ */
+export const ExitCode = {
+    /** Everything succeeded; for `ci`, all thresholds were met */
+    Success: 0,
+    /** Any other failure, e.g. an unreadable output path */
+    Failure: 1,
+    /** Unknown options, invalid option values, missing arguments, unsupported languages, invalid configuration */
+    InvalidArguments: 2,
+    /** The language's toolchain (e.g. cargo, a JDK, the .NET SDK) is not installed */
+    ToolchainMissing: 3,
+    /** The language server could not be downloaded or installed */
+    ServerInstallFailed: 4,
+    /** The language server failed to start, or exited or closed the connection before analysis finished */
+    ServerCrashed: 5,
+    /** Some files could not be analyzed; with `--fail-fast` or `--strict` the run stopped there without output */
+    PartialExtraction: 6,
+    /** `ci` thresholds failed, or `hook` found new errors or API changes */
+    ThresholdViolation: 7,
+    /** The output was written, but some files or feature sets failed and are listed in its `errors` */
+    CompletedWithErrors: 8
+} as const;
+
+export type ExitCode = (typeof ExitCode)[keyof typeof ExitCode];
+
+/** An error that ends the CLI with a specific exit code */
+export class ExitError extends Error {
+    /**
+     * @param message - What failed
+     * @param exitCode - Exit code to end the process with
+     */
+    constructor(
+        message: string,
+        readonly exitCode: ExitCode
+    ) {
+        super(message);
+        this.name = 'ExitError';
+    }
+}
+
+/**
+ * @param error - Error that ended a command
+ * @returns Its exit code, or {@link ExitCode.Failure} for errors without one
+ */
+export function exitCodeOf(error: unknown): ExitCode {
+    return error instanceof ExitError ? error.exitCode : ExitCode.Failure;
+}
@@ -0,0 +1,116 @@
+import type { FeatureConfiguration, SymbolInfo } from './types';
+
+/**
+ * Parses a `--feature-set` value: `default` (the crates' default features), `all` (like
+ * `--all-features`), `none` (like `--no-default-features`), or a comma-separated feature list
+ * enabled on top of the defaults, optionally prefixed with `none:` to drop the defaults.
+ *
+ * @param spec - Feature set as given on the command line
+ * @returns The configuration, named after the spec
+ */
+export function parseFeatureConfiguration(spec: string): FeatureConfiguration {
+    const name = spec.trim();
+    if (name === 'default') {
+        return { name };
+    }
+    if (name === 'all') {
+        return { name, features: 'all' };
+    }
+    if (name === 'none') {
+        return { name, noDefaultFeatures: true };
+    }
+
+    const noDefaultFeatures = name.startsWith('none:');
+    const features = (noDefaultFeatures ? name.slice('none:'.length) : name)
+        .split(',')
+        .map((feature) => feature.trim())
+        .filter((feature) => feature !== '');
+    if (features.length === 0) {
+        throw new Error(`Feature set '${spec}' lists no features`);
+    }
+    return { name, features, ...(noDefaultFeatures && { noDefaultFeatures }) };
+}
+
+/**
+ * @param configuration - Feature configuration to analyze under
+ * @returns rust-analyzer's `cargo` initialization options for the configuration
+ */
+export function rustAnalyzerCargoOptions(configuration: FeatureConfiguration): Record<string, unknown> {
+    return {
+        ...(configuration.features && { features: configuration.features }),
+        ...(configuration.noDefaultFeatures && { noDefaultFeatures: true })
+    };
+}
+
+/**
+ * Merges the symbols extracted under several feature configurations. A symbol is the same across
+ * configurations when its file, kind, name and line match under the same parent. Every symbol gets
+ * `configurations`, the names of the configurations it was found in; symbols missing from some
+ * configuration also get `cfg`, the conditions of the `cfg` attributes on them and their parents.
+ *
+ * @param runs - Symbols per configuration, in the order the configurations were given
+ * @returns The merged symbols; those of the first run come first, others are placed after their
+ * nearest preceding symbol in the same file
+ */
+export function mergeConfigurationSymbols(
+    runs: Array<{ configuration: string; symbols: SymbolInfo[] }>
+): SymbolInfo[] {
+    const merged: SymbolInfo[] = [];
+    for (const run of runs) {
+        mergeInto(merged, run.symbols, run.configuration);
+    }
+    tagCfg(merged, runs.length, []);
+    return merged;
+}
+
+function symbolKey(symbol: SymbolInfo): string {
+    return `${symbol.file}\0${symbol.kind}\0${symbol.name}\0${symbol.range.start.line}`;
+}
+
+function mergeInto(target: SymbolInfo[], incoming: SymbolInfo[], configuration: string): void {
+    const byKey = new Map(target.map((symbol) => [symbolKey(symbol), symbol]));
+    for (const symbol of incoming) {
+        const existing = byKey.get(symbolKey(symbol));
+        if (existing) {
+            existing.configurations?.push(configuration);
+            if (symbol.children) {
+                existing.children ??= [];
+                mergeInto(existing.children, symbol.children, configuration);
+            }
+            continue;
+        }
+
+        tagConfiguration(symbol, configuration);
+        // Keep source order: insert after the last symbol of the same file starting at or before it
+        let index = -1;
+        target.forEach((other, i) => {
+            if (other.file === symbol.file && other.range.start.line <= symbol.range.start.line) {
+                index = i;
+            }
+        });
+        target.splice(index === -1 ? target.length : index + 1, 0, symbol);
+        byKey.set(symbolKey(symbol), symbol);
+    }
+}
+
+function tagConfiguration(symbol: SymbolInfo, configuration: string): void {
+    symbol.configurations = [configuration];
+    symbol.children?.forEach((child) => tagConfiguration(child, configuration));
+}
+
+function tagCfg(symbols: SymbolInfo[], configurationCount: number, inherited: string[]): void {
+    for (const symbol of symbols) {
+        const conditions = [
+            ...inherited,
+            ...(symbol.attributes ?? [])
+                .filter((attribute) => attribute.name === 'cfg' && attribute.arguments)
+                .map((attribute) => attribute.arguments as string)
+        ];
+        if ((symbol.configurations?.length ?? 0) < configurationCount && conditions.length > 0) {
+            symbol.cfg = conditions.length === 1 ? conditions[0] : `all(${conditions.join(', ')})`;
+        }
+        if (symbol.children) {
+            tagCfg(symbol.children, configurationCount, conditions);
+        }
+    }
+}
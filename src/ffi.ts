@@ -0,0 +1,86 @@
+import type { SupportedLanguage, SymbolInfo } from './types';
+
+interface ForeignBlock {
+    /** Lines of the opening and closing brace */
+    start: number;
+    end: number;
+    abi: string;
+}
+
+/**
+ * Tags the items of foreign blocks with their `abi` and `foreign: true`: Rust `extern "C" { ... }`
+ * blocks (`extern { ... }` is `C` as well) declare foreign functions and statics, and C and C++
+ * `extern "C" { ... }` linkage blocks give their items C linkage, where only declarations without a
+ * body are foreign. Functions defined with an ABI, like `extern "C" fn`, carry it in `modifiers`.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param lines - Source lines of the file
+ * @param language - Language of the file
+ */
+export function addForeignItems(symbols: SymbolInfo[], lines: string[], language: SupportedLanguage): void {
+    if (language !== 'rust' && language !== 'c' && language !== 'cpp') {
+        return;
+    }
+    const blocks = findForeignBlocks(lines, language);
+    if (blocks.length === 0) {
+        return;
+    }
+
+    const visit = (symbol: SymbolInfo) => {
+        const { start, end } = symbol.range;
+        const block = blocks.find((candidate) => candidate.start <= start.line && end.line <= candidate.end);
+        if (block) {
+            symbol.abi = block.abi;
+            if (language === 'rust' || !hasBody(symbol, lines)) {
+                symbol.foreign = true;
+            }
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
+
+/**
+ * Foreign blocks. Rust blocks open on the `extern` line, as `extern "C"` followed by `fn` on the next
+ * line is a function exported to C; C and C++ blocks may open on a line of their own below it.
+ */
+function findForeignBlocks(lines: string[], language: SupportedLanguage): ForeignBlock[] {
+    const header =
+        language === 'rust' ? /^\s*(?:unsafe\s+)?extern\s*(?:"([^"]*)")?\s*(\{)/ : /^\s*extern\s*"([^"]*)"\s*(\{|$)/;
+    const blocks: ForeignBlock[] = [];
+    for (let line = 0; line < lines.length; line++) {
+        const match = header.exec(lines[line]);
+        if (!match) {
+            continue;
+        }
+        // Otherwise `extern "C"` applies to the single declaration that follows
+        const open = match[2] === '{' ? line : line + 1;
+        if (open !== line && !lines[open]?.trimStart().startsWith('{')) {
+            continue;
+        }
+        const end = closingLine(lines, open);
+        blocks.push({ start: line, end, abi: match[1] ?? 'C' });
+        line = end;
+    }
+    return blocks;
+}
+
+/** Line of the brace closing the first `{` on `line`, or the last line if it isn't closed */
+function closingLine(lines: string[], line: number): number {
+    let depth = 0;
+    for (let index = line; index < lines.length; index++) {
+        const text = lines[index].replace(/\/\/.*$/, '').replace(/"(?:\\.|[^"\\])*"/g, '""');
+        for (let column = index === line ? text.indexOf('{') : 0; column < text.length; column++) {
+            if (text[column] === '{') {
+                depth++;
+            } else if (text[column] === '}' && --depth === 0) {
+                return index;
+            }
+        }
+    }
+    return lines.length - 1;
+}
+
+function hasBody(symbol: SymbolInfo, lines: string[]): boolean {
+    return lines.slice(symbol.range.start.line, symbol.range.end.line + 1).some((line) => line.includes('{'));
+}
@@ -0,0 +1,141 @@
+import { execFile } from 'node:child_process';
+import { dirname } from 'node:path';
+import { promisify } from 'node:util';
+import type { Logger } from './logger';
+import type { BlameInfo, SymbolInfo } from './types';
+
+const execFileAsync = promisify(execFile);
+
+const UNCOMMITTED = '0000000000000000000000000000000000000000';
+const MAX_CONCURRENT_BLAMES = 8;
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+interface CommitInfo {
+    author: string;
+    email: string;
+    time: number;
+}
+
+interface FileBlame {
+    commits: Map<string, CommitInfo>;
+    /** Commit hash per 0-based line */
+    lines: string[];
+}
+
+/**
+ * Attaches the most recent commit touching each symbol's line range as `blame`, recursing into children.
+ * Files that are not tracked by git are skipped; uncommitted lines are ignored.
+ *
+ * @param symbols - Symbols to enrich in place
+ * @param logger - Logger for skipped files
+ * @param now - Reference time for `ageDays`
+ */
+export async function addGitBlame(symbols: SymbolInfo[], logger: Logger, now = Date.now()): Promise<void> {
+    const files = new Set<string>();
+    const collect = (symbol: SymbolInfo) => {
+        files.add(symbol.file);
+        symbol.children?.forEach(collect);
+    };
+    symbols.forEach(collect);
+
+    const blames = new Map<string, FileBlame>();
+    const queue = [...files];
+    const worker = async () => {
+        for (let file = queue.shift(); file !== undefined; file = queue.shift()) {
+            try {
+                blames.set(file, await blameFile(file));
+            } catch (error) {
+                logger.debug(`Skipping git blame for ${file}: ${error instanceof Error ? error.message : error}`);
+            }
+        }
+    };
+    await Promise.all(Array.from({ length: Math.min(MAX_CONCURRENT_BLAMES, queue.length) }, worker));
+
+    const annotate = (symbol: SymbolInfo) => {
+        const blame = blames.get(symbol.file);
+        if (blame) {
+            const info = blameRange(blame, symbol.range.start.line, symbol.range.end.line, now);
+            if (info) {
+                symbol.blame = info;
+            }
+        }
+        symbol.children?.forEach(annotate);
+    };
+    symbols.forEach(annotate);
+}
+
+async function blameFile(file: string): Promise<FileBlame> {
+    const { stdout } = await execFileAsync('git', ['blame', '--porcelain', '--', file], {
+        cwd: dirname(file),
+        maxBuffer: 256 * 1024 * 1024
+    });
+    return parsePorcelain(stdout);
+}
+
+/**
+ * Parses `git blame --porcelain` output. Each line group starts with
+ * `<hash> <original line> <final line> [<group size>]`; commit metadata follows
+ * only the first time a hash appears, and every source line is prefixed with a tab.
+ */
+function parsePorcelain(output: string): FileBlame {
+    const commits = new Map<string, CommitInfo>();
+    const lines: string[] = [];
+    let hash = '';
+    let finalLine = 0;
+
+    for (const line of output.split('\n')) {
+        if (line.startsWith('\t')) {
+            lines[finalLine - 1] = hash;
+            continue;
+        }
+
+        const header = /^([0-9a-f]{40}) \d+ (\d+)/.exec(line);
+        if (header) {
+            hash = header[1];
+            finalLine = Number(header[2]);
+            if (!commits.has(hash)) {
+                commits.set(hash, { author: '', email: '', time: 0 });
+            }
+            continue;
+        }
+
+        const commit = commits.get(hash);
+        if (!commit) {
+            continue;
+        }
+        if (line.startsWith('author ')) {
+            commit.author = line.slice('author '.length);
+        } else if (line.startsWith('author-mail ')) {
+            commit.email = line.slice('author-mail '.length).replace(/^<|>$/g, '');
+        } else if (line.startsWith('author-time ')) {
+            commit.time = Number(line.slice('author-time '.length)) * 1000;
+        }
+    }
+
+    return { commits, lines };
+}
+
+function blameRange(blame: FileBlame, startLine: number, endLine: number, now: number): BlameInfo | undefined {
+    let latestHash: string | undefined;
+    let latest: CommitInfo | undefined;
+
+    for (let line = startLine; line <= endLine && line < blame.lines.length; line++) {
+        const hash = blame.lines[line];
+        const commit = hash && hash !== UNCOMMITTED ? blame.commits.get(hash) : undefined;
+        if (commit && (!latest || commit.time > latest.time)) {
+            latest = commit;
+            latestHash = hash;
+        }
+    }
+
+    if (!latest || !latestHash) {
+        return undefined;
+    }
+    return {
+        author: latest.author,
+        email: latest.email,
+        commit: latestHash,
+        date: new Date(latest.time).toISOString(),
+        ageDays: Math.max(0, Math.floor((now - latest.time) / DAY_MS))
+    };
+}
@@ -0,0 +1,340 @@
+import { createServer, type Http2Server, type IncomingHttpHeaders, type ServerHttp2Stream } from 'node:http2';
+import { gunzipSync } from 'node:zlib';
+import type { Logger } from './logger';
+import { ProtoMessage, ProtoWriter } from './protobuf';
+import { querySymbols } from './query';
+import type { SessionPool } from './session';
+import { type SnapshotStore, type SnapshotSummary, summarizeSnapshot } from './snapshots';
+import type { HoverInfo, Position, Range, SourceLocation, SupportedLanguage, SymbolInfo } from './types';
+import { isSupportedLanguage, parseListenAddress } from './utils';
+
+const SERVICE = 'lspcli.v1.LspCli';
+
+// https://grpc.github.io/grpc/core/md_doc_statuscodes.html
+const GrpcStatus = {
+    OK: 0,
+    INVALID_ARGUMENT: 3,
+    NOT_FOUND: 5,
+    UNIMPLEMENTED: 12,
+    INTERNAL: 13
+} as const;
+
+class GrpcError extends Error {
+    constructor(
+        readonly code: number,
+        message: string
+    ) {
+        super(message);
+    }
+}
+
+/** Writes a response message, or for server-streaming methods one message of the stream */
+type Send = (message: ProtoWriter) => void;
+
+type Handler = (request: ProtoMessage, send: Send) => Promise<void>;
+
+// Fields of Symbol that have a typed counterpart in proto/lsp_cli.proto; everything else goes to extensions_json
+const TYPED_SYMBOL_FIELDS = new Set([
+    'name',
+    'kind',
+    'file',
+    'range',
+    'preview',
+    'documentation',
+    'comments',
+    'supertypes',
+    'children',
+    'definition'
+]);
+
+/**
+ * Starts the gRPC server (plaintext HTTP/2) implementing proto/lsp_cli.proto.
+ *
+ * @param address - Listen address, e.g. ":50051"
+ * @param sessions - Language server sessions, shared with the other server modes
+ * @param snapshots - Snapshot store, shared with the other server modes
+ * @param logger - Logger for request and server diagnostics
+ * @returns The listening server and a function that stops accepting requests
+ */
+export async function startGrpcServer(
+    address: string,
+    sessions: SessionPool,
+    snapshots: SnapshotStore,
+    logger: Logger
+): Promise<{ server: Http2Server; close: () => Promise<void> }> {
+    const { host, port } = parseListenAddress(address);
+
+    const handlers: Record<string, Handler> = {
+        Analyze: async (request, send) => {
+            const session = sessions.get(
+                requireLanguage(request.string(2)),
+                requireString(request.string(1), 'directory')
+            );
+            const snapshot = snapshots.add(await session.analyze());
+            send(encodeSnapshot(summarizeSnapshot(snapshot)));
+        },
+
+        ListSnapshots: async (_request, send) => {
+            const response = new ProtoWriter();
+            for (const summary of snapshots.list()) {
+                response.message(1, encodeSnapshot(summary));
+            }
+            send(response);
+        },
+
+        QuerySymbols: async (request, send) => {
+            const { output, symbols } = runQuery(request);
+            const response = new ProtoWriter().string(1, output.language).string(2, output.directory);
+            for (const symbol of symbols) {
+                response.message(3, encodeSymbol(symbol));
+            }
+            send(response);
+        },
+
+        StreamSymbols: async (request, send) => {
+            for (const symbol of runQuery(request).symbols) {
+                send(encodeSymbol(symbol));
+            }
+        },
+
+        Definition: async (request, send) => {
+            const { session, file, position } = positionRequest(request);
+            send(encodeLocations(await session.definition(file, position)));
+        },
+
+        Hover: async (request, send) => {
+            const { session, file, position } = positionRequest(request);
+            send(encodeHover(await session.hover(file, position)));
+        },
+
+        References: async (request, send) => {
+            const { session, file, position } = positionRequest(request);
+            const includeDeclaration = request.has(5) ? request.bool(5) : true;
+            send(encodeLocations(await session.references(file, position, includeDeclaration)));
+        }
+    };
+
+    const runQuery = (request: ProtoMessage) => {
+        const snapshotId = requireString(request.string(1), 'snapshot_id');
+        const snapshot = snapshots.get(snapshotId);
+        if (!snapshot) {
+            throw new GrpcError(GrpcStatus.NOT_FOUND, `Snapshot '${snapshotId}' not found`);
+        }
+        const symbols = querySymbols(snapshot.output.symbols, {
+            name: request.string(2) || undefined,
+            kind: request.string(3) || undefined,
+            file: request.string(4) || undefined
+        });
+        return { output: snapshot.output, symbols };
+    };
+
+    const positionRequest = (request: ProtoMessage) => {
+        const session = sessions.get(
+            requireLanguage(request.string(2)),
+            requireString(request.string(1), 'directory')
+        );
+        const file = requireString(request.string(3), 'file');
+        const position = request.message(4);
+        if (!position) {
+            throw new GrpcError(GrpcStatus.INVALID_ARGUMENT, "Missing required field 'position'");
+        }
+        return { session, file, position: { line: position.uint(1), character: position.uint(2) } };
+    };
+
+    const server = createServer();
+
+    server.on('stream', (stream, headers) => {
+        handleCall(stream, headers, handlers, logger);
+    });
+
+    await new Promise<void>((resolve, reject) => {
+        server.once('error', reject);
+        server.listen(port, host, () => {
+            server.off('error', reject);
+            resolve();
+        });
+    });
+
+    const close = () => new Promise<void>((resolve) => server.close(() => resolve()));
+
+    return { server, close };
+}
+
+function handleCall(
+    stream: ServerHttp2Stream,
+    headers: IncomingHttpHeaders,
+    handlers: Record<string, Handler>,
+    logger: Logger
+): void {
+    const path = String(headers[':path'] ?? '');
+    const method = path.startsWith(`/${SERVICE}/`) ? path.substring(SERVICE.length + 2) : undefined;
+    logger.debug(`gRPC ${path}`);
+
+    const chunks: Buffer[] = [];
+    stream.on('data', (chunk: Buffer) => chunks.push(chunk));
+    stream.on('error', (error) => logger.debug(`gRPC stream error on ${path}: ${error.message}`));
+
+    stream.on('end', () => {
+        let responded = false;
+        const respond = () => {
+            if (!responded) {
+                responded = true;
+                stream.respond(
+                    { ':status': 200, 'content-type': 'application/grpc+proto' },
+                    { waitForTrailers: true }
+                );
+            }
+        };
+
+        let status: number = GrpcStatus.OK;
+        let message = '';
+        stream.on('wantTrailers', () => {
+            stream.sendTrailers({ 'grpc-status': String(status), 'grpc-message': encodeURIComponent(message) });
+        });
+
+        const finish = (error?: unknown) => {
+            if (error) {
+                status = error instanceof GrpcError ? error.code : GrpcStatus.INTERNAL;
+                message = error instanceof Error ? error.message : String(error);
+                if (status === GrpcStatus.INTERNAL) {
+                    logger.error(`gRPC call failed: ${path}`, message);
+                }
+            }
+            respond();
+            stream.end();
+        };
+
+        const handler = method ? handlers[method] : undefined;
+        if (!handler) {
+            finish(new GrpcError(GrpcStatus.UNIMPLEMENTED, `Unknown method ${path}`));
+            return;
+        }
+
+        let request: ProtoMessage;
+        try {
+            request = decodeRequest(Buffer.concat(chunks), headers['grpc-encoding']);
+        } catch (error) {
+            finish(
+                error instanceof GrpcError ? error : new GrpcError(GrpcStatus.INVALID_ARGUMENT, String(error))
+            );
+            return;
+        }
+
+        handler(request, (response) => {
+            respond();
+            stream.write(frame(response.finish()));
+        }).then(() => finish(), finish);
+    });
+}
+
+/**
+ * Unwraps the length-prefixed gRPC message frame of a unary/server-streaming request.
+ */
+function decodeRequest(body: Buffer, encoding: string | string[] | undefined): ProtoMessage {
+    if (body.length < 5) {
+        return ProtoMessage.decode(Buffer.alloc(0));
+    }
+
+    const compressed = body[0] === 1;
+    const length = body.readUInt32BE(1);
+    let payload = body.subarray(5, 5 + length);
+
+    if (compressed) {
+        if (encoding !== 'gzip') {
+            throw new GrpcError(GrpcStatus.UNIMPLEMENTED, `Unsupported grpc-encoding '${encoding}'`);
+        }
+        payload = gunzipSync(payload);
+    }
+
+    return ProtoMessage.decode(payload);
+}
+
+function frame(payload: Buffer): Buffer {
+    const header = Buffer.alloc(5);
+    header.writeUInt8(0, 0);
+    header.writeUInt32BE(payload.length, 1);
+    return Buffer.concat([header, payload]);
+}
+
+function requireString(value: string, field: string): string {
+    if (!value) {
+        throw new GrpcError(GrpcStatus.INVALID_ARGUMENT, `Missing required field '${field}'`);
+    }
+    return value;
+}
+
+function requireLanguage(language: string): SupportedLanguage {
+    if (!isSupportedLanguage(requireString(language, 'language'))) {
+        throw new GrpcError(GrpcStatus.INVALID_ARGUMENT, `Unsupported language '${language}'`);
+    }
+    return language as SupportedLanguage;
+}
+
+function encodeSnapshot(summary: SnapshotSummary): ProtoWriter {
+    return new ProtoWriter()
+        .string(1, summary.id)
+        .string(2, summary.createdAt)
+        .string(3, summary.language)
+        .string(4, summary.directory)
+        .uint(5, summary.symbolCount);
+}
+
+function encodePosition(position: Position): ProtoWriter {
+    return new ProtoWriter().uint(1, position.line).uint(2, position.character);
+}
+
+function encodeRange(range: Range): ProtoWriter {
+    return new ProtoWriter().message(1, encodePosition(range.start)).message(2, encodePosition(range.end));
+}
+
+function encodeLocation(location: SourceLocation): ProtoWriter {
+    return new ProtoWriter()
+        .string(1, location.file)
+        .message(2, encodeRange(location.range))
+        .string(3, location.preview);
+}
+
+function encodeLocations(locations: SourceLocation[]): ProtoWriter {
+    const response = new ProtoWriter();
+    for (const location of locations) {
+        response.message(1, encodeLocation(location));
+    }
+    return response;
+}
+
+function encodeHover(hover: HoverInfo | undefined): ProtoWriter {
+    return new ProtoWriter()
+        .bool(1, hover !== undefined)
+        .string(2, hover?.contents)
+        .message(3, hover?.range ? encodeRange(hover.range) : undefined);
+}
+
+function encodeSymbol(symbol: SymbolInfo): ProtoWriter {
+    const writer = new ProtoWriter()
+        .string(1, symbol.name)
+        .string(2, symbol.kind)
+        .string(3, symbol.file)
+        .message(4, encodeRange(symbol.range))
+        .string(5, symbol.preview)
+        .string(6, symbol.documentation);
+
+    for (const comment of symbol.comments ?? []) {
+        writer.string(7, comment);
+    }
+    for (const supertype of symbol.supertypes ?? []) {
+        writer.string(8, supertype);
+    }
+    for (const child of symbol.children ?? []) {
+        writer.message(9, encodeSymbol(child));
+    }
+    writer.message(10, symbol.definition ? encodeLocation(symbol.definition) : undefined);
+
+    const extensions = Object.fromEntries(
+        Object.entries(symbol).filter(([key, value]) => !TYPED_SYMBOL_FIELDS.has(key) && value !== undefined)
+    );
+    if (Object.keys(extensions).length > 0) {
+        writer.string(15, JSON.stringify(extensions));
+    }
+
+    return writer;
+}
@@ -0,0 +1,94 @@
+import { execFile } from 'node:child_process';
+import { readFile } from 'node:fs/promises';
+import { extname, join, relative, resolve, sep } from 'node:path';
+import { promisify } from 'node:util';
+import type { ChangeReport, FileVersions } from './changes';
+import type { SupportedLanguage } from './types';
+import { SOURCE_EXTENSIONS } from './utils';
+
+const execFileAsync = promisify(execFile);
+
+// Hash of the empty tree, used as the base before the first commit
+const EMPTY_TREE = '4b825dc642cb6eb9a060e54bf8d69288fbee4904';
+
+async function git(cwd: string, args: string[]): Promise<string> {
+    const { stdout } = await execFileAsync('git', args, { cwd, maxBuffer: 256 * 1024 * 1024 });
+    return stdout;
+}
+
+/**
+ * @param directory - Any directory inside the repository
+ * @returns Absolute path of the repository's working tree root
+ */
+export async function getRepositoryRoot(directory: string): Promise<string> {
+    return (await git(directory, ['rev-parse', '--show-toplevel'])).trim();
+}
+
+/**
+ * Collects the old and new content of changed source files below a workspace directory.
+ *
+ * @param workspace - Workspace root; files outside it are ignored and paths are relative to it
+ * @param language - Only files with this language's extensions are collected
+ * @param staged - Compare the index with HEAD (pre-commit); otherwise compare the working tree with HEAD
+ * @returns One entry per changed file; `before` is missing for added and `after` for deleted files
+ */
+export async function collectChangedFiles(
+    workspace: string,
+    language: SupportedLanguage,
+    staged: boolean
+): Promise<FileVersions[]> {
+    const root = await getRepositoryRoot(workspace);
+    const base = await git(root, ['rev-parse', '--verify', '--quiet', 'HEAD']).then(
+        () => 'HEAD',
+        () => EMPTY_TREE
+    );
+
+    const diffArgs = ['diff', '--name-status', '-z', '--no-renames', ...(staged ? ['--cached'] : []), base];
+    const fields = (await git(root, diffArgs)).split('\0').filter((field) => field.length > 0);
+    const extensions = SOURCE_EXTENSIONS[language];
+    const files: FileVersions[] = [];
+
+    for (let i = 0; i + 1 < fields.length; i += 2) {
+        const status = fields[i];
+        const path = fields[i + 1];
+        const file = relative(workspace, join(root, path));
+        if (file.startsWith(`..${sep}`) || !extensions.includes(extname(path))) {
+            continue;
+        }
+
+        const before = status === 'A' ? undefined : await git(root, ['show', `${base}:${path}`]);
+        let after: string | undefined;
+        if (status !== 'D') {
+            after = staged ? await git(root, ['show', `:${path}`]) : await readFile(join(root, path), 'utf-8');
+        }
+        files.push({ file, before, after });
+    }
+
+    return files;
+}
+
+/**
+ * Sends changed files to a running `lsp-cli serve --http` daemon, whose language server is already warm.
+ *
+ * @param daemonUrl - Base URL of the daemon, e.g. http://127.0.0.1:8080
+ * @param workspace - Workspace root as seen by the daemon
+ * @param language - Language of the workspace
+ * @param files - Changed files
+ */
+export async function compareWithDaemon(
+    daemonUrl: string,
+    workspace: string,
+    language: SupportedLanguage,
+    files: FileVersions[]
+): Promise<ChangeReport> {
+    const response = await fetch(new URL('/compare', daemonUrl), {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ directory: resolve(workspace), language, files })
+    });
+    const body = await response.json();
+    if (!response.ok) {
+        throw new Error(`Daemon returned ${response.status}: ${body.error ?? response.statusText}`);
+    }
+    return body;
+}
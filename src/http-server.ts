@@ -0,0 +1,249 @@
+import { createHash, timingSafeEqual } from 'node:crypto';
+import { createServer, type IncomingMessage, type Server, type ServerResponse } from 'node:http';
+import { compareVersions, type FileVersions } from './changes';
+import type { Logger } from './logger';
+import { querySymbols } from './query';
+import type { SessionPool } from './session';
+import type { SnapshotStore } from './snapshots';
+import type { Position, SupportedLanguage } from './types';
+import { isSupportedLanguage, parseListenAddress, SUPPORTED_LANGUAGES } from './utils';
+
+class HttpError extends Error {
+    constructor(
+        readonly status: number,
+        message: string
+    ) {
+        super(message);
+    }
+}
+
+// Large enough for the file contents sent to /compare
+const MAX_BODY_BYTES = 16 * 1024 * 1024;
+
+/**
+ * Starts the REST API server.
+ *
+ * Endpoints (all JSON):
+ * - `POST /analyze` `{ directory, language }` runs an extraction and stores it as a snapshot
+ * - `GET /snapshots` lists stored snapshots
+ * - `GET /snapshots/:id` returns a snapshot in the CLI output schema
+ * - `GET /snapshots/:id/symbols?name=&kind=&file=` returns matching symbols in the CLI output schema
+ * - `DELETE /snapshots/:id` drops a snapshot
+ * - `POST /definition`, `POST /hover`, `POST /references` `{ directory, language, file, line, character }`
+ * - `POST /compare` `{ directory, language, files: [{ file, before?, after? }] }` reports changes between file versions
+ *
+ * With a token, all endpoints but `GET /health` require `Authorization: Bearer <token>`.
+ *
+ * @param address - Listen address, e.g. ":8080" (loopback) or "0.0.0.0:8080"
+ * @param sessions - Language server sessions, shared with the other server modes
+ * @param snapshots - Snapshot store, shared with the other server modes
+ * @param logger - Logger for request and server diagnostics
+ * @param token - Bearer token clients must send, if any
+ * @returns The listening server and a function that stops accepting requests
+ */
+export async function startHttpServer(
+    address: string,
+    sessions: SessionPool,
+    snapshots: SnapshotStore,
+    logger: Logger,
+    token?: string
+): Promise<{ server: Server; close: () => Promise<void> }> {
+    const { host, port } = parseListenAddress(address);
+
+    const routes = async (req: IncomingMessage, res: ServerResponse): Promise<void> => {
+        const url = new URL(req.url ?? '/', 'http://localhost');
+        const segments = url.pathname.split('/').filter((segment) => segment.length > 0);
+
+        if (req.method === 'GET' && url.pathname === '/health') {
+            return sendJson(res, 200, { status: 'ok' });
+        }
+
+        if (token !== undefined && !isAuthorized(req, token)) {
+            res.setHeader('WWW-Authenticate', 'Bearer');
+            throw new HttpError(401, 'Missing or invalid bearer token');
+        }
+
+        if (req.method === 'POST' && url.pathname === '/analyze') {
+            const body = await readJsonBody(req);
+            const session = sessions.get(requireLanguage(body), requireString(body, 'directory'));
+            const output = await session.analyze();
+            const snapshot = snapshots.add(output);
+
+            res.setHeader('Location', `/snapshots/${snapshot.id}`);
+            res.setHeader('X-Snapshot-Id', snapshot.id);
+            return sendJson(res, 201, output);
+        }
+
+        if (req.method === 'GET' && segments[0] === 'snapshots') {
+            if (segments.length === 1) {
+                return sendJson(res, 200, snapshots.list());
+            }
+
+            const snapshot = snapshots.get(segments[1]);
+            if (!snapshot) {
+                throw new HttpError(404, `Snapshot '${segments[1]}' not found`);
+            }
+
+            if (segments.length === 2) {
+                return sendJson(res, 200, snapshot.output);
+            }
+
+            if (segments.length === 3 && segments[2] === 'symbols') {
+                const symbols = querySymbols(snapshot.output.symbols, {
+                    name: url.searchParams.get('name') ?? undefined,
+                    kind: url.searchParams.get('kind') ?? undefined,
+                    file: url.searchParams.get('file') ?? undefined
+                });
+                return sendJson(res, 200, { ...snapshot.output, symbols });
+            }
+        }
+
+        if (req.method === 'DELETE' && segments[0] === 'snapshots' && segments.length === 2) {
+            if (!snapshots.remove(segments[1])) {
+                throw new HttpError(404, `Snapshot '${segments[1]}' not found`);
+            }
+            res.writeHead(204);
+            res.end();
+            return;
+        }
+
+        if (req.method === 'POST' && ['/definition', '/hover', '/references'].includes(url.pathname)) {
+            const body = await readJsonBody(req);
+            const session = sessions.get(requireLanguage(body), requireString(body, 'directory'));
+            const file = requireString(body, 'file');
+            const position = requirePosition(body);
+
+            if (url.pathname === '/definition') {
+                return sendJson(res, 200, { locations: await session.definition(file, position) });
+            }
+            if (url.pathname === '/hover') {
+                return sendJson(res, 200, { hover: (await session.hover(file, position)) ?? null });
+            }
+            return sendJson(res, 200, {
+                locations: await session.references(file, position, body.includeDeclaration !== false)
+            });
+        }
+
+        if (req.method === 'POST' && url.pathname === '/compare') {
+            const body = await readJsonBody(req);
+            const session = sessions.get(requireLanguage(body), requireString(body, 'directory'));
+            return sendJson(res, 200, await compareVersions(session, requireFileVersions(body)));
+        }
+
+        throw new HttpError(404, `No route for ${req.method} ${url.pathname}`);
+    };
+
+    const server = createServer((req, res) => {
+        logger.debug(`${req.method} ${req.url}`);
+        routes(req, res).catch((error) => {
+            const status = error instanceof HttpError ? error.status : 500;
+            const message = error instanceof Error ? error.message : String(error);
+            if (status === 500) {
+                logger.error(`Request failed: ${req.method} ${req.url}`, message);
+            }
+            sendJson(res, status, { error: message });
+        });
+    });
+
+    await new Promise<void>((resolve, reject) => {
+        server.once('error', reject);
+        server.listen(port, host, () => {
+            server.off('error', reject);
+            resolve();
+        });
+    });
+
+    const close = async () => {
+        const closed = new Promise<void>((resolve) => server.close(() => resolve()));
+        server.closeAllConnections();
+        await closed;
+    };
+
+    return { server, close };
+}
+
+/**
+ * @param req - Incoming request
+ * @param token - Token the server was started with
+ * @returns Whether the request carries the token as `Authorization: Bearer <token>`
+ */
+function isAuthorized(req: IncomingMessage, token: string): boolean {
+    // Digests have the same length, which timingSafeEqual needs, whatever was sent
+    const digest = (value: string) => createHash('sha256').update(value).digest();
+    return timingSafeEqual(digest(req.headers.authorization ?? ''), digest(`Bearer ${token}`));
+}
+
+function sendJson(res: ServerResponse, status: number, body: unknown): void {
+    if (res.headersSent) {
+        res.end();
+        return;
+    }
+    res.writeHead(status, { 'Content-Type': 'application/json' });
+    res.end(JSON.stringify(body, null, 2));
+}
+
+async function readJsonBody(req: IncomingMessage): Promise<Record<string, any>> {
+    const chunks: Buffer[] = [];
+    let size = 0;
+
+    for await (const chunk of req) {
+        size += chunk.length;
+        if (size > MAX_BODY_BYTES) {
+            throw new HttpError(413, 'Request body too large');
+        }
+        chunks.push(chunk);
+    }
+
+    try {
+        const body = JSON.parse(Buffer.concat(chunks).toString('utf-8') || '{}');
+        if (typeof body !== 'object' || body === null || Array.isArray(body)) {
+            throw new Error('not an object');
+        }
+        return body;
+    } catch (_error) {
+        throw new HttpError(400, 'Request body must be a JSON object');
+    }
+}
+
+function requireString(body: Record<string, any>, field: string): string {
+    const value = body[field];
+    if (typeof value !== 'string' || value.length === 0) {
+        throw new HttpError(400, `Missing required field '${field}'`);
+    }
+    return value;
+}
+
+function requireLanguage(body: Record<string, any>): SupportedLanguage {
+    const language = requireString(body, 'language');
+    if (!isSupportedLanguage(language)) {
+        throw new HttpError(
+            400,
+            `Unsupported language '${language}'. Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+        );
+    }
+    return language;
+}
+
+function requireFileVersions(body: Record<string, any>): FileVersions[] {
+    const { files } = body;
+    const valid =
+        Array.isArray(files) &&
+        files.every(
+            (entry) =>
+                typeof entry?.file === 'string' &&
+                (entry.before === undefined || typeof entry.before === 'string') &&
+                (entry.after === undefined || typeof entry.after === 'string')
+        );
+    if (!valid) {
+        throw new HttpError(400, "Field 'files' must be an array of { file, before?, after? } with string values");
+    }
+    return files;
+}
+
+function requirePosition(body: Record<string, any>): Position {
+    const { line, character } = body;
+    if (!Number.isInteger(line) || !Number.isInteger(character) || line < 0 || character < 0) {
+        throw new HttpError(400, "Fields 'line' and 'character' must be non-negative integers (0-based)");
+    }
+    return { line, character };
+}
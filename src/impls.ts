@@ -0,0 +1,123 @@
+import type { ImplInfo, SymbolInfo } from './types';
+
+// Kinds of the symbols impl blocks can be written for, by rust-analyzer's kind mapping
+const TYPE_KINDS = new Set(['struct', 'enum', 'class', 'typeParameter']);
+
+/**
+ * Parses the header of a Rust impl block, e.g. `impl<T: Clone> Processor<T> for Wrapper<T> where ...`.
+ *
+ * @param lines - Source lines of the file
+ * @param line - 0-based line of the impl block's `impl` keyword
+ * @returns The implementing type and trait, or undefined if the line doesn't start an impl block
+ */
+export function parseImplHeader(lines: string[], line: number): ImplInfo | undefined {
+    const source = lines.slice(line, line + 20).join(' ');
+    const start = source.search(/\bimpl\b/);
+    const end = source.search(/[{;]/);
+    if (start === -1 || (end !== -1 && end < start)) {
+        return undefined;
+    }
+    const header = (end === -1 ? source : source.slice(0, end)).replace(/\s+/g, ' ').trim();
+    const headerWhere = header.search(/\bwhere\b/);
+    const signature = (headerWhere === -1 ? header : header.slice(0, headerWhere)).trim();
+
+    let rest = source.slice(start + 'impl'.length, end === -1 ? undefined : end).trim();
+    if (rest.startsWith('<')) {
+        rest = rest.slice(closingAngle(rest) + 1).trim();
+    }
+    const where = rest.search(/\bwhere\b/);
+    if (where !== -1) {
+        rest = rest.slice(0, where).trim();
+    }
+
+    const forIndex = topLevelFor(rest);
+    if (forIndex === -1) {
+        return { type: baseName(rest), signature };
+    }
+    return {
+        type: baseName(rest.slice(forIndex + 'for'.length)),
+        trait: baseName(rest.slice(0, forIndex).replace(/^!/, '')),
+        signature
+    };
+}
+
+/**
+ * Links types and traits through the impl blocks among the symbols: each type gets the `traits`
+ * implemented for it and each trait its `implementors`. Types and traits are matched by name.
+ *
+ * @param symbols - Symbols of the analyzed files, with `impl` set on impl blocks; updated in place
+ */
+export function linkImplementations(symbols: SymbolInfo[]): void {
+    const all: SymbolInfo[] = [];
+    const collect = (symbol: SymbolInfo) => {
+        all.push(symbol);
+        symbol.children?.forEach(collect);
+    };
+    symbols.forEach(collect);
+
+    const traitsByType = new Map<string, Set<string>>();
+    const typesByTrait = new Map<string, Set<string>>();
+    for (const symbol of all) {
+        if (symbol.impl?.trait) {
+            add(traitsByType, symbol.impl.type, symbol.impl.trait);
+            add(typesByTrait, symbol.impl.trait, symbol.impl.type);
+        }
+    }
+
+    for (const symbol of all) {
+        if (TYPE_KINDS.has(symbol.kind) && traitsByType.has(symbol.name)) {
+            symbol.traits = [...(traitsByType.get(symbol.name) as Set<string>)].sort();
+        } else if (symbol.kind === 'interface' && typesByTrait.has(symbol.name)) {
+            symbol.implementors = [...(typesByTrait.get(symbol.name) as Set<string>)].sort();
+        }
+    }
+}
+
+function add(map: Map<string, Set<string>>, key: string, value: string): void {
+    if (!map.has(key)) {
+        map.set(key, new Set());
+    }
+    map.get(key)?.add(value);
+}
+
+/** Index of the `>` closing the `<` at the start of the text */
+function closingAngle(text: string): number {
+    let depth = 0;
+    for (let index = 0; index < text.length; index++) {
+        if (text[index] === '<') {
+            depth++;
+        } else if (text[index] === '>' && text[index - 1] !== '-') {
+            depth--;
+            if (depth === 0) {
+                return index;
+            }
+        }
+    }
+    return text.length - 1;
+}
+
+/** Index of the `for` separating trait and type, skipping `for<'a>` bounds inside generic arguments */
+function topLevelFor(text: string): number {
+    let depth = 0;
+    for (let index = 0; index < text.length; index++) {
+        const char = text[index];
+        if (char === '<' || char === '(') {
+            depth++;
+        } else if ((char === '>' && text[index - 1] !== '-') || char === ')') {
+            depth--;
+        } else if (depth === 0 && /^\bfor\b/.test(text.slice(index)) && /\s/.test(text[index - 1] ?? '')) {
+            return index;
+        }
+    }
+    return -1;
+}
+
+/** `Wrapper` for `Wrapper<T>`, `Display` for `fmt::Display`, `Foo` for `&'a mut dyn Foo` */
+function baseName(type: string): string {
+    const path = type
+        .trim()
+        .replace(/^(&\s*('\w+\s+)?(mut\s+)?|\*\s*(const|mut)\s+|dyn\s+)+/, '')
+        .split('<')[0]
+        .trim();
+    return path.split('::').pop() ?? path;
+}
@@ -1,35 +1,544 @@
-import { existsSync, readFileSync, writeFileSync } from 'node:fs';
-import { dirname, join, resolve } from 'node:path';
-import { Command } from 'commander';
+import { existsSync, readFileSync, statSync } from 'node:fs';
+import { dirname, join, relative, resolve } from 'node:path';
+import { Command, InvalidArgumentError } from 'commander';
+import { clearCache } from './cache';
+import { type CiThresholds, evaluateCi } from './ci';
+import { type ChangeReport, compareVersions } from './changes';
+import { createChunks } from './chunks';
+import { generateCompileCommands, resolveCompileCommands } from './compile-commands';
+import { applyConfig, loadConfig } from './config';
+import { addDependencyInfo, formatDependencyReport } from './dependencies';
+import { formatDoctorReport, runDoctor } from './doctor';
+import { embedChunks, writeEmbeddings } from './embeddings';
+import { ExitCode, ExitError, exitCodeOf } from './exit-codes';
+import { mergeConfigurationSymbols, parseFeatureConfiguration } from './feature-matrix';
+import { addGitBlame } from './git-blame';
+import { startGrpcServer } from './grpc-server';
+import { collectChangedFiles, compareWithDaemon, getRepositoryRoot } from './hook';
+import { startHttpServer } from './http-server';
+import { startJsonRpcServer } from './jsonrpc-server';
 import { LanguageClient } from './language-client';
-import { Logger } from './logger';
+import { formatLanguages, listLanguages } from './languages';
+import { type LogFilter, type LogFormat, Logger, type LoggerOptions, parseLogFilter } from './logger';
+import { applyPlugins, loadPlugins } from './plugins';
+import { createSnapshotPayload, getSnapshotSource, pushSnapshot } from './push';
+import { filterSymbolsByKind } from './query';
+import { fetchRepository, isArchive, isGitUrl, openArchive } from './remote';
+import { emptyFileCounts, type FileCounts, summarizeRun } from './run-summary';
+import { ciToSarif, diagnosticsToSarif } from './sarif';
+import { loadScripts } from './scripts';
+import { type ReleaseChannel, selfUpdate } from './self-update';
 import { ServerManager } from './server-manager';
-import type { SupportedLanguage } from './types';
-import { checkProjectFiles, checkToolchain } from './utils';
+import { Session, SessionPool } from './session';
+import { SnapshotStore } from './snapshots';
+import { createSyntheticWorkspace, relocateSymbols } from './stdin';
+import { addTokenCounts, loadTokenizer } from './tokenizer';
+import { formatToolSchemas, generateToolDefinitions } from './tool-schemas';
+import { createTracer } from './tracing';
+import type {
+    AnalysisOutput,
+    CallGraphEdge,
+    ExtractionError,
+    FeatureConfiguration,
+    SupportedLanguage,
+    SymbolInfo,
+    TrailingDocPolicy
+} from './types';
+import {
+    checkProjectFiles,
+    checkToolchain,
+    detectLanguages,
+    isLoopbackHost,
+    isSupportedLanguage,
+    parseListenAddress,
+    readFileList,
+    SOURCE_EXTENSIONS,
+    SUPPORTED_LANGUAGES,
+    writeOutput
+} from './utils';
+import { VERSION } from './version';
+import { WebhookNotifier } from './webhooks';
 
 const program = new Command();
 
+function collect(value: string, previous: string[]): string[] {
+    return [...previous, value];
+}
+
+function parseNumber(value: string): number {
+    const number = Number(value);
+    if (value.trim() === '' || !Number.isFinite(number) || number < 0) {
+        throw new InvalidArgumentError('Expected a non-negative number.');
+    }
+    return number;
+}
+
+function parseLineCount(value: string): number {
+    const number = Number(value);
+    if (!Number.isInteger(number) || number < 1) {
+        throw new InvalidArgumentError('Expected a positive number of lines.');
+    }
+    return number;
+}
+
+function parseCount(value: string): number {
+    const number = Number(value);
+    if (!Number.isInteger(number) || number < 1) {
+        throw new InvalidArgumentError('Expected a positive integer.');
+    }
+    return number;
+}
+
+function parseTrailingDocs(value: string): TrailingDocPolicy {
+    if (!['next', 'previous', 'drop', 'orphan'].includes(value)) {
+        throw new InvalidArgumentError('Expected next, previous, drop or orphan.');
+    }
+    return value as TrailingDocPolicy;
+}
+
+function increase(_value: string, previous: number): number {
+    return previous + 1;
+}
+
+function parseFilter(value: string): LogFilter {
+    try {
+        return parseLogFilter(value);
+    } catch (error) {
+        throw new InvalidArgumentError(error instanceof Error ? error.message : String(error));
+    }
+}
+
+function parseLogFormat(value: string): LogFormat {
+    if (value !== 'pretty' && value !== 'json') {
+        throw new InvalidArgumentError('Expected pretty or json.');
+    }
+    return value;
+}
+
+function parseSummaryFormat(value: string): 'text' | 'json' {
+    if (value !== 'text' && value !== 'json') {
+        throw new InvalidArgumentError('Expected text or json.');
+    }
+    return value;
+}
+
+/**
+ * Creates a command's logger with the root logging options.
+ *
+ * @param globals - Root options, e.g. from `command.optsWithGlobals()`
+ * @param options - Logger options of the command, e.g. `stderr` when stdout carries output
+ * @returns The logger
+ */
+function commandLogger(
+    globals: {
+        verbose?: number | boolean;
+        quiet?: number;
+        logFilter?: LogFilter;
+        progress?: boolean;
+        logFormat?: LogFormat;
+        logFile?: string;
+    },
+    options: LoggerOptions = {}
+): Logger {
+    return new Logger({
+        verbose: globals.verbose,
+        quiet: globals.quiet,
+        filter: globals.logFilter,
+        progress: globals.progress,
+        format: globals.logFormat,
+        file: globals.logFile,
+        ...options
+    });
+}
+
+function parseKinds(value: string): string[] {
+    return value
+        .split(',')
+        .map((kind) => kind.trim())
+        .filter((kind) => kind !== '');
+}
+
+function collectFeatureSet(value: string, previous: FeatureConfiguration[]): FeatureConfiguration[] {
+    try {
+        return [...previous, parseFeatureConfiguration(value)];
+    } catch (error) {
+        throw new InvalidArgumentError(error instanceof Error ? error.message : String(error));
+    }
+}
+
+// Options not given on the command line come from ~/.config/lsp-cli/config.toml and .lsp-cli.toml
+program.hook('preAction', (_program, actionCommand) => {
+    const logger = commandLogger(actionCommand.optsWithGlobals(), { stderr: true });
+    // The directory argument of the root command, `analyze`, `ci` and `diagnostics`
+    const [first] = actionCommand.args;
+    const directory = first && existsSync(first) && statSync(first).isDirectory() ? first : process.cwd();
+    try {
+        const { config, files } = loadConfig(directory);
+        files.forEach((file) => logger.debug(`Using configuration ${file}`));
+        applyConfig(actionCommand, config, logger);
+    } catch (error) {
+        logger.error('Invalid configuration', error instanceof Error ? error.message : String(error));
+        process.exit(ExitCode.InvalidArguments);
+    }
+});
+
+// Usage errors reported by commander (unknown options, invalid values, missing arguments); help and version exit with 0
+program.exitOverride((error) => {
+    process.exit(error.exitCode === 0 ? ExitCode.Success : ExitCode.InvalidArguments);
+});
+
 process.on('unhandledRejection', (reason, _promise) => {
     const logger = new Logger();
     logger.error('Unhandled Rejection', `${reason}`);
-    process.exit(1);
+    process.exit(exitCodeOf(reason));
 });
 
+interface AnalysisOptions {
+    withGitBlame?: boolean;
+    withDependencies?: boolean;
+    countTokens?: string;
+    plugin?: string[];
+    script?: string[];
+    compileCommands?: string;
+    generateCompileCommands?: boolean;
+    otlpEndpoint?: string;
+    trailingDocs?: TrailingDocPolicy;
+    featureSet?: FeatureConfiguration[];
+    includeBody?: boolean | number;
+    leadingComments?: boolean;
+    expandMacros?: boolean;
+    includeLocals?: boolean;
+    resolveTypes?: boolean;
+    ignore?: string[];
+    kinds?: string[];
+    /** Files to analyze instead of the whole directory, read from --files-from */
+    files?: string[];
+    /** Abort at the first failed file or feature set instead of recording it in `errors` */
+    failFast?: boolean;
+    /** Same as `failFast` */
+    strict?: boolean;
+    callGraph?: boolean;
+    /** Reuse the symbols of unchanged files from the previous run */
+    symbolCache?: boolean;
+    clearCache?: boolean;
+}
+
+async function ensureToolchainAndServer(language: SupportedLanguage, logger: Logger): Promise<void> {
+    // Check toolchain
+    const toolchainResult = await checkToolchain(language);
+    if (!toolchainResult.installed) {
+        throw new ExitError(
+            `Required toolchain not found for ${language}\n${toolchainResult.message}`,
+            ExitCode.ToolchainMissing
+        );
+    }
+
+    // Install/check LSP server
+    const serverManager = new ServerManager(logger);
+    logger.serverStatus(language, 'checking');
+    const serverPath = await serverManager.ensureServer(language);
+    logger.serverStatus(language, 'ready', serverPath);
+}
+
+/**
+ * Runs the extraction pipeline shared by the root command and `analyze`: toolchain and server checks,
+ * symbol extraction, optional enrichment, then plugins and scripts.
+ *
+ * @param directory - Absolute path of the workspace
+ * @param language - Language to extract
+ * @param options - Enrichment and plugin options of the root command
+ * @param logger - Logger for progress
+ * @param fileCounts - Receives the scanned, skipped and failed files
+ */
+async function runAnalysis(
+    directory: string,
+    language: SupportedLanguage,
+    options: AnalysisOptions,
+    logger: Logger,
+    fileCounts: FileCounts = emptyFileCounts()
+): Promise<AnalysisOutput> {
+    const plugins = [
+        ...(await loadPlugins(options.plugin ?? [], logger)),
+        ...(await loadScripts(options.script ?? [], logger))
+    ];
+
+    await ensureToolchainAndServer(language, logger);
+
+    // Check project files
+    const projectFileResult = await checkProjectFiles(directory, language);
+    if (!projectFileResult.found) {
+        logger.warn(`No project configuration found for ${language}`);
+        logger.warn(projectFileResult.message);
+        logger.warn('Results may be incomplete or inaccurate');
+    }
+
+    // Start LSP client and analyze
+    let compileCommandsDir: string | undefined;
+    if (language === 'c' || language === 'cpp') {
+        if (options.compileCommands) {
+            compileCommandsDir = resolveCompileCommands(resolve(options.compileCommands));
+        } else if (options.generateCompileCommands) {
+            compileCommandsDir = await generateCompileCommands(directory, logger);
+        }
+    }
+
+    if (options.clearCache && clearCache(directory)) {
+        logger.info(`Cleared the analysis cache of ${directory}`);
+    }
+
+    const tracer = createTracer(options.otlpEndpoint, logger);
+    const failFast = options.failFast || options.strict;
+    const errors: ExtractionError[] = [];
+    let edges: CallGraphEdge[] | undefined;
+    const extract = async (cargoFeatures?: FeatureConfiguration) => {
+        const client = new LanguageClient(language, directory, logger, {
+            compileCommandsDir,
+            tracer,
+            trailingDocs: options.trailingDocs,
+            cargoFeatures,
+            includeBody: options.includeBody,
+            leadingComments: options.leadingComments,
+            expandMacros: options.expandMacros,
+            includeLocals: options.includeLocals,
+            resolveTypes: options.resolveTypes,
+            ignore: options.ignore,
+            files: options.files,
+            failFast,
+            cache: options.symbolCache
+        });
+        // Starts the server once a file isn't in the cache
+        const symbols = await client.analyzeDirectory();
+        // Feature sets see the same calls; the first run's are enough
+        if (options.callGraph && !edges) {
+            edges = await client.extractCallGraph(symbols);
+        }
+        await client.stop();
+        errors.push(
+            ...client
+                .getExtractionErrors()
+                .map((error) => (cargoFeatures ? { ...error, configuration: cargoFeatures.name } : error))
+        );
+        // Feature sets analyze the same files again; count them once
+        const counts = client.getFileCounts();
+        fileCounts.scanned = Math.max(fileCounts.scanned, counts.scanned);
+        fileCounts.skipped = Math.max(fileCounts.skipped, counts.skipped);
+        fileCounts.failed.push(...counts.failed.filter((file) => !fileCounts.failed.includes(file)));
+        return { client, symbols };
+    };
+    logger.section(`Analyzing ${directory}`);
+
+    const featureConfigurations = options.featureSet ?? [];
+    if (featureConfigurations.length > 0 && language !== 'rust') {
+        logger.warn('--feature-set only applies to Rust and is ignored');
+    }
+    let client: LanguageClient;
+    let symbols: SymbolInfo[];
+    if (language === 'rust' && featureConfigurations.length > 0) {
+        // One rust-analyzer run per configuration; symbols cfg'd out of a configuration are missing from its run
+        const runs: Array<{ configuration: string; client: LanguageClient; symbols: SymbolInfo[] }> = [];
+        for (const configuration of featureConfigurations) {
+            logger.info(`Extracting with feature set '${configuration.name}'`);
+            try {
+                runs.push({ configuration: configuration.name, ...(await extract(configuration)) });
+            } catch (error) {
+                // Without --fail-fast, the other feature sets still make a snapshot
+                if (failFast || (runs.length === 0 && configuration === featureConfigurations.at(-1))) {
+                    throw error;
+                }
+                const reason = error instanceof Error ? error.message : String(error);
+                logger.error(`Feature set '${configuration.name}' failed`, reason);
+                errors.push({ configuration: configuration.name, reason });
+            }
+        }
+        ({ client } = runs[0]);
+        symbols = mergeConfigurationSymbols(runs);
+    } else {
+        ({ client, symbols } = await extract());
+    }
+    await tracer.flush();
+    if (options.kinds) {
+        symbols = filterSymbolsByKind(symbols, options.kinds);
+    }
+
+    if (options.withGitBlame) {
+        logger.info('Collecting git blame information...');
+        await addGitBlame(symbols, logger);
+    }
+    if (options.countTokens) {
+        addTokenCounts(symbols, await loadTokenizer(options.countTokens, logger));
+    }
+
+    const cargo = client.getCargoWorkspace();
+    const orphanedDocs = client.getOrphanedDocs();
+    const fileEncodings = client.getFileEncodings();
+    const fileAliases = client.getFileAliases();
+    const output: AnalysisOutput = {
+        language,
+        directory,
+        positionEncoding: client.getPositionEncoding(),
+        symbols,
+        ...(cargo && { cargo }),
+        ...(options.trailingDocs === 'orphan' && { orphanedDocs }),
+        ...(Object.keys(fileEncodings).length > 0 && { fileEncodings }),
+        ...(Object.keys(fileAliases).length > 0 && { fileAliases }),
+        ...(language === 'rust' && featureConfigurations.length > 0 && { featureConfigurations }),
+        ...(edges && { edges }),
+        ...(errors.length > 0 && { errors })
+    };
+    if (options.withDependencies) {
+        logger.info('Mapping symbols to dependencies...');
+        addDependencyInfo(output, logger);
+    }
+    return applyPlugins(output, plugins, logger);
+}
+
+/**
+ * Analyzes content read from stdin as if it were the given file, using a temporary single-file workspace.
+ * Symbol paths refer to the file name resolved against the current directory.
+ *
+ * @param language - Language of the content
+ * @param filename - Path to analyze the content as
+ * @param options - Enrichment and plugin options of the root command; git blame is not available
+ * @param logger - Logger for progress
+ */
+async function runStdinAnalysis(
+    language: SupportedLanguage,
+    filename: string,
+    options: AnalysisOptions,
+    logger: Logger
+): Promise<AnalysisOutput> {
+    if (options.withGitBlame) {
+        logger.warn('--with-git-blame is ignored for stdin input');
+    }
+    const plugins = [
+        ...(await loadPlugins(options.plugin ?? [], logger)),
+        ...(await loadScripts(options.script ?? [], logger))
+    ];
+    await ensureToolchainAndServer(language, logger);
+
+    const content = readFileSync(0, 'utf-8');
+    const workspace = createSyntheticWorkspace(language, filename, content);
+    const tracer = createTracer(options.otlpEndpoint, logger);
+    const client = new LanguageClient(language, workspace.directory, logger, {
+        tracer,
+        trailingDocs: options.trailingDocs,
+        includeBody: options.includeBody,
+        leadingComments: options.leadingComments,
+        expandMacros: options.expandMacros,
+        includeLocals: options.includeLocals,
+        resolveTypes: options.resolveTypes
+    });
+    try {
+        await client.start();
+        const extracted = await client.analyzeContent(workspace.file, content);
+        const symbols = options.kinds ? filterSymbolsByKind(extracted, options.kinds) : extracted;
+        if (options.countTokens) {
+            addTokenCounts(symbols, await loadTokenizer(options.countTokens, logger));
+        }
+        relocateSymbols(symbols, workspace.file, resolve(filename));
+        return applyPlugins(
+            { language, directory: process.cwd(), positionEncoding: client.getPositionEncoding(), symbols },
+            plugins,
+            logger
+        );
+    } finally {
+        await client.stop();
+        await tracer.flush();
+        workspace.cleanup();
+    }
+}
+
 program
     .name('lsp-cli')
     .description('Extract type information from codebases using LSP servers')
-    .version('1.0.0')
+    .version(VERSION)
     .option('--llm', 'Print llms.md documentation to stdout')
     .argument('[directory]', 'Directory to analyze')
     .argument('[language]', 'Language (java, cpp, c, csharp, haxe, typescript, dart, rust, python)')
-    .argument('[output-file]', 'Output file')
-    .option('-v, --verbose', 'Enable verbose logging')
+    .argument('[output-file]', 'Output file, or - for stdout')
+    .option('-v, --verbose', 'Verbose logging: -v for debug messages, -vv also every LSP request', increase, 0)
+    .option('-q, --quiet', 'Quiet logging: -q for warnings and errors only, -qq for errors only', increase, 0)
+    .option('--log-filter <filter>', 'Log levels per target, e.g. warn,transport=trace,extractor=debug', parseFilter)
+    .option('--profile <name>', 'Apply the options of [profile.<name>] from the configuration files')
+    .option('--no-progress', 'Disable the live progress display on stderr, e.g. in CI')
+    .option('--log-format <format>', 'Log format: pretty, or json for one structured record per line', parseLogFormat)
+    .option('--log-file <path>', 'Also append log lines to a file')
+    .option(
+        '--run-summary <format>',
+        'End-of-run summary: text for a table, json for one JSON line',
+        parseSummaryFormat
+    )
+    .option('--with-git-blame', 'Attach last author, commit and age from git blame to each symbol')
+    .option('--with-dependencies', 'Record the Cargo/npm packages each symbol uses (Rust, TypeScript/JavaScript)')
+    .option('--count-tokens <tokenizer>', 'Record token counts per symbol: model, encoding, .tiktoken file or estimate')
+    .option('--plugin <path>', 'Load a plugin (.js module or .wasm) to post-process symbols (repeatable)', collect, [])
+    .option('--script <path>', 'Run a JavaScript hook script on the output (repeatable)', collect, [])
+    .option('--compile-commands <path>', 'C/C++: compile_commands.json (or its directory) for clangd')
+    .option('--generate-compile-commands', 'C/C++: generate compile_commands.json with CMake, Meson or Bear first')
+    .option('--trailing-docs <policy>', 'Trailing doc comments: next, previous, drop or orphan', parseTrailingDocs)
+    .option('--otlp-endpoint <url>', 'Export traces via OTLP/HTTP (default: $OTEL_EXPORTER_OTLP_ENDPOINT)')
+    .option(
+        '--feature-set <features>',
+        'Rust: extract under a feature set (default, all, none or a,b) and merge (repeatable)',
+        collectFeatureSet,
+        []
+    )
+    .option(
+        '--include-body [max-lines]',
+        "Embed each symbol's source text (full range), cut to max-lines if given",
+        parseLineCount
+    )
+    .option('--leading-comments', 'Record regular (non-doc) comments directly above each symbol as leadingComments')
+    .option('--expand-macros', 'Rust: record the expansion of the macro invocation generating each symbol')
+    .option('--include-locals', 'Also extract nested functions, local types and named closures inside functions')
+    .option('--resolve-types', 'Record the (also inferred) type of fields, constants and variables from hover')
+    .option('--ignore <pattern>', 'Skip files and directories matching a glob, e.g. vendor/** (repeatable)', collect, [])
+    .option('--kinds <kinds>', 'Keep only symbols of these kinds, e.g. class,function', parseKinds)
+    .option('--files-from <path>', 'Analyze only the files listed in a file, or - for stdin (one path per line)')
+    .option('--fail-fast', 'Abort at the first file or feature set that fails, without writing output')
+    .option('--strict', 'Same as --fail-fast')
+    .option('--call-graph', 'Add edges: calls and references between functions and methods')
+    .option('--no-symbol-cache', 'Analyze all files again instead of reusing the symbols of unchanged ones')
+    .option('--clear-cache', "Remove the directory's analysis cache before analyzing")
     .action(
         async (
             directory?: string,
             language?: string,
             outputFile?: string,
-            options?: { verbose?: boolean; llm?: boolean }
+            options?: {
+                verbose?: number | boolean;
+                quiet?: number;
+                logFilter?: LogFilter;
+                llm?: boolean;
+                profile?: string;
+                progress?: boolean;
+                logFormat?: LogFormat;
+                logFile?: string;
+                runSummary?: 'text' | 'json';
+                withGitBlame?: boolean;
+                withDependencies?: boolean;
+                countTokens?: string;
+                plugin?: string[];
+                script?: string[];
+                compileCommands?: string;
+                generateCompileCommands?: boolean;
+                otlpEndpoint?: string;
+                trailingDocs?: TrailingDocPolicy;
+                featureSet?: FeatureConfiguration[];
+                includeBody?: boolean | number;
+                leadingComments?: boolean;
+                expandMacros?: boolean;
+                includeLocals?: boolean;
+                resolveTypes?: boolean;
+                ignore?: string[];
+                kinds?: string[];
+                filesFrom?: string;
+                failFast?: boolean;
+                strict?: boolean;
+                callGraph?: boolean;
+                symbolCache?: boolean;
+                clearCache?: boolean;
+            }
         ) => {
             // Handle --llm flag
             if (options?.llm) {
@@ -42,16 +551,16 @@ program
 
                     if (!existsSync(sourcePath)) {
                         logger.error('Could not find llms.md in distribution');
-                        process.exit(1);
+                        process.exit(ExitCode.Failure);
                     }
 
                     // Read and output the file contents to stdout
                     const content = readFileSync(sourcePath, 'utf8');
                     console.log(content);
-                    process.exit(0);
+                    process.exit(ExitCode.Success);
                 } catch (error) {
                     logger.error('Failed to read llms.md', error instanceof Error ? error.message : String(error));
-                    process.exit(1);
+                    process.exit(exitCodeOf(error));
                 }
             }
 
@@ -60,93 +569,807 @@ program
                 console.error('Error: Missing required arguments');
                 console.error('Usage: lsp-cli <directory> <language> <output-file>');
                 console.error('   or: lsp-cli --llm');
-                process.exit(1);
+                process.exit(ExitCode.InvalidArguments);
             }
-            const logger = new Logger({ verbose: options?.verbose });
+            // With - as output file, stdout carries the JSON and logs go to stderr
+            const logger = commandLogger(options ?? {}, { stderr: outputFile === '-' });
 
             try {
                 const dir = resolve(directory);
 
                 if (!existsSync(dir)) {
                     logger.error(`Directory '${dir}' does not exist`);
-                    process.exit(1);
-                }
-
-                const supportedLanguages: SupportedLanguage[] = [
-                    'java',
-                    'cpp',
-                    'c',
-                    'csharp',
-                    'haxe',
-                    'typescript',
-                    'dart',
-                    'rust',
-                    'python'
-                ];
-                if (!supportedLanguages.includes(language as SupportedLanguage)) {
+                    process.exit(ExitCode.InvalidArguments);
+                }
+
+                if (!isSupportedLanguage(language)) {
                     logger.error(
                         `Unsupported language '${language}'`,
-                        `Supported languages: ${supportedLanguages.join(', ')}`
+                        `Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
                     );
-                    process.exit(1);
+                    process.exit(ExitCode.InvalidArguments);
                 }
 
-                const lang = language as SupportedLanguage;
+                const lang = language;
+                const startedAt = Date.now();
+                const fileCounts = emptyFileCounts();
+                const files = options?.filesFrom ? readFileList(options.filesFrom) : undefined;
+                const output = await runAnalysis(dir, lang, { ...options, files }, logger, fileCounts);
+
+                const jsonOutput = JSON.stringify(output, null, 2);
+
+                logger.info(`Writing output to: ${outputFile === '-' ? 'stdout' : outputFile}`);
+                await writeOutput(outputFile, jsonOutput);
 
-                // Check toolchain
-                const toolchainResult = await checkToolchain(lang);
-                if (!toolchainResult.installed) {
-                    logger.error(`Required toolchain not found for ${lang}`, toolchainResult.message);
-                    process.exit(1);
+                logger.success(`Analysis complete! ${(jsonOutput.length / 1024).toFixed(1)} KB written`);
+                if (output.errors) {
+                    logger.warn(`${output.errors.length} files or feature sets failed; see the output's errors`);
+                }
+                logger.runSummary(
+                    summarizeRun(lang, fileCounts, output.symbols, logger.getRunStats(), startedAt),
+                    options?.runSummary ?? 'text'
+                );
+                if (output.errors) {
+                    process.exit(ExitCode.CompletedWithErrors);
                 }
+            } catch (error) {
+                logger.error('Analysis failed', error instanceof Error ? error.message : String(error));
+                if (options?.verbose && error instanceof Error && error.stack) {
+                    logger.debug(error.stack);
+                }
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
 
-                // Check project files
-                const projectFileResult = await checkProjectFiles(dir, lang);
-                if (!projectFileResult.found) {
-                    logger.warn(`No project configuration found for ${lang}`);
-                    logger.warn(projectFileResult.message);
-                    logger.warn('Results may be incomplete or inaccurate');
+program
+    .command('analyze')
+    .description('Analyze a directory, git repository or source archive, detecting the language if not given')
+    .argument('<target>', 'Directory, git URL, source archive (.tar.gz/.tgz/.tar/.zip/.crate), or - for stdin')
+    .option('-l, --language <language>', 'Language to analyze (default: the language with the most source files)')
+    .option('--ref <ref>', 'Branch, tag or commit to analyze (git URLs; default: the default branch)')
+    .option('-o, --output <file>', 'Output file (default: stdout)')
+    .option('--no-cache', 'Clone into a temporary directory instead of ~/.lsp-cli/repos')
+    .option('--filename <path>', 'With - as target: path the stdin content is analyzed as, e.g. src/lib.rs')
+    .action(
+        async (
+            target: string,
+            options: { language?: string; ref?: string; output?: string; cache: boolean; filename?: string },
+            command: Command
+        ) => {
+            const globals = command.optsWithGlobals();
+            const logger = commandLogger(globals, { stderr: !options.output || options.output === '-' });
+            let cleanup = () => {};
+            const startedAt = Date.now();
+            const fileCounts = emptyFileCounts();
+            let failures = 0;
+            let summary: (() => void) | undefined;
+            const writeAnalysis = async (output: AnalysisOutput) => {
+                await writeOutput(options.output ?? '-', JSON.stringify(output, null, 2));
+                if (options.output && options.output !== '-') {
+                    logger.success(`Wrote ${output.symbols.length} symbols to ${options.output}`);
                 }
+            };
 
-                // Install/check LSP server
-                const serverManager = new ServerManager(logger);
-                logger.serverStatus(lang, 'checking');
-                const serverPath = await serverManager.ensureServer(lang);
-                logger.serverStatus(lang, 'ready', serverPath);
+            if (options.language && !isSupportedLanguage(options.language)) {
+                logger.error(
+                    `Unsupported language '${options.language}'`,
+                    `Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+                );
+                process.exit(ExitCode.InvalidArguments);
+            }
 
-                // Start LSP client and analyze
-                const client = new LanguageClient(lang, dir, logger);
-                logger.section(`Analyzing ${dir}`);
+            if (target === '-' && !options.language) {
+                logger.error('Reading from stdin requires --language');
+                process.exit(ExitCode.InvalidArguments);
+            }
+            if (target === '-' && globals.filesFrom) {
+                logger.error('--files-from cannot be combined with - as target');
+                process.exit(ExitCode.InvalidArguments);
+            }
 
-                await client.start();
-                const symbols = await client.analyzeDirectory();
-                await client.stop();
+            try {
+                let directory = resolve(target);
+                let source: AnalysisOutput['source'];
+                if (target === '-') {
+                    const language = options.language as SupportedLanguage;
+                    const filename = options.filename ?? `stdin${SOURCE_EXTENSIONS[language][0]}`;
+                    await writeAnalysis(await runStdinAnalysis(language, filename, globals, logger));
+                    return;
+                }
+                if (isArchive(target)) {
+                    const archive = await openArchive(target, logger);
+                    directory = archive.directory;
+                    cleanup = archive.cleanup;
+                    source = { type: 'archive', path: /^https:\/\//.test(target) ? target : resolve(target) };
+                } else if (isGitUrl(target)) {
+                    const repository = await fetchRepository(target, options.ref, logger, options.cache);
+                    directory = repository.directory;
+                    cleanup = repository.cleanup;
+                    source = { type: 'git', url: target, ref: options.ref, commit: repository.commit };
+                } else if (!existsSync(directory)) {
+                    throw new Error(`Directory '${directory}' does not exist`);
+                }
 
-                // Output JSON
-                const output = {
-                    language: lang,
-                    directory: dir,
-                    symbols
-                };
+                // Paths listed for a cloned repository or an archive are relative to its root
+                const files = globals.filesFrom
+                    ? readFileList(globals.filesFrom, source ? directory : process.cwd())
+                    : undefined;
+                let language = options.language as SupportedLanguage | undefined;
+                if (!language) {
+                    const detected = detectLanguages(directory, files);
+                    if (detected.length === 0) {
+                        throw new Error('No source files of a supported language found');
+                    }
+                    language = detected[0].language;
+                    logger.info(
+                        `Detected ${detected.map((entry) => `${entry.language} (${entry.files} files)`).join(', ')}; ` +
+                            `analyzing ${language}`
+                    );
+                }
 
-                const jsonOutput = JSON.stringify(output, null, 2);
+                // Archives and uncached clones go to a new directory each time, which a cache would never match
+                const symbolCache = globals.symbolCache && options.cache && source?.type !== 'archive';
+                const output = await runAnalysis(
+                    directory,
+                    language,
+                    { ...globals, files, symbolCache },
+                    logger,
+                    fileCounts
+                );
+                await writeAnalysis(source ? { ...output, source } : output);
+                failures = output.errors?.length ?? 0;
+                const analyzed = language;
+                summary = () =>
+                    logger.runSummary(
+                        summarizeRun(analyzed, fileCounts, output.symbols, logger.getRunStats(), startedAt),
+                        globals.runSummary ?? 'text'
+                    );
+            } catch (error) {
+                cleanup();
+                logger.error('Analysis failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+            cleanup();
+            if (failures > 0) {
+                logger.warn(`${failures} files or feature sets failed; see the output's errors`);
+            }
+            summary?.();
+            if (failures > 0) {
+                process.exit(ExitCode.CompletedWithErrors);
+            }
+        }
+    );
 
-                logger.info(`Writing output to: ${outputFile}`);
-                writeFileSync(outputFile, jsonOutput);
+program
+    .command('serve')
+    .description('Run lsp-cli as a long-lived server exposing extraction and queries')
+    .option('--http <address>', 'Serve the REST API on [host]:port, e.g. :8080 (host default: 127.0.0.1)')
+    .option('--http-token-env <name>', 'Variable holding a bearer token the REST API requires', 'LSP_CLI_SERVE_TOKEN')
+    .option('--grpc <address>', 'Serve the gRPC API (proto/lsp_cli.proto) on [host]:port, e.g. :50051')
+    .option('--stdio', 'Speak JSON-RPC over stdin/stdout for use as an embedded subprocess')
+    .option('--webhook <url>', 'POST symbol changes to this URL when a snapshot is stored (repeatable)', collect, [])
+    .option('--webhook-secret-env <name>', 'Variable holding the webhook signing secret', 'LSP_CLI_WEBHOOK_SECRET')
+    .option('--max-snapshots <count>', 'Snapshots kept in memory; the oldest are dropped', parseCount, 100)
+    .action(
+        async (
+            options: {
+                http?: string;
+                httpTokenEnv: string;
+                grpc?: string;
+                stdio?: boolean;
+                webhook: string[];
+                webhookSecretEnv: string;
+                maxSnapshots: number;
+            },
+            command: Command
+        ) => {
+            // In stdio mode stdout carries the protocol, so all logging goes to stderr
+            const logger = commandLogger(command.optsWithGlobals(), { stderr: options.stdio });
+
+            if (!options.http && !options.grpc && !options.stdio) {
+                logger.error(
+                    'Nothing to serve',
+                    'Specify at least one of --http <address>, --grpc <address> or --stdio'
+                );
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            const tracer = createTracer(command.optsWithGlobals().otlpEndpoint, logger);
+            const sessions = new SessionPool(logger, { tracer });
+            const snapshots = new SnapshotStore(options.maxSnapshots);
+            if (options.webhook.length > 0) {
+                new WebhookNotifier(options.webhook, logger, process.env[options.webhookSecretEnv]).attach(snapshots);
+            }
+
+            if (options.stdio) {
+                if (options.http || options.grpc) {
+                    logger.error('--stdio cannot be combined with --http or --grpc');
+                    process.exit(ExitCode.InvalidArguments);
+                }
+                await startJsonRpcServer(sessions, snapshots, logger);
+                process.exit(ExitCode.Success);
+            }
+            const closers: Array<() => Promise<void>> = [];
 
-                logger.success(`Analysis complete!`);
-                logger.summary('Results', [
-                    { label: 'Language', value: lang, color: 'blue' },
-                    { label: 'Symbols found', value: symbols.length, color: 'green' },
-                    { label: 'Output file', value: outputFile },
-                    { label: 'File size', value: `${(jsonOutput.length / 1024).toFixed(1)} KB` }
-                ]);
+            try {
+                if (options.http) {
+                    const token = process.env[options.httpTokenEnv] || undefined;
+                    if (!token && !isLoopbackHost(parseListenAddress(options.http).host)) {
+                        logger.warn(
+                            `The REST API on ${options.http} is open to other machines without authentication; ` +
+                                `set ${options.httpTokenEnv} to require a bearer token`
+                        );
+                    }
+                    const api = await startHttpServer(options.http, sessions, snapshots, logger, token);
+                    closers.push(api.close);
+                    logger.success(`HTTP API listening on ${options.http}`);
+                }
+                if (options.grpc) {
+                    const api = await startGrpcServer(options.grpc, sessions, snapshots, logger);
+                    closers.push(api.close);
+                    logger.success(`gRPC API listening on ${options.grpc}`);
+                }
             } catch (error) {
-                logger.error('Analysis failed', error instanceof Error ? error.message : String(error));
-                if (options?.verbose && error instanceof Error && error.stack) {
-                    logger.debug(error.stack);
+                logger.error('Failed to start server', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+
+            const shutdown = () => {
+                logger.info('Shutting down...');
+                Promise.all(closers.map((close) => close()))
+                    .then(() => sessions.closeAll())
+                    .then(() => tracer.flush())
+                    .finally(() => process.exit(ExitCode.Success));
+            };
+            process.on('SIGINT', shutdown);
+            process.on('SIGTERM', shutdown);
+        }
+    );
+
+program
+    .command('ci')
+    .description('Analyze a workspace and fail when quality thresholds are violated')
+    .argument('<directory>', 'Directory to analyze')
+    .argument('<language>', 'Language (java, cpp, c, csharp, haxe, typescript, dart, rust, python)')
+    .option('--baseline <file>', 'Earlier analysis output (e.g. of the target branch) to compare against')
+    .option('--min-doc-coverage <percent>', 'Fail if fewer documentable symbols are documented', parseNumber)
+    .option('--max-errors <count>', 'Fail if there are more error-level diagnostics', parseNumber)
+    .option('--max-new-errors <count>', 'Fail if more errors than in the baseline appear', parseNumber)
+    .option('--max-removed-symbols <count>', 'Fail if more baseline symbols were removed', parseNumber)
+    .option('--output <file>', 'Also write the analysis, including diagnostics, to a file (usable as a baseline)')
+    .option('--summary <file>', 'Write the JSON summary to a file instead of stdout')
+    .option('--sarif <file>', 'Also write diagnostics and failed checks as SARIF 2.1.0')
+    .action(
+        async (
+            directory: string,
+            language: string,
+            options: CiThresholds & { baseline?: string; output?: string; summary?: string; sarif?: string },
+            command: Command
+        ) => {
+            // stdout carries the machine-readable summary, so logs go to stderr
+            const globals = command.optsWithGlobals();
+            const logger = commandLogger(globals, { stderr: true });
+
+            if (!isSupportedLanguage(language)) {
+                logger.error(
+                    `Unsupported language '${language}'`,
+                    `Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+                );
+                process.exit(ExitCode.InvalidArguments);
+            }
+            if ((options.maxNewErrors !== undefined || options.maxRemovedSymbols !== undefined) && !options.baseline) {
+                logger.error('--max-new-errors and --max-removed-symbols require --baseline <file>');
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            const tracer = createTracer(globals.otlpEndpoint, logger);
+            const session = new Session(language, directory, logger, { tracer });
+            try {
+                const baseline = options.baseline ? JSON.parse(readFileSync(options.baseline, 'utf8')) : undefined;
+
+                const output = await session.analyze();
+                output.diagnostics = await session.diagnostics();
+                await session.close();
+                await tracer.flush();
+
+                if (options.output) {
+                    await writeOutput(options.output, JSON.stringify(output, null, 2));
+                }
+
+                const summary = evaluateCi(output, options, baseline);
+                if (options.sarif) {
+                    await writeOutput(options.sarif, JSON.stringify(ciToSarif(summary, output, baseline), null, 2));
+                }
+                const json = JSON.stringify(summary, null, 2);
+                await writeOutput(options.summary ?? '-', json);
+
+                for (const check of summary.checks) {
+                    const line = `${check.name}: ${check.value} (threshold ${check.threshold})`;
+                    if (check.passed) {
+                        logger.success(line);
+                    } else {
+                        logger.error(line);
+                    }
+                }
+                process.exit(summary.passed ? ExitCode.Success : ExitCode.ThresholdViolation);
+            } catch (error) {
+                await session.close();
+                await tracer.flush();
+                logger.error('CI run failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('diagnostics')
+    .description("Report the language server's errors and warnings for a workspace")
+    .argument('<directory>', 'Directory to analyze')
+    .argument('<language>', 'Language (java, cpp, c, csharp, haxe, typescript, dart, rust, python)')
+    .option('--format <format>', 'Output format: json or sarif', 'json')
+    .option('--output <file>', 'Write to a file instead of stdout')
+    .action(
+        async (directory: string, language: string, options: { format: string; output?: string }, command: Command) => {
+            const logger = commandLogger(command.optsWithGlobals(), { stderr: true });
+
+            if (!isSupportedLanguage(language)) {
+                logger.error(
+                    `Unsupported language '${language}'`,
+                    `Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+                );
+                process.exit(ExitCode.InvalidArguments);
+            }
+            if (options.format !== 'json' && options.format !== 'sarif') {
+                logger.error(`Unsupported format '${options.format}'`, 'Supported formats: json, sarif');
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            const session = new Session(language, directory, logger);
+            try {
+                // Opening every file makes the server publish diagnostics for the whole workspace
+                const output = await session.analyze();
+                const diagnostics = await session.diagnostics();
+                await session.close();
+
+                const result =
+                    options.format === 'sarif'
+                        ? diagnosticsToSarif({ ...output, diagnostics })
+                        : { language: output.language, directory: output.directory, diagnostics };
+                const json = JSON.stringify(result, null, 2);
+                await writeOutput(options.output ?? '-', json);
+                if (options.output && options.output !== '-') {
+                    logger.success(`Wrote ${diagnostics.length} diagnostics to ${options.output}`);
+                }
+            } catch (error) {
+                await session.close();
+                logger.error('Collecting diagnostics failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('hook')
+    .description('Report new diagnostics and API changes in changed files, e.g. from a pre-commit hook')
+    .argument('<language>', 'Language (java, cpp, c, csharp, haxe, typescript, dart, rust, python)')
+    .option('--staged', 'Check staged changes (index vs HEAD) instead of the working tree')
+    .option('--directory <dir>', 'Workspace root (default: repository root)')
+    .option('--daemon <url>', 'Use a running `lsp-cli serve --http` daemon, e.g. http://127.0.0.1:8080')
+    .option('--allow-api-changes', 'Do not fail on removed symbols or changed signatures')
+    .option('--json', 'Print the report as JSON to stdout')
+    .action(
+        async (
+            language: string,
+            options: {
+                staged?: boolean;
+                directory?: string;
+                daemon?: string;
+                allowApiChanges?: boolean;
+                json?: boolean;
+            },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals(), { stderr: true });
+
+            if (!isSupportedLanguage(language)) {
+                logger.error(
+                    `Unsupported language '${language}'`,
+                    `Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+                );
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            let session: Session | undefined;
+            try {
+                const workspace = resolve(options.directory ?? (await getRepositoryRoot(process.cwd())));
+                const files = await collectChangedFiles(workspace, language, options.staged ?? false);
+                if (files.length === 0) {
+                    logger.debug(`No changed ${language} files`);
+                    process.exit(ExitCode.Success);
+                }
+                logger.debug(`Checking ${files.length} changed files`);
+
+                let report: ChangeReport;
+                if (options.daemon) {
+                    report = await compareWithDaemon(options.daemon, workspace, language, files);
+                } else {
+                    session = new Session(language, workspace, logger);
+                    report = await compareVersions(session, files);
+                    await session.close();
+                }
+
+                const errors = report.newDiagnostics.filter((diagnostic) => diagnostic.severity === 'error');
+                const apiChanges = report.removedSymbols.length + report.changedSignatures.length;
+                const failed = errors.length > 0 || (!options.allowApiChanges && apiChanges > 0);
+
+                if (options.json) {
+                    process.stdout.write(`${JSON.stringify({ passed: !failed, ...report }, null, 2)}\n`);
+                }
+                for (const diagnostic of report.newDiagnostics) {
+                    const { line, character } = diagnostic.range.start;
+                    const location = `${relative(workspace, diagnostic.file)}:${line + 1}:${character + 1}`;
+                    const text = `${location}: ${diagnostic.severity}: ${diagnostic.message}`;
+                    if (diagnostic.severity === 'error') {
+                        logger.error(text);
+                    } else {
+                        logger.warn(text);
+                    }
+                }
+                for (const symbol of report.removedSymbols) {
+                    logger.warn(`Removed ${symbol}`);
+                }
+                for (const change of report.changedSignatures) {
+                    logger.warn(`Changed ${change.symbol}: '${change.before}' -> '${change.after}'`);
+                }
+                process.exit(failed ? ExitCode.ThresholdViolation : ExitCode.Success);
+            } catch (error) {
+                await session?.close();
+                logger.error('Hook failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('chunks')
+    .description('Split an analysis into retrieval-ready chunks (JSONL) for RAG pipelines')
+    .argument('<analysis-file>', 'Output of `lsp-cli <directory> <language> <output-file>`')
+    .argument('[output-file]', 'JSONL output file (default: stdout)')
+    .option('--max-tokens <count>', 'Group small symbols and split large ones to this token budget', parseNumber)
+    .option('--tokenizer <tokenizer>', 'Count tokens with a model, encoding or .tiktoken file', 'estimate')
+    .action(
+        async (
+            analysisFile: string,
+            outputFile: string | undefined,
+            options: { maxTokens?: number; tokenizer: string },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals(), { stderr: !outputFile || outputFile === '-' });
+
+            try {
+                const output = JSON.parse(readFileSync(analysisFile, 'utf8'));
+                const tokenizer = await loadTokenizer(options.tokenizer, logger);
+                const chunks = createChunks(output, {
+                    maxTokens: options.maxTokens,
+                    countTokens: (text) => tokenizer.count(text)
+                });
+                const jsonl = chunks.map((chunk) => `${JSON.stringify(chunk)}\n`).join('');
+
+                await writeOutput(outputFile ?? '-', jsonl);
+                if (outputFile && outputFile !== '-') {
+                    logger.success(`Wrote ${chunks.length} chunks to ${outputFile}`);
                 }
-                process.exit(1);
+            } catch (error) {
+                logger.error('Creating chunks failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('embed')
+    .description('Compute embeddings for the chunks of an analysis via an OpenAI-compatible endpoint')
+    .argument('<analysis-file>', 'Output of `lsp-cli <directory> <language> <output-file>`')
+    .argument('<output-file>', 'Output file: .json, or .sqlite/.db (requires Node.js 22.5+)')
+    .requiredOption('--model <name>', 'Embedding model, e.g. text-embedding-3-small or nomic-embed-text')
+    .option('--endpoint <url>', 'OpenAI-compatible API base URL', 'https://api.openai.com/v1')
+    .option('--api-key-env <name>', 'Environment variable holding the API key', 'OPENAI_API_KEY')
+    .option('--batch-size <count>', 'Chunks per request', parseNumber, 64)
+    .option('--requests-per-minute <count>', 'Limit the request rate', parseNumber)
+    .option('--max-retries <count>', 'Retries per batch on network errors, 429 and 5xx', parseNumber, 5)
+    .option('--max-tokens <count>', 'Chunk token budget (see `lsp-cli chunks`)', parseNumber)
+    .option('--tokenizer <tokenizer>', 'Count chunk tokens with a model, encoding or .tiktoken file', 'estimate')
+    .action(
+        async (
+            analysisFile: string,
+            outputFile: string,
+            options: {
+                model: string;
+                endpoint: string;
+                apiKeyEnv: string;
+                batchSize: number;
+                requestsPerMinute?: number;
+                maxRetries: number;
+                maxTokens?: number;
+                tokenizer: string;
+            },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals());
+
+            try {
+                const output = JSON.parse(readFileSync(analysisFile, 'utf8'));
+                const tokenizer = await loadTokenizer(options.tokenizer, logger);
+                const chunks = createChunks(output, {
+                    maxTokens: options.maxTokens,
+                    countTokens: (text) => tokenizer.count(text)
+                });
+
+                logger.info(`Embedding ${chunks.length} chunks with ${options.model}`);
+                const embedded = await embedChunks(
+                    chunks,
+                    {
+                        endpoint: options.endpoint,
+                        model: options.model,
+                        apiKey: process.env[options.apiKeyEnv],
+                        batchSize: Math.max(1, options.batchSize),
+                        requestsPerMinute: options.requestsPerMinute,
+                        maxRetries: options.maxRetries
+                    },
+                    logger
+                );
+                await writeEmbeddings(outputFile, options.model, embedded);
+                logger.success(`Wrote ${embedded.length} embeddings to ${outputFile}`);
+            } catch (error) {
+                logger.error('Embedding failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('tools')
+    .description('Generate LLM tool-call schemas from the public functions and methods of an analysis')
+    .argument('<analysis-file>', 'Output of `lsp-cli <directory> <language> <output-file>`')
+    .argument('[output-file]', 'JSON output file (default: stdout)')
+    .option('--format <format>', 'Schema format: openai or anthropic', 'openai')
+    .option('--include <pattern>', 'Qualified name pattern to include, * as wildcard (repeatable)', collect, [])
+    .option('--exclude <pattern>', 'Qualified name pattern to exclude, * as wildcard (repeatable)', collect, [])
+    .action(
+        async (
+            analysisFile: string,
+            outputFile: string | undefined,
+            options: { format: string; include: string[]; exclude: string[] },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals(), { stderr: !outputFile || outputFile === '-' });
+
+            if (options.format !== 'openai' && options.format !== 'anthropic') {
+                logger.error(`Unsupported format '${options.format}'`, 'Supported formats: openai, anthropic');
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            try {
+                const output = JSON.parse(readFileSync(analysisFile, 'utf8'));
+                const definitions = generateToolDefinitions(output, {
+                    include: options.include.length > 0 ? options.include : undefined,
+                    exclude: options.exclude
+                });
+                const json = JSON.stringify(formatToolSchemas(definitions, options.format), null, 2);
+
+                await writeOutput(outputFile ?? '-', json);
+                if (outputFile && outputFile !== '-') {
+                    logger.success(`Wrote ${definitions.length} tool schemas to ${outputFile}`);
+                }
+            } catch (error) {
+                logger.error('Generating tool schemas failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('push')
+    .description('Upload an analysis snapshot to a remote index endpoint')
+    .argument('<analysis-file>', 'Output of `lsp-cli <directory> <language> <output-file>`')
+    .requiredOption('--endpoint <url>', 'URL the snapshot is POSTed to')
+    .option('--since <analysis-file>', 'Send only the files changed since this previously pushed analysis')
+    .option('--token-env <name>', 'Environment variable holding a bearer token', 'LSP_CLI_PUSH_TOKEN')
+    .option('--header <header>', 'Additional request header as "Name: value" (repeatable)', collect, [])
+    .option('--no-compress', 'Send the body uncompressed instead of gzip')
+    .option('--max-retries <count>', 'Retries on network errors, 429 and 5xx', parseNumber, 5)
+    .action(
+        async (
+            analysisFile: string,
+            options: {
+                endpoint: string;
+                since?: string;
+                tokenEnv: string;
+                header: string[];
+                compress: boolean;
+                maxRetries: number;
+            },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals());
+
+            const headers: Record<string, string> = {};
+            for (const header of options.header) {
+                const separator = header.indexOf(':');
+                if (separator <= 0) {
+                    logger.error(`Invalid header '${header}'`, 'Expected "Name: value"');
+                    process.exit(ExitCode.InvalidArguments);
+                }
+                headers[header.slice(0, separator).trim()] = header.slice(separator + 1).trim();
+            }
+
+            try {
+                const output = JSON.parse(readFileSync(analysisFile, 'utf8'));
+                const base = options.since ? JSON.parse(readFileSync(options.since, 'utf8')) : undefined;
+                const payload = createSnapshotPayload(output, await getSnapshotSource(output.directory), base);
+
+                if (payload.kind === 'delta') {
+                    logger.info(
+                        `Pushing delta: ${Object.keys(payload.changedFiles).length} changed, ` +
+                            `${payload.removedFiles.length} removed files`
+                    );
+                }
+                await pushSnapshot(
+                    payload,
+                    {
+                        endpoint: options.endpoint,
+                        token: process.env[options.tokenEnv],
+                        headers,
+                        compress: options.compress,
+                        maxRetries: options.maxRetries
+                    },
+                    logger
+                );
+                logger.success(`Pushed snapshot ${payload.id} to ${options.endpoint}`);
+            } catch (error) {
+                logger.error('Push failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('deps')
+    .description('Report which files and symbols use third-party packages, e.g. to plan upgrades')
+    .argument('<analysis-file>', 'Output of `lsp-cli <directory> <language> <output-file>`')
+    .argument('[package]', 'Package to list the using files and symbols of (default: all packages with counts)')
+    .option('--json', 'Print the dependency usage as JSON')
+    .action((analysisFile: string, packageName: string | undefined, options: { json?: boolean }, command: Command) => {
+        const logger = commandLogger(command.optsWithGlobals(), { stderr: true });
+
+        try {
+            const output: AnalysisOutput = JSON.parse(readFileSync(analysisFile, 'utf8'));
+            // Analyses made without --with-dependencies are mapped now, from the sources on disk
+            if (!output.dependencies) {
+                addDependencyInfo(output, logger);
+            }
+
+            if (options.json) {
+                const dependencies = packageName
+                    ? output.dependencies?.filter((dependency) => dependency.name === packageName)
+                    : output.dependencies;
+                process.stdout.write(`${JSON.stringify(dependencies ?? [], null, 2)}\n`);
+            } else {
+                process.stdout.write(`${formatDependencyReport(output, packageName)}\n`);
+            }
+        } catch (error) {
+            logger.error('Dependency report failed', error instanceof Error ? error.message : String(error));
+            process.exit(exitCodeOf(error));
+        }
+    });
+
+program
+    .command('self-update')
+    .description('Replace this lsp-cli with the newest release of its channel, or a pinned version')
+    .option('--channel <channel>', 'Release channel: stable, or prerelease to include release candidates', 'stable')
+    .option('--target-version <version>', 'Install exactly this version, e.g. 1.4.2, also to downgrade')
+    .option('--check', 'Only report whether an update is available')
+    .option('--public-key <file>', 'Verify the release signature with this PEM key instead of the embedded one')
+    .option('--insecure-skip-signature', 'Accept releases without a valid signature, checking only the checksum')
+    .option('--repository <owner/name>', 'GitHub repository publishing the releases', 'yannbam/lsp-cli')
+    .action(
+        async (
+            options: {
+                channel: string;
+                targetVersion?: string;
+                check?: boolean;
+                publicKey?: string;
+                insecureSkipSignature?: boolean;
+                repository: string;
+            },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals(), {});
+
+            if (options.channel !== 'stable' && options.channel !== 'prerelease') {
+                logger.error(`Unsupported channel '${options.channel}'`, 'Supported channels: stable, prerelease');
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            try {
+                await selfUpdate(
+                    {
+                        repository: options.repository,
+                        channel: options.channel as ReleaseChannel,
+                        version: options.targetVersion,
+                        publicKey: options.publicKey ? readFileSync(options.publicKey, 'utf8') : undefined,
+                        skipSignature: options.insecureSkipSignature,
+                        check: options.check,
+                        executable: process.argv[1],
+                        currentVersion: VERSION
+                    },
+                    logger
+                );
+            } catch (error) {
+                logger.error('Self-update failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
+            }
+        }
+    );
+
+program
+    .command('languages')
+    .description('List the supported languages with their files, language servers and install status')
+    .option('--json', 'Print the list as JSON')
+    .action(async (options: { json?: boolean }, command: Command) => {
+        const logger = commandLogger(command.optsWithGlobals(), { stderr: true });
+
+        try {
+            const languages = await listLanguages(new ServerManager(logger));
+            const text = options.json ? JSON.stringify(languages, null, 2) : formatLanguages(languages);
+            process.stdout.write(`${text}\n`);
+        } catch (error) {
+            logger.error('Listing languages failed', error instanceof Error ? error.message : String(error));
+            process.exit(exitCodeOf(error));
+        }
+    });
+
+program
+    .command('doctor')
+    .description('Check toolchains, language servers, PATH and the workspace, and suggest fixes')
+    .argument('[language]', 'Language to check (default: the languages found in the directory)')
+    .option('--directory <dir>', 'Workspace to detect languages and project files in', '.')
+    .option('--install', 'Install missing language servers')
+    .option('--no-sample', 'Skip the end-to-end extraction of a built-in sample project')
+    .option('--json', 'Print the report as JSON')
+    .action(
+        async (
+            language: string | undefined,
+            options: { directory: string; install?: boolean; sample: boolean; json?: boolean },
+            command: Command
+        ) => {
+            const logger = commandLogger(command.optsWithGlobals(), { stderr: true });
+
+            if (language && !isSupportedLanguage(language)) {
+                logger.error(
+                    `Unsupported language '${language}'`,
+                    `Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+                );
+                process.exit(ExitCode.InvalidArguments);
+            }
+
+            try {
+                const report = await runDoctor(
+                    {
+                        languages: language ? [language] : undefined,
+                        directory: resolve(options.directory),
+                        install: options.install,
+                        sample: options.sample
+                    },
+                    logger
+                );
+                const text = options.json ? JSON.stringify(report, null, 2) : formatDoctorReport(report);
+                process.stdout.write(`${text}\n`);
+                process.exit(report.passed ? ExitCode.Success : ExitCode.Failure);
+            } catch (error) {
+                logger.error('Doctor failed', error instanceof Error ? error.message : String(error));
+                process.exit(exitCodeOf(error));
             }
         }
     );
@@ -0,0 +1,126 @@
+import {
+    createMessageConnection,
+    ErrorCodes,
+    ResponseError,
+    StreamMessageReader,
+    StreamMessageWriter
+} from 'vscode-jsonrpc/node';
+import type { Logger } from './logger';
+import { querySymbols } from './query';
+import type { SessionPool } from './session';
+import type { SnapshotStore } from './snapshots';
+import type { Position, SupportedLanguage } from './types';
+import { isSupportedLanguage, SUPPORTED_LANGUAGES } from './utils';
+
+/**
+ * Serves the JSON-RPC 2.0 API on stdin/stdout, framed with Content-Length headers like LSP.
+ *
+ * Methods (params → result):
+ * - `analyze` `{ directory, language }` → `{ snapshotId, language, directory, symbols }`
+ * - `query` `{ snapshotId, name?, kind?, file? }` → `{ language, directory, symbols }`
+ * - `definition` `{ directory, language, file, line, character }` → `{ locations }`
+ * - `references` `{ directory, language, file, line, character, includeDeclaration? }` → `{ locations }`
+ * - `shutdown` → `null`; language servers are stopped and the connection is closed
+ *
+ * @param sessions - Language server sessions, kept alive for the lifetime of the connection
+ * @param snapshots - Snapshot store for `query`
+ * @param logger - Logger; must write to stderr since stdout carries the protocol
+ * @returns Resolves when the client sent `shutdown` or closed stdin
+ */
+export function startJsonRpcServer(sessions: SessionPool, snapshots: SnapshotStore, logger: Logger): Promise<void> {
+    const connection = createMessageConnection(
+        new StreamMessageReader(process.stdin),
+        new StreamMessageWriter(process.stdout)
+    );
+
+    connection.onRequest('analyze', async (params: Record<string, any>) => {
+        const session = sessions.get(requireLanguage(params), requireString(params, 'directory'));
+        const output = await session.analyze();
+        const snapshot = snapshots.add(output);
+        return { snapshotId: snapshot.id, ...output };
+    });
+
+    connection.onRequest('query', (params: Record<string, any>) => {
+        const snapshotId = requireString(params, 'snapshotId');
+        const snapshot = snapshots.get(snapshotId);
+        if (!snapshot) {
+            throw new ResponseError(ErrorCodes.InvalidParams, `Snapshot '${snapshotId}' not found`);
+        }
+        const symbols = querySymbols(snapshot.output.symbols, {
+            name: params.name,
+            kind: params.kind,
+            file: params.file
+        });
+        return { language: snapshot.output.language, directory: snapshot.output.directory, symbols };
+    });
+
+    connection.onRequest('definition', async (params: Record<string, any>) => {
+        const session = sessions.get(requireLanguage(params), requireString(params, 'directory'));
+        return { locations: await session.definition(requireString(params, 'file'), requirePosition(params)) };
+    });
+
+    connection.onRequest('references', async (params: Record<string, any>) => {
+        const session = sessions.get(requireLanguage(params), requireString(params, 'directory'));
+        const locations = await session.references(
+            requireString(params, 'file'),
+            requirePosition(params),
+            params.includeDeclaration !== false
+        );
+        return { locations };
+    });
+
+    return new Promise<void>((resolve) => {
+        let closed = false;
+        const close = async () => {
+            if (closed) return;
+            closed = true;
+            await sessions.closeAll();
+            resolve();
+        };
+
+        connection.onRequest('shutdown', async () => {
+            await close();
+            // Let the response go out before the connection goes away
+            setImmediate(() => connection.dispose());
+            return null;
+        });
+
+        connection.onClose(() => {
+            logger.debug('JSON-RPC client disconnected');
+            close();
+        });
+
+        connection.listen();
+        logger.debug('JSON-RPC server listening on stdio');
+    });
+}
+
+function requireString(params: Record<string, any> | undefined, field: string): string {
+    const value = params?.[field];
+    if (typeof value !== 'string' || value.length === 0) {
+        throw new ResponseError(ErrorCodes.InvalidParams, `Missing required param '${field}'`);
+    }
+    return value;
+}
+
+function requireLanguage(params: Record<string, any> | undefined): SupportedLanguage {
+    const language = requireString(params, 'language');
+    if (!isSupportedLanguage(language)) {
+        throw new ResponseError(
+            ErrorCodes.InvalidParams,
+            `Unsupported language '${language}'. Supported languages: ${SUPPORTED_LANGUAGES.join(', ')}`
+        );
+    }
+    return language;
+}
+
+function requirePosition(params: Record<string, any>): Position {
+    const { line, character } = params;
+    if (!Number.isInteger(line) || !Number.isInteger(character) || line < 0 || character < 0) {
+        throw new ResponseError(
+            ErrorCodes.InvalidParams,
+            "Params 'line' and 'character' must be non-negative integers (0-based)"
+        );
+    }
+    return { line, character };
+}
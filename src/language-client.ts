@@ -1,47 +1,212 @@
 import { type ChildProcess, spawn } from 'node:child_process';
-import { existsSync, readFileSync } from 'node:fs';
+import { existsSync } from 'node:fs';
+import { join, relative } from 'node:path';
 import {
+    type CallHierarchyIncomingCall,
+    CallHierarchyIncomingCallsRequest,
+    type CallHierarchyItem,
+    type CallHierarchyOutgoingCall,
+    CallHierarchyOutgoingCallsRequest,
+    CallHierarchyPrepareRequest,
     createMessageConnection,
     type DefinitionParams,
     DefinitionRequest,
+    type Diagnostic,
+    DiagnosticSeverity,
+    DidChangeTextDocumentNotification,
+    DidCloseTextDocumentNotification,
     DidOpenTextDocumentNotification,
     type DocumentSymbol,
     type DocumentSymbolParams,
     DocumentSymbolRequest,
     ExitNotification,
+    type Hover,
+    HoverRequest,
     type InitializeParams,
     InitializeRequest,
     type Location,
+    type LocationLink,
+    type MarkedString,
+    type MarkupContent,
     type Position as LSPPosition,
+    type Range as LSPRange,
     type MessageConnection,
+    PublishDiagnosticsNotification,
+    ReferencesRequest,
     ShutdownRequest,
     StreamMessageReader,
     StreamMessageWriter,
     type SymbolInformation,
     SymbolKind,
+    SymbolTag,
     type TextDocumentItem,
     type TypeHierarchyItem,
     TypeHierarchyPrepareRequest,
-    TypeHierarchySupertypesRequest
+    TypeHierarchySupertypesRequest,
+    type WorkDoneProgressBegin,
+    WorkDoneProgressCreateRequest,
+    type WorkDoneProgressEnd,
+    type WorkDoneProgressReport
 } from 'vscode-languageserver-protocol/node';
+import { extractAttributes } from './attributes';
+import { addBodies } from './bodies';
+import { ANY_FILE, AnalysisCache, type CachedFile } from './cache';
+import { addEdge, CALL_GRAPH_KINDS, findEnclosingSymbol, findSymbolAt, indexSymbols, symbolNode } from './call-graph';
+import { addCargoInfo, groupFilesByMember, readCargoMetadata } from './cargo';
+import { extractLeadingComments } from './comments';
+import { findCompileCommands } from './compile-commands';
+import { addDeprecation } from './deprecation';
+import { renderDocumentationMarkdown } from './documentation';
+import { readSourceFile } from './encoding';
+import { ExitCode, ExitError } from './exit-codes';
+import { rustAnalyzerCargoOptions } from './feature-matrix';
+import { addForeignItems } from './ffi';
+import { linkImplementations, parseImplHeader } from './impls';
+import { findRustClosures, isLocalItem } from './locals';
 import type { Logger } from './logger';
+import { attributeMacroSymbols, findMacroInvocations, type MacroInvocation, symbolsFromExpansion } from './macros';
+import { addFunctionModifiers } from './modifiers';
+import { mapNotebookSymbols, readNotebook } from './notebooks';
+import { addDisambiguation } from './overloads';
+import { addPositionOffsets } from './positions';
+import { addQualifiedNames } from './qualified-names';
+import type { FileCounts } from './run-summary';
 import { ServerManager } from './server-manager';
-import type { Position, SupportedLanguage, SymbolInfo } from './types';
-import { getAllFiles } from './utils';
+import { extractSignature, signatureFromHover, typeFromHover } from './signatures';
+import { addSymbolIds } from './symbol-ids';
+import { type Span, Tracer } from './tracing';
+import { addAliasTargets, expandAliases } from './type-aliases';
+import type {
+    CargoWorkspaceInfo,
+    CallGraphEdge,
+    CallGraphNode,
+    DiagnosticInfo,
+    ExtractionError,
+    FeatureConfiguration,
+    HoverInfo,
+    OrphanedDoc,
+    Position,
+    PositionEncoding,
+    Range,
+    SourceEncoding,
+    SourceLocation,
+    SupportedLanguage,
+    SymbolInfo,
+    TrailingDocPolicy
+} from './types';
+import { discoverFiles, getAllFiles, selectFiles, SOURCE_EXTENSIONS } from './utils';
+import { addVariantPayloads } from './variants';
+import { addVisibility } from './visibility';
+import { VERSION } from './version';
+
+// Server crashes an analysis recovers from by restarting the server; after that the remaining files are given up
+const MAX_RESTARTS = 5;
+// Server stderr kept for the excerpts of failed files, and lines per excerpt
+const STDERR_BUFFER_SIZE = 64 * 1024;
+const STDERR_EXCERPT_LINES = 20;
+
+// Statements re-exporting items defined elsewhere, matched from the start of the statement
+const REEXPORT_PATTERNS: Partial<Record<SupportedLanguage, RegExp>> = {
+    rust: /^\s*pub(\s*\([^)]*\))?\s+use\b/,
+    python: /^\s*from\s+\S+\s+import\b/,
+    typescript: /^\s*export\s+(type\s+)?(\{[^}]*\}|\*(\s+as\s+\w+)?)\s*from\b/
+};
+
+// Kinds of the symbols that get a `signature`
+const SIGNATURE_KINDS: SymbolKind[] = [
+    SymbolKind.Function,
+    SymbolKind.Method,
+    SymbolKind.Constructor,
+    SymbolKind.Class,
+    SymbolKind.Struct,
+    SymbolKind.Interface,
+    SymbolKind.Enum,
+    SymbolKind.Object
+];
+
+// Kinds whose nested symbols are locals
+const CALLABLE_KINDS: SymbolKind[] = [SymbolKind.Function, SymbolKind.Method, SymbolKind.Constructor];
+
+// Kinds of the symbols that get a `type`, by kind name
+const VALUE_KINDS = new Set(['field', 'constant', 'variable', 'property']);
+
+export interface LanguageClientOptions {
+    /** C/C++: directory containing compile_commands.json; by default the workspace's build directories are searched */
+    compileCommandsDir?: string;
+    /** Records spans for the server's lifetime, each analyzed file and each LSP request */
+    tracer?: Tracer;
+    /** Where doc comments below a definition go; `next` (the default) attaches them to the following symbol */
+    trailingDocs?: TrailingDocPolicy;
+    /** Rust: Cargo features rust-analyzer enables; by default each crate's default features */
+    cargoFeatures?: FeatureConfiguration;
+    /** Embed each symbol's source text; a number keeps only that many lines per symbol */
+    includeBody?: boolean | number;
+    /** Also record the regular (non-doc) comments directly above each symbol */
+    leadingComments?: boolean;
+    /** Rust: record the expansion of the macro invocation generating each symbol */
+    expandMacros?: boolean;
+    /** Keep function-local items (nested functions, local types, closures bound to names) as children */
+    includeLocals?: boolean;
+    /** Record the type of fields, constants and variables from hover, one request per symbol */
+    resolveTypes?: boolean;
+    /** Glob patterns of files and directories to skip, relative to the workspace root */
+    ignore?: string[];
+    /** Analyze only these files (absolute paths) instead of all files below the workspace root */
+    files?: string[];
+    /** Called with the diagnosis when the server connection closes unexpectedly, instead of exiting the process */
+    onClose?: (message: string) => void;
+    /** Abort the analysis at the first failed file, instead of recording it and restarting a crashed server */
+    failFast?: boolean;
+    /** Take the symbols of files unchanged since the last run from ~/.lsp-cli/cache, and update it */
+    cache?: boolean;
+}
 
 export class LanguageClient {
     private connection?: MessageConnection;
     private serverProcess?: ChildProcess;
     private serverManager: ServerManager;
     private initialized = false;
+    private stopping = false;
     private serverCapabilities: any = {};
+    private documentVersions = new Map<string, number>();
+    private diagnostics = new Map<string, DiagnosticInfo[]>();
+    private lastDiagnosticsAt = 0;
+    private cargo?: CargoWorkspaceInfo;
+    private tracer: Tracer;
+    private serverSpan?: Span;
+    private orphanedDocs = new Map<string, OrphanedDoc[]>();
+    private fileEncodings = new Map<string, SourceEncoding>();
+    private fileAliases = new Map<string, string[]>();
+    private failedFiles: string[] = [];
+    private extractionErrors: ExtractionError[] = [];
+    // Recent server stderr, and the total received, to cut out what was written during a file's analysis
+    private serverStderr = '';
+    private stderrReceived = 0;
+    private analyzing = false;
+    private serverCrashed = false;
+    private restarts = 0;
+    private cache?: AnalysisCache;
+    // Other files each analyzed file's symbols took data from, stored with them in the cache
+    private dependencies = new Map<string, Set<string>>();
+    private scannedFiles = 0;
+    private skippedFiles = 0;
+    private positionEncoding: PositionEncoding = 'utf-16';
+    // Work the server reports through `$/progress`, e.g. indexing, by progress token
+    private serverProgress = new Map<string | number, { title: string; message?: string; percentage?: number }>();
+    // Server process and JSON-RPC messages, and symbol extraction, for `--log-filter transport=trace` and the like
+    private transport: Logger;
+    private extractor: Logger;
 
     constructor(
         private language: SupportedLanguage,
         private workspaceRoot: string,
-        private logger: Logger
+        private logger: Logger,
+        private options: LanguageClientOptions = {}
     ) {
-        this.serverManager = new ServerManager(logger);
+        this.serverManager = new ServerManager(logger.scoped('install'));
+        this.tracer = options.tracer ?? new Tracer();
+        this.transport = logger.scoped('transport');
+        this.extractor = logger.scoped('extractor');
     }
 
     async start(): Promise<void> {
@@ -51,15 +216,32 @@ export class LanguageClient {
         // Validate server installation
         const validation = this.serverManager.validateServer(this.language);
         if (!validation.valid) {
-            throw new Error(`${validation.error}\n` +
+            throw new ExitError(`${validation.error}\n` +
                           `Language: ${this.language}\n` +
-                          `Suggestion: Try reinstalling the ${this.language} LSP server or check your system PATH`);
+                          `Suggestion: Try reinstalling the ${this.language} LSP server or check your system PATH`,
+                          ExitCode.ServerInstallFailed);
         }
 
         const command = this.serverManager.getServerCommand(this.language);
 
-        this.logger.debug(`Starting LSP server: ${command.join(' ')}`);
-        this.logger.debug(`Working directory: ${this.workspaceRoot}`);
+        if (this.language === 'c' || this.language === 'cpp') {
+            // clangd only looks next to the sources and in build/; point it at the database explicitly
+            const compileCommandsDir = this.options.compileCommandsDir ?? findCompileCommands(this.workspaceRoot);
+            if (compileCommandsDir) {
+                this.logger.debug(`Using ${join(compileCommandsDir, 'compile_commands.json')}`);
+                command.push(`--compile-commands-dir=${compileCommandsDir}`);
+            }
+        }
+
+        await this.loadCargoWorkspace();
+
+        this.serverSpan = this.tracer.startSpan('language server', {
+            'lsp.language': this.language,
+            'lsp.workspace': this.workspaceRoot,
+            'process.command_line': command.join(' ')
+        });
+        this.transport.debug(`Starting LSP server: ${command.join(' ')}`);
+        this.transport.debug(`Working directory: ${this.workspaceRoot}`);
 
         // Start the LSP server process
         this.serverProcess = spawn(command[0], command.slice(1), {
@@ -72,6 +254,11 @@ export class LanguageClient {
                 })
             }
         });
+        this.transport.event('server.started', {
+            language: this.language,
+            command: command.join(' '),
+            pid: this.serverProcess.pid
+        });
 
         this.serverProcess.on('error', (err) => {
             const errorMsg = `Failed to spawn LSP server process:\n` +
@@ -80,11 +267,11 @@ export class LanguageClient {
                            `  Working Directory: ${this.workspaceRoot}\n` +
                            `  Error: ${err.message}\n`;
             if (err.message.includes('ENOENT')) {
-                this.logger.error(errorMsg +
+                this.transport.error(errorMsg +
                     `  Suggestion: The ${this.language} LSP server executable was not found.\n` +
                     `  Please ensure the server is installed and accessible in your PATH.`);
             } else {
-                this.logger.error(errorMsg);
+                this.transport.error(errorMsg);
             }
         });
 
@@ -93,10 +280,13 @@ export class LanguageClient {
         this.serverProcess.stderr?.on('data', (data) => {
             const message = data.toString();
             stderrOutput += message;
-            this.logger.debug(`[LSP stderr]: ${message.trim()}`);
+            this.serverStderr = (this.serverStderr + message).slice(-STDERR_BUFFER_SIZE);
+            this.stderrReceived += message.length;
+            this.transport.debug(`[LSP stderr]: ${message.trim()}`);
         });
 
         this.serverProcess.on('exit', (code, signal) => {
+            this.transport.event('server.exited', { language: this.language, code, signal, expected: this.stopping });
             if (code !== 0 && code !== null && code !== 143) {
                 let errorMsg = `LSP server exited unexpectedly:\n` +
                              `  Language: ${this.language}\n` +
@@ -117,7 +307,7 @@ export class LanguageClient {
                     errorMsg += `  Suggestion: Install clangd: sudo apt install clangd (Linux) or brew install llvm (macOS)\n`;
                 }
 
-                this.logger.error(errorMsg);
+                this.transport.error(errorMsg);
             }
         });
 
@@ -139,11 +329,19 @@ export class LanguageClient {
                            `  Command: ${command.join(' ')}\n` +
                            `  Error: ${String(error)}\n` +
                            `  Working Directory: ${this.workspaceRoot}`;
-            this.logger.error(errorMsg);
+            this.transport.error(errorMsg);
         });
 
         // Track initialization state for better close diagnostics
         let connectionEstablished = false;
+        const crashed = (message: string) => {
+            this.transport.error(message);
+            if (this.options.onClose) {
+                this.options.onClose(message);
+            } else {
+                process.exit(ExitCode.ServerCrashed);
+            }
+        };
 
         this.connection.onClose(() => {
             let closeMsg = `LSP connection closed:\n` +
@@ -156,19 +354,42 @@ export class LanguageClient {
                 if (stderrOutput.trim()) {
                     closeMsg += `  Server stderr:\n${stderrOutput.split('\n').map(line => `    ${line}`).join('\n')}\n`;
                 }
-                this.logger.error(closeMsg);
-                process.exit(1);
+                crashed(closeMsg);
             } else if (!this.initialized) {
                 closeMsg += `  Status: Connection closed during initialization\n`;
-                this.logger.error(closeMsg);
-                process.exit(1);
+                crashed(closeMsg);
+            } else if (this.stopping) {
+                // Closed by stop(); the owner decides whether the process should exit
+                this.transport.debug(`${closeMsg}  Status: Stopped by client`);
+            } else if (this.analyzing && !this.options.failFast && !this.options.onClose) {
+                // The file being analyzed is recorded as failed and the analysis goes on with a new server
+                this.transport.warn(`${closeMsg}  Status: Closed by the server during analysis; restarting it`);
+                this.serverCrashed = true;
+                // Pending requests only fail once the connection is disposed
+                this.connection?.dispose();
             } else {
-                closeMsg += `  Status: Normal shutdown after analysis completion`;
-                this.logger.info(closeMsg);
-                process.exit(0);
+                // The server went away while we still had requests for it
+                closeMsg += `  Status: Closed by the server before analysis completed`;
+                crashed(closeMsg);
             }
         });
 
+        // Servers push diagnostics for opened documents; keep the latest set per file
+        this.connection.onNotification(PublishDiagnosticsNotification.type, (params) => {
+            const file = decodeURIComponent(params.uri.replace(/^file:\/\//, ''));
+            this.diagnostics.set(
+                file,
+                params.diagnostics.map((diagnostic) => this.toDiagnosticInfo(file, diagnostic))
+            );
+            this.lastDiagnosticsAt = Date.now();
+        });
+
+        // Servers ask before reporting their own work, like rust-analyzer's indexing
+        this.connection.onRequest(WorkDoneProgressCreateRequest.type, () => null);
+        this.connection.onUnhandledProgress(({ token, value }) => {
+            this.onServerProgress(token, value as WorkDoneProgressBegin | WorkDoneProgressReport | WorkDoneProgressEnd);
+        });
+
         // Mark connection as established
         connectionEstablished = true;
 
@@ -176,14 +397,19 @@ export class LanguageClient {
         this.connection.listen();
 
         // Initialize the LSP server
+        this.logger.stage(`Starting ${this.language} LSP server`);
         try {
             await this.initialize();
+            this.logger.stageDone();
         } catch (error) {
+            this.logger.stageFailed();
             this.logger.error(
                 'Failed to initialize LSP server',
                 error instanceof Error ? error.message : String(error)
             );
-            throw error;
+            this.serverSpan?.end(error);
+            const message = error instanceof Error ? error.message : String(error);
+            throw new ExitError(`Failed to initialize LSP server: ${message}`, ExitCode.ServerCrashed);
         }
     }
 
@@ -197,10 +423,19 @@ export class LanguageClient {
             rootUri: `file://${this.workspaceRoot}`,
             rootPath: this.workspaceRoot,
             capabilities: {
+                general: {
+                    positionEncodings: ['utf-16', 'utf-8']
+                },
+                window: {
+                    workDoneProgress: true
+                },
                 textDocument: {
                     documentSymbol: {
-                        hierarchicalDocumentSymbolSupport: true
-                    }
+                        hierarchicalDocumentSymbolSupport: true,
+                        tagSupport: { valueSet: [SymbolTag.Deprecated] }
+                    },
+                    publishDiagnostics: {},
+                    callHierarchy: {}
                 }
             },
             workspaceFolders: [
@@ -208,85 +443,498 @@ export class LanguageClient {
                     uri: `file://${this.workspaceRoot}`,
                     name: 'workspace'
                 }
-            ]
+            ],
+            // Load the whole Cargo workspace even when only a member directory is analyzed
+            ...((this.cargo || this.options.cargoFeatures) && {
+                initializationOptions: {
+                    ...(this.cargo && { linkedProjects: [join(this.cargo.workspaceRoot, 'Cargo.toml')] }),
+                    ...(this.options.cargoFeatures && { cargo: rustAnalyzerCargoOptions(this.options.cargoFeatures) })
+                }
+            })
         };
 
-        const result = await this.connection.sendRequest(InitializeRequest.type, initParams);
+        const result = await this.traced(
+            InitializeRequest.method,
+            this.connection.sendRequest(InitializeRequest.type, initParams),
+            this.serverSpan
+        );
         this.serverCapabilities = result.capabilities;
+        // Servers without the capability use UTF-16; we don't offer UTF-32
+        this.positionEncoding = result.capabilities.positionEncoding === 'utf-8' ? 'utf-8' : 'utf-16';
 
-        this.logger.debug(`Server capabilities: ${JSON.stringify(result.capabilities, null, 2)}`);
+        this.transport.debug(`Server capabilities: ${JSON.stringify(result.capabilities, null, 2)}`);
 
         await this.connection.sendNotification('initialized', {});
 
         this.initialized = true;
     }
 
+    /**
+     * Tracks the work the server reports and shows the most recent on the progress line.
+     *
+     * @param token - Token the server created the progress with
+     * @param value - Begin, report or end of the work
+     */
+    private onServerProgress(
+        token: string | number,
+        value: WorkDoneProgressBegin | WorkDoneProgressReport | WorkDoneProgressEnd
+    ): void {
+        if (value.kind === 'begin') {
+            this.serverProgress.set(token, { title: value.title, message: value.message, percentage: value.percentage });
+        } else if (value.kind === 'report') {
+            const progress = this.serverProgress.get(token);
+            if (progress) {
+                progress.message = value.message ?? progress.message;
+                progress.percentage = value.percentage ?? progress.percentage;
+            }
+        } else {
+            this.serverProgress.delete(token);
+        }
+
+        const latest = [...this.serverProgress.values()].pop();
+        if (!latest) {
+            this.logger.activity(undefined);
+            return;
+        }
+        const text = latest.message ? `${latest.title}: ${latest.message}` : latest.title;
+        this.transport.debug(`Server progress: ${text}`);
+        this.logger.activity(text, latest.percentage);
+    }
+
     async stop(): Promise<void> {
+        this.stopping = true;
+
         if (this.connection && this.initialized) {
             try {
-                await this.connection.sendRequest(ShutdownRequest.type);
+                await this.traced(
+                    ShutdownRequest.method,
+                    this.connection.sendRequest(ShutdownRequest.type),
+                    this.serverSpan
+                );
                 await this.connection.sendNotification(ExitNotification.type);
             } catch (error) {
-                this.logger.debug(`Error during shutdown: ${error instanceof Error ? error.message : String(error)}`);
+                this.transport.debug(`Error during shutdown: ${error instanceof Error ? error.message : String(error)}`);
             }
         }
 
         if (this.serverProcess) {
             this.serverProcess.kill();
         }
+        this.serverSpan?.end();
     }
 
+    /**
+     * Extracts the symbols of all source files below the workspace root, or of the `files` option. The
+     * server is started first if `start()` wasn't called; with the `cache` option only once a file
+     * isn't in the cache.
+     *
+     * @returns Symbols of all analyzed files
+     */
     async analyzeDirectory(): Promise<SymbolInfo[]> {
-        if (!this.connection || !this.initialized) {
-            throw new Error('Client not initialized');
+        if (this.options.cache) {
+            this.cache = await this.openCache();
+            if (!this.initialized) {
+                // The server reports its encoding when it starts; until then symbols come from the cache
+                this.positionEncoding = this.cache.positionEncoding ?? this.positionEncoding;
+                await this.loadCargoWorkspace();
+            }
         }
 
-        const symbols: SymbolInfo[] = [];
         const files = this.getSourceFiles();
-
         this.logger.info(`Found ${files.length} ${this.language} files to analyze`);
+        this.cache?.scan(files);
+
+        this.analyzing = true;
+        try {
+            const symbols = await this.analyzeWorkspace(files);
+            try {
+                this.cache?.save(this.positionEncoding);
+            } catch (error) {
+                this.logger.warn(`Saving the cache failed: ${error instanceof Error ? error.message : String(error)}`);
+            }
+            return symbols;
+        } finally {
+            this.analyzing = false;
+        }
+    }
+
+    /**
+     * Starts the server unless it is running, e.g. when all files so far came from the cache.
+     */
+    private async ensureStarted(): Promise<void> {
+        if (!this.initialized) {
+            await this.start();
+        }
+    }
+
+    /**
+     * Reads the Cargo workspace layout of a Rust project, once.
+     */
+    private async loadCargoWorkspace(): Promise<void> {
+        if (this.language !== 'rust' || this.cargo) {
+            return;
+        }
+        try {
+            this.cargo = await readCargoMetadata(this.workspaceRoot);
+            this.logger.debug(
+                `Cargo workspace ${this.cargo.workspaceRoot}: ${this.cargo.members.map((m) => m.name).join(', ')}`
+            );
+        } catch (error) {
+            this.logger.debug(`cargo metadata failed: ${error instanceof Error ? error.message : String(error)}`);
+        }
+    }
+
+    /**
+     * Opens the cache matching everything the extracted symbols depend on besides the files themselves.
+     *
+     * @returns The cache of this workspace, language, lsp-cli and server version and extraction options
+     */
+    private async openCache(): Promise<AnalysisCache> {
+        const settings = {
+            lspCli: VERSION,
+            server: (await this.serverManager.getServerVersion(this.language)) ?? 'unknown',
+            compileCommandsDir: this.options.compileCommandsDir,
+            trailingDocs: this.options.trailingDocs,
+            cargoFeatures: this.options.cargoFeatures,
+            includeBody: this.options.includeBody,
+            leadingComments: this.options.leadingComments,
+            expandMacros: this.options.expandMacros,
+            includeLocals: this.options.includeLocals,
+            resolveTypes: this.options.resolveTypes
+        };
+        return AnalysisCache.open(this.workspaceRoot, this.language, settings, this.extractor);
+    }
+
+    private async analyzeWorkspace(files: string[]): Promise<SymbolInfo[]> {
+        if (!this.cargo) {
+            const symbols = [...(await this.analyzeFiles(files)), ...(await this.analyzeNotebooks())];
+            if (this.language === 'rust') {
+                linkImplementations(symbols);
+            }
+            expandAliases(symbols);
+            this.logger.success(`Analysis complete: found ${symbols.length} symbols`);
+            return symbols;
+        }
+
+        // Analyze Rust workspaces crate by crate so each symbol can be tagged with its crate
+        const symbols: SymbolInfo[] = [];
+        for (const [member, memberFiles] of groupFilesByMember(files, this.cargo)) {
+            if (memberFiles.length > 0) {
+                this.logger.info(`Crate ${member?.name ?? '(outside workspace members)'}: ${memberFiles.length} files`);
+                symbols.push(...(await this.analyzeFiles(memberFiles)));
+            }
+        }
+        addCargoInfo(symbols, this.cargo);
+        linkImplementations(symbols);
+        expandAliases(symbols);
+        this.logger.success(`Analysis complete: found ${symbols.length} symbols`);
+        return symbols;
+    }
+
+    /**
+     * @returns The unit of `character` columns negotiated with the server
+     */
+    getPositionEncoding(): PositionEncoding {
+        return this.positionEncoding;
+    }
+
+    /**
+     * @returns The Cargo workspace layout for Rust projects, if `cargo metadata` succeeded
+     */
+    getCargoWorkspace(): CargoWorkspaceInfo | undefined {
+        return this.cargo;
+    }
+
+    /**
+     * @returns Trailing doc comments of the analyzed files that no symbol received, with the `orphan` policy
+     */
+    getOrphanedDocs(): OrphanedDoc[] {
+        return [...this.orphanedDocs.values()].flat();
+    }
+
+    /**
+     * @returns Detected encodings of the analyzed files that are not plain UTF-8, keyed by path
+     */
+    getFileEncodings(): Record<string, SourceEncoding> {
+        return Object.fromEntries([...this.fileEncodings].filter(([, encoding]) => encoding !== 'utf-8'));
+    }
+
+    /**
+     * @returns Other paths reaching the analyzed files through symlinks or hardlinks, keyed by the analyzed path
+     */
+    getFileAliases(): Record<string, string[]> {
+        return Object.fromEntries(this.fileAliases);
+    }
+
+    /**
+     * @returns Files and notebooks whose analysis failed, so the result misses their symbols
+     */
+    getFailedFiles(): string[] {
+        return [...this.failedFiles];
+    }
+
+    /**
+     * @returns Why each failed file or notebook failed, with the server's stderr output at the time
+     */
+    getExtractionErrors(): ExtractionError[] {
+        return [...this.extractionErrors];
+    }
+
+    /**
+     * @returns Files and notebooks analyzed, left out by `--ignore` or `--files-from`, and failed
+     */
+    getFileCounts(): FileCounts {
+        return { scanned: this.scannedFiles, skipped: this.skippedFiles, failed: this.getFailedFiles() };
+    }
+
+    /**
+     * Extracts the symbols of files, taking those of files unchanged since the last run from the cache.
+     *
+     * @param files - Absolute paths of the files
+     * @returns Symbols of the files, in their order
+     */
+    private async analyzeFiles(files: string[]): Promise<SymbolInfo[]> {
+        this.scannedFiles += files.length;
+        const cached = new Map<string, CachedFile>();
+        for (const file of files) {
+            const entry = this.cache?.lookup(file);
+            if (entry) {
+                cached.set(file, entry);
+                this.fileEncodings.set(file, entry.encoding);
+                if (entry.orphanedDocs) {
+                    this.orphanedDocs.set(file, entry.orphanedDocs);
+                }
+            }
+        }
+        if (this.cache) {
+            this.logger.info(`${cached.size} of ${files.length} files unchanged since the last run, taken from cache`);
+        }
+
+        const pending = files.filter((file) => !cached.has(file));
+        const analyzed = new Map<string, SymbolInfo[]>();
+        if (pending.length > 0) {
+            await this.ensureStarted();
+            await this.extractFiles(pending, analyzed);
+        }
+        // In the order of the files, as without the cache
+        return files.flatMap((file) => cached.get(file)?.symbols ?? analyzed.get(file) ?? []);
+    }
+
+    /**
+     * Extracts the symbols of files from the server, recording failures, and stores them in the cache.
+     *
+     * @param files - Absolute paths of the files
+     * @param analyzed - Receives the symbols of each successfully analyzed file
+     */
+    private async extractFiles(files: string[], analyzed: Map<string, SymbolInfo[]>): Promise<void> {
+        this.logger.stage('Extracting symbols', files.length);
 
         for (let i = 0; i < files.length; i++) {
             const file = files[i];
 
+            const relativePath = relative(this.workspaceRoot, file);
+            const startedAt = Date.now();
+            const stderrStart = this.stderrReceived;
             this.logger.file(file, 'analyzing');
-            this.logger.progress(i + 1, files.length);
+            this.extractor.event('file.started', { file: relativePath });
+            this.logger.progress(i, files.length, relativePath);
 
             try {
                 const fileSymbols = await this.analyzeFile(file);
-                symbols.push(...fileSymbols);
+                analyzed.set(file, fileSymbols);
+                this.cache?.store(
+                    file,
+                    {
+                        symbols: fileSymbols,
+                        encoding: this.fileEncodings.get(file) ?? 'utf-8',
+                        ...(this.orphanedDocs.has(file) && { orphanedDocs: this.orphanedDocs.get(file) })
+                    },
+                    this.dependencies.get(file)
+                );
                 this.logger.file(file, 'done');
+                this.extractor.event('file.finished', {
+                    file: relativePath,
+                    symbols: fileSymbols.length,
+                    durationMs: Date.now() - startedAt
+                });
             } catch (error) {
-                this.logger.file(file, 'error');
-                this.logger.error(`Error analyzing ${file}`, error instanceof Error ? error.message : String(error));
+                const restarts = this.restarts;
+                if (!(await this.recordFailure(file, error, stderrStart))) {
+                    this.giveUp(files.slice(i + 1));
+                    break;
+                }
+                if (this.restarts !== restarts) {
+                    // Starting the new server ended the stage
+                    this.logger.stage('Extracting symbols', files.length);
+                }
+            }
+        }
+
+        this.logger.progress(files.length, files.length);
+        this.logger.stageDone(`${files.length} files`);
+    }
+
+    /**
+     * Analyzes the code cells of the workspace's Jupyter notebooks in this client's language. Each
+     * notebook is analyzed as a virtual document next to it, which never touches the disk.
+     */
+    private async analyzeNotebooks(): Promise<SymbolInfo[]> {
+        const symbols: SymbolInfo[] = [];
+        const notebooks = (
+            this.options.files
+                ? selectFiles(this.workspaceRoot, this.options.files, ['.ipynb'], this.options.ignore).files
+                : getAllFiles(this.workspaceRoot, ['.ipynb'], this.options.ignore)
+        ).filter(
+            (file) => !file.includes('.ipynb_checkpoints')
+        );
+
+        for (const [index, notebook] of notebooks.entries()) {
+            const stderrStart = this.stderrReceived;
+            try {
+                const document = readNotebook(notebook);
+                if (document?.language !== this.language) {
+                    continue;
+                }
+                this.scannedFiles++;
+                await this.ensureStarted();
+                this.logger.file(notebook, 'analyzing');
+                const virtualPath = `${notebook}${SOURCE_EXTENSIONS[this.language][0]}`;
+                const notebookSymbols = await this.analyzeContent(virtualPath, document.content);
+                mapNotebookSymbols(notebookSymbols, document, notebook);
+                // Ids were derived from the virtual path
+                addSymbolIds(notebookSymbols, notebook, this.workspaceRoot);
+                symbols.push(...notebookSymbols);
+                this.logger.file(notebook, 'done');
+            } catch (error) {
+                if (!(await this.recordFailure(notebook, error, stderrStart))) {
+                    this.giveUp(notebooks.slice(index + 1));
+                    break;
+                }
             }
         }
 
         this.logger.clearLine();
-        this.logger.success(`Analysis complete: found ${symbols.length} symbols`);
         return symbols;
     }
 
+    /**
+     * Records a file or notebook whose analysis failed, with what the server wrote to stderr meanwhile,
+     * and restarts the server if it crashed on the file. With `failFast`, throws instead.
+     *
+     * @param file - Absolute path of the file or notebook
+     * @param error - Why the analysis failed
+     * @param stderrStart - Server stderr characters received before the analysis of the file started
+     * @returns False if the server crashed and could not be restarted, so no further files can be analyzed
+     */
+    private async recordFailure(file: string, error: unknown, stderrStart: number): Promise<boolean> {
+        const message = error instanceof Error ? error.message : String(error);
+        const crashed = this.serverCrashed;
+        const reason = crashed ? `Language server crashed: ${message}` : message;
+        const stderr = this.stderrSince(stderrStart);
+        const relativePath = relative(this.workspaceRoot, file);
+
+        this.logger.file(file, 'error');
+        this.failedFiles.push(file);
+        this.extractionErrors.push({ file, reason, ...(stderr !== undefined && { stderr }) });
+        this.extractor.event('file.failed', { file: relativePath, error: reason }, 'error');
+        this.logger.error(`Error analyzing ${file}`, reason);
+        if (this.options.failFast) {
+            throw new ExitError(`Analyzing ${relativePath} failed: ${reason}`, ExitCode.PartialExtraction);
+        }
+        if (!crashed) {
+            return true;
+        }
+
+        if (++this.restarts > MAX_RESTARTS) {
+            this.logger.error(`The ${this.language} LSP server crashed ${MAX_RESTARTS} times; giving up`);
+            return false;
+        }
+        try {
+            await this.restart();
+            return true;
+        } catch (restartError) {
+            const detail = restartError instanceof Error ? restartError.message : String(restartError);
+            this.logger.error(`Restarting the ${this.language} LSP server failed`, detail);
+            return false;
+        }
+    }
+
+    /**
+     * Records files that can't be analyzed anymore because the server is gone.
+     *
+     * @param files - Absolute paths of the remaining files or notebooks
+     */
+    private giveUp(files: string[]): void {
+        for (const file of files) {
+            this.failedFiles.push(file);
+            this.extractionErrors.push({ file, reason: 'Not analyzed: the language server could not be restarted' });
+        }
+    }
+
+    /**
+     * Replaces a crashed server with a new one. Documents are opened again as they are analyzed.
+     */
+    private async restart(): Promise<void> {
+        this.serverProcess?.kill();
+        this.serverSpan?.end(new Error('Language server crashed'));
+        this.initialized = false;
+        this.serverCrashed = false;
+        this.documentVersions.clear();
+        this.serverProgress.clear();
+        await this.start();
+        this.transport.event('server.restarted', { language: this.language, reason: 'crash' }, 'info');
+    }
+
+    /**
+     * @param start - Server stderr characters received before the excerpt
+     * @returns The last lines the server wrote to stderr since then, or undefined if it wrote nothing
+     */
+    private stderrSince(start: number): string | undefined {
+        const length = Math.min(this.stderrReceived - start, this.serverStderr.length);
+        const text = length > 0 ? this.serverStderr.slice(-length).trim() : '';
+        return text ? text.split('\n').slice(-STDERR_EXCERPT_LINES).join('\n') : undefined;
+    }
+
     private async analyzeFile(filePath: string): Promise<SymbolInfo[]> {
+        // The server gets the decoded content, positions are mapped back to the file's own bytes
+        const { content, encoding } = readSourceFile(filePath);
+        this.fileEncodings.set(filePath, encoding);
+        this.dependencies.delete(filePath);
+        return this.analyzeContent(filePath, content);
+    }
+
+    /**
+     * Extracts the symbols of a file from the given content instead of the file on disk,
+     * e.g. a staged or committed version. The server keeps this content for the document
+     * until it is analyzed again.
+     *
+     * @param filePath - Absolute path of the file
+     * @param content - Content to analyze
+     * @returns Symbols found in the content
+     */
+    async analyzeContent(filePath: string, content: string): Promise<SymbolInfo[]> {
+        // File spans sit under the server span so requests can be told apart per server
+        return this.tracer.trace(
+            'analyze file',
+            { 'code.filepath': filePath },
+            async (span) => {
+                const symbols = await this.extractContentSymbols(filePath, content);
+                span.setAttribute('lsp.symbol_count', symbols.length);
+                return symbols;
+            },
+            this.serverSpan
+        );
+    }
+
+    private async extractContentSymbols(filePath: string, content: string): Promise<SymbolInfo[]> {
         if (!this.connection) {
             throw new Error('Connection not established');
         }
 
-        const content = readFileSync(filePath, 'utf-8');
         const lines = content.split('\n');
         const uri = `file://${filePath}`;
 
-        // Open the document
-        const textDocument: TextDocumentItem = {
-            uri,
-            languageId: this.getLanguageId(),
-            version: 1,
-            text: content
-        };
-
-        await this.connection.sendNotification(DidOpenTextDocumentNotification.type, {
-            textDocument
-        });
+        await this.openDocument(filePath, content);
 
         // Request document symbols
         const params: DocumentSymbolParams = {
@@ -296,27 +944,33 @@ export class LanguageClient {
         };
 
         // Add timeout to prevent hanging
-        const symbolsPromise = this.connection.sendRequest(DocumentSymbolRequest.type, params) as Promise<
-            DocumentSymbol[] | SymbolInformation[]
-        >;
+        const symbolsPromise = this.traced(
+            DocumentSymbolRequest.method,
+            this.connection.sendRequest(DocumentSymbolRequest.type, params) as Promise<
+                DocumentSymbol[] | SymbolInformation[]
+            >
+        );
 
+        let timeout: NodeJS.Timeout | undefined;
         const timeoutPromise = new Promise<DocumentSymbol[] | SymbolInformation[]>((_, reject) => {
-            setTimeout(() => reject(new Error('Document symbol request timed out after 10s')), 10000);
+            timeout = setTimeout(() => reject(new Error('Document symbol request timed out after 10s')), 10000);
         });
 
-        const symbols = await Promise.race([symbolsPromise, timeoutPromise]);
+        // Clear the timer so a finished run doesn't keep the process alive
+        const symbols = await Promise.race([symbolsPromise, timeoutPromise]).finally(() => clearTimeout(timeout));
 
         // Debug logging for C#
         if (this.language === 'csharp') {
-            console.log(
-                `[DEBUG] Document symbols response for ${filePath}:`,
-                symbols === null
-                    ? 'null'
-                    : symbols === undefined
-                      ? 'undefined'
-                      : Array.isArray(symbols)
-                        ? `array of ${symbols.length}`
-                        : typeof symbols
+            this.extractor.debug(
+                `Document symbols response for ${filePath}: ${
+                    symbols === null
+                        ? 'null'
+                        : symbols === undefined
+                          ? 'undefined'
+                          : Array.isArray(symbols)
+                            ? `array of ${symbols.length}`
+                            : typeof symbols
+                }`
             );
         }
 
@@ -327,6 +981,331 @@ export class LanguageClient {
         return await this.extractSymbols(symbols, filePath, lines);
     }
 
+    /**
+     * Opens a document on the server, or sends its full new content if it is already open.
+     * Servers reject a second didOpen for the same URI, so re-analysis in long-lived sessions goes through didChange.
+     */
+    private async openDocument(filePath: string, content: string): Promise<void> {
+        if (!this.connection) {
+            throw new Error('Connection not established');
+        }
+
+        const uri = `file://${filePath}`;
+        const version = this.documentVersions.get(uri);
+
+        if (version === undefined) {
+            const textDocument: TextDocumentItem = {
+                uri,
+                languageId: this.getLanguageId(),
+                version: 1,
+                text: content
+            };
+            await this.connection.sendNotification(DidOpenTextDocumentNotification.type, { textDocument });
+            this.documentVersions.set(uri, 1);
+            return;
+        }
+
+        await this.connection.sendNotification(DidChangeTextDocumentNotification.type, {
+            textDocument: { uri, version: version + 1 },
+            contentChanges: [{ text: content }]
+        });
+        this.documentVersions.set(uri, version + 1);
+    }
+
+    /**
+     * Closes a document opened by an analysis, so the server goes back to the file on disk, or forgets
+     * it if there is none. Its diagnostics are dropped with it.
+     *
+     * @param filePath - Absolute path of the file
+     */
+    async closeDocument(filePath: string): Promise<void> {
+        const uri = `file://${filePath}`;
+        if (!this.connection || !this.documentVersions.has(uri)) {
+            return;
+        }
+        await this.connection.sendNotification(DidCloseTextDocumentNotification.type, { textDocument: { uri } });
+        this.documentVersions.delete(uri);
+        this.diagnostics.delete(filePath);
+    }
+
+    /**
+     * Makes sure a file is known to the server before a position request is sent for it.
+     */
+    private async ensureDocumentOpen(filePath: string): Promise<void> {
+        if (!this.documentVersions.has(`file://${filePath}`)) {
+            await this.openDocument(filePath, readSourceFile(filePath).content);
+        }
+    }
+
+    /**
+     * Resolves the definition(s) of the symbol at a position.
+     *
+     * @param filePath - Absolute path of the file containing the position
+     * @param position - 0-based line/character position
+     * @returns Definition locations with a one-line preview each
+     */
+    async getDefinitionAt(filePath: string, position: Position): Promise<SourceLocation[]> {
+        if (!this.connection || !this.initialized) {
+            throw new Error('Client not initialized');
+        }
+
+        await this.ensureDocumentOpen(filePath);
+        const response = await this.traced(
+            DefinitionRequest.method,
+            this.connection.sendRequest(DefinitionRequest.type, {
+                textDocument: { uri: `file://${filePath}` },
+                position
+            })
+        );
+
+        if (!response) {
+            return [];
+        }
+
+        const locations = (Array.isArray(response) ? response : [response]) as Array<Location | LocationLink>;
+        return locations.map((location) =>
+            'targetUri' in location
+                ? this.toSourceLocation({ uri: location.targetUri, range: location.targetSelectionRange })
+                : this.toSourceLocation(location)
+        );
+    }
+
+    /**
+     * Retrieves hover information (type signature, docs) for the symbol at a position.
+     *
+     * @param filePath - Absolute path of the file containing the position
+     * @param position - 0-based line/character position
+     * @returns Hover contents flattened to plain text/markdown, or undefined if the server has none
+     */
+    async getHoverAt(filePath: string, position: Position): Promise<HoverInfo | undefined> {
+        if (!this.connection || !this.initialized) {
+            throw new Error('Client not initialized');
+        }
+
+        await this.ensureDocumentOpen(filePath);
+        const hover = (await this.traced(
+            HoverRequest.method,
+            this.connection.sendRequest(HoverRequest.type, {
+                textDocument: { uri: `file://${filePath}` },
+                position
+            })
+        )) as Hover | null;
+
+        if (!hover) {
+            return undefined;
+        }
+
+        return {
+            contents: this.flattenHoverContents(hover.contents),
+            range: hover.range
+                ? { start: this.convertPosition(hover.range.start), end: this.convertPosition(hover.range.end) }
+                : undefined
+        };
+    }
+
+    /**
+     * Finds all references to the symbol at a position.
+     *
+     * @param filePath - Absolute path of the file containing the position
+     * @param position - 0-based line/character position
+     * @param includeDeclaration - Whether the declaration itself is part of the result
+     * @returns Reference locations with a one-line preview each
+     */
+    async getReferencesAt(filePath: string, position: Position, includeDeclaration = true): Promise<SourceLocation[]> {
+        if (!this.connection || !this.initialized) {
+            throw new Error('Client not initialized');
+        }
+
+        await this.ensureDocumentOpen(filePath);
+        const locations = await this.traced(
+            ReferencesRequest.method,
+            this.connection.sendRequest(ReferencesRequest.type, {
+                textDocument: { uri: `file://${filePath}` },
+                position,
+                context: { includeDeclaration }
+            })
+        );
+
+        return (locations ?? []).map((location) => this.toSourceLocation(location));
+    }
+
+    /**
+     * Builds the call graph of extracted functions, methods and constructors: their outgoing and incoming
+     * calls from the call hierarchy, and their other references, e.g. a function passed as a callback.
+     * Servers without call hierarchy support only contribute references. Failures of single symbols are
+     * logged at debug level and leave their edges out.
+     *
+     * @param symbols - Symbols extracted by `analyzeDirectory()`, with ids
+     * @returns Edges between symbols, and to and from code outside the output
+     */
+    async extractCallGraph(symbols: SymbolInfo[]): Promise<CallGraphEdge[]> {
+        // Not started yet if all files came from the cache
+        await this.ensureStarted();
+        const connection = this.connection;
+        if (!connection) {
+            throw new Error('Client not initialized');
+        }
+
+        const index = indexSymbols(symbols);
+        const callables = [...index.values()].flat().filter((symbol) => CALL_GRAPH_KINDS.has(symbol.kind));
+        const hierarchy = Boolean(this.serverCapabilities.callHierarchyProvider);
+        if (!hierarchy) {
+            this.logger.warn(
+                `The ${this.language} LSP server has no call hierarchy; the call graph only has references`
+            );
+        }
+        const edges = new Map<string, CallGraphEdge>();
+        // Call sites, so references that are calls don't become a second edge
+        const callSites = new Set<string>();
+        const site = (file: string, position: Position) => `${file}:${position.line}:${position.character}`;
+        const itemNode = (item: CallHierarchyItem): CallGraphNode => {
+            const file = decodeURIComponent(item.uri.replace(/^file:\/\//, ''));
+            const symbol = findSymbolAt(index, file, item.selectionRange.start);
+            return symbol
+                ? symbolNode(symbol)
+                : { name: item.name, file, range: this.convertRange(item.selectionRange) };
+        };
+        const call = (from: CallGraphNode, to: CallGraphNode, ranges: LSPRange[]) => {
+            for (const range of ranges) {
+                callSites.add(site(from.file, range.start));
+            }
+            addEdge(edges, { kind: 'call', from, to, ranges: ranges.map((range) => this.convertRange(range)) });
+        };
+
+        this.logger.stage('Building call graph', callables.length);
+        for (const [i, symbol] of callables.entries()) {
+            this.logger.progress(i, callables.length, symbol.qualifiedName ?? symbol.name);
+            const position = (symbol.selectionRange ?? symbol.range).start;
+            const node = symbolNode(symbol);
+            try {
+                await this.ensureDocumentOpen(symbol.file);
+                const textDocument = { uri: `file://${symbol.file}` };
+                const items = hierarchy
+                    ? ((await this.traced(
+                          CallHierarchyPrepareRequest.method,
+                          connection.sendRequest(CallHierarchyPrepareRequest.type, { textDocument, position })
+                      )) as CallHierarchyItem[] | null)
+                    : null;
+                if (items && items.length > 0) {
+                    const outgoing = (await this.traced(
+                        CallHierarchyOutgoingCallsRequest.method,
+                        connection.sendRequest(CallHierarchyOutgoingCallsRequest.type, { item: items[0] })
+                    )) as CallHierarchyOutgoingCall[] | null;
+                    for (const outgoingCall of outgoing ?? []) {
+                        call(node, itemNode(outgoingCall.to), outgoingCall.fromRanges);
+                    }
+                    // Finds callers that aren't extracted callables themselves, e.g. module-level code
+                    const incoming = (await this.traced(
+                        CallHierarchyIncomingCallsRequest.method,
+                        connection.sendRequest(CallHierarchyIncomingCallsRequest.type, { item: items[0] })
+                    )) as CallHierarchyIncomingCall[] | null;
+                    for (const incomingCall of incoming ?? []) {
+                        call(itemNode(incomingCall.from), node, incomingCall.fromRanges);
+                    }
+                }
+
+                for (const reference of await this.getReferencesAt(symbol.file, position, false)) {
+                    if (callSites.has(site(reference.file, reference.range.start))) {
+                        continue;
+                    }
+                    const referrer = findEnclosingSymbol(index, reference.file, reference.range.start);
+                    const from = referrer
+                        ? symbolNode(referrer)
+                        : { name: '<top level>', file: reference.file, range: reference.range };
+                    addEdge(edges, { kind: 'reference', from, to: node, ranges: [reference.range] });
+                }
+            } catch (error) {
+                const message = error instanceof Error ? error.message : String(error);
+                this.extractor.debug(`Call graph of ${symbol.name} in ${symbol.file} failed: ${message}`);
+            }
+        }
+        this.logger.progress(callables.length, callables.length);
+        this.logger.stageDone(`${edges.size} edges`);
+        return [...edges.values()];
+    }
+
+    /**
+     * Returns the diagnostics published for documents opened so far. Servers publish asynchronously,
+     * so this first waits until no new diagnostics arrived for `quietMs`, or until `timeoutMs` passed.
+     *
+     * @param quietMs - How long the server must stay silent before the result is considered complete
+     * @param timeoutMs - Upper bound for the wait
+     * @returns Diagnostics of all files, sorted by file and position
+     */
+    async collectDiagnostics(quietMs = 1000, timeoutMs = 15000): Promise<DiagnosticInfo[]> {
+        const deadline = Date.now() + timeoutMs;
+        const waitStart = Date.now();
+        while (Date.now() < deadline) {
+            const quietSince = Math.max(this.lastDiagnosticsAt, waitStart);
+            if (Date.now() - quietSince >= quietMs) {
+                break;
+            }
+            await new Promise((resolve) => setTimeout(resolve, 100));
+        }
+
+        return [...this.diagnostics.values()]
+            .flat()
+            .sort(
+                (a, b) =>
+                    a.file.localeCompare(b.file) ||
+                    a.range.start.line - b.range.start.line ||
+                    a.range.start.character - b.range.start.character
+            );
+    }
+
+    private toDiagnosticInfo(file: string, diagnostic: Diagnostic): DiagnosticInfo {
+        const severities: Record<DiagnosticSeverity, DiagnosticInfo['severity']> = {
+            [DiagnosticSeverity.Error]: 'error',
+            [DiagnosticSeverity.Warning]: 'warning',
+            [DiagnosticSeverity.Information]: 'information',
+            [DiagnosticSeverity.Hint]: 'hint'
+        };
+        return {
+            file,
+            range: {
+                start: this.convertPosition(diagnostic.range.start),
+                end: this.convertPosition(diagnostic.range.end)
+            },
+            // The spec lets clients interpret a missing severity; treat it as an error like editors do
+            severity: severities[diagnostic.severity ?? DiagnosticSeverity.Error],
+            message: diagnostic.message,
+            source: diagnostic.source,
+            code: diagnostic.code !== undefined ? String(diagnostic.code) : undefined
+        };
+    }
+
+    private flattenHoverContents(contents: MarkupContent | MarkedString | MarkedString[]): string {
+        if (Array.isArray(contents)) {
+            return contents.map((part) => this.flattenHoverContents(part)).join('\n\n');
+        }
+        if (typeof contents === 'string') {
+            return contents;
+        }
+        if ('kind' in contents) {
+            return contents.value;
+        }
+        return `\`\`\`${contents.language}\n${contents.value}\n\`\`\``;
+    }
+
+    /**
+     * Converts an LSP location into an output location, reading the target line for the preview.
+     */
+    private toSourceLocation(location: Location): SourceLocation {
+        const file = location.uri.replace('file://', '');
+        const range = {
+            start: this.convertPosition(location.range.start),
+            end: this.convertPosition(location.range.end)
+        };
+
+        try {
+            const preview = readSourceFile(file).content.split('\n')[location.range.start.line]?.trim();
+            return { file, range, preview };
+        } catch (_error) {
+            // If we can't read the file, still return the location without preview
+            return { file, range };
+        }
+    }
+
     private async extractSymbols(
         symbols: DocumentSymbol[] | SymbolInformation[],
         filePath: string,
@@ -342,6 +1321,16 @@ export class LanguageClient {
         const firstSymbol = symbols[0];
         const isSymbolInformation = 'location' in firstSymbol;
 
+        // With the default policy, trailing doc comments keep attaching to the next symbol
+        const trailingDocs =
+            (this.options.trailingDocs ?? 'next') === 'next' ? [] : this.findTrailingDocs(symbols, lines);
+        const trailingDocLines = new Set<number>();
+        for (const block of trailingDocs) {
+            for (let line = block.start; line <= block.end; line++) {
+                trailingDocLines.add(line);
+            }
+        }
+
         if (isSymbolInformation) {
             // Handle SymbolInformation[] format (flat structure)
             const symbolInfos = symbols as SymbolInformation[];
@@ -357,8 +1346,16 @@ export class LanguageClient {
                         },
                         end: this.convertPosition(symbol.location.range.end)
                     },
+                    fullRange: this.convertRange(symbol.location.range),
                     preview: lines[symbol.location.range.start.line]?.trim() || '',
-                    documentation: this.extractDocumentation(lines, symbol.location.range.start.line),
+                    signature: await this.getSignature(
+                        filePath,
+                        lines,
+                        symbol.location.range.start,
+                        this.cleanSymbolName(symbol.name),
+                        symbol.kind
+                    ),
+                    ...this.extractDocumentation(lines, symbol.location.range.start.line, trailingDocLines),
                     comments: this.shouldExtractComments(symbol.kind)
                         ? this.extractInlineComments(
                               lines,
@@ -366,12 +1363,25 @@ export class LanguageClient {
                               symbol.location.range.end.line
                           )
                         : undefined,
+                    leadingComments: this.options.leadingComments
+                        ? extractLeadingComments(lines, symbol.location.range.start.line, this.language)
+                        : undefined,
                     supertypes:
                         symbol.kind === SymbolKind.Class || symbol.kind === SymbolKind.Interface
                             ? await this.getSupertypes(filePath, symbol.location.range.start)
                             : undefined,
+                    attributes: extractAttributes(lines, symbol.location.range.start.line, this.language),
+                    deprecated: this.isTaggedDeprecated(symbol) ? {} : undefined,
                     children: undefined // SymbolInformation doesn't have hierarchical children
                 };
+                if (this.isReexport(lines, symbol.location.range.start.line)) {
+                    symbolInfo.reexportOf = await this.resolveReexport(
+                        filePath,
+                        lines,
+                        symbol.location.range.start.line,
+                        symbolInfo.name
+                    );
+                }
 
                 allSymbols.push(symbolInfo);
             }
@@ -379,16 +1389,40 @@ export class LanguageClient {
             // Handle DocumentSymbol[] format (hierarchical structure)
             const documentSymbols = symbols as DocumentSymbol[];
             for (const symbol of documentSymbols) {
-                await this.extractDocumentSymbol(symbol, filePath, lines, allSymbols);
+                await this.extractDocumentSymbol(symbol, filePath, lines, allSymbols, trailingDocLines);
             }
         }
 
-        // Post-process C/C++ anonymous structs with typedef names
-        if (this.language === 'c' || this.language === 'cpp') {
-            return this.mergeAnonymousStructsWithTypedefs(allSymbols);
+        if (this.language === 'rust') {
+            await this.addMacroSymbols(allSymbols, filePath, lines);
+        }
+
+        this.applyTrailingDocPolicy(allSymbols, trailingDocs, filePath, lines);
+        addVisibility(allSymbols, lines, this.language);
+        addDeprecation(allSymbols);
+        addFunctionModifiers(allSymbols, lines, this.language);
+        addForeignItems(allSymbols, lines, this.language);
+        if (this.options.resolveTypes) {
+            await this.addResolvedTypes(allSymbols, filePath);
+        }
+        addVariantPayloads(allSymbols, lines, this.language, this.positionEncoding);
+        addAliasTargets(allSymbols, lines, this.language);
+        addQualifiedNames(allSymbols, filePath, lines, this.language, this.workspaceRoot);
+        addDisambiguation(allSymbols, this.language);
+        addPositionOffsets(allSymbols, lines, this.positionEncoding, this.fileEncodings.get(filePath));
+        if (this.options.includeBody) {
+            const maxLines = typeof this.options.includeBody === 'number' ? this.options.includeBody : undefined;
+            addBodies(allSymbols, lines, maxLines);
         }
 
-        return allSymbols;
+        // Post-process C/C++ anonymous structs with typedef names
+        const result =
+            this.language === 'c' || this.language === 'cpp'
+                ? this.mergeAnonymousStructsWithTypedefs(allSymbols)
+                : allSymbols;
+
+        addSymbolIds(result, filePath, this.workspaceRoot);
+        return result;
     }
 
     /**
@@ -398,7 +1432,8 @@ export class LanguageClient {
         symbol: DocumentSymbol,
         filePath: string,
         lines: string[],
-        allSymbols: SymbolInfo[]
+        allSymbols: SymbolInfo[],
+        trailingDocLines: Set<number>
     ): Promise<void> {
         // For C/C++, check if this is a forward declaration or friend declaration
         const preview = lines[symbol.selectionRange.start.line]?.trim() || '';
@@ -422,14 +1457,28 @@ export class LanguageClient {
                 },
                 end: this.convertPosition(symbol.range.end)
             },
+            fullRange: this.convertRange(symbol.range),
+            selectionRange: this.convertRange(symbol.selectionRange),
             preview,
-            documentation: this.extractDocumentation(lines, symbol.selectionRange.start.line),
+            signature: await this.getSignature(
+                filePath,
+                lines,
+                symbol.selectionRange.start,
+                this.cleanSymbolName(symbol.name),
+                symbol.kind
+            ),
+            ...this.extractDocumentation(lines, symbol.selectionRange.start.line, trailingDocLines),
             comments: this.shouldExtractComments(symbol.kind)
                 ? this.extractInlineComments(lines, symbol.selectionRange.start.line, symbol.range.end.line)
                 : undefined,
+            leadingComments: this.options.leadingComments
+                ? extractLeadingComments(lines, symbol.selectionRange.start.line, this.language)
+                : undefined,
             supertypes: this.isTypeSymbol(symbol)
                 ? await this.getSupertypes(filePath, symbol.selectionRange.start)
                 : undefined,
+            attributes: extractAttributes(lines, symbol.selectionRange.start.line, this.language),
+            deprecated: this.isTaggedDeprecated(symbol) ? {} : undefined,
             children: undefined // Will be populated by recursive calls
         };
 
@@ -440,26 +1489,75 @@ export class LanguageClient {
             (symbol.kind === SymbolKind.Method || symbol.kind === SymbolKind.Function)
         ) {
             symbolInfo.definition = await this.getDefinition(filePath, symbol.selectionRange.start);
+            // A definition can move, or be added in any file
+            this.dependOn(filePath, symbolInfo.definition?.file ?? ANY_FILE);
+        }
+
+        if (this.language === 'rust' && /^(unsafe\s+)?impl\b/.test(symbolInfo.name)) {
+            symbolInfo.impl = parseImplHeader(lines, symbol.selectionRange.start.line);
+        }
+
+        // Servers like clangd and pyright start the range at the declaration itself
+        if (symbolInfo.fullRange && (symbolInfo.documentation || symbolInfo.attributes)) {
+            const start = this.leadingCommentStart(lines, symbolInfo.fullRange.start.line);
+            if (start < symbolInfo.fullRange.start.line) {
+                symbolInfo.fullRange.start = { line: start, character: 0 };
+            }
+        }
+
+        if (this.isReexport(lines, symbol.selectionRange.start.line)) {
+            symbolInfo.reexportOf = await this.resolveReexport(
+                filePath,
+                lines,
+                symbol.selectionRange.start.line,
+                symbolInfo.name
+            );
         }
 
         allSymbols.push(symbolInfo);
 
-        // Recursively process children
-        if (symbol.children) {
+        // Recursively process children; locals of functions only with `includeLocals`, and only items
+        const callable = CALLABLE_KINDS.includes(symbol.kind);
+        if (symbol.children && (!callable || this.options.includeLocals)) {
             const childSymbols: SymbolInfo[] = [];
             for (const child of symbol.children) {
-                await this.extractDocumentSymbol(child, filePath, lines, childSymbols);
+                if (
+                    !callable ||
+                    isLocalItem(
+                        this.getSymbolKindName(child.kind),
+                        child.name,
+                        lines[child.selectionRange.start.line] ?? ''
+                    )
+                ) {
+                    await this.extractDocumentSymbol(child, filePath, lines, childSymbols, trailingDocLines);
+                }
             }
             if (childSymbols.length > 0) {
                 symbolInfo.children = childSymbols;
             }
+            if (symbolInfo.impl) {
+                // Link methods and associated items to the type and trait of their impl block
+                for (const child of childSymbols) {
+                    child.impl = symbolInfo.impl;
+                }
+            }
+        }
+
+        // rust-analyzer reports no `let` bindings, so closures bound to names are found in the source
+        if (callable && this.options.includeLocals && this.language === 'rust') {
+            const closures = findRustClosures(symbolInfo, lines, this.positionEncoding);
+            if (closures.length > 0) {
+                symbolInfo.children = [...(symbolInfo.children ?? []), ...closures].sort(
+                    (a, b) => a.range.start.line - b.range.start.line
+                );
+            }
         }
     }
 
     private async getDefinition(
         filePath: string,
         position: LSPPosition
-    ): Promise<SymbolInfo['definition'] | undefined> {
+    ): Promise<SourceLocation | undefined> {
         if (!this.connection) {
             return undefined;
         }
@@ -472,7 +1570,10 @@ export class LanguageClient {
                 position
             };
 
-            const response = await this.connection.sendRequest(DefinitionRequest.type, params);
+            const response = await this.traced(
+                DefinitionRequest.method,
+                this.connection.sendRequest(DefinitionRequest.type, params)
+            );
 
             if (!response) {
                 return undefined;
@@ -488,45 +1589,47 @@ export class LanguageClient {
             // Take the first location
             const location = locations[0] as Location;
 
-            // Convert file URI to path
-            const definitionFile = location.uri.replace('file://', '');
-
             // Skip if it's the same file (not a real definition, just the declaration)
-            if (definitionFile === filePath) {
+            if (location.uri.replace('file://', '') === filePath) {
                 return undefined;
             }
 
-            // Read the definition file to get preview
-            try {
-                const content = readFileSync(definitionFile, 'utf-8');
-                const lines = content.split('\n');
-                const preview = lines[location.range.start.line]?.trim();
-
-                return {
-                    file: definitionFile,
-                    range: {
-                        start: this.convertPosition(location.range.start),
-                        end: this.convertPosition(location.range.end)
-                    },
-                    preview
-                };
-            } catch (_error) {
-                // If we can't read the file, still return the location without preview
-                return {
-                    file: definitionFile,
-                    range: {
-                        start: this.convertPosition(location.range.start),
-                        end: this.convertPosition(location.range.end)
-                    }
-                };
-            }
+            return this.toSourceLocation(location);
         } catch (error) {
-            this.logger.debug(`Failed to get definition: ${error}`);
+            this.extractor.debug(`Failed to get definition: ${error}`);
             return undefined;
         }
     }
 
-    private extractDocumentation(lines: string[], symbolStartLine: number): string | undefined {
+    /**
+     * Extracts a symbol's documentation in its three forms: `documentation` with the comment markers
+     * stripped, `rawDocumentation` as written in the source and `markdownDocumentation`.
+     */
+    private extractDocumentation(
+        lines: string[],
+        symbolStartLine: number,
+        trailingDocLines?: Set<number>
+    ): Pick<SymbolInfo, 'documentation' | 'rawDocumentation' | 'markdownDocumentation'> {
+        const docLines = this.findDocumentation(lines, symbolStartLine, trailingDocLines);
+        return docLines ? this.documentationForms(docLines) : {};
+    }
+
+    private documentationForms(
+        docLines: string[]
+    ): Pick<SymbolInfo, 'documentation' | 'rawDocumentation' | 'markdownDocumentation'> {
+        return {
+            documentation: this.cleanDocumentation(docLines),
+            rawDocumentation: docLines.join('\n'),
+            markdownDocumentation: renderDocumentationMarkdown(docLines, this.language)
+        };
+    }
+
+    /** Source lines of the doc comment above a symbol, or of its Python docstring */
+    private findDocumentation(
+        lines: string[],
+        symbolStartLine: number,
+        trailingDocLines?: Set<number>
+    ): string[] | undefined {
         if (symbolStartLine <= 0) return undefined;
 
         // For Python, check for docstring after the function definition first
@@ -547,6 +1650,11 @@ export class LanguageClient {
                 continue;
             }
 
+            // Trailing doc comments of the symbol above are handled by the trailing docs policy
+            if (trailingDocLines?.has(currentLine)) {
+                break;
+            }
+
             // Skip annotations for Java (@Override, @Deprecated, etc)
             if (this.language === 'java' && line.startsWith('@')) {
                 currentLine--;
@@ -559,6 +1667,12 @@ export class LanguageClient {
                 continue;
             }
 
+            // Skip decorators for Python (@property, @app.route(...), etc)
+            if (this.language === 'python' && line.startsWith('@')) {
+                currentLine--;
+                continue;
+            }
+
             // Skip attributes for Rust (#[derive], #[cfg], etc)
             if (this.language === 'rust' && line.startsWith('#[') && line.endsWith(']')) {
                 currentLine--;
@@ -577,7 +1691,7 @@ export class LanguageClient {
                     checkLine--;
                 }
 
-                return this.cleanXmlDocumentation(xmlDocLines);
+                return xmlDocLines;
             }
 
             // Block documentation /** */ or /*! */
@@ -589,8 +1703,7 @@ export class LanguageClient {
                 while (checkLine >= 0) {
                     const checkLineContent = lines[checkLine].trim();
                     if (checkLineContent.startsWith('/**') || checkLineContent.startsWith('/*!')) {
-                        const docLines = lines.slice(checkLine, docEndLine + 1);
-                        return this.cleanBlockDocumentation(docLines);
+                        return lines.slice(checkLine, docEndLine + 1);
                     }
                     checkLine--;
 
@@ -617,7 +1730,7 @@ export class LanguageClient {
                     }
                 }
 
-                return this.cleanSlashDocumentation(slashDocLines);
+                return slashDocLines;
             }
 
             // Python docstrings (""" or ''')
@@ -626,7 +1739,7 @@ export class LanguageClient {
 
                 // Handle single-line docstring
                 if (line.endsWith(quote) && line.length > quote.length * 2) {
-                    return this.cleanPythonDocstring([lines[currentLine]]);
+                    return [lines[currentLine]];
                 }
 
                 // Handle multi-line docstring
@@ -651,7 +1764,7 @@ export class LanguageClient {
                 }
 
                 if (foundEnd || docLines.length === 1) {
-                    return this.cleanPythonDocstring(docLines);
+                    return docLines;
                 }
             }
 
@@ -874,6 +1987,19 @@ export class LanguageClient {
             .trim();
     }
 
+    private cleanDocumentation(docLines: string[]): string {
+        const first = docLines[0].trim();
+        if (first.startsWith('"""') || first.startsWith("'''")) {
+            return this.cleanPythonDocstring(docLines);
+        }
+        if (first.startsWith('/*')) {
+            return this.cleanBlockDocumentation(docLines);
+        }
+        return this.language === 'csharp'
+            ? this.cleanXmlDocumentation(docLines)
+            : this.cleanSlashDocumentation(docLines);
+    }
+
     private cleanBlockDocumentation(docLines: string[]): string {
         // Remove /** and */ and clean up * prefixes
         const cleaned = docLines.map((line, index) => {
@@ -982,7 +2108,7 @@ export class LanguageClient {
      * Extracts Python docstrings that come after the symbol definition.
      * Python docstrings appear as the first statement in the function body.
      */
-    private extractPythonDocstringAfterSymbol(lines: string[], symbolStartLine: number): string | undefined {
+    private extractPythonDocstringAfterSymbol(lines: string[], symbolStartLine: number): string[] | undefined {
         // Start scanning from the line after the symbol definition
         let currentLine = symbolStartLine; // symbolStartLine is 0-based, but we want to check starting from the def line
 
@@ -1018,13 +2144,13 @@ export class LanguageClient {
     /**
      * Extracts a Python docstring starting from a specific line.
      */
-    private extractPythonDocstringFromLine(lines: string[], startLine: number): string | undefined {
+    private extractPythonDocstringFromLine(lines: string[], startLine: number): string[] | undefined {
         const line = lines[startLine].trim();
         const quote = line.startsWith('"""') ? '"""' : "'''";
 
         // Handle single-line docstring
         if (line.endsWith(quote) && line.length > quote.length * 2) {
-            return this.cleanPythonDocstring([lines[startLine]]);
+            return [lines[startLine]];
         }
 
         // Handle multi-line docstring
@@ -1048,12 +2174,107 @@ export class LanguageClient {
         }
 
         if (foundEnd || docLines.length === 1) {
-            return this.cleanPythonDocstring(docLines);
+            return docLines;
         }
 
         return undefined;
     }
 
+    /**
+     * Finds doc comments directly below a symbol's last line that are followed by a blank line (or
+     * the end of the enclosing block), like documentation written after a definition.
+     */
+    private findTrailingDocs(
+        symbols: DocumentSymbol[] | SymbolInformation[],
+        lines: string[]
+    ): Array<{ start: number; end: number }> {
+        const endLines = new Set<number>();
+        const collect = (symbol: DocumentSymbol | SymbolInformation) => {
+            endLines.add('location' in symbol ? symbol.location.range.end.line : symbol.range.end.line);
+            if ('children' in symbol) {
+                symbol.children?.forEach(collect);
+            }
+        };
+        symbols.forEach(collect);
+
+        const slashDocs = ['csharp', 'c', 'cpp', 'rust'].includes(this.language);
+        const blocks: Array<{ start: number; end: number }> = [];
+        for (const endLine of endLines) {
+            const start = endLine + 1;
+            const first = lines[start]?.trim() ?? '';
+            let end = start;
+            if (first.startsWith('/**')) {
+                while (end < lines.length - 1 && !lines[end].includes('*/')) {
+                    end++;
+                }
+            } else if (slashDocs && first.startsWith('///')) {
+                while (lines[end + 1]?.trim().startsWith('///')) {
+                    end++;
+                }
+            } else {
+                continue;
+            }
+
+            // Doc comments directly followed by code document that code
+            const next = lines[end + 1]?.trim();
+            if (next === undefined || next === '' || /^}[;,]?$/.test(next)) {
+                blocks.push({ start, end });
+            }
+        }
+        return blocks;
+    }
+
+    /**
+     * Attaches trailing doc comments to the symbol above, reports them as orphaned or drops them,
+     * depending on the `trailingDocs` option.
+     */
+    private applyTrailingDocPolicy(
+        symbols: SymbolInfo[],
+        blocks: Array<{ start: number; end: number }>,
+        filePath: string,
+        lines: string[]
+    ): void {
+        const orphaned: OrphanedDoc[] = [];
+        for (const block of blocks) {
+            const docLines = lines.slice(block.start, block.end + 1);
+            const documentation = this.cleanDocumentation(docLines);
+
+            if (this.options.trailingDocs === 'previous') {
+                const owner = this.findSymbolEndingAt(symbols, block.start - 1);
+                if (owner && !owner.documentation) {
+                    Object.assign(owner, this.documentationForms(docLines));
+                }
+            } else if (this.options.trailingDocs === 'orphan') {
+                this.extractor.warn(`Orphaned doc comment at ${filePath}:${block.start + 1}`);
+                orphaned.push({
+                    file: filePath,
+                    range: {
+                        start: { line: block.start, character: 0 },
+                        end: { line: block.end, character: lines[block.end].length }
+                    },
+                    documentation
+                });
+            }
+        }
+        this.orphanedDocs.set(filePath, orphaned);
+    }
+
+    /** The innermost symbol whose range ends on a line */
+    private findSymbolEndingAt(symbols: SymbolInfo[], line: number): SymbolInfo | undefined {
+        for (const symbol of symbols) {
+            if (symbol.range.start.line <= line && line <= symbol.range.end.line) {
+                const child = symbol.children && this.findSymbolEndingAt(symbol.children, line);
+                if (child) {
+                    return child;
+                }
+                if (symbol.range.end.line === line) {
+                    return symbol;
+                }
+            }
+        }
+        return undefined;
+    }
+
     private mergeAnonymousStructsWithTypedefs(symbols: SymbolInfo[]): SymbolInfo[] {
         const result: SymbolInfo[] = [];
         const toSkip = new Set<number>();
@@ -1112,9 +2333,10 @@ export class LanguageClient {
                 position: position
             };
 
-            const items = (await this.connection.sendRequest(TypeHierarchyPrepareRequest.type, prepareParams)) as
-                | TypeHierarchyItem[]
-                | null;
+            const items = (await this.traced(
+                TypeHierarchyPrepareRequest.method,
+                this.connection.sendRequest(TypeHierarchyPrepareRequest.type, prepareParams)
+            )) as TypeHierarchyItem[] | null;
 
             if (!items || items.length === 0) {
                 return undefined;
@@ -1125,23 +2347,58 @@ export class LanguageClient {
                 item: items[0]
             };
 
-            const supertypes = (await this.connection.sendRequest(
-                TypeHierarchySupertypesRequest.type,
-                supertypesParams
+            const supertypes = (await this.traced(
+                TypeHierarchySupertypesRequest.method,
+                this.connection.sendRequest(TypeHierarchySupertypesRequest.type, supertypesParams)
             )) as TypeHierarchyItem[] | null;
 
             if (!supertypes || supertypes.length === 0) {
                 return undefined;
             }
 
+            for (const item of supertypes) {
+                this.dependOn(filePath, item.uri.startsWith('file://') ? item.uri.replace('file://', '') : ANY_FILE);
+            }
+
             // Extract just the names
             return supertypes.map((item) => item.name);
         } catch (error) {
-            this.logger.debug(`Error getting supertypes: ${error}`);
+            this.extractor.debug(`Error getting supertypes: ${error}`);
             return undefined;
         }
     }
 
+    /**
+     * Records an LSP request as a span while it is pending.
+     *
+     * @param method - LSP method name
+     * @param request - The request's response promise
+     * @param parent - Parent span; defaults to the file being analyzed, then the server span
+     */
+    private async traced<R>(method: string, request: Promise<R>, parent?: Span): Promise<R> {
+        const span = this.tracer.startSpan(
+            method,
+            { 'rpc.system': 'jsonrpc', 'rpc.method': method },
+            parent ?? this.tracer.active() ?? this.serverSpan
+        );
+        const startedAt = Date.now();
+        this.transport.trace(`→ ${method}`);
+        try {
+            const response = await request;
+            span.end();
+            this.transport.trace(`← ${method} (${Date.now() - startedAt}ms)`);
+            return response;
+        } catch (error) {
+            span.end(error);
+            this.transport.event(
+                'request.failed',
+                { language: this.language, method, error: error instanceof Error ? error.message : String(error) },
+                'warn'
+            );
+            throw error;
+        }
+    }
+
     private convertPosition(lspPosition: LSPPosition): Position {
         return {
             line: lspPosition.line,
@@ -1149,6 +2406,25 @@ export class LanguageClient {
         };
     }
 
+    /**
+     * First line of the comments and attributes directly above a line, without blank lines in between.
+     *
+     * @param lines - Source lines of the file
+     * @param line - 0-based line of the declaration
+     * @returns The first such line, or `line` itself if there are none
+     */
+    private leadingCommentStart(lines: string[], line: number): number {
+        let start = line;
+        while (start > 0 && /^\s*(\/\/|\/\*|\*|#\[|@|\[)/.test(lines[start - 1])) {
+            start--;
+        }
+        return start;
+    }
+
+    private convertRange(lspRange: LSPRange): Range {
+        return { start: this.convertPosition(lspRange.start), end: this.convertPosition(lspRange.end) };
+    }
+
     private cleanSymbolName(name: string): string {
         // For Java, strip generic type parameters from class/interface names
         if (this.language === 'java') {
@@ -1161,6 +2437,232 @@ export class LanguageClient {
         return name;
     }
 
+    /**
+     * Reads the full declaration header of a function or type from the source, falling back to hover
+     * for headers spanning too many lines, since `DocumentSymbol.detail` truncates generics and where clauses.
+     *
+     * @param filePath - File containing the symbol
+     * @param lines - Source lines of the file
+     * @param position - Position of the symbol's name
+     * @param name - Symbol name
+     * @param kind - Symbol kind; other symbols than functions and types get no signature
+     * @returns The signature, or undefined if none could be determined
+     */
+    private async getSignature(
+        filePath: string,
+        lines: string[],
+        position: LSPPosition,
+        name: string,
+        kind: SymbolKind
+    ): Promise<string | undefined> {
+        if (!SIGNATURE_KINDS.includes(kind)) {
+            return undefined;
+        }
+        const signature = extractSignature(lines, position.line, this.language);
+        if (signature) {
+            return signature;
+        }
+        // Hover can show types from anywhere in the workspace
+        this.dependOn(filePath, ANY_FILE);
+        try {
+            const hover = await this.getHoverAt(filePath, position);
+            return hover && signatureFromHover(hover.contents, name, this.language);
+        } catch (error) {
+            this.extractor.debug(`Failed to get signature of ${name} from hover: ${error}`);
+            return undefined;
+        }
+    }
+
+    /**
+     * Records the type of fields, constants, properties and variables outside of functions from hover,
+     * which also covers inferred types like `static GLOBAL_COUNTER = AtomicUsize::new(0)`.
+     *
+     * @param symbols - Symbols of one file; updated in place
+     * @param filePath - Absolute path of the file
+     */
+    private async addResolvedTypes(symbols: SymbolInfo[], filePath: string): Promise<void> {
+        for (const symbol of symbols) {
+            if (VALUE_KINDS.has(symbol.kind)) {
+                // Inferred types can come from anywhere in the workspace
+                this.dependOn(filePath, ANY_FILE);
+                const { line, character } = (symbol.selectionRange ?? symbol.fullRange ?? symbol.range).start;
+                try {
+                    const hover = await this.getHoverAt(filePath, { line, character });
+                    symbol.type = hover && typeFromHover(hover.contents, symbol.name);
+                } catch (error) {
+                    this.extractor.debug(`Failed to get type of ${symbol.name} from hover: ${error}`);
+                }
+            }
+            // Locals of functions are left out
+            if (symbol.children && !['function', 'method', 'constructor'].includes(symbol.kind)) {
+                await this.addResolvedTypes(symbol.children, filePath);
+            }
+        }
+    }
+
+    /**
+     * Attributes symbols declared inside Rust macro invocations to the macro, and adds the items
+     * generated by invocations rust-analyzer reports no symbols for, read from their expansion.
+     *
+     * @param symbols - Symbols of one file; updated in place
+     * @param filePath - Absolute path of the file
+     * @param lines - Source lines of the file
+     */
+    private async addMacroSymbols(symbols: SymbolInfo[], filePath: string, lines: string[]): Promise<void> {
+        const invocations = findMacroInvocations(lines, symbols);
+        const attributed = attributeMacroSymbols(symbols, invocations, lines, this.positionEncoding);
+        for (const [invocation, existing] of attributed) {
+            if (existing.length > 0 && !this.options.expandMacros) {
+                continue;
+            }
+            const expansion = await this.expandMacro(filePath, lines, invocation);
+            if (!expansion) {
+                continue;
+            }
+            for (const symbol of existing) {
+                if (symbol.macro) {
+                    symbol.macro.expansion = expansion;
+                }
+            }
+            if (existing.length > 0) {
+                continue;
+            }
+
+            const generated = symbolsFromExpansion(
+                expansion,
+                invocation,
+                filePath,
+                lines,
+                this.positionEncoding,
+                this.options.expandMacros ?? false
+            );
+            for (const symbol of generated) {
+                // A doc comment above the invocation documents what it generates
+                Object.assign(symbol, this.extractDocumentation(lines, invocation.start.line));
+            }
+            // Invocations in modules and impl blocks generate members of them
+            let siblings = symbols;
+            for (;;) {
+                const container = siblings.find(
+                    (symbol) =>
+                        !symbol.macro &&
+                        symbol.range.start.line < invocation.start.line &&
+                        invocation.end.line <= symbol.range.end.line
+                );
+                if (!container) {
+                    break;
+                }
+                container.children ??= [];
+                siblings = container.children;
+            }
+            siblings.push(...generated);
+            siblings.sort((a, b) => a.range.start.line - b.range.start.line);
+        }
+    }
+
+    /**
+     * Expands a macro invocation with rust-analyzer's `rust-analyzer/expandMacro` extension.
+     *
+     * @returns The expanded source, or undefined if the server can't expand it
+     */
+    private async expandMacro(
+        filePath: string,
+        lines: string[],
+        invocation: MacroInvocation
+    ): Promise<string | undefined> {
+        if (!this.connection) {
+            return undefined;
+        }
+        const text = lines[invocation.line].slice(0, invocation.column);
+        const position = {
+            line: invocation.line,
+            character: this.positionEncoding === 'utf-8' ? Buffer.byteLength(text) : text.length
+        };
+        // Macros can be defined in any file of the workspace
+        this.dependOn(filePath, ANY_FILE);
+        try {
+            const response = (await this.traced(
+                'rust-analyzer/expandMacro',
+                this.connection.sendRequest('rust-analyzer/expandMacro', {
+                    textDocument: { uri: `file://${filePath}` },
+                    position
+                })
+            )) as { name: string; expansion: string } | null;
+            return response?.expansion;
+        } catch (error) {
+            this.extractor.debug(`Failed to expand ${invocation.name}! at ${filePath}:${invocation.line + 1}: ${error}`);
+            return undefined;
+        }
+    }
+
+    /**
+     * @param lines - Source lines of the file
+     * @param line - Line of the symbol, possibly one name in a multi-line import list
+     * @returns Whether the statement declaring the symbol re-exports an item defined elsewhere
+     */
+    private isReexport(lines: string[], line: number): boolean {
+        const pattern = REEXPORT_PATTERNS[this.language];
+        if (!pattern) {
+            return false;
+        }
+        let start = line;
+        while (start > 0 && line - start < 50 && /[,({\\]$/.test(lines[start - 1].trim())) {
+            start--;
+        }
+        return pattern.test(lines.slice(start, line + 50).join('\n'));
+    }
+
+    /**
+     * Follows a re-export to the definition of the re-exported item.
+     *
+     * @param filePath - File containing the re-export
+     * @param lines - Source lines of the file
+     * @param line - Line of the re-exported name
+     * @param name - Re-exported name, used to find its column
+     * @returns The canonical definition, or undefined if the server doesn't resolve it to another place
+     */
+    private async resolveReexport(
+        filePath: string,
+        lines: string[],
+        line: number,
+        name: string
+    ): Promise<SourceLocation | undefined> {
+        const text = lines[line] ?? '';
+        const prefix = text.slice(0, Math.max(text.lastIndexOf(name), 0));
+        const character = this.positionEncoding === 'utf-8' ? Buffer.byteLength(prefix) : prefix.length;
+        try {
+            const locations = await this.getDefinitionAt(filePath, { line, character });
+            const target = locations.find(
+                (location) => location.file !== filePath || location.range.start.line !== line
+            );
+            // An unresolved re-export may resolve once another file changes
+            this.dependOn(filePath, target?.file ?? ANY_FILE);
+            return target;
+        } catch (error) {
+            this.extractor.debug(`Failed to resolve re-export ${name}: ${error}`);
+            this.dependOn(filePath, ANY_FILE);
+            return undefined;
+        }
+    }
+
+    /**
+     * Notes that the symbols of a file took data from another file, so their cache entry is only
+     * reused while that file is unchanged too.
+     *
+     * @param filePath - File being analyzed
+     * @param dependency - File the data came from, or `ANY_FILE` if it can't be told
+     */
+    private dependOn(filePath: string, dependency: string): void {
+        if (this.cache && dependency !== filePath) {
+            this.dependencies.set(filePath, (this.dependencies.get(filePath) ?? new Set()).add(dependency));
+        }
+    }
+
+    /** Whether the server tags a symbol deprecated, through `tags` or the older `deprecated` property */
+    private isTaggedDeprecated(symbol: DocumentSymbol | SymbolInformation): boolean {
+        return symbol.tags?.includes(SymbolTag.Deprecated) === true || symbol.deprecated === true;
+    }
+
     private isTypeSymbol(symbol: DocumentSymbol): boolean {
         const typeKinds: SymbolKind[] = [SymbolKind.Class, SymbolKind.Interface, SymbolKind.Enum, SymbolKind.Struct];
         return typeKinds.includes(symbol.kind);
@@ -1244,19 +2746,12 @@ export class LanguageClient {
     }
 
     private getSourceFiles(): string[] {
-        const extensionMap: { [key in SupportedLanguage]: string[] } = {
-            java: ['.java'],
-            cpp: ['.cpp', '.cxx', '.cc', '.hpp', '.hxx', '.hh', '.h'],
-            c: ['.c', '.h'],
-            csharp: ['.cs'],
-            haxe: ['.hx'],
-            dart: ['.dart'],
-            typescript: ['.ts', '.tsx', '.js'],
-            rust: ['.rs'],
-            python: ['.py', '.pyi']
-        };
-
-        const extensions = extensionMap[this.language];
-        return getAllFiles(this.workspaceRoot, extensions);
+        // Listed files are still analyzed against the whole workspace, which the server indexes
+        const { files, aliases, skipped } = this.options.files
+            ? selectFiles(this.workspaceRoot, this.options.files, SOURCE_EXTENSIONS[this.language], this.options.ignore)
+            : discoverFiles(this.workspaceRoot, SOURCE_EXTENSIONS[this.language], this.options.ignore);
+        this.fileAliases = aliases;
+        this.skippedFiles = skipped;
+        return files;
     }
 }
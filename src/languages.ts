@@ -0,0 +1,70 @@
+import { SERVER_NAMES, ServerManager } from './server-manager';
+import type { SupportedLanguage } from './types';
+import { PROJECT_FILES, SOURCE_EXTENSIONS, SUPPORTED_LANGUAGES } from './utils';
+
+/** What lsp-cli supports for a language, for wrappers discovering its capabilities */
+export interface LanguageInfo {
+    language: SupportedLanguage;
+    /** Extensions of the analyzed source files */
+    extensions: string[];
+    /** Files marking a project of the language */
+    projectFiles: string[];
+    /** Language server backing the analysis */
+    server: string;
+    /** Whether the server is installed; it is installed on first use otherwise */
+    installed: boolean;
+    /** Version of the installed server, if it can be determined */
+    version?: string;
+    /** Directory the server is (or will be) installed in */
+    path: string;
+}
+
+/**
+ * Lists the supported languages with their files and language servers.
+ *
+ * @param serverManager - Manager of the installed servers
+ * @returns One entry per supported language, in the order of `SUPPORTED_LANGUAGES`
+ */
+export async function listLanguages(serverManager: ServerManager = new ServerManager()): Promise<LanguageInfo[]> {
+    return Promise.all(
+        SUPPORTED_LANGUAGES.map(async (language) => {
+            const version = await serverManager.getServerVersion(language);
+            return {
+                language,
+                extensions: SOURCE_EXTENSIONS[language],
+                projectFiles: PROJECT_FILES[language],
+                server: SERVER_NAMES[language],
+                installed: serverManager.isServerInstalled(language),
+                ...(version !== undefined && { version }),
+                path: serverManager.getServerDirectory(language)
+            };
+        })
+    );
+}
+
+/**
+ * Renders the supported languages as an aligned table.
+ *
+ * @param languages - Entries of `listLanguages`
+ * @returns Plain-text table with a header row
+ */
+export function formatLanguages(languages: LanguageInfo[]): string {
+    const rows = [
+        ['LANGUAGE', 'EXTENSIONS', 'PROJECT FILES', 'SERVER', 'STATUS'],
+        ...languages.map((info) => [
+            info.language,
+            info.extensions.join(' '),
+            info.projectFiles.join(' '),
+            info.server,
+            info.installed ? `installed${info.version ? ` (${info.version})` : ''}` : 'not installed'
+        ])
+    ];
+    const widths = rows[0].map((_, column) => Math.max(...rows.map((row) => row[column].length)));
+    return rows
+        .map((row) =>
+            row
+                .map((cell, column) => (column < row.length - 1 ? cell.padEnd(widths[column]) : cell))
+                .join('  ')
+        )
+        .join('\n');
+}
@@ -0,0 +1,210 @@
+/**
+ * Library entry point for embedding lsp-cli in Node.js applications.
+ * Everything exported here is the public API and follows semver; other modules are internal.
+ */
+import { Logger } from './logger';
+import type { SymbolQuery } from './query';
+import { Session } from './session';
+import type { AnalysisOutput, SupportedLanguage, SymbolInfo } from './types';
+
+export { addBodies } from './bodies';
+export { cacheDirectory, clearCache } from './cache';
+export {
+    addEdge,
+    CALL_GRAPH_KINDS,
+    findEnclosingSymbol,
+    findSymbolAt,
+    indexSymbols,
+    type SymbolIndex,
+    symbolNode
+} from './call-graph';
+export { type Chunk, type ChunkOptions, createChunks } from './chunks';
+export { type ChangeReport, compareVersions, type FileVersions, type SignatureChange } from './changes';
+export {
+    type CiCheck,
+    type CiSummary,
+    type CiThresholds,
+    evaluateCi,
+    findNewErrors,
+    findRemovedSymbols,
+    findUndocumented
+} from './ci';
+export { extractLeadingComments } from './comments';
+export { type ConfigTable, type ConfigValue, loadConfig, parseToml, profileNames, userConfigPath } from './config';
+export { addDependencyInfo, formatDependencyReport } from './dependencies';
+export { addDeprecation } from './deprecation';
+export {
+    type DoctorCheck,
+    type DoctorOptions,
+    type DoctorReport,
+    type DoctorStatus,
+    formatDoctorReport,
+    runDoctor
+} from './doctor';
+export { renderDocumentationMarkdown } from './documentation';
+export { decode, detectEncoding, readSourceFile } from './encoding';
+export { type EmbeddedChunk, type EmbeddingOptions, embedChunks, writeEmbeddings } from './embeddings';
+export { ExitCode, ExitError, exitCodeOf } from './exit-codes';
+export { mergeConfigurationSymbols, parseFeatureConfiguration, rustAnalyzerCargoOptions } from './feature-matrix';
+export { addForeignItems } from './ffi';
+export { addGitBlame } from './git-blame';
+export { linkImplementations, parseImplHeader } from './impls';
+export { LanguageClient, type LanguageClientOptions } from './language-client';
+export { formatLanguages, type LanguageInfo, listLanguages } from './languages';
+export { findRustClosures, isLocalItem } from './locals';
+export {
+    type LogFilter,
+    type LogFormat,
+    Logger,
+    type LoggerOptions,
+    type LogLevel,
+    type LogRecord,
+    parseLogFilter
+} from './logger';
+export { attributeMacroSymbols, findMacroInvocations, type MacroInvocation, symbolsFromExpansion } from './macros';
+export { addFunctionModifiers } from './modifiers';
+export { addDisambiguation } from './overloads';
+export { applyPlugins, loadPlugins, type Plugin, type PluginContext } from './plugins';
+export { addPositionOffsets } from './positions';
+export { ProgressDisplay, type ProgressDisplayOptions } from './progress';
+export {
+    createSnapshotPayload,
+    type DeltaSnapshotPayload,
+    type FullSnapshotPayload,
+    getSnapshotSource,
+    type PushOptions,
+    pushSnapshot,
+    type SnapshotPayload,
+    type SnapshotSource,
+    snapshotId
+} from './push';
+export { addQualifiedNames } from './qualified-names';
+export { filterSymbolsByKind, querySymbols, type SymbolQuery } from './query';
+export {
+    emptyFileCounts,
+    type FileCounts,
+    formatRunSummary,
+    type PhaseTiming,
+    type RunSummary,
+    summarizeRun
+} from './run-summary';
+export { ciToSarif, diagnosticsToSarif, type SarifLog } from './sarif';
+export { loadScript, loadScripts } from './scripts';
+export { Session, SessionPool } from './session';
+export { extractSignature, signatureFromHover, typeFromHover } from './signatures';
+export { addSymbolIds } from './symbol-ids';
+export type {
+    AnalysisOutput,
+    AnalysisSource,
+    AttributeInfo,
+    BlameInfo,
+    CallGraphEdge,
+    CallGraphNode,
+    CargoPackageInfo,
+    CargoWorkspaceInfo,
+    CrateInfo,
+    DependencyUsage,
+    DeprecationInfo,
+    DiagnosticInfo,
+    DisambiguationInfo,
+    ExtractionError,
+    FeatureConfiguration,
+    FunctionModifiers,
+    HoverInfo,
+    ImplInfo,
+    MacroOrigin,
+    OrphanedDoc,
+    Position,
+    PositionEncoding,
+    Range,
+    SourceEncoding,
+    SourceLocation,
+    SupportedLanguage,
+    SymbolInfo,
+    TokenCounts,
+    TrailingDocPolicy,
+    Visibility
+} from './types';
+export { addTokenCounts, BpeTokenizer, estimateTokenizer, loadTokenizer, type Tokenizer } from './tokenizer';
+export {
+    formatToolSchemas,
+    generateToolDefinitions,
+    type JsonSchema,
+    type ToolDefinition,
+    type ToolSchemaFormat,
+    type ToolSchemaOptions
+} from './tool-schemas';
+export { addAliasTargets, expandAliases } from './type-aliases';
+export {
+    type AttributeValue,
+    createTracer,
+    OtlpExporter,
+    parseTraceparent,
+    Span,
+    type SpanContext,
+    type SpanData,
+    type SpanExporter,
+    Tracer
+} from './tracing';
+export {
+    type DiscoveredFiles,
+    detectLanguages,
+    discoverFiles,
+    globToRegExp,
+    isSupportedLanguage,
+    SUPPORTED_LANGUAGES
+} from './utils';
+export { addVariantPayloads } from './variants';
+export { addVisibility } from './visibility';
+export { diffSymbols, type SymbolDelta, type SymbolReference, WebhookNotifier, type WebhookPayload } from './webhooks';
+
+export interface ExtractOptions {
+    /** Receives progress and diagnostics; silent by default */
+    logger?: Logger;
+}
+
+/**
+ * Extracts all symbols of a workspace, starting and stopping a language server for this call.
+ * Use a {@link Session} instead to keep the server running across several calls.
+ *
+ * @param directory - Workspace root to analyze
+ * @param language - Language of the workspace
+ * @param options - Optional logger
+ * @returns The same data the CLI writes to its output file
+ */
+export async function extractSymbols(
+    directory: string,
+    language: SupportedLanguage,
+    options: ExtractOptions = {}
+): Promise<AnalysisOutput> {
+    const session = new Session(language, directory, options.logger ?? new Logger({ silent: true }));
+    try {
+        return await session.analyze();
+    } finally {
+        await session.close();
+    }
+}
+
+/**
+ * Extracts the symbols of a workspace and returns those matching a query,
+ * starting and stopping a language server for this call.
+ *
+ * @param directory - Workspace root to analyze
+ * @param language - Language of the workspace
+ * @param query - Criteria that must all match
+ * @param options - Optional logger
+ * @returns Matching symbols in document order, nested symbols included
+ */
+export async function findSymbols(
+    directory: string,
+    language: SupportedLanguage,
+    query: SymbolQuery,
+    options: ExtractOptions = {}
+): Promise<SymbolInfo[]> {
+    const session = new Session(language, directory, options.logger ?? new Logger({ silent: true }));
+    try {
+        return await session.query(query);
+    } finally {
+        await session.close();
+    }
+}
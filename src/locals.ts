@@ -0,0 +1,112 @@
+import type { PositionEncoding, SymbolInfo } from './types';
+
+// Kinds of local items kept with `--include-locals`; variables only when bound to a closure
+const LOCAL_ITEM_KINDS = new Set([
+    'function',
+    'method',
+    'class',
+    'struct',
+    'enum',
+    'interface',
+    'typeParameter',
+    'module'
+]);
+
+// What follows a variable's name when it is bound to a closure: `= |x|`, `= move ||`, `= lambda x:`,
+// `= (a, b) =>`, `= x ->`, `= function`, `= [&](int x)`
+const CLOSURE_BINDING =
+    /^[^=]*=(?![=>])\s*(?:(?:async\s+)?(?:move\s+)?\||lambda\b|(?:async\s+)?(?:\([^)]*\)|[A-Za-z_]\w*)\s*(?:=>|->)|(?:async\s+)?function\b|\[[^\]]*\]\s*[({])/;
+
+/**
+ * Tells function-local items apart from other locals: nested functions, local types and
+ * variables bound to a closure are items, other local variables and constants are not.
+ *
+ * @param kind - Kind name of the local symbol
+ * @param name - Name of the local symbol
+ * @param line - Source line declaring it
+ * @returns Whether the symbol is kept with `--include-locals`
+ */
+export function isLocalItem(kind: string, name: string, line: string): boolean {
+    if (LOCAL_ITEM_KINDS.has(kind)) {
+        return true;
+    }
+    const nameIndex = line.indexOf(name);
+    return nameIndex !== -1 && CLOSURE_BINDING.test(line.slice(nameIndex + name.length));
+}
+
+/**
+ * Finds Rust closures bound by `let` in a function's body, which rust-analyzer doesn't report as
+ * symbols. Bodies of nested functions are skipped; they are searched as symbols of their own.
+ *
+ * @param fn - The enclosing function, with its nested functions as children
+ * @param lines - Source lines of the file
+ * @param encoding - Encoding of the server's `character` values
+ * @returns Variable symbols for the closures, spanning the `let` statement
+ */
+export function findRustClosures(fn: SymbolInfo, lines: string[], encoding: PositionEncoding): SymbolInfo[] {
+    const character = (line: number, column: number) =>
+        encoding === 'utf-8' ? Buffer.byteLength(lines[line].slice(0, column)) : column;
+    const nested = fn.children ?? [];
+    const closures: SymbolInfo[] = [];
+
+    for (let line = fn.range.start.line + 1; line < fn.range.end.line; line++) {
+        if (nested.some((child) => child.range.start.line <= line && line <= child.range.end.line)) {
+            continue;
+        }
+        const match = /^(\s*)let\s+(?:mut\s+)?([A-Za-z_]\w*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:move\s+)?\|/.exec(
+            lines[line]
+        );
+        if (!match) {
+            continue;
+        }
+        const name = match[2];
+        const nameColumn = lines[line].indexOf(name, match[1].length + 'let'.length);
+        const end = statementEnd(lines, line, match[0].length, fn.range.end.line);
+        closures.push({
+            name,
+            kind: 'variable',
+            file: fn.file,
+            range: {
+                start: { line, character: 0 },
+                end: { line: end.line, character: character(end.line, end.column) }
+            },
+            fullRange: {
+                start: { line, character: character(line, match[1].length) },
+                end: { line: end.line, character: character(end.line, end.column) }
+            },
+            selectionRange: {
+                start: { line, character: character(line, nameColumn) },
+                end: { line, character: character(line, nameColumn + name.length) }
+            },
+            preview: lines[line].trim()
+        });
+        line = end.line;
+    }
+    return closures;
+}
+
+/** Position just past the `;` ending a statement, or the end of `lastLine` if none is found */
+function statementEnd(
+    lines: string[],
+    line: number,
+    column: number,
+    lastLine: number
+): { line: number; column: number } {
+    let depth = 0;
+    for (let index = line; index <= lastLine && index < lines.length; index++) {
+        const text = lines[index];
+        for (let col = index === line ? column : 0; col < text.length; col++) {
+            const char = text[col];
+            if (text.startsWith('//', col)) {
+                break;
+            } else if (char === '(' || char === '[' || char === '{') {
+                depth++;
+            } else if (char === ')' || char === ']' || char === '}') {
+                depth--;
+            } else if (char === ';' && depth <= 0) {
+                return { line: index, column: col + 1 };
+            }
+        }
+    }
+    return { line: lastLine, column: lines[lastLine]?.length ?? 0 };
+}
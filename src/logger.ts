@@ -1,82 +1,311 @@
+import { appendFileSync } from 'node:fs';
+import { stripVTControlCharacters } from 'node:util';
 import chalk from 'chalk';
+import { ProgressDisplay } from './progress';
+import { formatRunSummary, type PhaseTiming, type RunSummary } from './run-summary';
+
+/** `pretty` for people, `json` for one JSON object per line that log processors can parse */
+export type LogFormat = 'pretty' | 'json';
+
+export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
+
+// Levels by increasing severity; a threshold shows its level and the ones after it
+const LEVELS: Array<LogLevel | 'off'> = ['trace', 'debug', 'info', 'warn', 'error', 'off'];
+
+/**
+ * Minimum level per target, parsed from `--log-filter`: `default` applies to targets without a
+ * directive of their own.
+ */
+export interface LogFilter {
+    default?: LogLevel | 'off';
+    targets: Record<string, LogLevel | 'off'>;
+}
+
+/** A log line in `--log-format json`; events add their fields next to these */
+export interface LogRecord {
+    time: string;
+    level: LogLevel;
+    /** Part of lsp-cli that logged it, e.g. `transport` */
+    target?: string;
+    /** Name of a structured event, e.g. `file.finished` */
+    event?: string;
+    message?: string;
+    [field: string]: unknown;
+}
 
 export interface LoggerOptions {
-    verbose?: boolean;
+    // Debug messages with true or 1 (-v), also trace messages with 2 (-vv)
+    verbose?: boolean | number;
+    // Only warnings and errors with 1 (-q), only errors with 2 (-qq)
+    quiet?: number;
+    // Levels per target, e.g. `info,transport=trace`; see parseLogFilter()
+    filter?: LogFilter;
+    // Write everything to stderr, keeping stdout free for machine-readable output
+    stderr?: boolean;
+    // Suppress all output, e.g. when embedded as a library
+    silent?: boolean;
+    // Show the live progress line on stderr; off with --no-progress and in JSON format
+    progress?: boolean;
+    // Format of log lines, on the console and in the log file
+    format?: LogFormat;
+    // Also append every log line to this file, without colors
+    file?: string;
 }
 
 export class Logger {
-    private verbose: boolean;
+    private threshold: LogLevel | 'off';
+    private filter: LogFilter;
+    private target?: string;
+    private stderr: boolean;
+    private silent: boolean;
+    private format: LogFormat;
+    private file?: string;
+    // Shared with scoped() loggers, which draw on the same progress line
+    private shared: {
+        display: ProgressDisplay;
+        stage?: { name: string; startedAt: number };
+        // For the end-of-run summary
+        warnings: number;
+        errors: number;
+        phases: PhaseTiming[];
+    };
 
     constructor(options: LoggerOptions = {}) {
-        this.verbose = options.verbose ?? false;
+        const verbosity = options.verbose === true ? 1 : Number(options.verbose ?? 0);
+        this.threshold = LEVELS[Math.min(Math.max(2 - verbosity + (options.quiet ?? 0), 0), 4)];
+        this.filter = options.filter ?? { targets: {} };
+        this.stderr = options.stderr ?? false;
+        this.silent = options.silent ?? false;
+        this.format = options.format ?? 'pretty';
+        this.file = options.file;
+        this.shared = {
+            display: new ProgressDisplay({
+                enabled: !this.silent && (options.progress ?? true) && this.format === 'pretty'
+            }),
+            warnings: 0,
+            errors: 0,
+            phases: []
+        };
+    }
+
+    /**
+     * A logger for one part of lsp-cli, whose level can be set on its own with `--log-filter`.
+     *
+     * @param target - Name of the part, e.g. `transport` or `extractor`
+     * @returns Logger writing to the same outputs, tagged with the target
+     */
+    scoped(target: string): Logger {
+        return Object.assign(Object.create(Logger.prototype), this, { target }) as Logger;
+    }
+
+    /**
+     * @param level - Level of a message
+     * @returns Whether messages of this level are shown for this logger's target
+     */
+    enabled(level: LogLevel): boolean {
+        let threshold = this.filter.default ?? this.threshold;
+        let matched = '';
+        for (const [target, targetLevel] of Object.entries(this.filter.targets)) {
+            const matches = this.target === target || this.target?.startsWith(`${target}.`);
+            if (matches && target.length > matched.length) {
+                threshold = targetLevel;
+                matched = target;
+            }
+        }
+        return LEVELS.indexOf(level) >= LEVELS.indexOf(threshold);
+    }
+
+    private get display(): ProgressDisplay {
+        return this.shared.display;
+    }
+
+    private log(...args: unknown[]): void {
+        if (this.silent) {
+            return;
+        }
+        this.display.suspend(() => {
+            if (this.stderr) {
+                console.error(...args);
+            } else {
+                console.log(...args);
+            }
+        });
+        this.appendToFile(args);
+    }
+
+    /**
+     * Writes a record as a JSON line in JSON format; pretty output is written by the callers.
+     *
+     * @param record - Level, message or event and its fields, without the time
+     * @param stderr - Write to stderr, as errors always are
+     * @returns Whether the record was written, i.e. the format is JSON
+     */
+    private json(record: { level: LogLevel; [field: string]: unknown }, stderr = this.stderr): boolean {
+        if (this.format !== 'json') {
+            return false;
+        }
+        if (this.silent) {
+            return true;
+        }
+        const line = JSON.stringify({
+            time: new Date().toISOString(),
+            ...(this.target !== undefined && { target: this.target }),
+            ...record
+        });
+        (stderr ? process.stderr : process.stdout).write(`${line}\n`);
+        if (this.file) {
+            appendFileSync(this.file, `${line}\n`);
+        }
+        return true;
+    }
+
+    private appendToFile(args: unknown[]): void {
+        if (this.file) {
+            appendFileSync(this.file, `${stripVTControlCharacters(args.map(String).join(' '))}\n`);
+        }
     }
 
     // Success messages
     success(message: string): void {
-        console.log(chalk.green('✓'), message);
+        if (this.enabled('info') && !this.json({ level: 'info', message })) {
+            this.log(chalk.green('✓'), message);
+        }
     }
 
     // Info messages
     info(message: string): void {
-        console.log(chalk.blue('ℹ'), message);
+        if (this.enabled('info') && !this.json({ level: 'info', message })) {
+            this.log(chalk.blue('ℹ'), message);
+        }
     }
 
     // Warning messages
     warn(message: string): void {
-        console.log(chalk.yellow('⚠'), message);
+        this.shared.warnings++;
+        if (this.enabled('warn') && !this.json({ level: 'warn', message })) {
+            this.log(chalk.yellow('⚠'), message);
+        }
     }
 
     // Error messages
     error(message: string, details?: string): void {
-        console.error(chalk.red('✗'), message);
-        if (details) {
-            console.error(chalk.red('  '), details);
+        this.shared.errors++;
+        if (
+            this.silent ||
+            !this.enabled('error') ||
+            this.json({ level: 'error', message, ...(details !== undefined && { details }) }, true)
+        ) {
+            return;
+        }
+        this.display.suspend(() => {
+            console.error(chalk.red('✗'), message);
+            if (details) {
+                console.error(chalk.red('  '), details);
+            }
+        });
+        this.appendToFile(details ? ['✗', message, `\n   ${details}`] : ['✗', message]);
+    }
+
+    /**
+     * Structured event, like a file finished or a request failed. In JSON format every event of an
+     * enabled level is a line with its fields; pretty output shows events only with debug messages,
+     * as errors and warnings are reported by their own messages there.
+     *
+     * @param event - Dotted event name, e.g. `file.finished`
+     * @param fields - Data of the event, e.g. the file and its symbol count
+     * @param level - Level of the event
+     */
+    event(event: string, fields: Record<string, unknown> = {}, level: LogLevel = 'debug'): void {
+        if (!this.enabled(this.format === 'json' ? level : 'debug') || this.json({ level, event, ...fields })) {
+            return;
         }
+        const details = Object.entries(fields)
+            .map(([key, value]) => `${key}=${typeof value === 'string' ? value : JSON.stringify(value)}`)
+            .join(' ');
+        this.log(chalk.gray('[EVENT]'), ...this.prefix(), event, chalk.dim(details));
+    }
+
+    // Stage of a multi-stage run, shown on the progress line until the next stage or stageDone()
+    stage(name: string, total?: number): void {
+        this.shared.stage = { name, startedAt: Date.now() };
+        this.event('stage.started', { stage: name, ...(total !== undefined && { total }) }, 'info');
+        this.display.start(name, total);
     }
 
-    // Progress messages
+    // Progress of the current stage, with counts and ETA
     progress(current: number, total: number, label?: string): void {
-        const percentage = Math.round((current / total) * 100);
-        const filled = Math.round(percentage / 5);
-        const empty = 20 - filled;
-        const bar = chalk.cyan('█').repeat(filled) + chalk.gray('░').repeat(empty);
-        const progressText = label ? `${bar} ${percentage}% - ${label}` : `${bar} ${percentage}% (${current}/${total})`;
+        this.display.update(current, total, label);
+    }
 
-        // Use carriage return to update the same line
-        process.stdout.write(`\r${progressText}`);
+    // What the language server reports it is working on, e.g. indexing; undefined when it is done
+    activity(text: string | undefined, percentage?: number): void {
+        this.display.setActivity(text, percentage);
+    }
 
-        // Add newline when complete
-        if (current === total) {
-            console.log();
+    // Finishes the current stage with its duration
+    stageDone(summary?: string): void {
+        const stage = this.shared.stage;
+        if (stage) {
+            const fields = { stage: stage.name, durationMs: Date.now() - stage.startedAt };
+            this.shared.phases.push({ name: stage.name, durationMs: fields.durationMs });
+            this.event('stage.finished', { ...fields, ...(summary !== undefined && { summary }) }, 'info');
+            this.shared.stage = undefined;
         }
+        this.display.finish(summary);
+    }
+
+    // Ends the current stage as failed
+    stageFailed(reason?: string): void {
+        const stage = this.shared.stage;
+        if (stage) {
+            const fields = { stage: stage.name, durationMs: Date.now() - stage.startedAt };
+            this.shared.phases.push({ name: stage.name, durationMs: fields.durationMs });
+            this.event('stage.failed', { ...fields, ...(reason !== undefined && { reason }) }, 'error');
+            this.shared.stage = undefined;
+        }
+        this.display.fail(reason);
     }
 
     // Step messages (for multi-step processes)
     step(stepNumber: number, totalSteps: number, message: string): void {
+        if (!this.enabled('info') || this.json({ level: 'info', message, step: stepNumber, totalSteps })) {
+            return;
+        }
         const stepText = chalk.dim(`[${stepNumber}/${totalSteps}]`);
-        console.log(chalk.blue('→'), stepText, message);
+        this.log(chalk.blue('→'), stepText, message);
     }
 
-    // Debug messages (only shown in verbose mode)
+    // Debug messages (shown with -v)
     debug(message: string): void {
-        if (this.verbose) {
-            console.log(chalk.gray('[DEBUG]'), message);
+        if (this.enabled('debug') && !this.json({ level: 'debug', message })) {
+            this.log(chalk.gray('[DEBUG]'), ...this.prefix(), message);
+        }
+    }
+
+    // Trace messages, like every LSP request (shown with -vv)
+    trace(message: string): void {
+        if (this.enabled('trace') && !this.json({ level: 'trace', message })) {
+            this.log(chalk.gray('[TRACE]'), ...this.prefix(), message);
         }
     }
 
     // Section headers
     section(title: string): void {
-        console.log();
-        console.log(chalk.bold.underline(title));
-        console.log();
+        if (this.format === 'json' || !this.enabled('info')) {
+            return;
+        }
+        this.log();
+        this.log(chalk.bold.underline(title));
+        this.log();
     }
 
     // File analysis
     file(filename: string, status: 'analyzing' | 'done' | 'error' = 'analyzing'): void {
         const icon = status === 'error' ? chalk.red('✗') : status === 'done' ? chalk.green('✓') : chalk.blue('→');
 
-        if (this.verbose) {
-            console.log(`${icon} ${chalk.dim(filename)}`);
+        // JSON logs get file.started/finished/failed events instead
+        if (this.enabled('debug') && this.format === 'pretty') {
+            this.log(`${icon} ${chalk.dim(filename)}`);
         }
     }
 
@@ -85,16 +314,56 @@ export class Logger {
         title: string,
         items: Array<{ label: string; value: string | number; color?: 'green' | 'yellow' | 'red' | 'blue' }>
     ): void {
-        console.log();
-        console.log(chalk.bold(title));
-        console.log(chalk.gray('─'.repeat(40)));
+        const values = Object.fromEntries(items.map((item) => [item.label, item.value]));
+        if (!this.enabled('info') || this.json({ level: 'info', message: title, ...values })) {
+            return;
+        }
+        this.log();
+        this.log(chalk.bold(title));
+        this.log(chalk.gray('─'.repeat(40)));
 
         items.forEach((item) => {
             const colorFn = item.color ? chalk[item.color] : chalk.white;
-            console.log(`  ${item.label}: ${colorFn(item.value)}`);
+            this.log(`  ${item.label}: ${colorFn(item.value)}`);
         });
 
-        console.log(chalk.gray('─'.repeat(40)));
+        this.log(chalk.gray('─'.repeat(40)));
+    }
+
+    /**
+     * @returns Warnings and errors logged so far, by this logger and its scoped loggers, and the stages run
+     */
+    getRunStats(): { warnings: number; errors: number; phases: PhaseTiming[] } {
+        const { warnings, errors, phases } = this.shared;
+        return { warnings, errors, phases: [...phases] };
+    }
+
+    /**
+     * Prints the end-of-run summary: a table, or for machines one JSON line; JSON logs get it as
+     * a `run.summary` event either way.
+     *
+     * @param summary - Summary of the run
+     * @param format - `text` for the table, `json` for the JSON line
+     */
+    runSummary(summary: RunSummary, format: 'text' | 'json'): void {
+        if (this.silent || this.json({ level: 'info', event: 'run.summary', ...summary })) {
+            return;
+        }
+        if (format === 'json') {
+            // Asked for explicitly, so shown at any verbosity
+            this.log(JSON.stringify(summary));
+            return;
+        }
+        if (!this.enabled('info')) {
+            return;
+        }
+        this.log();
+        this.log(chalk.bold('Summary'));
+        this.log(chalk.gray('─'.repeat(40)));
+        for (const line of formatRunSummary(summary)) {
+            this.log(`  ${line}`);
+        }
+        this.log(chalk.gray('─'.repeat(40)));
     }
 
     // LSP server status
@@ -113,16 +382,60 @@ export class Logger {
             error: `LSP server error for ${language}`
         };
 
-        console.log(`${icons[status]} ${messages[status]}`);
+        const level = status === 'error' ? 'error' : 'info';
+        const record = { level, event: `server.${status}`, language, message: messages[status] } as const;
+        if (!this.enabled(level) || this.json({ ...record, ...(details !== undefined && { details }) })) {
+            return;
+        }
+        this.log(`${icons[status]} ${messages[status]}`);
         if (details) {
-            console.log(`  ${chalk.dim(details)}`);
+            this.log(`  ${chalk.dim(details)}`);
         }
     }
 
-    // Clear current line (useful for progress updates)
+    // Target of scoped loggers in pretty debug and trace lines
+    private prefix(): string[] {
+        return this.target ? [chalk.dim(`${this.target}:`)] : [];
+    }
+
+    // Clear the progress line, ending progress reported without a stage
     clearLine(): void {
-        process.stdout.write(`\r${' '.repeat(80)}\r`);
+        this.display.finish();
+    }
+}
+
+/**
+ * Parses a `--log-filter` in env-filter syntax: comma-separated directives, each a level for all
+ * targets (`debug`), a target to show in full (`transport`), or a target and its level
+ * (`transport=trace`). The most specific directive for a target wins; `a` also covers `a.b`.
+ *
+ * @param filter - Filter text, e.g. `warn,transport=trace,extractor=debug`
+ * @returns The parsed filter
+ * @throws Error for unknown levels
+ */
+export function parseLogFilter(filter: string): LogFilter {
+    const parsed: LogFilter = { targets: {} };
+    const level = (text: string): LogLevel | 'off' => {
+        const lower = text.trim().toLowerCase() as LogLevel | 'off';
+        if (!LEVELS.includes(lower)) {
+            throw new Error(`Unknown log level '${text.trim()}' (expected ${LEVELS.join(', ')})`);
+        }
+        return lower;
+    };
+    for (const directive of filter.split(',').map((part) => part.trim())) {
+        if (directive === '') {
+            continue;
+        }
+        const [target, targetLevel] = directive.split('=');
+        if (targetLevel !== undefined) {
+            parsed.targets[target.trim()] = level(targetLevel);
+        } else if (LEVELS.includes(target.toLowerCase() as LogLevel | 'off')) {
+            parsed.default = level(target);
+        } else {
+            parsed.targets[target] = 'trace';
+        }
     }
+    return parsed;
 }
 
 // Default logger instance
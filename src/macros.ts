@@ -0,0 +1,252 @@
+import { extractSignature } from './signatures';
+import type { Position, PositionEncoding, Range, SymbolInfo, Visibility } from './types';
+
+// Kinds whose bodies hold statements rather than items
+const CALLABLE_KINDS = new Set(['function', 'method', 'constructor']);
+
+// Item declarations at the top level of an expansion, by rust-analyzer's kind mapping
+const ITEM_KINDS: Record<string, string> = {
+    fn: 'function',
+    struct: 'struct',
+    union: 'struct',
+    enum: 'enum',
+    trait: 'interface',
+    type: 'typeParameter',
+    const: 'constant',
+    static: 'constant',
+    mod: 'module'
+};
+
+/** A macro invocation in item position, like `create_test_data!(TEST_VALUE, i32, 42);` */
+export interface MacroInvocation {
+    /** Macro name as written, including any path, e.g. `create_test_data` or `serde::forward` */
+    name: string;
+    /** 0-based line and UTF-16 column of the macro name's last segment, where `expandMacro` is asked */
+    line: number;
+    column: number;
+    /** From the macro name to the closing delimiter, columns in UTF-16 code units */
+    start: { line: number; column: number };
+    end: { line: number; column: number };
+}
+
+/**
+ * Finds the Rust macro invocations in item position — at the top level, in modules and in impl
+ * blocks, but not in function bodies — which are the ones that can generate symbols.
+ * `macro_rules!` definitions are not invocations.
+ *
+ * @param lines - Source lines of the file
+ * @param symbols - Symbols of the file, to tell function bodies apart
+ * @returns Invocations in document order
+ */
+export function findMacroInvocations(lines: string[], symbols: SymbolInfo[]): MacroInvocation[] {
+    const bodies: Range[] = [];
+    const collect = (symbol: SymbolInfo) => {
+        if (CALLABLE_KINDS.has(symbol.kind)) {
+            bodies.push(symbol.range);
+        } else {
+            symbol.children?.forEach(collect);
+        }
+    };
+    symbols.forEach(collect);
+
+    const invocations: MacroInvocation[] = [];
+    for (let line = 0; line < lines.length; line++) {
+        const match = /^(\s*)((?:[A-Za-z_]\w*::)*([A-Za-z_]\w*))!\s*[([{]/.exec(lines[line]);
+        if (!match || match[3] === 'macro_rules' || bodies.some((body) => inside(body, line))) {
+            continue;
+        }
+        const open = match[0].length - 1;
+        const end = closingDelimiter(lines, line, open);
+        if (!end) {
+            continue;
+        }
+        invocations.push({
+            name: match[2],
+            line,
+            column: match[1].length + match[2].length - match[3].length,
+            start: { line, column: match[1].length },
+            end
+        });
+        line = end.line;
+    }
+    return invocations;
+}
+
+/**
+ * Records the generating macro on symbols declared inside macro invocations.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param invocations - Macro invocations of the file
+ * @param lines - Source lines of the file
+ * @param encoding - Encoding of the server's `character` values
+ * @returns For each invocation, the symbols attributed to it
+ */
+export function attributeMacroSymbols(
+    symbols: SymbolInfo[],
+    invocations: MacroInvocation[],
+    lines: string[],
+    encoding: PositionEncoding
+): Map<MacroInvocation, SymbolInfo[]> {
+    const attributed = new Map<MacroInvocation, SymbolInfo[]>(invocations.map((invocation) => [invocation, []]));
+    const visit = (symbol: SymbolInfo) => {
+        const start = (symbol.selectionRange ?? symbol.range).start;
+        const invocation = invocations.find((candidate) => {
+            const range = invocationRange(candidate, lines, encoding);
+            return before(range.start, start) && !before(range.end, start);
+        });
+        if (invocation) {
+            symbol.macro = { name: invocation.name, invocation: invocationRange(invocation, lines, encoding) };
+            attributed.get(invocation)?.push(symbol);
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+    return attributed;
+}
+
+/**
+ * Creates symbols for the items at the top level of a macro expansion, located at the invocation.
+ * The selection range is the first occurrence of the item's name in the invocation's arguments, or
+ * the macro name for names the macro makes up itself.
+ *
+ * @param expansion - Expanded source as returned by rust-analyzer's `expandMacro`
+ * @param invocation - The expanded invocation
+ * @param filePath - Absolute path of the file
+ * @param lines - Source lines of the file
+ * @param encoding - Encoding of the server's `character` values
+ * @param includeExpansion - Whether to record the expansion text on the symbols
+ * @returns Symbols of the generated items, with `macro` set
+ */
+export function symbolsFromExpansion(
+    expansion: string,
+    invocation: MacroInvocation,
+    filePath: string,
+    lines: string[],
+    encoding: PositionEncoding,
+    includeExpansion: boolean
+): SymbolInfo[] {
+    const character = (line: number, column: number) => toCharacter(lines, encoding, line, column);
+
+    const expansionLines = expansion.split('\n');
+    const symbols: SymbolInfo[] = [];
+    let depth = 0;
+    for (let index = 0; index < expansionLines.length; index++) {
+        const text = expansionLines[index];
+        const item =
+            depth === 0 &&
+            /^\s*(?:#\[.*\]\s*)*(pub(?:\s*\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern\s*"[^"]*")\s+)*(fn|struct|union|enum|trait|type|const|static|mod)\s+(?:mut\s+)?([A-Za-z_]\w*)/.exec(
+                text
+            );
+        if (item) {
+            const name = item[3];
+            const fullRange = invocationRange(invocation, lines, encoding);
+            const selection = findName(lines, invocation, name);
+            symbols.push({
+                name,
+                kind: ITEM_KINDS[item[2]],
+                file: filePath,
+                range: { start: { line: invocation.start.line, character: 0 }, end: { ...fullRange.end } },
+                fullRange,
+                selectionRange: {
+                    start: { line: selection.line, character: character(selection.line, selection.column) },
+                    end: {
+                        line: selection.line,
+                        character: character(selection.line, selection.column + selection.length)
+                    }
+                },
+                preview: lines[invocation.start.line].trim(),
+                signature: ['fn', 'struct', 'union', 'enum', 'trait'].includes(item[2])
+                    ? extractSignature(expansionLines, index, 'rust')
+                    : undefined,
+                visibility: rustVisibility(item[1]),
+                macro: {
+                    name: invocation.name,
+                    invocation: invocationRange(invocation, lines, encoding),
+                    ...(includeExpansion && { expansion })
+                }
+            });
+        }
+        for (const char of text) {
+            if (char === '{') {
+                depth++;
+            } else if (char === '}') {
+                depth = Math.max(depth - 1, 0);
+            }
+        }
+    }
+    return symbols;
+}
+
+/** The invocation's range in the server's encoding */
+function invocationRange(invocation: MacroInvocation, lines: string[], encoding: PositionEncoding): Range {
+    const { start, end } = invocation;
+    return {
+        start: { line: start.line, character: toCharacter(lines, encoding, start.line, start.column) },
+        end: { line: end.line, character: toCharacter(lines, encoding, end.line, end.column) }
+    };
+}
+
+function toCharacter(lines: string[], encoding: PositionEncoding, line: number, column: number): number {
+    return encoding === 'utf-8' ? Buffer.byteLength(lines[line].slice(0, column)) : column;
+}
+
+function inside(range: Range, line: number): boolean {
+    return range.start.line <= line && line <= range.end.line;
+}
+
+/** Whether `a` is at or before `b` */
+function before(a: Position, b: Position): boolean {
+    return a.line < b.line || (a.line === b.line && a.character <= b.character);
+}
+
+/** Position just past the delimiter closing the one at `open`, or undefined if it isn't closed nearby */
+function closingDelimiter(lines: string[], line: number, open: number): { line: number; column: number } | undefined {
+    let depth = 0;
+    for (let index = line; index < lines.length && index - line < 200; index++) {
+        const text = lines[index];
+        for (let column = index === line ? open : 0; column < text.length; column++) {
+            const char = text[column];
+            if (char === '"') {
+                // Skip string literals, delimiters inside them don't count
+                column++;
+                while (column < text.length && text[column] !== '"') {
+                    column += text[column] === '\\' ? 2 : 1;
+                }
+            } else if (text.startsWith('//', column)) {
+                break;
+            } else if (char === '(' || char === '[' || char === '{') {
+                depth++;
+            } else if ((char === ')' || char === ']' || char === '}') && --depth === 0) {
+                return { line: index, column: column + 1 };
+            }
+        }
+    }
+    return undefined;
+}
+
+function findName(
+    lines: string[],
+    invocation: MacroInvocation,
+    name: string
+): { line: number; column: number; length: number } {
+    const pattern = new RegExp(`\\b${name}\\b`);
+    for (let line = invocation.start.line; line <= invocation.end.line; line++) {
+        const from = line === invocation.start.line ? invocation.start.column + invocation.name.length : 0;
+        const match = pattern.exec(lines[line].slice(from));
+        if (match) {
+            return { line, column: from + match.index, length: name.length };
+        }
+    }
+    return { line: invocation.line, column: invocation.column, length: invocation.name.length };
+}
+
+function rustVisibility(modifier: string | undefined): Visibility {
+    const restriction = /\(\s*(crate|super|self|in)\b/.exec(modifier ?? '')?.[1];
+    if (!modifier) {
+        return 'private';
+    }
+    if (!restriction) {
+        return 'public';
+    }
+    return restriction === 'in' ? 'restricted' : restriction === 'self' ? 'private' : (restriction as Visibility);
+}
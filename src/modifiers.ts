@@ -0,0 +1,111 @@
+import type { FunctionModifiers, SupportedLanguage, SymbolInfo } from './types';
+
+// Kinds that get `modifiers`, and whose nested functions are skipped when scanning a body
+const CALLABLE_KINDS = new Set(['function', 'method', 'constructor']);
+
+/**
+ * Sets `modifiers` on functions, methods and constructors from their declaration header and, for
+ * generators and coroutines that aren't marked in the header, their body: `yield` in Python,
+ * `yield return` in C#, `co_yield`/`co_await` in C++.
+ *
+ * @param symbols - Symbols of one file, with `signature` already set; updated in place
+ * @param lines - Source lines of the file
+ * @param language - Language of the file
+ */
+export function addFunctionModifiers(symbols: SymbolInfo[], lines: string[], language: SupportedLanguage): void {
+    const visit = (symbol: SymbolInfo) => {
+        if (CALLABLE_KINDS.has(symbol.kind)) {
+            const modifiers = modifiersOf(symbol, lines, language);
+            if (Object.keys(modifiers).length > 0) {
+                symbol.modifiers = modifiers;
+            }
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
+
+function modifiersOf(symbol: SymbolInfo, lines: string[], language: SupportedLanguage): FunctionModifiers {
+    const header = symbol.signature ?? symbol.preview;
+    // Modifiers come before the name, except Dart's `async`, `async*` and `sync*` after the parameters
+    const nameIndex = header.indexOf(symbol.name);
+    const prefix = nameIndex > 0 ? header.slice(0, nameIndex) : header;
+    const suffix = nameIndex >= 0 ? header.slice(nameIndex + symbol.name.length) : header;
+    const has = (word: string) => new RegExp(`\\b${word}\\b`).test(prefix);
+
+    const flags: FunctionModifiers = {};
+    switch (language) {
+        case 'rust':
+            flags.async = has('async');
+            flags.unsafe = has('unsafe');
+            flags.const = has('const');
+            setExtern(flags, prefix, 'C');
+            break;
+        case 'typescript':
+            flags.async = has('async');
+            flags.generator = /\bfunction\s*\*|\*\s*$/.test(prefix);
+            flags.extern = has('declare');
+            break;
+        case 'python':
+            flags.async = has('async');
+            flags.generator = bodyMatches(symbol, lines, language, /\byield\b/);
+            break;
+        case 'csharp':
+            flags.async = has('async');
+            flags.unsafe = has('unsafe');
+            flags.extern = has('extern');
+            flags.generator = bodyMatches(symbol, lines, language, /\byield\s+(return|break)\b/);
+            break;
+        case 'java':
+            flags.extern = has('native');
+            break;
+        case 'cpp':
+        case 'c':
+            flags.const = has('constexpr') || has('consteval');
+            setExtern(flags, prefix);
+            flags.generator = bodyMatches(symbol, lines, language, /\bco_yield\b/);
+            flags.async = bodyMatches(symbol, lines, language, /\bco_(await|return)\b/);
+            break;
+        case 'dart':
+            flags.async = /\basync\b/.test(suffix);
+            flags.generator = /\b(async|sync)\s*\*/.test(suffix);
+            flags.const = has('const');
+            flags.extern = has('external');
+            break;
+        case 'haxe':
+            flags.extern = has('extern');
+            break;
+    }
+
+    // Only the flags that apply are kept
+    for (const key of Object.keys(flags) as Array<keyof FunctionModifiers>) {
+        if (!flags[key]) {
+            delete flags[key];
+        }
+    }
+    return flags;
+}
+
+/** `extern` with its ABI string, e.g. `extern "C"`; `defaultAbi` is used when none is written */
+function setExtern(flags: FunctionModifiers, prefix: string, defaultAbi?: string): void {
+    const match = /\bextern\b(?:\s*"([^"]*)")?/.exec(prefix);
+    if (match) {
+        flags.extern = true;
+        flags.abi = match[1] ?? defaultAbi;
+    }
+}
+
+/** Whether a line of the symbol's body, outside nested functions and comments, matches a pattern */
+function bodyMatches(symbol: SymbolInfo, lines: string[], language: SupportedLanguage, pattern: RegExp): boolean {
+    const nested = (symbol.children ?? []).filter((child) => CALLABLE_KINDS.has(child.kind));
+    const comment = language === 'python' ? /#.*$/ : /\/\/.*$/;
+    for (let line = symbol.range.start.line; line <= symbol.range.end.line && line < lines.length; line++) {
+        if (nested.some((child) => child.range.start.line <= line && line <= child.range.end.line)) {
+            continue;
+        }
+        if (pattern.test(lines[line].replace(comment, ''))) {
+            return true;
+        }
+    }
+    return false;
+}
@@ -0,0 +1,117 @@
+import { readFileSync } from 'node:fs';
+import type { Range, SupportedLanguage, SymbolInfo } from './types';
+
+/** Code cells of a notebook concatenated into one document a language server can analyze */
+export interface NotebookDocument {
+    language: SupportedLanguage;
+    content: string;
+    /** Position of each code cell in `content`, in document order */
+    cells: Array<{ index: number; startLine: number; lineCount: number }>;
+}
+
+// Kernel language names as found in notebook metadata
+const NOTEBOOK_LANGUAGES: Record<string, SupportedLanguage> = {
+    python: 'python',
+    python3: 'python',
+    typescript: 'typescript',
+    javascript: 'typescript',
+    java: 'java',
+    'c++': 'cpp',
+    cpp: 'cpp',
+    c: 'c',
+    'c#': 'csharp',
+    csharp: 'csharp',
+    rust: 'rust',
+    dart: 'dart',
+    haxe: 'haxe'
+};
+
+/**
+ * @param path - Path of an `.ipynb` file
+ * @returns The supported language of the notebook's kernel, if any
+ */
+export function getNotebookLanguage(path: string): SupportedLanguage | undefined {
+    try {
+        return notebookLanguage(JSON.parse(readFileSync(path, 'utf-8')));
+    } catch {
+        return undefined;
+    }
+}
+
+function notebookLanguage(notebook: any): SupportedLanguage | undefined {
+    const name = notebook.metadata?.kernelspec?.language ?? notebook.metadata?.language_info?.name;
+    return typeof name === 'string' ? NOTEBOOK_LANGUAGES[name.toLowerCase()] : undefined;
+}
+
+/**
+ * Concatenates the code cells of a Jupyter notebook, separated by a blank line. In Python
+ * notebooks, IPython magics and shell escapes are commented out so they don't produce
+ * syntax errors; line numbers are preserved.
+ *
+ * @param path - Path of an `.ipynb` file
+ * @returns The virtual document, or undefined if the notebook's language is not supported
+ */
+export function readNotebook(path: string): NotebookDocument | undefined {
+    const notebook = JSON.parse(readFileSync(path, 'utf-8'));
+    const language = notebookLanguage(notebook);
+    if (!language) {
+        return undefined;
+    }
+
+    const lines: string[] = [];
+    const cells: NotebookDocument['cells'] = [];
+    (notebook.cells ?? []).forEach((cell: any, index: number) => {
+        if (cell.cell_type !== 'code') {
+            return;
+        }
+        const source: string = Array.isArray(cell.source) ? cell.source.join('') : (cell.source ?? '');
+        let cellLines = source.split('\n');
+        if (language === 'python') {
+            // A cell magic like %%bash makes the whole cell foreign code
+            const cellMagic = cellLines[0]?.startsWith('%%');
+            cellLines = cellLines.map((line) => (cellMagic || /^\s*[%!]/.test(line) ? `# ${line}` : line));
+        }
+        cells.push({ index, startLine: lines.length, lineCount: cellLines.length });
+        lines.push(...cellLines, '');
+    });
+
+    return { language, content: lines.join('\n'), cells };
+}
+
+/**
+ * Maps symbols from a notebook's virtual document back to the notebook: `file` becomes the
+ * notebook path, `cell` the index of the containing cell (counting all cells, as in the notebook
+ * JSON) and ranges are made relative to that cell's source.
+ *
+ * @param symbols - Symbols extracted from the virtual document; updated in place, including children
+ * @param document - The virtual document
+ * @param notebookPath - Path of the `.ipynb` file
+ */
+export function mapNotebookSymbols(symbols: SymbolInfo[], document: NotebookDocument, notebookPath: string): void {
+    const cellAt = (line: number) =>
+        [...document.cells].reverse().find((cell) => cell.startLine <= line) ?? document.cells[0];
+
+    const map = (symbol: SymbolInfo) => {
+        const cell = cellAt(symbol.range.start.line);
+        const lastLine = cell.lineCount - 1;
+        const shift = (range: Range): Range => ({
+            start: { line: range.start.line - cell.startLine, character: range.start.character },
+            // Symbols can't span cells, but clamp in case a server reports a range into the separator
+            end:
+                range.end.line - cell.startLine > lastLine
+                    ? { line: lastLine, character: 0 }
+                    : { line: range.end.line - cell.startLine, character: range.end.character }
+        });
+        symbol.file = notebookPath;
+        symbol.cell = cell.index;
+        symbol.range = shift(symbol.range);
+        if (symbol.fullRange) {
+            symbol.fullRange = shift(symbol.fullRange);
+        }
+        if (symbol.selectionRange) {
+            symbol.selectionRange = shift(symbol.selectionRange);
+        }
+        symbol.children?.forEach(map);
+    };
+    symbols.forEach(map);
+}
@@ -0,0 +1,107 @@
+import { createHash } from 'node:crypto';
+import type { DisambiguationInfo, SupportedLanguage, SymbolInfo } from './types';
+
+// Kinds whose parameters are counted for `arity`
+const CALLABLE_KINDS = new Set(['function', 'method', 'constructor']);
+
+/**
+ * Sets `disambiguation` on symbols sharing their name with another symbol of the same file, such
+ * as overloads, or a trait method and an inherent method of the same name: the `arity` of callables,
+ * a `signatureHash` of their declaration header, and the `owner` they are declared in — the
+ * implemented trait and type for Rust impl blocks (`Drawable for Rectangle`), else the containing
+ * symbol's name.
+ *
+ * @param symbols - Symbols of one file, with `signature` and `impl` already set; updated in place
+ * @param language - Language of the file
+ */
+export function addDisambiguation(symbols: SymbolInfo[], language: SupportedLanguage): void {
+    const byName = new Map<string, Array<{ symbol: SymbolInfo; parent?: SymbolInfo }>>();
+    const collect = (symbol: SymbolInfo, parent?: SymbolInfo) => {
+        const named = byName.get(symbol.name) ?? [];
+        named.push({ symbol, parent });
+        byName.set(symbol.name, named);
+        symbol.children?.forEach((child) => collect(child, symbol));
+    };
+    symbols.forEach((symbol) => collect(symbol));
+
+    for (const named of byName.values()) {
+        if (named.length < 2) {
+            continue;
+        }
+        for (const { symbol, parent } of named) {
+            const header = (symbol.signature ?? symbol.preview).replace(/\s+/g, ' ').trim();
+            const arity = CALLABLE_KINDS.has(symbol.kind) ? countParameters(header, symbol.name, language) : undefined;
+            const info: DisambiguationInfo = {
+                ...(arity !== undefined && { arity }),
+                signatureHash: createHash('sha256').update(header).digest('hex').slice(0, 8)
+            };
+            const owner = parent && ownerName(parent);
+            symbol.disambiguation = owner ? { ...info, owner } : info;
+        }
+    }
+}
+
+/** `Trait for Type` or `Type` for Rust impl blocks, else the container's name */
+function ownerName(parent: SymbolInfo): string {
+    if (!parent.impl) {
+        return parent.name;
+    }
+    return parent.impl.trait ? `${parent.impl.trait} for ${parent.impl.type}` : parent.impl.type;
+}
+
+/**
+ * Counts the parameters in the first parameter list after the name, skipping generic parameters.
+ * Receivers (`self`, `&mut self`, Python's `self` and `cls`) and C's `(void)` don't count.
+ */
+function countParameters(header: string, name: string, language: SupportedLanguage): number | undefined {
+    const nameIndex = header.indexOf(name);
+    let index = nameIndex >= 0 ? nameIndex + name.length : 0;
+    let angles = 0;
+    for (; index < header.length; index++) {
+        const char = header[index];
+        if (char === '<') {
+            angles++;
+        } else if (char === '>' && header[index - 1] !== '-' && angles > 0) {
+            angles--;
+        } else if (char === '(' && angles === 0) {
+            break;
+        }
+    }
+    if (index >= header.length) {
+        return undefined;
+    }
+
+    const parameters: string[] = [];
+    let depth = 0;
+    let current = '';
+    for (index++; index < header.length; index++) {
+        const char = header[index];
+        if ('([{<'.includes(char)) {
+            depth++;
+        } else if (')]}'.includes(char) || (char === '>' && header[index - 1] !== '-')) {
+            if (depth === 0 && char === ')') {
+                break;
+            }
+            depth--;
+        } else if (char === ',' && depth === 0) {
+            parameters.push(current.trim());
+            current = '';
+            continue;
+        }
+        current += char;
+    }
+    parameters.push(current.trim());
+
+    return parameters.filter((parameter, position) => {
+        if (parameter === '' || ((language === 'c' || language === 'cpp') && parameter === 'void')) {
+            return false;
+        }
+        if (position > 0) {
+            return true;
+        }
+        if (language === 'rust') {
+            return !/^(&\s*('\w+\s+)?)?(mut\s+)?self\b/.test(parameter);
+        }
+        return !(language === 'python' && /^(self|cls)\b/.test(parameter));
+    }).length;
+}
@@ -0,0 +1,155 @@
+import { readFile } from 'node:fs/promises';
+import { basename, extname, resolve } from 'node:path';
+import { pathToFileURL } from 'node:url';
+import type { Logger } from './logger';
+import type { AnalysisOutput, SupportedLanguage, SymbolInfo } from './types';
+
+export interface PluginContext {
+    language: SupportedLanguage;
+    directory: string;
+    logger: Logger;
+}
+
+/**
+ * A processor that hooks into the extraction pipeline. All hooks are optional and run
+ * in the order plugins were given, each seeing the result of the previous one.
+ */
+export interface Plugin {
+    name: string;
+    /** Filters or rewrites the symbol tree, e.g. to drop symbols or add computed fields */
+    transformSymbols?(symbols: SymbolInfo[], context: PluginContext): SymbolInfo[] | Promise<SymbolInfo[]>;
+    /** Returns a custom section stored under `sections[plugin.name]` in the output */
+    outputSection?(output: AnalysisOutput, context: PluginContext): unknown | Promise<unknown>;
+}
+
+/**
+ * Loads plugins from files. `.wasm` files are WebAssembly modules using the JSON ABI
+ * described in the README; everything else is imported as a JavaScript module whose
+ * default export (or module namespace) implements {@link Plugin}.
+ *
+ * @param paths - Plugin files, relative to the current directory or absolute
+ * @param logger - Logger handed to WebAssembly plugins for their log output
+ * @returns The loaded plugins in the given order
+ */
+export async function loadPlugins(paths: string[], logger: Logger): Promise<Plugin[]> {
+    const plugins: Plugin[] = [];
+    for (const path of paths) {
+        const file = resolve(path);
+        try {
+            plugins.push(extname(file) === '.wasm' ? await loadWasmPlugin(file, logger) : await loadJsPlugin(file));
+        } catch (error) {
+            throw new Error(`Failed to load plugin ${path}: ${error instanceof Error ? error.message : String(error)}`);
+        }
+        logger.debug(`Loaded plugin ${plugins[plugins.length - 1].name} from ${file}`);
+    }
+    return plugins;
+}
+
+/**
+ * Runs all plugin hooks on an analysis result.
+ *
+ * @param output - Result of the extraction
+ * @param plugins - Plugins to apply, in order
+ * @param logger - Passed to plugins through their context
+ * @returns The transformed output; plugins may have modified the input symbols in place
+ */
+export async function applyPlugins(output: AnalysisOutput, plugins: Plugin[], logger: Logger): Promise<AnalysisOutput> {
+    const context: PluginContext = { language: output.language, directory: output.directory, logger };
+    let result = output;
+
+    for (const plugin of plugins) {
+        if (plugin.transformSymbols) {
+            result = { ...result, symbols: await plugin.transformSymbols(result.symbols, context) };
+        }
+    }
+
+    for (const plugin of plugins) {
+        if (plugin.outputSection) {
+            const section = await plugin.outputSection(result, context);
+            if (section !== undefined) {
+                result = { ...result, sections: { ...result.sections, [plugin.name]: section } };
+            }
+        }
+    }
+
+    return result;
+}
+
+async function loadJsPlugin(file: string): Promise<Plugin> {
+    const module = await import(pathToFileURL(file).href);
+    const exported = module.default ?? module;
+    const plugin: Plugin = { name: basename(file, extname(file)), ...exported };
+
+    if (!plugin.transformSymbols && !plugin.outputSection) {
+        throw new Error('Plugin exports neither transformSymbols nor outputSection');
+    }
+    return plugin;
+}
+
+// Minimal typing of the WebAssembly global, which isn't part of the es2022 lib
+interface WasmMemory {
+    buffer: ArrayBuffer;
+}
+
+declare const WebAssembly: {
+    Memory: new (descriptor: { initial: number }) => WasmMemory;
+    instantiate(bytes: Uint8Array, imports: object): Promise<{ instance: { exports: Record<string, unknown> } }>;
+};
+
+interface WasmPluginExports {
+    memory: WasmMemory;
+    alloc(length: number): number;
+    transform_symbols?(pointer: number, length: number): bigint;
+    output_section?(pointer: number, length: number): bigint;
+}
+
+/*
+ * WebAssembly plugins exchange UTF-8 JSON through linear memory: the host calls alloc(len) and
+ * writes the input there, then calls the hook, which returns (pointer << 32) | length of its
+ * JSON result. An optional `env.log(pointer, length)` import writes debug messages.
+ */
+async function loadWasmPlugin(file: string, logger: Logger): Promise<Plugin> {
+    let memory: WasmMemory | undefined;
+    const decoder = new TextDecoder();
+    const imports = {
+        env: {
+            log: (pointer: number, length: number) => {
+                if (memory) {
+                    const message = decoder.decode(new Uint8Array(memory.buffer, pointer, length));
+                    logger.debug(`[${basename(file)}] ${message}`);
+                }
+            }
+        }
+    };
+
+    const { instance } = await WebAssembly.instantiate(await readFile(file), imports);
+    const exports = instance.exports as unknown as WasmPluginExports;
+    if (!(exports.memory instanceof WebAssembly.Memory) || typeof exports.alloc !== 'function') {
+        throw new Error('WebAssembly plugin must export memory and alloc');
+    }
+    if (!exports.transform_symbols && !exports.output_section) {
+        throw new Error('WebAssembly plugin exports neither transform_symbols nor output_section');
+    }
+    memory = exports.memory;
+
+    const call = (hook: (pointer: number, length: number) => bigint, input: unknown): unknown => {
+        const bytes = new TextEncoder().encode(JSON.stringify(input));
+        const pointer = exports.alloc(bytes.length);
+        new Uint8Array(exports.memory.buffer, pointer, bytes.length).set(bytes);
+
+        const packed = BigInt.asUintN(64, hook(pointer, bytes.length));
+        const resultPointer = Number(packed >> 32n);
+        const resultLength = Number(packed & 0xffffffffn);
+        return JSON.parse(decoder.decode(new Uint8Array(exports.memory.buffer, resultPointer, resultLength)));
+    };
+
+    const plugin: Plugin = { name: basename(file, '.wasm') };
+    const { transform_symbols, output_section } = exports;
+    if (transform_symbols) {
+        plugin.transformSymbols = (symbols) => call(transform_symbols, symbols) as SymbolInfo[];
+    }
+    if (output_section) {
+        plugin.outputSection = (output) => call(output_section, output);
+    }
+    return plugin;
+}
@@ -0,0 +1,62 @@
+import { byteOrderMarkLength, encodedLength } from './encoding';
+import type { Position, PositionEncoding, SourceEncoding, SymbolInfo } from './types';
+
+/**
+ * Adds both column encodings and the byte offset to the positions of all ranges of symbols and their
+ * children: `utf16Character` (JavaScript string index), `utf8Character` (bytes into the line) and
+ * `byteOffset` (bytes into the file as stored on disk, so in the file's own encoding and counting any
+ * byte order mark). `character` stays in the encoding the server negotiated.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param lines - Decoded lines of the file, split on `\n`
+ * @param encoding - Encoding of the server's `character` values
+ * @param fileEncoding - Encoding of the file on disk
+ */
+export function addPositionOffsets(
+    symbols: SymbolInfo[],
+    lines: string[],
+    encoding: PositionEncoding,
+    fileEncoding: SourceEncoding = 'utf-8'
+): void {
+    const lineStarts: number[] = [];
+    let fileLength = byteOrderMarkLength(fileEncoding);
+    const newlineLength = encodedLength('\n', fileEncoding);
+    for (const line of lines) {
+        lineStarts.push(fileLength);
+        fileLength += encodedLength(line, fileEncoding) + newlineLength;
+    }
+
+    const convert = (position: Position) => {
+        const text = lines[position.line] ?? '';
+        const utf16 = encoding === 'utf-8' ? utf8ToUtf16(text, position.character) : position.character;
+        const utf8 = Buffer.byteLength(text.slice(0, utf16));
+        position.utf16Character = utf16;
+        position.utf8Character = utf8;
+        position.byteOffset =
+            (lineStarts[position.line] ?? fileLength) + encodedLength(text.slice(0, utf16), fileEncoding);
+    };
+    const visit = (symbol: SymbolInfo) => {
+        for (const range of [symbol.range, symbol.fullRange, symbol.selectionRange]) {
+            if (range) {
+                convert(range.start);
+                convert(range.end);
+            }
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
+
+/** String index of the character starting at a byte column */
+function utf8ToUtf16(text: string, column: number): number {
+    let bytes = 0;
+    let index = 0;
+    for (const char of text) {
+        if (bytes >= column) {
+            break;
+        }
+        bytes += Buffer.byteLength(char);
+        index += char.length;
+    }
+    return index;
+}
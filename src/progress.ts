@@ -0,0 +1,233 @@
+import chalk from 'chalk';
+
+// Minimum time between two redraws of the live line
+const RENDER_INTERVAL_MS = 100;
+// Without a terminal, counted stages are logged at these steps of their total
+const PLAIN_REPORT_PERCENT = 10;
+
+const SPINNER = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+export interface ProgressDisplayOptions {
+    /** Show progress at all; `--no-progress` turns it off */
+    enabled?: boolean;
+    /** Where progress goes; stderr by default, so stdout stays free for output */
+    stream?: NodeJS.WriteStream;
+}
+
+interface Stage {
+    name: string;
+    total?: number;
+    current: number;
+    detail?: string;
+    startedAt: number;
+    lastPlainPercent: number;
+}
+
+/**
+ * A live progress line for multi-stage runs: the current stage with a bar, counts and ETA when its
+ * total is known, or a spinner and the elapsed time when it isn't, followed by what the language
+ * server reports it is busy with. On terminals the line is redrawn in place; otherwise stage starts,
+ * every tenth of a counted stage and stage ends are written as plain lines.
+ */
+export class ProgressDisplay {
+    private stream: NodeJS.WriteStream;
+    private enabled: boolean;
+    private live: boolean;
+    private stage?: Stage;
+    private activity?: string;
+    private shown = false;
+    private lastRenderAt = 0;
+    private frame = 0;
+    private timer?: NodeJS.Timeout;
+
+    constructor(options: ProgressDisplayOptions = {}) {
+        this.stream = options.stream ?? process.stderr;
+        this.enabled = options.enabled ?? true;
+        this.live = this.enabled && Boolean(this.stream.isTTY);
+    }
+
+    /**
+     * Starts a stage, finishing the previous one silently.
+     *
+     * @param name - What is being done, e.g. "Extracting symbols"
+     * @param total - Number of steps, e.g. files or bytes; unknown for open-ended stages
+     */
+    start(name: string, total?: number): void {
+        this.stage = { name, total, current: 0, startedAt: Date.now(), lastPlainPercent: 0 };
+        if (!this.enabled) {
+            return;
+        }
+        if (this.live) {
+            // Keep the spinner and elapsed time moving while nothing is reported
+            this.timer ??= setInterval(() => this.render(true), RENDER_INTERVAL_MS * 2);
+            this.timer.unref();
+            this.render(true);
+        } else {
+            this.stream.write(`${chalk.blue('→')} ${name}${total !== undefined ? ` (${total})` : ''}\n`);
+        }
+    }
+
+    /**
+     * Reports progress of the current stage.
+     *
+     * @param current - Steps done
+     * @param total - Number of steps, if it changed or wasn't known at the start
+     * @param detail - What is being worked on, e.g. the current file
+     */
+    update(current: number, total?: number, detail?: string): void {
+        if (!this.stage) {
+            this.start('', total);
+        }
+        const stage = this.stage!;
+        stage.current = current;
+        stage.total = total ?? stage.total;
+        stage.detail = detail;
+        if (!this.enabled) {
+            return;
+        }
+        if (this.live) {
+            this.render(current === stage.total);
+            return;
+        }
+        const percent = stage.total ? Math.floor((current / stage.total) * 100) : 0;
+        if (percent >= stage.lastPlainPercent + PLAIN_REPORT_PERCENT) {
+            stage.lastPlainPercent = percent - (percent % PLAIN_REPORT_PERCENT);
+            this.stream.write(`  ${stage.name} ${percent}% (${current}/${stage.total})${this.eta(stage)}\n`);
+        }
+    }
+
+    /**
+     * Shows what the language server is busy with next to the stage, from its `$/progress` reports.
+     *
+     * @param text - Title and message of the server's work, or undefined when it is idle again
+     * @param percentage - Server-reported completion, if any
+     */
+    setActivity(text: string | undefined, percentage?: number): void {
+        this.activity = text && (percentage !== undefined ? `${text} ${Math.round(percentage)}%` : text);
+        if (this.live) {
+            this.render(false);
+        }
+    }
+
+    /**
+     * Finishes the current stage with a line saying how long it took.
+     *
+     * @param summary - Result of the stage, e.g. "120 files"
+     */
+    finish(summary?: string): void {
+        this.end(chalk.green('✓'), summary);
+    }
+
+    /**
+     * Ends the current stage as failed.
+     *
+     * @param reason - Why it failed, e.g. an error message
+     */
+    fail(reason?: string): void {
+        this.end(chalk.red('✗'), reason);
+    }
+
+
+    /**
+     * Runs a function that writes to the terminal with the live line removed, then draws it again.
+     *
+     * @param write - Writes log output
+     */
+    suspend(write: () => void): void {
+        const shown = this.shown;
+        this.clear();
+        write();
+        if (shown) {
+            this.render(true);
+        }
+    }
+
+    /** Removes the live line */
+    clear(): void {
+        if (this.shown) {
+            this.stream.write('\r\x1b[2K');
+            this.shown = false;
+        }
+    }
+
+    private end(icon: string, summary?: string): void {
+        const stage = this.stage;
+        this.stage = undefined;
+        this.clear();
+        if (this.timer) {
+            clearInterval(this.timer);
+            this.timer = undefined;
+        }
+        if (!this.enabled || !stage?.name) {
+            return;
+        }
+        const elapsed = formatDuration(Date.now() - stage.startedAt);
+        this.stream.write(`${icon} ${stage.name}${summary ? `: ${summary}` : ''} ${chalk.dim(`(${elapsed})`)}\n`);
+    }
+
+    private render(force: boolean): void {
+        if (!this.live || (!this.stage && !this.activity)) {
+            return;
+        }
+        const now = Date.now();
+        if (!force && now - this.lastRenderAt < RENDER_INTERVAL_MS) {
+            return;
+        }
+        this.lastRenderAt = now;
+        this.frame = (this.frame + 1) % SPINNER.length;
+
+        const parts: string[] = [];
+        const stage = this.stage;
+        if (stage?.total) {
+            const ratio = Math.min(stage.current / stage.total, 1);
+            const filled = Math.round(ratio * 20);
+            const bar = chalk.cyan('█').repeat(filled) + chalk.gray('░').repeat(20 - filled);
+            parts.push(`${stage.name} ${bar} ${Math.round(ratio * 100)}% (${stage.current}/${stage.total})${this.eta(stage)}`);
+        } else if (stage) {
+            parts.push(`${chalk.cyan(SPINNER[this.frame])} ${stage.name} ${chalk.dim(formatDuration(now - stage.startedAt))}`);
+        }
+        if (stage?.detail) {
+            parts.push(chalk.dim(stage.detail));
+        }
+        if (this.activity) {
+            parts.push(chalk.yellow(this.activity));
+        }
+
+        const line = parts.join(chalk.dim(' · '));
+        const width = this.stream.columns ?? 80;
+        this.stream.write(`\r\x1b[2K${truncate(line, width - 1)}`);
+        this.shown = true;
+    }
+
+    private eta(stage: Stage): string {
+        if (!stage.total || stage.current === 0 || stage.current >= stage.total) {
+            return '';
+        }
+        const elapsed = Date.now() - stage.startedAt;
+        return ` ETA ${formatDuration((elapsed / stage.current) * (stage.total - stage.current))}`;
+    }
+}
+
+/** `1m05s`, `42s` */
+function formatDuration(ms: number): string {
+    const seconds = Math.round(ms / 1000);
+    return seconds >= 60 ? `${Math.floor(seconds / 60)}m${String(seconds % 60).padStart(2, '0')}s` : `${seconds}s`;
+}
+
+/** Cuts a line with color codes to a number of visible characters */
+function truncate(line: string, width: number): string {
+    let visible = 0;
+    let result = '';
+    for (let index = 0; index < line.length; index++) {
+        if (line[index] === '\x1b') {
+            // Color codes take no space
+            const end = line.indexOf('m', index);
+            result += line.slice(index, end + 1);
+            index = end;
+        } else if (visible < width) {
+            result += line[index];
+            visible++;
+        }
+    }
+    return result;
+}
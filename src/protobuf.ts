@@ -0,0 +1,171 @@
+/**
+ * Minimal protobuf (proto3) wire-format encoder/decoder for the gRPC service.
+ * Only the wire types used by proto/lsp_cli.proto are supported: varint, length-delimited and
+ * (when skipping unknown fields) fixed32/fixed64.
+ */
+
+const WIRE_VARINT = 0;
+const WIRE_FIXED64 = 1;
+const WIRE_LENGTH_DELIMITED = 2;
+const WIRE_FIXED32 = 5;
+
+export class ProtoWriter {
+    private chunks: Buffer[] = [];
+
+    /**
+     * Writes a string field; empty strings and undefined are omitted (proto3 default values).
+     */
+    string(field: number, value: string | undefined): this {
+        if (value) {
+            this.bytes(field, Buffer.from(value, 'utf-8'));
+        }
+        return this;
+    }
+
+    /**
+     * Writes an unsigned integer field; 0 and undefined are omitted.
+     */
+    uint(field: number, value: number | undefined): this {
+        if (value) {
+            this.tag(field, WIRE_VARINT);
+            this.varint(value);
+        }
+        return this;
+    }
+
+    /**
+     * Writes a bool field; false and undefined are omitted.
+     */
+    bool(field: number, value: boolean | undefined): this {
+        return this.uint(field, value ? 1 : 0);
+    }
+
+    /**
+     * Writes an embedded message field; undefined is omitted.
+     */
+    message(field: number, value: ProtoWriter | undefined): this {
+        if (value) {
+            this.bytes(field, value.finish());
+        }
+        return this;
+    }
+
+    finish(): Buffer {
+        return Buffer.concat(this.chunks);
+    }
+
+    private bytes(field: number, value: Buffer): void {
+        this.tag(field, WIRE_LENGTH_DELIMITED);
+        this.varint(value.length);
+        this.chunks.push(value);
+    }
+
+    private tag(field: number, wireType: number): void {
+        this.varint(field * 8 + wireType);
+    }
+
+    private varint(value: number): void {
+        const bytes: number[] = [];
+        let remaining = value;
+        while (remaining > 0x7f) {
+            bytes.push((remaining % 0x80) | 0x80);
+            remaining = Math.floor(remaining / 0x80);
+        }
+        bytes.push(remaining);
+        this.chunks.push(Buffer.from(bytes));
+    }
+}
+
+/**
+ * Decoded message: field number to all values seen for it (repeated fields keep every value).
+ * Varints decode to numbers, length-delimited fields to raw buffers.
+ */
+export class ProtoMessage {
+    private constructor(private fields: Map<number, Array<number | Buffer>>) {}
+
+    /**
+     * @param buffer - Encoded message bytes
+     * @throws If the buffer is truncated or uses an unsupported wire type
+     */
+    static decode(buffer: Buffer): ProtoMessage {
+        const fields = new Map<number, Array<number | Buffer>>();
+        let offset = 0;
+
+        const readVarint = (): number => {
+            let result = 0;
+            let multiplier = 1;
+            while (true) {
+                if (offset >= buffer.length) {
+                    throw new Error('Truncated varint');
+                }
+                const byte = buffer[offset++];
+                result += (byte & 0x7f) * multiplier;
+                if ((byte & 0x80) === 0) {
+                    return result;
+                }
+                multiplier *= 0x80;
+            }
+        };
+
+        while (offset < buffer.length) {
+            const tag = readVarint();
+            const field = Math.floor(tag / 8);
+            const wireType = tag % 8;
+            let value: number | Buffer;
+
+            if (wireType === WIRE_VARINT) {
+                value = readVarint();
+            } else if (wireType === WIRE_LENGTH_DELIMITED) {
+                const length = readVarint();
+                if (offset + length > buffer.length) {
+                    throw new Error('Truncated length-delimited field');
+                }
+                value = buffer.subarray(offset, offset + length);
+                offset += length;
+            } else if (wireType === WIRE_FIXED64 || wireType === WIRE_FIXED32) {
+                // Not used by our schema, skip
+                offset += wireType === WIRE_FIXED64 ? 8 : 4;
+                continue;
+            } else {
+                throw new Error(`Unsupported wire type ${wireType}`);
+            }
+
+            const values = fields.get(field) ?? [];
+            values.push(value);
+            fields.set(field, values);
+        }
+
+        return new ProtoMessage(fields);
+    }
+
+    string(field: number): string {
+        const value = this.last(field);
+        return Buffer.isBuffer(value) ? value.toString('utf-8') : '';
+    }
+
+    uint(field: number): number {
+        const value = this.last(field);
+        return typeof value === 'number' ? value : 0;
+    }
+
+    bool(field: number): boolean {
+        return this.uint(field) !== 0;
+    }
+
+    /**
+     * @returns Whether the field was sent, which for `optional` fields tells a default value from no value
+     */
+    has(field: number): boolean {
+        return this.fields.has(field);
+    }
+
+    message(field: number): ProtoMessage | undefined {
+        const value = this.last(field);
+        return Buffer.isBuffer(value) ? ProtoMessage.decode(value) : undefined;
+    }
+
+    private last(field: number): number | Buffer | undefined {
+        const values = this.fields.get(field);
+        return values?.[values.length - 1];
+    }
+}
@@ -0,0 +1,180 @@
+import { execFile } from 'node:child_process';
+import { createHash } from 'node:crypto';
+import { relative } from 'node:path';
+import { promisify } from 'node:util';
+import { gzipSync } from 'node:zlib';
+import type { Logger } from './logger';
+import type { AnalysisOutput, SupportedLanguage, SymbolInfo } from './types';
+import { fetchWithRetry } from './utils';
+
+const execFileAsync = promisify(execFile);
+
+/** Repository state the snapshot was taken from, when the analyzed directory is in a git repository */
+export interface SnapshotSource {
+    repository?: string;
+    commit?: string;
+    branch?: string;
+}
+
+interface PayloadHeader {
+    format: 'lsp-cli-snapshot';
+    version: 1;
+    /** SHA-256 of the analysis output; deltas refer to their base by this id */
+    id: string;
+    createdAt: string;
+    language: SupportedLanguage;
+    directory: string;
+    source: SnapshotSource;
+}
+
+export interface FullSnapshotPayload extends PayloadHeader {
+    kind: 'full';
+    output: AnalysisOutput;
+}
+
+export interface DeltaSnapshotPayload extends PayloadHeader {
+    kind: 'delta';
+    baseId: string;
+    /** Complete top-level symbols of every added or changed file, keyed by path relative to `directory` */
+    changedFiles: Record<string, SymbolInfo[]>;
+    /** Files that had symbols in the base and have none now */
+    removedFiles: string[];
+}
+
+export type SnapshotPayload = FullSnapshotPayload | DeltaSnapshotPayload;
+
+export interface PushOptions {
+    endpoint: string;
+    /** Sent as `Authorization: Bearer <token>` */
+    token?: string;
+    /** Additional request headers */
+    headers?: Record<string, string>;
+    /** Gzip the request body (default true) */
+    compress?: boolean;
+    maxRetries?: number;
+}
+
+/**
+ * @param output - Analysis result
+ * @returns Stable id of the analysis: the SHA-256 of its JSON
+ */
+export function snapshotId(output: AnalysisOutput): string {
+    return createHash('sha256').update(JSON.stringify(output)).digest('hex');
+}
+
+/**
+ * Builds the upload payload for an analysis: the full output, or with `base` only the files whose
+ * symbols changed since the base analysis.
+ *
+ * @param output - Analysis to push
+ * @param source - Repository metadata recorded with the snapshot
+ * @param base - Previously pushed analysis of the same directory
+ */
+export function createSnapshotPayload(
+    output: AnalysisOutput,
+    source: SnapshotSource,
+    base?: AnalysisOutput
+): SnapshotPayload {
+    const header: PayloadHeader = {
+        format: 'lsp-cli-snapshot',
+        version: 1,
+        id: snapshotId(output),
+        createdAt: new Date().toISOString(),
+        language: output.language,
+        directory: output.directory,
+        source
+    };
+    if (!base) {
+        return { ...header, kind: 'full', output };
+    }
+
+    const before = symbolsByFile(base);
+    const after = symbolsByFile(output);
+    const changedFiles: Record<string, SymbolInfo[]> = {};
+    for (const [file, symbols] of after) {
+        if (JSON.stringify(symbols) !== JSON.stringify(before.get(file))) {
+            changedFiles[file] = symbols;
+        }
+    }
+    const removedFiles = [...before.keys()].filter((file) => !after.has(file));
+
+    return { ...header, kind: 'delta', baseId: snapshotId(base), changedFiles, removedFiles };
+}
+
+function symbolsByFile(output: AnalysisOutput): Map<string, SymbolInfo[]> {
+    const files = new Map<string, SymbolInfo[]>();
+    for (const symbol of output.symbols) {
+        const file = relative(output.directory, symbol.file);
+        files.set(file, [...(files.get(file) ?? []), symbol]);
+    }
+    return files;
+}
+
+/**
+ * Reads the origin URL, commit and branch of the repository containing a directory.
+ *
+ * @param directory - Analyzed directory
+ * @returns Whatever could be determined; empty outside a git repository
+ */
+export async function getSnapshotSource(directory: string): Promise<SnapshotSource> {
+    const git = (args: string[]) =>
+        execFileAsync('git', args, { cwd: directory }).then(
+            ({ stdout }) => stdout.trim() || undefined,
+            () => undefined
+        );
+    const [repository, commit, branch] = await Promise.all([
+        git(['remote', 'get-url', 'origin']),
+        git(['rev-parse', 'HEAD']),
+        git(['symbolic-ref', '--short', '-q', 'HEAD'])
+    ]);
+    return {
+        ...(repository ? { repository } : {}),
+        ...(commit ? { commit } : {}),
+        ...(branch ? { branch } : {})
+    };
+}
+
+/**
+ * Uploads a snapshot payload with `POST`, retrying network errors, 429 and 5xx responses.
+ *
+ * @param payload - Payload from `createSnapshotPayload`
+ * @param options - Endpoint, credentials and transport settings
+ * @param logger - Logger for retries
+ * @returns The response body, parsed as JSON when possible
+ */
+export async function pushSnapshot(payload: SnapshotPayload, options: PushOptions, logger: Logger): Promise<unknown> {
+    const json = JSON.stringify(payload);
+    const compress = options.compress ?? true;
+    const response = await fetchWithRetry(
+        options.endpoint,
+        {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/json',
+                ...(compress ? { 'Content-Encoding': 'gzip' } : {}),
+                ...(options.token ? { Authorization: `Bearer ${options.token}` } : {}),
+                ...options.headers
+            },
+            body: compress ? gzipSync(json) : json
+        },
+        {
+            maxRetries: options.maxRetries ?? 5,
+            onRetry: (reason, delay) => logger.warn(`Push failed (${reason}), retrying in ${delay}ms`)
+        }
+    );
+
+    const text = await response.text();
+    if (!response.ok) {
+        // Servers answer 409 or 412 when they don't have the delta's base snapshot
+        const hint =
+            payload.kind === 'delta' && (response.status === 409 || response.status === 412)
+                ? ` (the endpoint may not have base snapshot ${payload.baseId}; push without --since)`
+                : '';
+        throw new Error(`Endpoint returned HTTP ${response.status}${hint}: ${text}`);
+    }
+    try {
+        return text ? JSON.parse(text) : undefined;
+    } catch (_error) {
+        return text;
+    }
+}
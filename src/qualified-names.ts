@@ -0,0 +1,144 @@
+import { existsSync, readFileSync } from 'node:fs';
+import { basename, dirname, join, relative, sep } from 'node:path';
+import type { SupportedLanguage, SymbolInfo } from './types';
+
+// Separator between a container and its members in each language's qualified names
+const SEPARATORS: Record<SupportedLanguage, string> = {
+    rust: '::',
+    cpp: '::',
+    c: '.',
+    java: '.',
+    csharp: '.',
+    haxe: '.',
+    python: '.',
+    typescript: '.',
+    dart: '.'
+};
+
+/**
+ * Sets `qualifiedName` on symbols and their children, in the language's own notation:
+ * `crate::nested::ModuleStruct::public_method` and `<crate::traits::Rectangle as Drawable>::draw`
+ * in Rust, `pkg.module.Class.method` in Python, `com.example.Class.method` in Java and Haxe,
+ * `"src/models/user".User.greet` in TypeScript, `package:app/user.dart.User.greet` in Dart, and the
+ * namespace/class chain in C# and C++, where namespaces are symbols themselves.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param filePath - Absolute path of the file
+ * @param lines - Source lines of the file
+ * @param language - Language of the file
+ * @param workspaceRoot - Analyzed directory, for module paths that are relative to it
+ */
+export function addQualifiedNames(
+    symbols: SymbolInfo[],
+    filePath: string,
+    lines: string[],
+    language: SupportedLanguage,
+    workspaceRoot: string
+): void {
+    const separator = SEPARATORS[language];
+    const prefix = modulePath(filePath, lines, language, workspaceRoot);
+
+    const visit = (symbol: SymbolInfo, container: string | undefined) => {
+        const qualifiedName =
+            language === 'rust' && symbol.impl && /^(unsafe\s+)?impl\b/.test(symbol.name)
+                ? rustImplPath(symbol, container ?? 'crate')
+                : container
+                  ? `${container}${separator}${symbol.name}`
+                  : symbol.name;
+        symbol.qualifiedName = qualifiedName;
+        symbol.children?.forEach((child) => visit(child, qualifiedName));
+    };
+    symbols.forEach((symbol) => visit(symbol, prefix));
+}
+
+/** `crate::module::Type` for inherent impls, `<crate::module::Type as Trait>` for trait impls */
+function rustImplPath(symbol: SymbolInfo, module: string): string {
+    const type = `${module}::${symbol.impl?.type}`;
+    return symbol.impl?.trait ? `<${type} as ${symbol.impl.trait}>` : type;
+}
+
+function modulePath(
+    filePath: string,
+    lines: string[],
+    language: SupportedLanguage,
+    workspaceRoot: string
+): string | undefined {
+    switch (language) {
+        case 'rust':
+            return rustModulePath(filePath);
+        case 'python':
+            return pythonModulePath(filePath);
+        case 'java':
+        case 'haxe':
+            return lines.map((line) => /^\s*package\s+([\w.]+)\s*;/.exec(line)?.[1]).find(Boolean);
+        case 'typescript':
+            return `"${relative(workspaceRoot, filePath)
+                .split(sep)
+                .join('/')
+                .replace(/\.(d\.)?[cm]?[jt]sx?$/, '')}"`;
+        case 'dart':
+            return dartLibraryUri(filePath, workspaceRoot);
+        default:
+            return undefined;
+    }
+}
+
+/**
+ * Module path from the crate root: `src/nested/mod.rs` is `crate::nested`; crate roots like
+ * `src/lib.rs`, `src/main.rs`, `src/bin/tool.rs` or `tests/api.rs` are `crate`.
+ */
+function rustModulePath(filePath: string): string {
+    let manifestDir = dirname(filePath);
+    while (!existsSync(join(manifestDir, 'Cargo.toml')) && dirname(manifestDir) !== manifestDir) {
+        manifestDir = dirname(manifestDir);
+    }
+
+    const segments = relative(manifestDir, filePath).split(sep);
+    let path = segments.slice(1);
+    if (segments[0] !== 'src' || segments[1] === 'bin') {
+        // Other targets are roots themselves: a single file like tests/api.rs, or a directory with main.rs
+        const targetDir = segments[0] === 'src' ? 2 : 1;
+        path = segments.slice(targetDir);
+        if (path.length === 1) {
+            path = [];
+        } else if (existsSync(join(manifestDir, ...segments.slice(0, targetDir + 1), 'main.rs'))) {
+            path = path.slice(1);
+        }
+    }
+
+    const modules = path.map((segment) => segment.replace(/\.rs$/, ''));
+    if (/^(lib|main|mod)$/.test(modules[modules.length - 1] ?? '')) {
+        modules.pop();
+    }
+    return ['crate', ...modules].join('::');
+}
+
+/** Dotted module name from the outermost directory that is still a package (has `__init__.py`) */
+function pythonModulePath(filePath: string): string {
+    const modules = [basename(filePath).replace(/\.pyi?$/, '')];
+    let directory = dirname(filePath);
+    while (existsSync(join(directory, '__init__.py')) && dirname(directory) !== directory) {
+        modules.unshift(basename(directory));
+        directory = dirname(directory);
+    }
+    if (modules[modules.length - 1] === '__init__') {
+        modules.pop();
+    }
+    return modules.join('.');
+}
+
+/** `package:<name>/<path>` for files under the package's `lib/`, otherwise the path relative to the workspace */
+function dartLibraryUri(filePath: string, workspaceRoot: string): string {
+    for (let directory = dirname(filePath); dirname(directory) !== directory; directory = dirname(directory)) {
+        const pubspec = join(directory, 'pubspec.yaml');
+        if (existsSync(pubspec)) {
+            const name = /^name:\s*(\S+)/m.exec(readFileSync(pubspec, 'utf-8'))?.[1];
+            const path = relative(join(directory, 'lib'), filePath);
+            if (name && !path.startsWith('..')) {
+                return `package:${name}/${path.split(sep).join('/')}`;
+            }
+            break;
+        }
+    }
+    return relative(workspaceRoot, filePath).split(sep).join('/');
+}
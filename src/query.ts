@@ -0,0 +1,69 @@
+import type { SymbolInfo } from './types';
+
+export interface SymbolQuery {
+    /** Exact symbol name */
+    name?: string;
+    /** Symbol kind as written to the output (e.g. "class", "method") */
+    kind?: string;
+    /** Absolute file path, or a suffix of it (e.g. "src/User.ts") */
+    file?: string;
+}
+
+/**
+ * Finds all symbols matching a query, searching nested children as well.
+ * Matching symbols are returned with their own children intact.
+ *
+ * @param symbols - Symbol tree to search
+ * @param query - Criteria that must all match; an empty query matches every top-level symbol
+ * @returns Matching symbols in document order
+ */
+export function querySymbols(symbols: SymbolInfo[], query: SymbolQuery): SymbolInfo[] {
+    if (!query.name && !query.kind) {
+        return query.file ? symbols.filter((symbol) => matchesFile(symbol, query.file!)) : symbols;
+    }
+
+    const results: SymbolInfo[] = [];
+
+    const visit = (symbol: SymbolInfo) => {
+        if (
+            (!query.name || symbol.name === query.name) &&
+            (!query.kind || symbol.kind === query.kind) &&
+            (!query.file || matchesFile(symbol, query.file))
+        ) {
+            results.push(symbol);
+        }
+        symbol.children?.forEach(visit);
+    };
+
+    symbols.forEach(visit);
+    return results;
+}
+
+function matchesFile(symbol: SymbolInfo, file: string): boolean {
+    return symbol.file === file || symbol.file.endsWith(`/${file.replace(/^\.?\//, '')}`);
+}
+
+/**
+ * Keeps only symbols of the given kinds. Children of a dropped symbol take its place when their
+ * kind is kept, so `class,method` keeps the classes inside a dropped module, with their methods.
+ * Their `containerId` then refers to the nearest kept ancestor, or is left out when there is none.
+ *
+ * @param symbols - Symbol tree to filter
+ * @param kinds - Kinds to keep, as written to the output (e.g. "class", "method")
+ * @param containerId - Id of the nearest kept ancestor of `symbols`, if any
+ * @returns The filtered tree; kept symbols are copies with filtered children
+ */
+export function filterSymbolsByKind(symbols: SymbolInfo[], kinds: string[], containerId?: string): SymbolInfo[] {
+    return symbols.flatMap((symbol) => {
+        if (!kinds.includes(symbol.kind)) {
+            return symbol.children ? filterSymbolsByKind(symbol.children, kinds, containerId) : [];
+        }
+        const children = symbol.children && filterSymbolsByKind(symbol.children, kinds, symbol.id);
+        // Assigning keeps the field in its place in the output
+        const kept = { ...symbol, containerId, children: children && children.length > 0 ? children : undefined };
+        if (containerId === undefined) {
+            delete kept.containerId;
+        }
+        return [kept];
+    });
+}
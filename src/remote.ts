@@ -0,0 +1,130 @@
+import { execFile } from 'node:child_process';
+import { createHash } from 'node:crypto';
+import { existsSync, mkdirSync, mkdtempSync, readdirSync, rmSync, statSync } from 'node:fs';
+import { homedir, tmpdir } from 'node:os';
+import { basename, join } from 'node:path';
+import { promisify } from 'node:util';
+import type { Logger } from './logger';
+import { downloadFile, extractArchive } from './utils';
+
+const execFileAsync = promisify(execFile);
+
+export interface FetchedRepository {
+    /** Working tree of the checkout */
+    directory: string;
+    /** Commit that was checked out */
+    commit: string;
+    /** Removes the checkout if it was made in a temporary directory */
+    cleanup: () => void;
+}
+
+const ARCHIVE_EXTENSIONS = ['.tar.gz', '.tgz', '.tar', '.zip', '.crate'];
+
+/**
+ * @param target - Analysis target given on the command line
+ * @returns Whether the target is a source archive (path or URL) that `openArchive` can extract
+ */
+export function isArchive(target: string): boolean {
+    const path = target.replace(/[?#].*$/, '').toLowerCase();
+    return ARCHIVE_EXTENSIONS.some((extension) => path.endsWith(extension));
+}
+
+/**
+ * Extracts a source archive into a temporary directory, downloading it first if it's an https URL.
+ * Supports `.tar.gz`/`.tgz`, `.tar`, `.zip` and crates.io `.crate` files.
+ *
+ * @param target - Archive path or URL
+ * @param logger - Logger for progress
+ * @returns The extracted sources; archives with a single top-level directory (as crates and
+ * GitHub tarballs have) are unwrapped to that directory
+ */
+export async function openArchive(target: string, logger: Logger): Promise<{ directory: string; cleanup: () => void }> {
+    const workspace = mkdtempSync(join(tmpdir(), 'lsp-cli-archive-'));
+    const cleanup = () => rmSync(workspace, { recursive: true, force: true });
+
+    try {
+        let archive = target;
+        if (/^https:\/\//.test(target)) {
+            // Keep the extension so extractArchive can tell the format
+            archive = join(workspace, basename(new URL(target).pathname));
+            logger.info(`Downloading ${target}...`);
+            await downloadFile(target, archive);
+        } else if (!existsSync(archive)) {
+            throw new Error(`Archive '${target}' does not exist`);
+        }
+
+        const directory = join(workspace, 'source');
+        mkdirSync(directory);
+        await extractArchive(archive, directory);
+
+        const entries = readdirSync(directory);
+        const root =
+            entries.length === 1 && statSync(join(directory, entries[0])).isDirectory()
+                ? join(directory, entries[0])
+                : directory;
+        return { directory: root, cleanup };
+    } catch (error) {
+        cleanup();
+        throw error;
+    }
+}
+
+/**
+ * @param target - Analysis target given on the command line
+ * @returns Whether the target is a git URL rather than a local path
+ */
+export function isGitUrl(target: string): boolean {
+    return /^(https?|ssh|git|file):\/\//.test(target) || /^[\w.-]+@[\w.-]+:/.test(target);
+}
+
+/**
+ * Shallow-fetches one ref of a repository. Checkouts are cached in ~/.lsp-cli/repos per URL and
+ * updated on each call, so repeated analyses only download what changed.
+ *
+ * @param url - Repository URL
+ * @param ref - Branch, tag or commit; the remote's default branch if omitted
+ * @param logger - Logger for progress
+ * @param cache - Reuse the cached checkout; otherwise clone into a temporary directory
+ */
+export async function fetchRepository(
+    url: string,
+    ref: string | undefined,
+    logger: Logger,
+    cache = true
+): Promise<FetchedRepository> {
+    const directory = cache
+        ? join(homedir(), '.lsp-cli', 'repos', createHash('sha256').update(url).digest('hex').slice(0, 16))
+        : mkdtempSync(join(tmpdir(), 'lsp-cli-repo-'));
+    const git = async (args: string[]) => (await execFileAsync('git', args, { cwd: directory })).stdout.trim();
+
+    try {
+        if (!existsSync(join(directory, '.git'))) {
+            mkdirSync(directory, { recursive: true });
+            await git(['init', '--quiet']);
+            await git(['remote', 'add', 'origin', url]);
+        }
+
+        // Fetching a single ref by name works for branches, tags and (on most hosts) commit hashes alike
+        logger.info(`Fetching ${url}${ref ? ` at ${ref}` : ''}...`);
+        await git(['fetch', '--quiet', '--depth', '1', 'origin', ref ?? 'HEAD']);
+        await git(['checkout', '--quiet', '--force', '--detach', 'FETCH_HEAD']);
+        // Drop files left over from a previously analyzed ref
+        await git(['clean', '--quiet', '-ffdx']);
+
+        return {
+            directory,
+            commit: await git(['rev-parse', 'HEAD']),
+            cleanup: () => {
+                if (!cache) {
+                    rmSync(directory, { recursive: true, force: true });
+                }
+            }
+        };
+    } catch (error) {
+        if (!cache) {
+            rmSync(directory, { recursive: true, force: true });
+        }
+        const stderr = (error as { stderr?: string }).stderr?.trim();
+        throw new Error(`Fetching ${url} failed${stderr ? `: ${stderr}` : ''}`);
+    }
+}
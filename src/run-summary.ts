@@ -0,0 +1,106 @@
+import type { SupportedLanguage, SymbolInfo } from './types';
+
+/** Files of one language in a run */
+export interface FileCounts {
+    /** Files handed to the language server, notebooks included */
+    scanned: number;
+    /** Files of the language left out by `--ignore`, or listed by `--files-from` but outside the directory or missing */
+    skipped: number;
+    /** Files whose analysis failed, so their symbols are missing */
+    failed: string[];
+}
+
+/** Time spent in one phase of a run, e.g. downloading the server or extracting symbols */
+export interface PhaseTiming {
+    name: string;
+    durationMs: number;
+}
+
+/** What a run did, printed at its end; `--run-summary json` prints it as is */
+export interface RunSummary {
+    files: Partial<Record<SupportedLanguage, { scanned: number; skipped: number; failed: number }>>;
+    symbols: { total: number; byKind: Record<string, number> };
+    warnings: number;
+    errors: number;
+    /** Phases in the order they first ran; repeated phases, like extraction per crate, are added up */
+    phases: PhaseTiming[];
+    elapsedMs: number;
+}
+
+/**
+ * @returns Counts of a run that hasn't analyzed anything yet
+ */
+export function emptyFileCounts(): FileCounts {
+    return { scanned: 0, skipped: 0, failed: [] };
+}
+
+/**
+ * Puts together the summary of a run.
+ *
+ * @param language - Analyzed language
+ * @param files - Files of the run
+ * @param symbols - Extracted symbols, nested ones included
+ * @param log - Warnings, errors and phases as counted by the logger
+ * @param startedAt - Start of the run, from `Date.now()`
+ * @returns The summary
+ */
+export function summarizeRun(
+    language: SupportedLanguage,
+    files: FileCounts,
+    symbols: SymbolInfo[],
+    log: { warnings: number; errors: number; phases: PhaseTiming[] },
+    startedAt: number
+): RunSummary {
+    const byKind: Record<string, number> = {};
+    let total = 0;
+    const count = (symbol: SymbolInfo) => {
+        byKind[symbol.kind] = (byKind[symbol.kind] ?? 0) + 1;
+        total++;
+        symbol.children?.forEach(count);
+    };
+    symbols.forEach(count);
+
+    const phases = new Map<string, number>();
+    for (const phase of log.phases) {
+        phases.set(phase.name, (phases.get(phase.name) ?? 0) + phase.durationMs);
+    }
+
+    return {
+        files: { [language]: { scanned: files.scanned, skipped: files.skipped, failed: files.failed.length } },
+        symbols: {
+            total,
+            byKind: Object.fromEntries(Object.entries(byKind).sort(([, a], [, b]) => b - a))
+        },
+        warnings: log.warnings,
+        errors: log.errors,
+        phases: [...phases].map(([name, durationMs]) => ({ name, durationMs })),
+        elapsedMs: Date.now() - startedAt
+    };
+}
+
+/**
+ * Lays out a summary as a few aligned lines for the terminal.
+ *
+ * @param summary - Summary of the run
+ * @returns Lines of the table, without colors
+ */
+export function formatRunSummary(summary: RunSummary): string[] {
+    const seconds = (ms: number) => `${(ms / 1000).toFixed(1)}s`;
+    const rows: Array<[string, string]> = [];
+    for (const [language, counts] of Object.entries(summary.files)) {
+        rows.push([
+            `Files (${language})`,
+            `${counts.scanned} scanned, ${counts.skipped} skipped, ${counts.failed} failed`
+        ]);
+    }
+    const kinds = Object.entries(summary.symbols.byKind).map(([kind, count]) => `${count} ${kind}`);
+    rows.push(['Symbols', `${summary.symbols.total}${kinds.length > 0 ? ` (${kinds.join(', ')})` : ''}`]);
+    rows.push(['Warnings', `${summary.warnings}, errors: ${summary.errors}`]);
+    for (const phase of summary.phases) {
+        rows.push([phase.name, seconds(phase.durationMs)]);
+    }
+    rows.push(['Elapsed', seconds(summary.elapsedMs)]);
+
+    const width = Math.max(...rows.map(([label]) => label.length));
+    return rows.map(([label, value]) => `${label.padEnd(width)}  ${value}`);
+}
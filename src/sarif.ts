@@ -0,0 +1,163 @@
+import { relative, sep } from 'node:path';
+import { type CiSummary, findRemovedSymbols, findUndocumented } from './ci';
+import type { AnalysisOutput, DiagnosticInfo, Range } from './types';
+
+/*
+ * SARIF 2.1.0 (https://docs.oasis-open.org/sarif/sarif/v2.1.0/) as consumed by GitHub code scanning,
+ * GitLab and Azure DevOps. Only the subset lsp-cli produces is typed here.
+ */
+
+type SarifLevel = 'error' | 'warning' | 'note';
+
+interface SarifResult {
+    ruleId: string;
+    level: SarifLevel;
+    message: { text: string };
+    locations: Array<{
+        physicalLocation: {
+            artifactLocation: { uri: string; uriBaseId: string };
+            region?: { startLine: number; startColumn: number; endLine: number; endColumn: number };
+        };
+    }>;
+}
+
+interface SarifRule {
+    id: string;
+    shortDescription: { text: string };
+}
+
+export interface SarifLog {
+    $schema: string;
+    version: '2.1.0';
+    runs: Array<{
+        tool: { driver: { name: string; rules: SarifRule[] } };
+        originalUriBaseIds: Record<string, { uri: string }>;
+        results: SarifResult[];
+    }>;
+}
+
+const SRCROOT = 'SRCROOT';
+
+const CI_RULES: SarifRule[] = [
+    { id: 'lsp-cli/undocumented-symbol', shortDescription: { text: 'Symbol has no documentation' } },
+    { id: 'lsp-cli/removed-symbol', shortDescription: { text: 'Symbol from the baseline was removed' } }
+];
+
+/**
+ * Converts language server diagnostics to SARIF. Rules are derived from each diagnostic's
+ * source and code, e.g. `typescript/2322`.
+ *
+ * @param output - Analysis including diagnostics
+ * @returns A SARIF log with one run
+ */
+export function diagnosticsToSarif(output: AnalysisOutput): SarifLog {
+    const results = (output.diagnostics ?? []).map((diagnostic) => diagnosticResult(output.directory, diagnostic));
+    return createLog(output.directory, rulesFor(results), results);
+}
+
+/**
+ * Converts a CI run to SARIF: all diagnostics at their own level, plus undocumented symbols when
+ * doc coverage is checked and removed symbols when compared against a baseline. The latter are
+ * reported as warnings or errors if their check failed and as notes otherwise.
+ *
+ * @param summary - Result of {@link evaluateCi}
+ * @param output - Analysis the summary was computed from
+ * @param baseline - Baseline the summary was computed against
+ * @returns A SARIF log with one run
+ */
+export function ciToSarif(summary: CiSummary, output: AnalysisOutput, baseline?: AnalysisOutput): SarifLog {
+    const failed = new Set(summary.checks.filter((check) => !check.passed).map((check) => check.name));
+    const checked = new Set(summary.checks.map((check) => check.name));
+    const directory = output.directory;
+
+    const results = (output.diagnostics ?? []).map((diagnostic) => diagnosticResult(directory, diagnostic));
+
+    if (checked.has('docCoverage')) {
+        const level = failed.has('docCoverage') ? 'warning' : 'note';
+        for (const symbol of findUndocumented(output)) {
+            results.push({
+                ruleId: CI_RULES[0].id,
+                level,
+                message: { text: `${symbol.kind} '${symbol.name}' has no documentation` },
+                locations: [location(directory, symbol.file, symbol.range)]
+            });
+        }
+    }
+
+    if (baseline && checked.has('removedSymbols')) {
+        const level = failed.has('removedSymbols') ? 'error' : 'note';
+        for (const { symbol } of findRemovedSymbols(output, baseline)) {
+            results.push({
+                ruleId: CI_RULES[1].id,
+                level,
+                message: { text: `${symbol.kind} '${symbol.name}' was removed` },
+                // The range refers to the baseline version of the file, so only the file is reported
+                locations: [location(baseline.directory, symbol.file)]
+            });
+        }
+    }
+
+    const rules = [...rulesFor(results), ...CI_RULES.filter((rule) => hasRule(results, rule.id))];
+    return createLog(directory, rules, results);
+}
+
+function createLog(directory: string, rules: SarifRule[], results: SarifResult[]): SarifLog {
+    return {
+        $schema: 'https://json.schemastore.org/sarif-2.1.0.json',
+        version: '2.1.0',
+        runs: [
+            {
+                tool: { driver: { name: 'lsp-cli', rules } },
+                originalUriBaseIds: { [SRCROOT]: { uri: `file://${pathToUri(directory)}/` } },
+                results
+            }
+        ]
+    };
+}
+
+function diagnosticResult(directory: string, diagnostic: DiagnosticInfo): SarifResult {
+    return {
+        ruleId: diagnosticRuleId(diagnostic),
+        level: diagnostic.severity === 'error' ? 'error' : diagnostic.severity === 'warning' ? 'warning' : 'note',
+        message: { text: diagnostic.message },
+        locations: [location(directory, diagnostic.file, diagnostic.range)]
+    };
+}
+
+function diagnosticRuleId(diagnostic: DiagnosticInfo): string {
+    const source = diagnostic.source ?? 'lsp';
+    return diagnostic.code ? `${source}/${diagnostic.code}` : source;
+}
+
+/** Rules for all diagnostic results; the lsp-cli rules are added separately */
+function rulesFor(results: SarifResult[]): SarifRule[] {
+    const ids = new Set(results.map((result) => result.ruleId).filter((id) => !id.startsWith('lsp-cli/')));
+    return [...ids].sort().map((id) => ({ id, shortDescription: { text: `Diagnostic ${id}` } }));
+}
+
+function hasRule(results: SarifResult[], id: string): boolean {
+    return results.some((result) => result.ruleId === id);
+}
+
+function location(directory: string, file: string, range?: Range): SarifResult['locations'][number] {
+    return {
+        physicalLocation: {
+            artifactLocation: { uri: pathToUri(relative(directory, file)), uriBaseId: SRCROOT },
+            // SARIF lines and columns are 1-based; LSP positions are 0-based
+            ...(range
+                ? {
+                      region: {
+                          startLine: range.start.line + 1,
+                          startColumn: range.start.character + 1,
+                          endLine: range.end.line + 1,
+                          endColumn: range.end.character + 1
+                      }
+                  }
+                : {})
+        }
+    };
+}
+
+function pathToUri(path: string): string {
+    return path.split(sep).map(encodeURIComponent).join('/');
+}
@@ -0,0 +1,131 @@
+import { readFile } from 'node:fs/promises';
+import { basename, extname, resolve } from 'node:path';
+import { createContext, Script } from 'node:vm';
+import type { Logger } from './logger';
+import type { Plugin, PluginContext } from './plugins';
+import type { AnalysisOutput, SymbolInfo } from './types';
+
+/** Hook functions a script may define at its top level */
+interface ScriptHooks {
+    onSymbol?: (symbol: SymbolInfo, context: ScriptContext) => unknown;
+    onFile?: (file: string, symbols: SymbolInfo[], context: ScriptContext) => unknown;
+    onEnd?: (output: AnalysisOutput, context: ScriptContext) => unknown;
+}
+
+interface ScriptContext {
+    language: string;
+    directory: string;
+    /** Parent of the symbol passed to onSymbol, if nested */
+    parent?: SymbolInfo;
+}
+
+const HOOK_NAMES = ['onSymbol', 'onFile', 'onEnd'] as const;
+
+/**
+ * Loads a hook script. Scripts are plain JavaScript evaluated in a separate VM context
+ * with no access to `require`, `process` or the file system; only `log()` is provided.
+ * Scripts are not a security boundary and are expected to be trusted.
+ *
+ * Hooks, all optional:
+ * - `onSymbol(symbol, context)`: called for every symbol, parents before children. May modify
+ *   the symbol in place; returning `false` drops it together with its children.
+ * - `onFile(file, symbols, context)`: called once per file with that file's top-level symbols
+ *   after all onSymbol calls. Returning an array replaces them.
+ * - `onEnd(output, context)`: called once at the end of the run. A returned value other than
+ *   `undefined` is written to `output.sections[<script name>]`.
+ *
+ * @param path - Script file
+ * @param logger - Receives the script's `log()` output at debug level
+ * @returns A plugin running the script's hooks
+ */
+export async function loadScript(path: string, logger: Logger): Promise<Plugin> {
+    const file = resolve(path);
+    const name = basename(file, extname(file));
+    const source = await readFile(file, 'utf8');
+
+    const sandbox = createContext({
+        log: (...args: unknown[]) => logger.debug(`[${name}] ${args.map(formatLogArgument).join(' ')}`)
+    });
+    try {
+        new Script(source, { filename: file }).runInContext(sandbox, { timeout: 5000 });
+    } catch (error) {
+        throw new Error(`Failed to load script ${path}: ${error instanceof Error ? error.message : String(error)}`);
+    }
+
+    const hooks: ScriptHooks = {};
+    for (const hook of HOOK_NAMES) {
+        if (typeof sandbox[hook] === 'function') {
+            hooks[hook] = sandbox[hook];
+        }
+    }
+    if (!hooks.onSymbol && !hooks.onFile && !hooks.onEnd) {
+        throw new Error(`Script ${path} defines none of ${HOOK_NAMES.join(', ')}`);
+    }
+    logger.debug(`Loaded script ${name} with hooks: ${Object.keys(hooks).join(', ')}`);
+
+    const plugin: Plugin = { name };
+    if (hooks.onSymbol || hooks.onFile) {
+        plugin.transformSymbols = (symbols, context) => runSymbolHooks(symbols, hooks, toScriptContext(context));
+    }
+    const { onEnd } = hooks;
+    if (onEnd) {
+        plugin.outputSection = (output, context) => onEnd(output, toScriptContext(context));
+    }
+    return plugin;
+}
+
+/**
+ * @param paths - Script files, in the order their hooks should run
+ * @param logger - Receives script log output
+ */
+export async function loadScripts(paths: string[], logger: Logger): Promise<Plugin[]> {
+    const plugins: Plugin[] = [];
+    for (const path of paths) {
+        plugins.push(await loadScript(path, logger));
+    }
+    return plugins;
+}
+
+function runSymbolHooks(symbols: SymbolInfo[], hooks: ScriptHooks, context: ScriptContext): SymbolInfo[] {
+    const { onSymbol, onFile } = hooks;
+
+    const visit = (list: SymbolInfo[], parent?: SymbolInfo): SymbolInfo[] => {
+        const kept: SymbolInfo[] = [];
+        for (const symbol of list) {
+            if (onSymbol && onSymbol(symbol, { ...context, parent }) === false) {
+                continue;
+            }
+            if (symbol.children) {
+                symbol.children = visit(symbol.children, symbol);
+            }
+            kept.push(symbol);
+        }
+        return kept;
+    };
+
+    let result = visit(symbols);
+
+    if (onFile) {
+        const byFile = new Map<string, SymbolInfo[]>();
+        for (const symbol of result) {
+            const list = byFile.get(symbol.file) ?? [];
+            list.push(symbol);
+            byFile.set(symbol.file, list);
+        }
+        result = [];
+        for (const [file, fileSymbols] of byFile) {
+            const replaced = onFile(file, fileSymbols, context);
+            result.push(...(Array.isArray(replaced) ? replaced : fileSymbols));
+        }
+    }
+
+    return result;
+}
+
+function toScriptContext(context: PluginContext): ScriptContext {
+    return { language: context.language, directory: context.directory };
+}
+
+function formatLogArgument(value: unknown): string {
+    return typeof value === 'string' ? value : JSON.stringify(value);
+}
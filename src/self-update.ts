@@ -0,0 +1,305 @@
+import { createHash, createPublicKey, verify } from 'node:crypto';
+import {
+    chmodSync,
+    closeSync,
+    fsyncSync,
+    openSync,
+    realpathSync,
+    renameSync,
+    rmSync,
+    statSync,
+    writeFileSync
+} from 'node:fs';
+import { basename, dirname, join } from 'node:path';
+import type { Logger } from './logger';
+import { fetchWithRetry } from './utils';
+
+/** Bundled CLI attached to each release */
+export const BUNDLE_ASSET = 'lsp-cli.js';
+/** `sha256sum` output covering the release's assets */
+export const CHECKSUMS_ASSET = 'SHA256SUMS';
+/** Detached signature of the checksums file, made with the release key */
+export const SIGNATURE_ASSET = 'SHA256SUMS.sig';
+/**
+ * PEM public key the release workflow signs the checksums with, verified by default. Empty until the
+ * release signing key is set up; until then updates need `--public-key` or an explicit opt-out.
+ */
+export const RELEASE_PUBLIC_KEY = '';
+
+/** `stable` follows full releases, `prerelease` also release candidates and betas */
+export type ReleaseChannel = 'stable' | 'prerelease';
+
+export interface Release {
+    /** Version without the leading `v` of the tag */
+    version: string;
+    tag: string;
+    prerelease: boolean;
+    /** Download URLs by asset name */
+    assets: Record<string, string>;
+}
+
+export interface SelfUpdateOptions {
+    /** GitHub repository publishing the releases, `owner/name` */
+    repository: string;
+    channel: ReleaseChannel;
+    /** Install exactly this version, also if it is older than the running one */
+    version?: string;
+    /** PEM public key (Ed25519 or RSA) the checksums must be signed with; the release key if omitted */
+    publicKey?: string;
+    /** Accept releases without a valid signature, verifying only their checksums */
+    skipSignature?: boolean;
+    /** Only report whether an update is available */
+    check?: boolean;
+    /** Path of the running CLI, which is replaced */
+    executable: string;
+    /** Version of the running CLI */
+    currentVersion: string;
+}
+
+export interface SelfUpdateResult {
+    current: string;
+    target: string;
+    /** Whether the executable was replaced; false when up to date or with `check` */
+    updated: boolean;
+    path: string;
+}
+
+/**
+ * Updates the running CLI to the newest release of its channel, or to a pinned version. The bundle
+ * must match its entry in the release's checksums, and the checksums must carry a valid signature by
+ * the release key unless `skipSignature` is set. The new bundle is written next to the executable and
+ * renamed over it, so an interrupted update leaves the old version working.
+ *
+ * @param options - Release source, target version and executable to replace
+ * @param logger - Logger for progress
+ * @returns The versions and whether the executable was replaced
+ */
+export async function selfUpdate(options: SelfUpdateOptions, logger: Logger): Promise<SelfUpdateResult> {
+    const path = realpathSync(options.executable);
+    if (!path.endsWith('.js')) {
+        throw new Error(`${path} is not the bundled CLI; update source checkouts with git pull and npm run build`);
+    }
+
+    const release = await findRelease(options.repository, options.channel, options.version);
+    const result = { current: options.currentVersion, target: release.version, updated: false, path };
+    const newer = compareSemver(release.version, options.currentVersion) > 0;
+    if (release.version === options.currentVersion || (!options.version && !newer)) {
+        logger.info(`lsp-cli ${options.currentVersion} is up to date (${options.channel} channel)`);
+        return result;
+    }
+    if (options.check) {
+        logger.info(`lsp-cli ${release.version} is available (installed: ${options.currentVersion})`);
+        return result;
+    }
+
+    const publicKey = options.publicKey ?? (RELEASE_PUBLIC_KEY || undefined);
+    if (!options.skipSignature && !publicKey) {
+        throw new Error(
+            'This build has no embedded release key; pass --public-key <file>, ' +
+                'or --insecure-skip-signature to rely on the checksum alone'
+        );
+    }
+    for (const asset of [BUNDLE_ASSET, CHECKSUMS_ASSET, ...(options.skipSignature ? [] : [SIGNATURE_ASSET])]) {
+        if (!release.assets[asset]) {
+            throw new Error(`Release ${release.tag} has no ${asset}`);
+        }
+    }
+    logger.stage(`Downloading lsp-cli ${release.version}`);
+    const bundle = await download(release.assets[BUNDLE_ASSET]);
+    const checksums = await download(release.assets[CHECKSUMS_ASSET]);
+    const signature = options.skipSignature ? undefined : await download(release.assets[SIGNATURE_ASSET]);
+    logger.stageDone();
+
+    if (signature && publicKey) {
+        verifySignature(checksums, signature, publicKey);
+        logger.debug(`Signature of ${CHECKSUMS_ASSET} verified`);
+    } else {
+        logger.warn(`Not verifying the signature of ${release.tag}; only its checksum is checked`);
+    }
+    verifyChecksum(bundle, checksums.toString('utf8'), BUNDLE_ASSET);
+    if (!bundle.subarray(0, 2).equals(Buffer.from('#!'))) {
+        throw new Error(`${BUNDLE_ASSET} of ${release.tag} is not an executable script`);
+    }
+
+    replaceFile(path, bundle);
+    logger.success(`Updated lsp-cli ${options.currentVersion} → ${release.version} (${path})`);
+    return { ...result, updated: true };
+}
+
+/**
+ * Looks up a release on GitHub. `GITHUB_TOKEN` is sent if set, to avoid the API's rate limit.
+ *
+ * @param repository - Repository publishing the releases, `owner/name`
+ * @param channel - Channel to take the newest release of
+ * @param version - Exact version to look up instead, with or without leading `v`
+ * @returns The release
+ */
+export async function findRelease(repository: string, channel: ReleaseChannel, version?: string): Promise<Release> {
+    const base = `https://api.github.com/repos/${repository}/releases`;
+    if (version) {
+        const tag = version.startsWith('v') ? version : `v${version}`;
+        const response = await github(`${base}/tags/${encodeURIComponent(tag)}`);
+        if (response.status === 404) {
+            throw new Error(`No release ${tag} in ${repository}`);
+        }
+        return toRelease((await json(response)) as GitHubRelease);
+    }
+
+    const releases = (await json(await github(`${base}?per_page=30`))) as GitHubRelease[];
+    const candidates = releases
+        .filter((release) => !release.draft && (channel === 'prerelease' || !release.prerelease))
+        .map(toRelease)
+        .sort((a, b) => compareSemver(b.version, a.version));
+    if (candidates.length === 0) {
+        throw new Error(`No ${channel} release in ${repository}`);
+    }
+    return candidates[0];
+}
+
+/**
+ * Compares versions like `1.2.3` and `1.3.0-rc.1`; a prerelease sorts before its release.
+ *
+ * @param a - Version
+ * @param b - Version
+ * @returns Negative if a is older, positive if newer, 0 if equal
+ */
+export function compareSemver(a: string, b: string): number {
+    const parse = (version: string) => {
+        const [core, pre] = version.replace(/^v/, '').split('-', 2);
+        return { parts: core.split('.').map((part) => Number(part) || 0), pre };
+    };
+    const left = parse(a);
+    const right = parse(b);
+    for (let i = 0; i < Math.max(left.parts.length, right.parts.length); i++) {
+        const difference = (left.parts[i] ?? 0) - (right.parts[i] ?? 0);
+        if (difference !== 0) {
+            return difference;
+        }
+    }
+    if (left.pre === right.pre) {
+        return 0;
+    }
+    if (left.pre === undefined || right.pre === undefined) {
+        return left.pre === undefined ? 1 : -1;
+    }
+    return left.pre.localeCompare(right.pre, undefined, { numeric: true });
+}
+
+/**
+ * @param content - Downloaded asset
+ * @param checksums - Checksums file in `sha256sum` format
+ * @param name - Asset name the checksum is listed under
+ * @throws If the asset is not listed or its checksum differs
+ */
+export function verifyChecksum(content: Buffer, checksums: string, name: string): void {
+    const entry = checksums
+        .split('\n')
+        .map((line) => line.trim().match(/^([0-9a-f]{64})\s+\*?(.+)$/i))
+        .find((match) => match && basename(match[2]) === name);
+    if (!entry) {
+        throw new Error(`${CHECKSUMS_ASSET} has no checksum for ${name}`);
+    }
+    const actual = createHash('sha256').update(content).digest('hex');
+    if (actual !== entry[1].toLowerCase()) {
+        throw new Error(`Checksum mismatch for ${name}: expected ${entry[1]}, got ${actual}`);
+    }
+}
+
+/**
+ * @param data - Signed content
+ * @param signature - Detached signature, raw or base64
+ * @param publicKey - PEM public key, Ed25519 or RSA with SHA-256
+ * @throws If the signature is not valid for the content
+ */
+export function verifySignature(data: Buffer, signature: Buffer, publicKey: string): void {
+    const key = createPublicKey(publicKey);
+    const text = signature.toString('utf8').trim();
+    const raw = /^[A-Za-z0-9+/=\s]+$/.test(text) ? Buffer.from(text, 'base64') : signature;
+    const algorithm = key.asymmetricKeyType === 'ed25519' ? null : 'sha256';
+    if (!verify(algorithm, data, key, raw)) {
+        throw new Error(`Invalid signature on ${CHECKSUMS_ASSET}; refusing to update`);
+    }
+}
+
+interface GitHubRelease {
+    tag_name: string;
+    draft: boolean;
+    prerelease: boolean;
+    assets: Array<{ name: string; browser_download_url: string }>;
+}
+
+/**
+ * @param release - Release as returned by the GitHub API
+ * @returns The release with its assets by name
+ */
+function toRelease(release: GitHubRelease): Release {
+    return {
+        version: release.tag_name.replace(/^v/, ''),
+        tag: release.tag_name,
+        prerelease: release.prerelease,
+        assets: Object.fromEntries(release.assets.map((asset) => [asset.name, asset.browser_download_url]))
+    };
+}
+
+/**
+ * @param url - GitHub API URL
+ * @returns The response, after retrying rate limits and server errors
+ */
+function github(url: string): Promise<Response> {
+    const headers: Record<string, string> = { Accept: 'application/vnd.github+json', 'User-Agent': 'lsp-cli' };
+    if (process.env.GITHUB_TOKEN) {
+        headers.Authorization = `Bearer ${process.env.GITHUB_TOKEN}`;
+    }
+    return fetchWithRetry(url, { headers }, { maxRetries: 3 });
+}
+
+/**
+ * @param response - GitHub API response
+ * @returns Its JSON body
+ * @throws If the request failed
+ */
+async function json(response: Response): Promise<unknown> {
+    if (!response.ok) {
+        throw new Error(`GitHub API request failed: HTTP ${response.status}`);
+    }
+    return response.json();
+}
+
+/**
+ * @param url - Download URL of a release asset
+ * @returns Its content
+ */
+async function download(url: string): Promise<Buffer> {
+    const response = await fetchWithRetry(url, { headers: { 'User-Agent': 'lsp-cli' } }, { maxRetries: 3 });
+    if (!response.ok) {
+        throw new Error(`Downloading ${url} failed: HTTP ${response.status}`);
+    }
+    return Buffer.from(await response.arrayBuffer());
+}
+
+/**
+ * Replaces a file atomically, keeping its permissions: the content goes to a temporary file in the
+ * same directory, which is renamed over the original.
+ *
+ * @param path - File to replace
+ * @param content - New content
+ */
+function replaceFile(path: string, content: Buffer): void {
+    const mode = statSync(path).mode & 0o777;
+    const temporary = join(dirname(path), `.${basename(path)}.${process.pid}.update`);
+    try {
+        const descriptor = openSync(temporary, 'w', mode);
+        try {
+            writeFileSync(descriptor, content);
+            fsyncSync(descriptor);
+        } finally {
+            closeSync(descriptor);
+        }
+        // The umask may have dropped permission bits at creation
+        chmodSync(temporary, mode);
+        renameSync(temporary, path);
+    } catch (error) {
+        rmSync(temporary, { force: true });
+        throw error;
+    }
+}
@@ -1,14 +1,28 @@
 import { exec } from 'node:child_process';
-import { existsSync, mkdirSync, readdirSync } from 'node:fs';
+import { existsSync, mkdirSync, readdirSync, readFileSync } from 'node:fs';
 import { homedir } from 'node:os';
 import { join } from 'node:path';
 import { promisify } from 'node:util';
+import { ExitCode, ExitError } from './exit-codes';
 import type { Logger } from './logger';
 import type { ServerConfig, SupportedLanguage } from './types';
 import { downloadFile, extractArchive } from './utils';
 
 const execAsync = promisify(exec);
 
+/** Language server backing each language */
+export const SERVER_NAMES: { [key in SupportedLanguage]: string } = {
+    java: 'Eclipse JDT Language Server',
+    cpp: 'clangd',
+    c: 'clangd',
+    csharp: 'OmniSharp',
+    haxe: 'Haxe Language Server',
+    typescript: 'typescript-language-server',
+    dart: 'Dart Analysis Server',
+    rust: 'rust-analyzer',
+    python: 'Pyright'
+};
+
 export class ServerManager {
     private baseDir: string;
 
@@ -28,10 +42,19 @@ export class ServerManager {
         this.logger?.serverStatus(language, 'installing');
         mkdirSync(serverDir, { recursive: true });
 
-        if (config.installScript) {
-            await config.installScript(serverDir);
-        } else {
-            await this.downloadAndExtract(config.downloadUrl, serverDir);
+        try {
+            if (config.installScript) {
+                // Install scripts run package managers and builds that don't report progress
+                this.logger?.stage(`Installing ${language} LSP server`);
+                await config.installScript(serverDir);
+                this.logger?.stageDone();
+            } else {
+                await this.downloadAndExtract(config.downloadUrl, serverDir);
+            }
+        } catch (error) {
+            const message = error instanceof Error ? error.message : String(error);
+            this.logger?.stageFailed();
+            throw new ExitError(`Installing the ${language} LSP server failed: ${message}`, ExitCode.ServerInstallFailed);
         }
 
         return serverDir;
@@ -61,7 +84,66 @@ export class ServerManager {
         return { valid: true };
     }
 
-    private isServerInstalled(language: SupportedLanguage): boolean {
+    /**
+     * @param language - Language of the server
+     * @returns Directory the server is installed in, below `~/.lsp-cli/servers`
+     */
+    getServerDirectory(language: SupportedLanguage): string {
+        return join(this.baseDir, language);
+    }
+
+    /**
+     * Finds out the version of an installed server: from its package for npm-installed servers, from the
+     * pinned download for jdtls and OmniSharp, and by running it with `--version` otherwise.
+     *
+     * @param language - Language of the server
+     * @returns Version, or undefined if the server is not installed or doesn't run
+     */
+    async getServerVersion(language: SupportedLanguage): Promise<string | undefined> {
+        if (!this.isServerInstalled(language)) {
+            return undefined;
+        }
+        const serverDir = join(this.baseDir, language);
+        const packageVersion = (name: string) => {
+            try {
+                return JSON.parse(readFileSync(join(serverDir, 'node_modules', name, 'package.json'), 'utf8')).version;
+            } catch (_error) {
+                return undefined;
+            }
+        };
+        const run = async (command: string) => {
+            try {
+                const { stdout, stderr } = await execAsync(command, { timeout: 10_000 });
+                return (stdout || stderr).trim().split('\n')[0] || undefined;
+            } catch (_error) {
+                return undefined;
+            }
+        };
+
+        switch (language) {
+            case 'java':
+                return '1.31.0';
+            case 'csharp':
+                return '1.39.11';
+            case 'typescript':
+                return packageVersion('typescript-language-server');
+            case 'python':
+                return packageVersion('pyright');
+            case 'dart':
+                // The server ships with the SDK
+                return run('dart --version');
+            case 'haxe':
+                return undefined;
+            default:
+                return run(`"${this.getServerCommand(language)[0]}" --version`);
+        }
+    }
+
+    /**
+     * @param language - Language of the server
+     * @returns Whether the server's files are in place
+     */
+    isServerInstalled(language: SupportedLanguage): boolean {
         const serverDir = join(this.baseDir, language);
 
         switch (language) {
@@ -221,8 +303,22 @@ exec rust-analyzer "$@"
         const filename = url.split('/').pop()!;
         const downloadPath = join(targetDir, filename);
 
-        await downloadFile(url, downloadPath);
+        this.logger?.stage(`Downloading ${filename}`);
+        let size = 0;
+        await downloadFile(url, downloadPath, (received, total) => {
+            size = received;
+            if (total !== undefined) {
+                this.logger?.progress(received, total, formatBytes(received));
+            } else {
+                this.logger?.activity(formatBytes(received));
+            }
+        });
+        this.logger?.activity(undefined);
+        this.logger?.stageDone(formatBytes(size));
+
+        this.logger?.stage(`Extracting ${filename}`);
         await extractArchive(downloadPath, targetDir);
+        this.logger?.stageDone();
 
         // Handle clangd directory renaming
         const clangdVersionedDir = readdirSync(targetDir).find((name) => name.startsWith('clangd_'));
@@ -308,3 +404,8 @@ exec rust-analyzer "$@"
         }
     }
 }
+
+/** `12.3 MB`, `512 KB` */
+function formatBytes(bytes: number): string {
+    return bytes >= 1024 * 1024 ? `${(bytes / (1024 * 1024)).toFixed(1)} MB` : `${Math.round(bytes / 1024)} KB`;
+}
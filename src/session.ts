@@ -0,0 +1,224 @@
+import { isAbsolute, resolve } from 'node:path';
+import { ExitCode, ExitError } from './exit-codes';
+import { LanguageClient, type LanguageClientOptions } from './language-client';
+import type { Logger } from './logger';
+import { querySymbols, type SymbolQuery } from './query';
+import type {
+    AnalysisOutput,
+    DiagnosticInfo,
+    HoverInfo,
+    Position,
+    SourceLocation,
+    SupportedLanguage,
+    SymbolInfo
+} from './types';
+import { checkToolchain } from './utils';
+
+/**
+ * A live LSP session for one workspace and language.
+ * Keeps the language server running between requests, so repeated analyses and
+ * per-position queries don't pay the server startup and indexing cost each time.
+ */
+export class Session {
+    readonly directory: string;
+    private client?: LanguageClient;
+    private starting?: Promise<LanguageClient>;
+    private starts = 0;
+
+    /**
+     * @param language - Language of the workspace
+     * @param directory - Workspace root; resolved to an absolute path
+     * @param logger - Logger for server and extraction diagnostics
+     * @param clientOptions - Options for the language client, e.g. a tracer
+     */
+    constructor(
+        readonly language: SupportedLanguage,
+        directory: string,
+        private logger: Logger,
+        private clientOptions: LanguageClientOptions = {}
+    ) {
+        this.directory = resolve(directory);
+    }
+
+    /**
+     * Extracts all symbols of the workspace.
+     *
+     * @returns The analysis result in the same shape the CLI writes to its output file
+     */
+    async analyze(): Promise<AnalysisOutput> {
+        const client = await this.getClient();
+        const symbols = await client.analyzeDirectory();
+        const cargo = client.getCargoWorkspace();
+        const orphanedDocs = client.getOrphanedDocs();
+        const fileEncodings = client.getFileEncodings();
+        const fileAliases = client.getFileAliases();
+        return {
+            language: this.language,
+            directory: this.directory,
+            positionEncoding: client.getPositionEncoding(),
+            symbols,
+            ...(cargo && { cargo }),
+            ...(this.clientOptions.trailingDocs === 'orphan' && { orphanedDocs }),
+            ...(Object.keys(fileEncodings).length > 0 && { fileEncodings }),
+            ...(Object.keys(fileAliases).length > 0 && { fileAliases })
+        };
+    }
+
+    /**
+     * Extracts the symbols of a file from the given content, e.g. a staged or committed version.
+     * The server keeps analyzing that content for the file until it is analyzed again.
+     *
+     * @param file - File path, absolute or relative to the workspace root
+     * @param content - Content to analyze
+     */
+    async analyzeContent(file: string, content: string): Promise<SymbolInfo[]> {
+        const client = await this.getClient();
+        return client.analyzeContent(this.resolveFile(file), content);
+    }
+
+    /**
+     * Closes a file opened by {@link analyzeContent}, so the server goes back to its on-disk content,
+     * or forgets it if it doesn't exist.
+     *
+     * @param file - File path, absolute or relative to the workspace root
+     */
+    async closeFile(file: string): Promise<void> {
+        await this.client?.closeDocument(this.resolveFile(file));
+    }
+
+    /**
+     * Returns the diagnostics the server reported for files opened so far, i.e. after {@link analyze}
+     * every source file of the workspace.
+     */
+    async diagnostics(): Promise<DiagnosticInfo[]> {
+        const client = await this.getClient();
+        return client.collectDiagnostics();
+    }
+
+    /**
+     * Analyzes the workspace and returns the symbols matching a query.
+     * To run several queries against one analysis, call {@link analyze} once and use `querySymbols`.
+     *
+     * @param query - Criteria that must all match
+     * @returns Matching symbols in document order, nested symbols included
+     */
+    async query(query: SymbolQuery): Promise<SymbolInfo[]> {
+        const output = await this.analyze();
+        return querySymbols(output.symbols, query);
+    }
+
+    /**
+     * @param file - File path, absolute or relative to the workspace root
+     * @param position - 0-based line/character position
+     */
+    async definition(file: string, position: Position): Promise<SourceLocation[]> {
+        const client = await this.getClient();
+        return client.getDefinitionAt(this.resolveFile(file), position);
+    }
+
+    /**
+     * @param file - File path, absolute or relative to the workspace root
+     * @param position - 0-based line/character position
+     */
+    async hover(file: string, position: Position): Promise<HoverInfo | undefined> {
+        const client = await this.getClient();
+        return client.getHoverAt(this.resolveFile(file), position);
+    }
+
+    /**
+     * @param file - File path, absolute or relative to the workspace root
+     * @param position - 0-based line/character position
+     * @param includeDeclaration - Whether the declaration itself is part of the result
+     */
+    async references(file: string, position: Position, includeDeclaration = true): Promise<SourceLocation[]> {
+        const client = await this.getClient();
+        return client.getReferencesAt(this.resolveFile(file), position, includeDeclaration);
+    }
+
+    /**
+     * Shuts down the language server. The session can be reused afterwards and will start a new server.
+     */
+    async close(): Promise<void> {
+        const client = this.client;
+        this.client = undefined;
+        this.starting = undefined;
+        await client?.stop();
+    }
+
+    private async getClient(): Promise<LanguageClient> {
+        if (this.client) {
+            return this.client;
+        }
+
+        // Concurrent requests share one startup instead of spawning several servers
+        if (!this.starting) {
+            this.starting = this.startClient().catch((error) => {
+                this.starting = undefined;
+                throw error;
+            });
+        }
+
+        this.client = await this.starting;
+        return this.client;
+    }
+
+    private async startClient(): Promise<LanguageClient> {
+        const toolchainResult = await checkToolchain(this.language);
+        if (!toolchainResult.installed) {
+            throw new ExitError(
+                `Required toolchain not found for ${this.language}\n${toolchainResult.message}`,
+                ExitCode.ToolchainMissing
+            );
+        }
+
+        const client = new LanguageClient(this.language, this.directory, this.logger, this.clientOptions);
+        await client.start();
+        if (this.starts++ > 0) {
+            // A new server after close()
+            this.logger.event('server.restarted', { language: this.language, directory: this.directory }, 'info');
+        }
+        return client;
+    }
+
+    private resolveFile(file: string): string {
+        return isAbsolute(file) ? file : resolve(this.directory, file);
+    }
+}
+
+/**
+ * Keeps one session per (language, directory) pair for the server modes.
+ */
+export class SessionPool {
+    private sessions = new Map<string, Session>();
+
+    /**
+     * @param logger - Logger for all sessions
+     * @param clientOptions - Options for the language clients of all sessions
+     */
+    constructor(
+        private logger: Logger,
+        private clientOptions: LanguageClientOptions = {}
+    ) {}
+
+    /**
+     * @param language - Language of the workspace
+     * @param directory - Workspace root; resolved to an absolute path
+     * @returns The existing session for this workspace, or a new one
+     */
+    get(language: SupportedLanguage, directory: string): Session {
+        const root = resolve(directory);
+        const key = `${language}:${root}`;
+        let session = this.sessions.get(key);
+        if (!session) {
+            session = new Session(language, root, this.logger, this.clientOptions);
+            this.sessions.set(key, session);
+        }
+        return session;
+    }
+
+    async closeAll(): Promise<void> {
+        const sessions = [...this.sessions.values()];
+        this.sessions.clear();
+        await Promise.all(sessions.map((session) => session.close()));
+    }
+}
@@ -0,0 +1,175 @@
+import type { SupportedLanguage } from './types';
+
+// Declarations whose header spans more lines are left to the hover fallback
+const MAX_HEADER_LINES = 30;
+
+// Modifiers and declaration keywords preceding the type in `Type name` declarations
+const MODIFIERS = new Set([
+    'public',
+    'private',
+    'protected',
+    'internal',
+    'static',
+    'final',
+    'const',
+    'constexpr',
+    'readonly',
+    'volatile',
+    'transient',
+    'late',
+    'var',
+    'val',
+    'let',
+    'extern',
+    'mutable',
+    'inline'
+]);
+
+/**
+ * Extracts the complete declaration header of a symbol from the source: everything from the
+ * declaration line up to the body or terminating `;`, including generic parameters, bounds,
+ * multi-line parameter lists and where clauses. Comments are dropped and whitespace is collapsed.
+ *
+ * @param lines - Source lines of the file
+ * @param line - 0-based line of the symbol's name
+ * @param language - Language of the file
+ * @returns The header, e.g. `impl<T, U, V> ComplexGeneric<T, U, V> where T: Clone + Send + Sync, U: Default`,
+ * or undefined if it doesn't end within a few lines
+ */
+export function extractSignature(lines: string[], line: number, language: SupportedLanguage): string | undefined {
+    const parts: string[] = [];
+    // Template parameter lists are written on the line above the declaration
+    if (language === 'cpp' && /^\s*template\s*</.test(lines[line - 1] ?? '')) {
+        parts.push(stripLineComment(lines[line - 1], language));
+    }
+
+    let depth = 0;
+    for (let index = line; index < lines.length && index - line < MAX_HEADER_LINES; index++) {
+        const text = stripLineComment(lines[index], language);
+        for (let column = 0; column < text.length; column++) {
+            const char = text[column];
+            if (char === '"' || char === "'" || char === '`') {
+                column = skipString(text, column, language);
+            } else if (char === '(' || char === '[' || char === '<') {
+                depth++;
+            } else if (char === ')' || char === ']' || (char === '>' && !/[-=]/.test(text[column - 1] ?? ''))) {
+                depth = Math.max(depth - 1, 0);
+            } else if (depth === 0 && (char === '{' || char === ';')) {
+                return normalize([...parts, text.slice(0, column)]);
+            } else if (depth === 0 && language === 'python' && char === ':' && text.slice(column + 1).trim() === '') {
+                return normalize([...parts, text.slice(0, column)]);
+            }
+        }
+        parts.push(text);
+    }
+    return undefined;
+}
+
+/**
+ * Extracts a declaration from hover contents, for headers too long to read from the source.
+ *
+ * @param contents - Hover contents as markdown
+ * @param name - Symbol name, to pick the right code block (servers like rust-analyzer put the module path first)
+ * @param language - Language of the symbol
+ * @returns The declaration without its body, or undefined if no code block mentions the name
+ */
+export function signatureFromHover(
+    contents: string,
+    name: string,
+    language: SupportedLanguage
+): string | undefined {
+    const blocks = [...contents.matchAll(/```\w*\n([\s\S]*?)```/g)].map((match) => match[1]);
+    const block = blocks.find((code) => code.includes(name));
+    if (!block) {
+        return undefined;
+    }
+    const lines = block.split('\n');
+    return extractSignature(lines, 0, language) ?? normalize(lines);
+}
+
+/**
+ * Extracts the type of a field, constant or variable from hover contents, covering the `name: Type`
+ * notation (Rust, TypeScript, Python, Haxe), the `Type name` notation (Java, C#, Dart, C++) and
+ * clangd's `Type: \`int\`` line. Initializers are dropped.
+ *
+ * @param contents - Hover contents as markdown
+ * @param name - Symbol name
+ * @returns The type, e.g. `AtomicUsize` for rust-analyzer's `static GLOBAL_COUNTER: AtomicUsize`, or undefined
+ */
+export function typeFromHover(contents: string, name: string): string | undefined {
+    const clangdType = /^Type:\s*`([^`]+)`/m.exec(contents)?.[1];
+    if (clangdType) {
+        return clangdType.trim();
+    }
+
+    const blocks = [...contents.matchAll(/```\w*\n([\s\S]*?)```/g)].map((match) => match[1]);
+    const escaped = name.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+    for (const line of (blocks.length > 0 ? blocks.join('\n') : contents).split('\n')) {
+        const declaration = withoutInitializer(line.trim());
+        // `static NAME: Type`, `(property) User.name?: string`, `var x:Int`
+        const annotated = new RegExp(`(?:^|[\\s.])${escaped}\\??\\s*:(?!:)\\s*(.+)$`).exec(declaration);
+        if (annotated) {
+            return annotated[1].replace(/;$/, '').trim();
+        }
+        // `private static final int MAX_SIZE`, `(field) string Config.Name`
+        const prefixed = new RegExp(`^(?:\\(\\w[\\w ]*\\)\\s*)?(.+?)\\s+(?:[\\w.]+\\.)?${escaped};?$`).exec(
+            declaration
+        );
+        if (prefixed) {
+            const type = prefixed[1]
+                .split(/\s+/)
+                .filter((word) => !MODIFIERS.has(word))
+                .join(' ');
+            if (type !== '') {
+                return type;
+            }
+        }
+    }
+    return undefined;
+}
+
+/** Cuts an initializer (` = value`) at the top level, keeping default type arguments like `<T = string>` */
+function withoutInitializer(declaration: string): string {
+    let depth = 0;
+    for (let index = 0; index < declaration.length; index++) {
+        const char = declaration[index];
+        if (char === '<' || char === '(' || char === '[' || char === '{') {
+            depth++;
+        } else if (char === ')' || char === ']' || char === '}' || (char === '>' && declaration[index - 1] !== '=')) {
+            depth--;
+        } else if (
+            depth === 0 &&
+            char === '=' &&
+            !/[=!<>]/.test(declaration[index - 1] ?? '') &&
+            !/[=>]/.test(declaration[index + 1] ?? '')
+        ) {
+            return declaration.slice(0, index).trim();
+        }
+    }
+    return declaration;
+}
+
+function stripLineComment(text: string, language: SupportedLanguage): string {
+    return text.replace(language === 'python' ? /(^|\s)#.*$/ : /(^|\s)\/\/.*$/, '');
+}
+
+function skipString(text: string, open: number, language: SupportedLanguage): number {
+    // Rust lifetimes like 'a aren't strings
+    if (text[open] === "'" && language === 'rust' && !/^'(\\.|.)'/.test(text.slice(open))) {
+        return open;
+    }
+    let index = open + 1;
+    while (index < text.length && text[index] !== text[open]) {
+        index += text[index] === '\\' ? 2 : 1;
+    }
+    return index;
+}
+
+function normalize(parts: string[]): string {
+    return parts
+        .join(' ')
+        .replace(/\/\*[\s\S]*?\*\//g, ' ')
+        .replace(/\s+/g, ' ')
+        .replace(/\s*,\s*$/, '')
+        .trim();
+}
@@ -0,0 +1,101 @@
+import { randomUUID } from 'node:crypto';
+import type { AnalysisOutput } from './types';
+
+export interface Snapshot {
+    id: string;
+    createdAt: string;
+    output: AnalysisOutput;
+}
+
+export interface SnapshotSummary {
+    id: string;
+    createdAt: string;
+    language: string;
+    directory: string;
+    symbolCount: number;
+}
+
+/** Called after a snapshot was added, with the previous snapshot of the same directory and language */
+export type SnapshotListener = (snapshot: Snapshot, previous: Snapshot | undefined) => void;
+
+/** Snapshots kept by default before the oldest are dropped */
+export const DEFAULT_SNAPSHOT_LIMIT = 100;
+
+/**
+ * In-memory store of analysis results produced by the server modes. Holds at most `limit`
+ * snapshots; adding one more drops the oldest.
+ */
+export class SnapshotStore {
+    private snapshots = new Map<string, Snapshot>();
+    private listeners: SnapshotListener[] = [];
+
+    /**
+     * @param limit - Maximum number of snapshots kept
+     */
+    constructor(private limit = DEFAULT_SNAPSHOT_LIMIT) {}
+
+    /**
+     * @param output - Analysis result to store
+     * @returns The stored snapshot with its newly assigned id
+     */
+    add(output: AnalysisOutput): Snapshot {
+        const previous = this.latest(output.directory, output.language);
+        const snapshot: Snapshot = { id: randomUUID(), createdAt: new Date().toISOString(), output };
+        this.snapshots.set(snapshot.id, snapshot);
+        // Maps iterate in insertion order, so the first snapshots are the oldest
+        for (const id of [...this.snapshots.keys()].slice(0, Math.max(this.snapshots.size - this.limit, 0))) {
+            this.snapshots.delete(id);
+        }
+        for (const listener of this.listeners) {
+            listener(snapshot, previous);
+        }
+        return snapshot;
+    }
+
+    /**
+     * Registers a listener for new snapshots, e.g. to notify webhooks.
+     */
+    onAdd(listener: SnapshotListener): void {
+        this.listeners.push(listener);
+    }
+
+    /**
+     * @returns The most recent snapshot of a directory and language
+     */
+    latest(directory: string, language: string): Snapshot | undefined {
+        let latest: Snapshot | undefined;
+        // Maps iterate in insertion order, so the last match is the newest
+        for (const snapshot of this.snapshots.values()) {
+            if (snapshot.output.directory === directory && snapshot.output.language === language) {
+                latest = snapshot;
+            }
+        }
+        return latest;
+    }
+
+    get(id: string): Snapshot | undefined {
+        return this.snapshots.get(id);
+    }
+
+    /**
+     * @param id - Id of the snapshot to drop
+     * @returns Whether there was such a snapshot
+     */
+    remove(id: string): boolean {
+        return this.snapshots.delete(id);
+    }
+
+    list(): SnapshotSummary[] {
+        return [...this.snapshots.values()].map(summarizeSnapshot);
+    }
+}
+
+export function summarizeSnapshot(snapshot: Snapshot): SnapshotSummary {
+    return {
+        id: snapshot.id,
+        createdAt: snapshot.createdAt,
+        language: snapshot.output.language,
+        directory: snapshot.output.directory,
+        symbolCount: snapshot.output.symbols.length
+    };
+}
@@ -0,0 +1,103 @@
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { basename, dirname, isAbsolute, join, normalize } from 'node:path';
+import type { SupportedLanguage, SymbolInfo } from './types';
+
+export interface SyntheticWorkspace {
+    /** Temporary workspace root */
+    directory: string;
+    /** Absolute path of the content inside the workspace */
+    file: string;
+    cleanup: () => void;
+}
+
+/** Minimal project files that make each language server treat the workspace as a project */
+function projectFiles(language: SupportedLanguage, file: string): Record<string, string> {
+    switch (language) {
+        case 'rust':
+            return {
+                'Cargo.toml': [
+                    '[package]',
+                    'name = "stdin"',
+                    'version = "0.0.0"',
+                    'edition = "2021"',
+                    '',
+                    '[lib]',
+                    `path = "${file}"`,
+                    ''
+                ].join('\n')
+            };
+        case 'typescript':
+            return { 'tsconfig.json': '{ "compilerOptions": { "strict": true, "allowJs": true, "noEmit": true } }\n' };
+        case 'dart':
+            return { 'pubspec.yaml': "name: stdin\nenvironment:\n  sdk: '>=3.0.0 <4.0.0'\n" };
+        case 'csharp':
+            return {
+                'stdin.csproj': [
+                    '<Project Sdk="Microsoft.NET.Sdk">',
+                    '  <PropertyGroup>',
+                    '    <TargetFramework>net8.0</TargetFramework>',
+                    '  </PropertyGroup>',
+                    '</Project>',
+                    ''
+                ].join('\n')
+            };
+        case 'haxe':
+            return { 'build.hxml': '-cp .\n--no-output\n' };
+        case 'cpp':
+            return { 'compile_flags.txt': '-std=c++17\n' };
+        case 'c':
+            return { 'compile_flags.txt': '-std=c11\n' };
+        default:
+            return {};
+    }
+}
+
+/**
+ * Creates a temporary workspace holding a single file, for analyzing content that doesn't exist on
+ * disk (e.g. read from stdin or an editor buffer).
+ *
+ * @param language - Language of the content
+ * @param filename - Path the content is analyzed as, relative to the workspace; it determines
+ * module paths and the extension the server expects
+ * @param content - File content
+ */
+export function createSyntheticWorkspace(
+    language: SupportedLanguage,
+    filename: string,
+    content: string
+): SyntheticWorkspace {
+    // Keep the file inside the workspace whatever path was given
+    const relativePath = normalize(isAbsolute(filename) ? basename(filename) : filename).replace(/^(\.\.[/\\])+/, '');
+    const directory = mkdtempSync(join(tmpdir(), 'lsp-cli-stdin-'));
+    const file = join(directory, relativePath);
+
+    for (const [name, text] of Object.entries(projectFiles(language, relativePath))) {
+        writeFileSync(join(directory, name), text);
+    }
+    mkdirSync(dirname(file), { recursive: true });
+    writeFileSync(file, content);
+
+    return { directory, file, cleanup: () => rmSync(directory, { recursive: true, force: true }) };
+}
+
+/**
+ * Rewrites the file paths of symbols, e.g. from a synthetic workspace to the path the content
+ * was given as.
+ *
+ * @param symbols - Symbols to update in place, including children and definitions
+ * @param from - Current path
+ * @param to - Replacement path
+ */
+export function relocateSymbols(symbols: SymbolInfo[], from: string, to: string): void {
+    const relocate = (symbol: SymbolInfo) => {
+        if (symbol.file === from) {
+            symbol.file = to;
+        }
+        if (symbol.definition?.file === from) {
+            symbol.definition.file = to;
+        }
+        symbol.children?.forEach(relocate);
+    };
+    symbols.forEach(relocate);
+}
@@ -0,0 +1,33 @@
+import { createHash } from 'node:crypto';
+import { relative } from 'node:path';
+import type { SymbolInfo } from './types';
+
+/**
+ * Sets `id` on symbols and their children, and `containerId` on the children, so flat listings of
+ * symbols can be put back into their tree. An id is derived from the file's path relative to the
+ * analyzed directory and the kinds and names of the symbol and its containers, so it stays the same
+ * across runs and edits that don't rename or move the symbol. Same-named siblings of the same kind,
+ * like overloads, are told apart by their order.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param filePath - Absolute path of the file
+ * @param workspaceRoot - Analyzed directory
+ */
+export function addSymbolIds(symbols: SymbolInfo[], filePath: string, workspaceRoot: string): void {
+    const visit = (siblings: SymbolInfo[], path: string, containerId: string | undefined) => {
+        const seen = new Map<string, number>();
+        for (const symbol of siblings) {
+            const segment = `${symbol.kind}:${symbol.name}`;
+            const occurrence = (seen.get(segment) ?? 0) + 1;
+            seen.set(segment, occurrence);
+            const symbolPath = `${path}\0${segment}${occurrence > 1 ? `#${occurrence}` : ''}`;
+            symbol.id = createHash('sha256').update(symbolPath).digest('hex').slice(0, 16);
+            symbol.containerId = containerId;
+            if (symbol.children) {
+                visit(symbol.children, symbolPath, symbol.id);
+            }
+        }
+    };
+    // Forward slashes, so ids match across platforms
+    visit(symbols, relative(workspaceRoot, filePath).split('\\').join('/'), undefined);
+}
@@ -0,0 +1,214 @@
+import { existsSync, mkdirSync, readFileSync, renameSync } from 'node:fs';
+import { homedir } from 'node:os';
+import { join } from 'node:path';
+import type { Logger } from './logger';
+import type { SymbolInfo } from './types';
+import { downloadFile } from './utils';
+
+export interface Tokenizer {
+    /** Encoding name, e.g. "cl100k_base", or "estimate" */
+    name: string;
+    count(text: string): number;
+}
+
+const CONTRACTIONS = "'(?:[sS]|[tT]|[rR][eE]|[vV][eE]|[mM]|[lL][lL]|[dD])";
+const UPPER = '[\\p{Lu}\\p{Lt}\\p{Lm}\\p{Lo}\\p{M}]';
+const LOWER = '[\\p{Ll}\\p{Lm}\\p{Lo}\\p{M}]';
+
+/** Pre-tokenization patterns of the tiktoken encodings, with case-insensitive groups expanded for JavaScript */
+const ENCODINGS: Record<string, { pattern: string; url: string }> = {
+    o200k_base: {
+        pattern: [
+            `[^\\r\\n\\p{L}\\p{N}]?${UPPER}*${LOWER}+(?:${CONTRACTIONS})?`,
+            `[^\\r\\n\\p{L}\\p{N}]?${UPPER}+${LOWER}*(?:${CONTRACTIONS})?`,
+            '\\p{N}{1,3}',
+            ' ?[^\\s\\p{L}\\p{N}]+[\\r\\n/]*',
+            '\\s*[\\r\\n]+',
+            '\\s+(?!\\S)',
+            '\\s+'
+        ].join('|'),
+        url: 'https://openaipublic.blob.core.windows.net/encoders/o200k_base.tiktoken'
+    },
+    cl100k_base: {
+        pattern: [
+            CONTRACTIONS,
+            '[^\\r\\n\\p{L}\\p{N}]?\\p{L}+',
+            '\\p{N}{1,3}',
+            ' ?[^\\s\\p{L}\\p{N}]+[\\r\\n]*',
+            '\\s*[\\r\\n]+',
+            '\\s+(?!\\S)',
+            '\\s+'
+        ].join('|'),
+        url: 'https://openaipublic.blob.core.windows.net/encoders/cl100k_base.tiktoken'
+    },
+    p50k_base: {
+        pattern: "'s|'t|'re|'ve|'m|'ll|'d| ?\\p{L}+| ?\\p{N}+| ?[^\\s\\p{L}\\p{N}]+|\\s+(?!\\S)|\\s+",
+        url: 'https://openaipublic.blob.core.windows.net/encoders/p50k_base.tiktoken'
+    }
+};
+
+/** Model name prefixes and the encoding they use */
+const MODEL_ENCODINGS: Array<[string, string]> = [
+    ['gpt-4o', 'o200k_base'],
+    ['gpt-4.1', 'o200k_base'],
+    ['gpt-5', 'o200k_base'],
+    ['o1', 'o200k_base'],
+    ['o3', 'o200k_base'],
+    ['o4', 'o200k_base'],
+    ['gpt-4', 'cl100k_base'],
+    ['gpt-3.5-turbo', 'cl100k_base'],
+    ['text-embedding-3', 'cl100k_base'],
+    ['text-embedding-ada-002', 'cl100k_base'],
+    ['text-davinci-003', 'p50k_base'],
+    ['code-davinci', 'p50k_base']
+];
+
+/** Heuristic used when no encoding is configured: about 4 characters per token for code */
+export const estimateTokenizer: Tokenizer = {
+    name: 'estimate',
+    count: (text) => Math.ceil(text.length / 4)
+};
+
+/**
+ * Byte-pair encoding compatible with tiktoken. Only counts tokens; token ids are not needed
+ * for budgeting, which allows caching counts per pre-tokenized piece.
+ */
+export class BpeTokenizer implements Tokenizer {
+    private pattern: RegExp;
+    private pieceCounts = new Map<string, number>();
+
+    /**
+     * @param name - Encoding name
+     * @param ranks - Merge rank per token, keyed by the token's bytes as a latin1 string
+     * @param pattern - Pre-tokenization regular expression source
+     */
+    constructor(
+        readonly name: string,
+        private ranks: Map<string, number>,
+        pattern: string
+    ) {
+        this.pattern = new RegExp(pattern, 'gu');
+    }
+
+    /**
+     * Parses a `.tiktoken` ranks file: one base64-encoded token and its rank per line.
+     */
+    static fromRanksFile(name: string, path: string, pattern: string): BpeTokenizer {
+        const ranks = new Map<string, number>();
+        for (const line of readFileSync(path, 'utf-8').split('\n')) {
+            const [token, rank] = line.split(' ');
+            if (token && rank) {
+                ranks.set(Buffer.from(token, 'base64').toString('latin1'), Number(rank));
+            }
+        }
+        return new BpeTokenizer(name, ranks, pattern);
+    }
+
+    count(text: string): number {
+        let total = 0;
+        for (const [piece] of text.matchAll(this.pattern)) {
+            let count = this.pieceCounts.get(piece);
+            if (count === undefined) {
+                count = this.countPiece(Buffer.from(piece, 'utf-8').toString('latin1'));
+                this.pieceCounts.set(piece, count);
+            }
+            total += count;
+        }
+        return total;
+    }
+
+    /** Standard BPE: repeatedly merge the adjacent pair with the lowest rank until none is in the vocabulary */
+    private countPiece(bytes: string): number {
+        if (this.ranks.has(bytes)) {
+            return 1;
+        }
+
+        const parts = Array.from(bytes);
+        while (parts.length > 1) {
+            let best = -1;
+            let bestRank = Number.POSITIVE_INFINITY;
+            for (let i = 0; i < parts.length - 1; i++) {
+                const rank = this.ranks.get(parts[i] + parts[i + 1]);
+                if (rank !== undefined && rank < bestRank) {
+                    best = i;
+                    bestRank = rank;
+                }
+            }
+            if (best < 0) {
+                break;
+            }
+            parts.splice(best, 2, parts[best] + parts[best + 1]);
+        }
+        return parts.length;
+    }
+}
+
+/**
+ * Resolves a tokenizer by model name (e.g. "gpt-4o"), encoding name (e.g. "cl100k_base"),
+ * path to a `.tiktoken` ranks file, or "estimate". Ranks files for known encodings are
+ * downloaded once to ~/.lsp-cli/tokenizers.
+ *
+ * @param spec - Model, encoding, ranks file or "estimate"
+ * @param logger - Logger for download progress
+ */
+export async function loadTokenizer(spec: string, logger: Logger): Promise<Tokenizer> {
+    if (spec === 'estimate') {
+        return estimateTokenizer;
+    }
+
+    if (spec.endsWith('.tiktoken')) {
+        // Custom ranks files are assumed to use the cl100k_base pre-tokenization unless named after an encoding
+        const encoding = Object.keys(ENCODINGS).find((name) => spec.endsWith(`${name}.tiktoken`)) ?? 'cl100k_base';
+        return BpeTokenizer.fromRanksFile(encoding, spec, ENCODINGS[encoding].pattern);
+    }
+
+    const encodingName = ENCODINGS[spec] ? spec : MODEL_ENCODINGS.find(([prefix]) => spec.startsWith(prefix))?.[1];
+    if (!encodingName) {
+        throw new Error(
+            `Unknown tokenizer '${spec}'. Use a model name (e.g. gpt-4o), an encoding ` +
+                `(${Object.keys(ENCODINGS).join(', ')}), a path to a .tiktoken file, or 'estimate'`
+        );
+    }
+
+    const encoding = ENCODINGS[encodingName];
+    const cacheDir = join(homedir(), '.lsp-cli', 'tokenizers');
+    const path = join(cacheDir, `${encodingName}.tiktoken`);
+    if (!existsSync(path)) {
+        mkdirSync(cacheDir, { recursive: true });
+        logger.info(`Downloading ${encodingName} tokenizer...`);
+        // Download to a temporary name so an interrupted download isn't mistaken for a complete file
+        await downloadFile(encoding.url, `${path}.download`);
+        renameSync(`${path}.download`, path);
+    }
+
+    return BpeTokenizer.fromRanksFile(encodingName, path, encoding.pattern);
+}
+
+/**
+ * Records token counts for each symbol's declaration line, documentation and full source range,
+ * recursing into children. A parent's body count includes its children's source.
+ *
+ * @param symbols - Symbols to enrich in place; their files are read from disk
+ * @param tokenizer - Tokenizer to count with
+ */
+export function addTokenCounts(symbols: SymbolInfo[], tokenizer: Tokenizer): void {
+    const fileLines = new Map<string, string[]>();
+
+    const annotate = (symbol: SymbolInfo) => {
+        let lines = fileLines.get(symbol.file);
+        if (!lines) {
+            lines = readFileSync(symbol.file, 'utf-8').split('\n');
+            fileLines.set(symbol.file, lines);
+        }
+
+        const body = lines.slice(symbol.range.start.line, symbol.range.end.line + 1).join('\n');
+        symbol.tokens = {
+            signature: tokenizer.count(symbol.preview),
+            ...(symbol.documentation ? { documentation: tokenizer.count(symbol.documentation) } : {}),
+            body: tokenizer.count(body)
+        };
+        symbol.children?.forEach(annotate);
+    };
+
+    symbols.forEach(annotate);
+}
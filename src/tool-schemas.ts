@@ -0,0 +1,446 @@
+import { readFileSync } from 'node:fs';
+import type { AnalysisOutput, SupportedLanguage, SymbolInfo } from './types';
+
+/** JSON Schema subset used for tool parameters */
+export interface JsonSchema {
+    type?: 'string' | 'integer' | 'number' | 'boolean' | 'array' | 'object';
+    description?: string;
+    enum?: string[];
+    items?: JsonSchema;
+    properties?: Record<string, JsonSchema>;
+    required?: string[];
+    additionalProperties?: boolean | JsonSchema;
+}
+
+/** Provider-neutral tool definition derived from one function or method */
+export interface ToolDefinition {
+    /** Qualified name made safe for tool names (`[a-zA-Z0-9_-]{1,64}`) */
+    name: string;
+    description: string;
+    parameters: JsonSchema;
+    /** Where the tool's implementation lives */
+    source: { file: string; line: number; qualifiedName: string };
+}
+
+export type ToolSchemaFormat = 'openai' | 'anthropic';
+
+export interface ToolSchemaOptions {
+    /** Qualified-name patterns to include, `*` matching any characters; default all public functions and methods */
+    include?: string[];
+    /** Qualified-name patterns to exclude */
+    exclude?: string[];
+}
+
+interface Parameter {
+    name: string;
+    type?: string;
+    optional: boolean;
+}
+
+const CALLABLE_KINDS = new Set(['function', 'method']);
+
+// Receivers and implicit parameters that callers don't supply
+const IMPLICIT_PARAMETERS = new Set(['self', 'cls', 'this', '&self', '&mut self', 'mut self']);
+
+/**
+ * Derives tool definitions from the public functions and methods of an analysis. Parameters are
+ * parsed from each declaration and described from `@param`, `Args:`, `<param>` or Rust
+ * `# Arguments` documentation; types are mapped to JSON Schema where they have an obvious equivalent.
+ *
+ * @param output - Analysis result; declarations are read from the source files
+ * @param options - Name patterns selecting the functions to expose
+ * @returns One definition per selected function, in document order
+ */
+export function generateToolDefinitions(output: AnalysisOutput, options: ToolSchemaOptions = {}): ToolDefinition[] {
+    const include = options.include?.map(patternToRegExp);
+    const exclude = options.exclude?.map(patternToRegExp) ?? [];
+    const fileLines = new Map<string, string[]>();
+    const definitions: ToolDefinition[] = [];
+    const names = new Set<string>();
+
+    const visit = (symbol: SymbolInfo, parents: string[], parent?: SymbolInfo) => {
+        const path = [...parents, symbol.name];
+        const qualifiedName = path.join('.');
+        if (
+            CALLABLE_KINDS.has(symbol.kind) &&
+            isPublic(symbol, output.language, parent) &&
+            (!include || include.some((pattern) => pattern.test(qualifiedName))) &&
+            !exclude.some((pattern) => pattern.test(qualifiedName))
+        ) {
+            definitions.push(toolDefinition(symbol, qualifiedName, output.language));
+        }
+        symbol.children?.forEach((child) => visit(child, path, symbol));
+    };
+    output.symbols.forEach((symbol) => visit(symbol, []));
+
+    return definitions;
+
+    function toolDefinition(symbol: SymbolInfo, qualifiedName: string, language: SupportedLanguage): ToolDefinition {
+        let lines = fileLines.get(symbol.file);
+        if (!lines) {
+            lines = readFileSync(symbol.file, 'utf-8').split('\n');
+            fileLines.set(symbol.file, lines);
+        }
+
+        const name = uniqueName(toolName(qualifiedName), names);
+        names.add(name);
+        return {
+            name,
+            description: describe(symbol),
+            parameters: parametersSchema(
+                parseParameters(declaration(lines, symbol), language),
+                parameterDocs(symbol.documentation ?? ''),
+                language
+            ),
+            source: { file: symbol.file, line: symbol.range.start.line + 1, qualifiedName }
+        };
+    }
+}
+
+/**
+ * Wraps tool definitions in the request format of an LLM provider.
+ *
+ * @param definitions - Definitions from `generateToolDefinitions`
+ * @param format - `openai` (Chat Completions `tools`) or `anthropic` (Messages API `tools`)
+ */
+export function formatToolSchemas(definitions: ToolDefinition[], format: ToolSchemaFormat): unknown[] {
+    return definitions.map(({ name, description, parameters }) =>
+        format === 'openai'
+            ? { type: 'function', function: { name, description, parameters } }
+            : { name, description, input_schema: parameters }
+    );
+}
+
+function patternToRegExp(pattern: string): RegExp {
+    const escaped = pattern.replace(/[.+?^${}()|[\]\\]/g, '\\$&').replace(/\*/g, '.*');
+    return new RegExp(`^${escaped}$`);
+}
+
+function isPublic(symbol: SymbolInfo, language: SupportedLanguage, parent?: SymbolInfo): boolean {
+    const preview = symbol.preview.trim();
+    if (/^(private|protected|internal)\b/.test(preview) || /\b(private|protected)\s/.test(preview.split('(')[0])) {
+        return false;
+    }
+    switch (language) {
+        case 'rust':
+            return /^pub\b/.test(preview.replace(/^(#\[[^\]]*\]\s*)+/, ''));
+        case 'python':
+        case 'dart':
+            return !symbol.name.startsWith('_');
+        case 'csharp':
+            // Class members are private unless declared otherwise; interface members are always public
+            return /\bpublic\b/.test(preview) || parent?.kind === 'interface';
+        case 'haxe':
+            // Class fields are private by default; module-level functions are public
+            return /\bpublic\b/.test(preview) || parent?.kind === 'interface' || !parent;
+        case 'typescript':
+            return !symbol.name.startsWith('#');
+        case 'c':
+        case 'cpp':
+            return !/^static\b/.test(preview);
+        default:
+            return true;
+    }
+}
+
+function toolName(qualifiedName: string): string {
+    return qualifiedName.replace(/[^a-zA-Z0-9_-]+/g, '_').replace(/^_+|_+$/g, '').slice(0, 64) || 'tool';
+}
+
+function uniqueName(name: string, taken: Set<string>): string {
+    // Overloads share a qualified name; number them
+    let candidate = name;
+    for (let index = 2; taken.has(candidate); index++) {
+        candidate = `${name.slice(0, 64 - String(index).length - 1)}_${index}`;
+    }
+    return candidate;
+}
+
+/** First paragraph of the documentation, without tags, or the declaration if undocumented */
+function describe(symbol: SymbolInfo): string {
+    const summary: string[] = [];
+    for (const line of (symbol.documentation ?? '').split('\n')) {
+        const text = line.replace(/<\/?summary>/g, '').trim();
+        if (/^([@\\]\w|<param|<returns|#\s|Args:|Arguments:|Parameters:|Returns:|Raises:)/.test(text)) {
+            break;
+        }
+        if (text === '' && summary.length > 0) {
+            break;
+        }
+        if (text !== '') {
+            summary.push(text.replace(/<see cref="([^"]+)"\s*\/>/g, '$1'));
+        }
+    }
+    return summary.join(' ') || symbol.preview.trim();
+}
+
+/** Source text from the symbol's start through its balanced parameter list */
+function declaration(lines: string[], symbol: SymbolInfo): string {
+    const text: string[] = [];
+    let depth = 0;
+    let opened = false;
+    for (let line = symbol.range.start.line; line <= symbol.range.end.line && line < lines.length; line++) {
+        text.push(lines[line]);
+        for (const char of lines[line]) {
+            if (char === '(') {
+                depth++;
+                opened = true;
+            } else if (char === ')') {
+                depth--;
+            }
+        }
+        if (opened && depth <= 0) {
+            break;
+        }
+    }
+    return text.join('\n');
+}
+
+function parseParameters(declarationText: string, language: SupportedLanguage): Parameter[] {
+    const open = declarationText.indexOf('(');
+    if (open < 0) {
+        return [];
+    }
+    const list = splitTopLevel(declarationText.slice(open + 1), ')');
+    const parameters: Parameter[] = [];
+
+    for (const raw of list) {
+        const text = raw.replace(/\/\*.*?\*\/|\/\/.*$/gm, '').trim();
+        if (text === '' || text === 'void' || text === '/' || IMPLICIT_PARAMETERS.has(text)) {
+            continue;
+        }
+        // Dart named ({...}) and optional positional ([...]) parameter groups
+        if (language === 'dart' && /^[{[]/.test(text)) {
+            const group = text.slice(1, text.lastIndexOf(text[0] === '{' ? '}' : ']'));
+            for (const member of splitTopLevel(`${group})`, ')')) {
+                const parameter = parseParameter(member.trim(), language);
+                if (parameter) {
+                    parameters.push({ ...parameter, optional: !/^required\s/.test(member.trim()) });
+                }
+            }
+            continue;
+        }
+        const parameter = parseParameter(text, language);
+        if (parameter && !IMPLICIT_PARAMETERS.has(parameter.name)) {
+            parameters.push(parameter);
+        }
+    }
+    return parameters;
+}
+
+/** Splits at commas outside brackets, strings and generics, stopping at the unbalanced terminator */
+function splitTopLevel(text: string, terminator: string): string[] {
+    const parts: string[] = [];
+    let depth = 0;
+    let quote = '';
+    let current = '';
+
+    for (let i = 0; i < text.length; i++) {
+        const char = text[i];
+        if (quote) {
+            if (char === quote && text[i - 1] !== '\\') {
+                quote = '';
+            }
+        } else if (char === '"' || char === "'" || char === '`') {
+            quote = char;
+        } else if ('([{<'.includes(char)) {
+            depth++;
+        } else if (char === '>' && text[i - 1] !== '=' && text[i - 1] !== '-') {
+            depth--;
+        } else if (')]}'.includes(char)) {
+            if (depth === 0 && char === terminator) {
+                break;
+            }
+            depth--;
+        } else if (char === ',' && depth === 0) {
+            parts.push(current);
+            current = '';
+            continue;
+        }
+        current += char;
+    }
+    parts.push(current);
+    return parts;
+}
+
+function parseParameter(text: string, language: SupportedLanguage): Parameter | undefined {
+    const [declared, defaultValue] = splitDefault(text);
+    let optional = defaultValue !== undefined;
+
+    switch (language) {
+        case 'typescript':
+        case 'haxe':
+        case 'python':
+        case 'rust': {
+            // name: Type, name?: Type (TypeScript), ?name: Type (Haxe), mut name: Type (Rust)
+            const colon = declared.search(/:(?!:)/);
+            let name = (colon >= 0 ? declared.slice(0, colon) : declared).trim();
+            const type = colon >= 0 ? declared.slice(colon + 1).trim() : undefined;
+            if (name.startsWith('*') || name.startsWith('...')) {
+                // Variadics: Python *args/**kwargs can't be expressed as named properties
+                if (language === 'python') {
+                    return undefined;
+                }
+                name = name.slice(3);
+                return { name, type: type ?? 'any[]', optional: true };
+            }
+            if (name.endsWith('?') || name.startsWith('?')) {
+                optional = true;
+                name = name.replace(/^\?|\?$/g, '');
+            }
+            name = name.replace(/^((public|private|protected|readonly|mut)\s+)+/, '').trim();
+            if (name.startsWith('{') || name.startsWith('[')) {
+                // Destructured parameter: describe it by its type only
+                name = 'options';
+            }
+            return { name, type, optional };
+        }
+        default: {
+            // Type name (C-family, Java, C#, Dart)
+            const cleaned = declared
+                .replace(/@\w+(\([^)]*\))?\s*/g, '')
+                .replace(/\[\[[^\]]*\]\]\s*/g, '')
+                .replace(/^((required|final|const|ref|out|in|params|this|scoped|covariant)\s+)+/, '')
+                .trim();
+            const match = /^(.*?)([A-Za-z_$][\w$]*)\s*(\[\s*\w*\s*\])?$/.exec(cleaned);
+            if (!match || !match[1].trim()) {
+                return undefined;
+            }
+            let type = `${match[1].trim()}${match[3] ? '[]' : ''}`;
+            if (type.endsWith('...')) {
+                type = `${type.slice(0, -3)}[]`;
+                optional = true;
+            }
+            return { name: match[2], type, optional };
+        }
+    }
+}
+
+function splitDefault(text: string): [string, string | undefined] {
+    let depth = 0;
+    for (let i = 0; i < text.length; i++) {
+        const char = text[i];
+        if ('([{<'.includes(char)) {
+            depth++;
+        } else if (')]}'.includes(char) || (char === '>' && text[i - 1] !== '=')) {
+            depth--;
+        } else if (char === '=' && depth === 0 && text[i + 1] !== '>' && text[i - 1] !== '=') {
+            return [text.slice(0, i).trim(), text.slice(i + 1).trim()];
+        }
+    }
+    return [text.trim(), undefined];
+}
+
+/** Parameter descriptions from the documentation conventions of the supported languages */
+function parameterDocs(documentation: string): Map<string, string> {
+    const docs = new Map<string, string>();
+    const patterns = [
+        // JSDoc, Javadoc, Doxygen, Haxe: @param name - description, @param {Type} name description
+        /^\s*[@\\]param(?:\s*\[\w+\])?\s+(?:\{[^}]*\}\s+)?\[?(\w+)[^\s\]]*\]?\s*(?:-\s*)?(.*)$/gm,
+        // C# XML documentation
+        /<param\s+name="(\w+)">([\s\S]*?)<\/param>/g,
+        // Rust: * `name` - description
+        /^\s*[*-]\s+`(\w+)`\s*[-:]\s*(.*)$/gm,
+        // Python (Google style): name (type): description
+        /^\s*(\w+)(?:\s*\([^)]*\))?:\s+(.+)$/gm
+    ];
+    for (const pattern of patterns) {
+        for (const [, name, description] of documentation.matchAll(pattern)) {
+            if (!docs.has(name) && description.trim()) {
+                docs.set(name, description.replace(/\s+/g, ' ').trim());
+            }
+        }
+    }
+    return docs;
+}
+
+function parametersSchema(
+    parameters: Parameter[],
+    docs: Map<string, string>,
+    language: SupportedLanguage
+): JsonSchema {
+    const properties: Record<string, JsonSchema> = {};
+    const required: string[] = [];
+
+    for (const parameter of parameters) {
+        const nullable = parameter.type !== undefined && isNullable(parameter.type);
+        const schema = parameter.type ? typeSchema(unwrapNullable(parameter.type), language) : {};
+        const description = docs.get(parameter.name);
+        properties[parameter.name] = description ? { ...schema, description } : schema;
+        if (!parameter.optional && !nullable) {
+            required.push(parameter.name);
+        }
+    }
+
+    return { type: 'object', properties, ...(required.length > 0 ? { required } : {}) };
+}
+
+function isNullable(type: string): boolean {
+    return /^(Option|Optional|Nullable|Null)<.*>$|^Optional\[.*\]$|\?$|\|\s*(null|undefined|None)\b|\bNone\s*\|/.test(
+        type.trim()
+    );
+}
+
+function unwrapNullable(type: string): string {
+    const trimmed = type.trim();
+    const wrapped = /^(?:Option|Optional|Nullable|Null)<(.*)>$|^Optional\[(.*)\]$/.exec(trimmed);
+    if (wrapped) {
+        return (wrapped[1] ?? wrapped[2]).trim();
+    }
+    return trimmed
+        .replace(/\?$/, '')
+        .split('|')
+        .map((part) => part.trim())
+        .filter((part) => !['null', 'undefined', 'None'].includes(part))
+        .join(' | ');
+}
+
+const SEQUENCE_TYPE = new RegExp(
+    '^(?:Vec|List|IList|IEnumerable|ICollection|Array|ArrayList|Set|HashSet|Iterable|vector|list|set|Sequence|' +
+        'ReadonlyArray)\\s*[<[](.*)[>\\]]$'
+);
+const MAP_TYPE = /^(Map|HashMap|BTreeMap|Dictionary|IDictionary|Record|dict|Dict|map|unordered_map)\b/;
+const STRING_TYPE = /^(string|String|str|char|Char|wstring|string_view|CharSequence|PathBuf|Path)$/;
+const INTEGER_TYPE = new RegExp(
+    '^(int|Int|Integer|long|Long|short|Short|byte|Byte|sbyte|uint|ulong|ushort|size_t|ssize_t|BigInteger|bigint|' +
+        '[iu](8|16|32|64|128|size)|u?int(8|16|32|64)_t)$'
+);
+
+/** Maps a source type to JSON Schema; unknown types keep their name in the description */
+function typeSchema(type: string, language: SupportedLanguage): JsonSchema {
+    const normalized = type
+        .replace(/^((const|final|readonly|mut|unsigned|signed)\s+)+/, '')
+        .replace(/^&(?:'\w+\s+)?(?:mut\s+)?/, '')
+        .replace(/\s*[&*]+$/, '')
+        .replace(/^(std::|java\.lang\.|System\.|typing\.)/, '')
+        .trim();
+
+    const array =
+        /^(.*)\[\]$/.exec(normalized) ?? SEQUENCE_TYPE.exec(normalized) ?? /^\[(.*?)(?:;\s*\d+)?\]$/.exec(normalized);
+    if (array) {
+        return { type: 'array', items: typeSchema(array[1], language) };
+    }
+
+    if (MAP_TYPE.test(normalized)) {
+        return { type: 'object' };
+    }
+    if (STRING_TYPE.test(normalized)) {
+        return { type: 'string' };
+    }
+    if (/^(bool|boolean|Boolean|Bool)$/.test(normalized)) {
+        return { type: 'boolean' };
+    }
+    if (INTEGER_TYPE.test(normalized)) {
+        return { type: 'integer' };
+    }
+    if (/^(float|Float|double|Double|decimal|Decimal|number|num|f32|f64)$/.test(normalized)) {
+        return { type: 'number' };
+    }
+    if (/^(object|Object|any|unknown|dynamic|Dynamic|Any)$/.test(normalized)) {
+        return {};
+    }
+    if (language === 'typescript' && /^('[^']*'|"[^"]*")(\s*\|\s*('[^']*'|"[^"]*"))*$/.test(normalized)) {
+        return { type: 'string', enum: normalized.split('|').map((literal) => literal.trim().slice(1, -1)) };
+    }
+    return { description: `Type: ${type}` };
+}
@@ -0,0 +1,309 @@
+import { AsyncLocalStorage } from 'node:async_hooks';
+import { randomBytes } from 'node:crypto';
+import type { Logger } from './logger';
+import { fetchWithRetry } from './utils';
+
+export type AttributeValue = string | number | boolean;
+
+/** A finished span, as handed to exporters */
+export interface SpanData {
+    traceId: string;
+    spanId: string;
+    parentSpanId?: string;
+    name: string;
+    /** Nanoseconds since the Unix epoch, as decimal strings like OTLP expects */
+    startTimeUnixNano: string;
+    endTimeUnixNano: string;
+    attributes: Record<string, AttributeValue>;
+    /** Set when the span's operation failed */
+    error?: string;
+}
+
+export interface SpanExporter {
+    export(spans: SpanData[]): Promise<void>;
+}
+
+/** Identifies a span in another process, e.g. the CI step that started lsp-cli */
+export interface SpanContext {
+    traceId: string;
+    spanId: string;
+}
+
+// Spans are exported in batches of at most this size, at the latest after FLUSH_INTERVAL_MS
+const MAX_BATCH_SIZE = 512;
+const FLUSH_INTERVAL_MS = 5000;
+
+function now(): string {
+    return BigInt(Math.round((performance.timeOrigin + performance.now()) * 1e6)).toString();
+}
+
+export class Span {
+    readonly spanId = randomBytes(8).toString('hex');
+    private readonly startTimeUnixNano = now();
+    private ended = false;
+
+    constructor(
+        private tracer: Tracer,
+        readonly name: string,
+        readonly traceId: string,
+        private parentSpanId: string | undefined,
+        private attributes: Record<string, AttributeValue>
+    ) {}
+
+    setAttribute(key: string, value: AttributeValue): void {
+        this.attributes[key] = value;
+    }
+
+    /**
+     * Finishes the span; later calls are ignored.
+     *
+     * @param error - Marks the span as failed with this error's message
+     */
+    end(error?: unknown): void {
+        if (this.ended) {
+            return;
+        }
+        this.ended = true;
+        this.tracer.record({
+            traceId: this.traceId,
+            spanId: this.spanId,
+            ...(this.parentSpanId && { parentSpanId: this.parentSpanId }),
+            name: this.name,
+            startTimeUnixNano: this.startTimeUnixNano,
+            endTimeUnixNano: now(),
+            attributes: this.attributes,
+            ...(error !== undefined && { error: error instanceof Error ? error.message : String(error) })
+        });
+    }
+}
+
+/**
+ * Creates spans and hands finished ones to an exporter in batches. Without an exporter, spans are
+ * discarded, so instrumented code doesn't need to check whether tracing is enabled.
+ */
+export class Tracer {
+    private context = new AsyncLocalStorage<Span>();
+    private buffer: SpanData[] = [];
+    private timer?: NodeJS.Timeout;
+    private pending = new Set<Promise<void>>();
+
+    /**
+     * @param exporter - Receives finished spans
+     * @param remoteParent - Parent of spans started without one, e.g. from a `TRACEPARENT` variable
+     */
+    constructor(
+        private exporter?: SpanExporter,
+        private remoteParent?: SpanContext
+    ) {}
+
+    /**
+     * @returns The span of the innermost enclosing `trace` call, if any
+     */
+    active(): Span | undefined {
+        return this.context.getStore();
+    }
+
+    /**
+     * Starts a span; it must be finished with `end()`.
+     *
+     * @param name - Operation name
+     * @param attributes - Initial attributes
+     * @param parent - Parent span; defaults to the active span, then the remote parent
+     */
+    startSpan(name: string, attributes: Record<string, AttributeValue> = {}, parent = this.active()): Span {
+        const traceId = parent?.traceId ?? this.remoteParent?.traceId ?? randomBytes(16).toString('hex');
+        return new Span(this, name, traceId, parent?.spanId ?? this.remoteParent?.spanId, { ...attributes });
+    }
+
+    /**
+     * Runs an operation in a span that is active for everything the operation calls.
+     *
+     * @param name - Operation name
+     * @param attributes - Initial attributes
+     * @param operation - Operation to run; receives the span to add attributes
+     * @param parent - Parent span; defaults to the active span, then the remote parent
+     * @returns The operation's result; the span is marked failed if it throws
+     */
+    async trace<T>(
+        name: string,
+        attributes: Record<string, AttributeValue>,
+        operation: (span: Span) => Promise<T>,
+        parent = this.active()
+    ): Promise<T> {
+        const span = this.startSpan(name, attributes, parent);
+        try {
+            const result = await this.context.run(span, () => operation(span));
+            span.end();
+            return result;
+        } catch (error) {
+            span.end(error);
+            throw error;
+        }
+    }
+
+    /** @internal Called by `Span.end` */
+    record(span: SpanData): void {
+        if (!this.exporter) {
+            return;
+        }
+        this.buffer.push(span);
+        if (this.buffer.length >= MAX_BATCH_SIZE) {
+            void this.flush();
+        } else if (!this.timer) {
+            // Don't keep the process alive just to export
+            this.timer = setTimeout(() => void this.flush(), FLUSH_INTERVAL_MS).unref();
+        }
+    }
+
+    /**
+     * Exports all finished spans and waits for exports still in flight. Call before exiting.
+     */
+    async flush(): Promise<void> {
+        clearTimeout(this.timer);
+        this.timer = undefined;
+        if (this.exporter && this.buffer.length > 0) {
+            const exported = this.exporter.export(this.buffer.splice(0));
+            this.pending.add(exported);
+            void exported.finally(() => this.pending.delete(exported));
+        }
+        await Promise.all(this.pending);
+    }
+}
+
+/**
+ * Exports spans with the OTLP/HTTP JSON protocol, as accepted by the OpenTelemetry Collector and
+ * most tracing backends on `/v1/traces`.
+ */
+export class OtlpExporter implements SpanExporter {
+    /**
+     * @param endpoint - Full traces URL, e.g. `http://localhost:4318/v1/traces`
+     * @param logger - Logger for export failures; they never fail the run
+     * @param headers - Extra request headers, e.g. for authentication
+     * @param resource - Resource attributes; `service.name` defaults to `lsp-cli`
+     */
+    constructor(
+        private endpoint: string,
+        private logger: Logger,
+        private headers: Record<string, string> = {},
+        private resource: Record<string, AttributeValue> = {}
+    ) {}
+
+    async export(spans: SpanData[]): Promise<void> {
+        const body = {
+            resourceSpans: [
+                {
+                    resource: { attributes: toKeyValues({ 'service.name': 'lsp-cli', ...this.resource }) },
+                    scopeSpans: [
+                        {
+                            scope: { name: 'lsp-cli' },
+                            spans: spans.map((span) => ({
+                                traceId: span.traceId,
+                                spanId: span.spanId,
+                                ...(span.parentSpanId && { parentSpanId: span.parentSpanId }),
+                                name: span.name,
+                                // SPAN_KIND_INTERNAL
+                                kind: 1,
+                                startTimeUnixNano: span.startTimeUnixNano,
+                                endTimeUnixNano: span.endTimeUnixNano,
+                                attributes: toKeyValues(span.attributes),
+                                // STATUS_CODE_ERROR or STATUS_CODE_UNSET
+                                status: span.error ? { code: 2, message: span.error } : { code: 0 }
+                            }))
+                        }
+                    ]
+                }
+            ]
+        };
+
+        try {
+            const response = await fetchWithRetry(
+                this.endpoint,
+                {
+                    method: 'POST',
+                    headers: { 'Content-Type': 'application/json', ...this.headers },
+                    body: JSON.stringify(body)
+                },
+                { maxRetries: 2 }
+            );
+            if (!response.ok) {
+                this.logger.warn(`Exporting ${spans.length} spans failed: HTTP ${response.status}`);
+            }
+        } catch (error) {
+            const message = error instanceof Error ? error.message : String(error);
+            this.logger.warn(`Exporting ${spans.length} spans to ${this.endpoint} failed: ${message}`);
+        }
+    }
+}
+
+function toKeyValues(attributes: Record<string, AttributeValue>) {
+    return Object.entries(attributes).map(([key, value]) => ({
+        key,
+        value:
+            typeof value === 'boolean'
+                ? { boolValue: value }
+                : typeof value === 'number'
+                  ? Number.isInteger(value)
+                      ? { intValue: value }
+                      : { doubleValue: value }
+                  : { stringValue: value }
+    }));
+}
+
+/**
+ * Parses a W3C `traceparent` header value, e.g. `00-<trace-id>-<span-id>-01`.
+ *
+ * @param value - Header or environment variable value
+ * @returns The parent span, or undefined if the value is missing or malformed
+ */
+export function parseTraceparent(value: string | undefined): SpanContext | undefined {
+    const match = value?.trim().match(/^[\da-f]{2}-([\da-f]{32})-([\da-f]{16})-[\da-f]{2}$/);
+    return match ? { traceId: match[1], spanId: match[2] } : undefined;
+}
+
+/** Parses `key=value,key=value` lists as used by OTEL_EXPORTER_OTLP_HEADERS and OTEL_RESOURCE_ATTRIBUTES */
+function parseKeyValueList(value: string | undefined): Record<string, string> {
+    const entries: Record<string, string> = {};
+    for (const pair of value?.split(',') ?? []) {
+        const separator = pair.indexOf('=');
+        if (separator > 0) {
+            entries[decodeURIComponent(pair.slice(0, separator).trim())] = decodeURIComponent(
+                pair.slice(separator + 1).trim()
+            );
+        }
+    }
+    return entries;
+}
+
+/**
+ * Creates a tracer configured like other OpenTelemetry SDKs. The endpoint comes from the argument,
+ * `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`, or `OTEL_EXPORTER_OTLP_ENDPOINT` plus `/v1/traces`; headers
+ * from `OTEL_EXPORTER_OTLP_HEADERS`; resource attributes from `OTEL_SERVICE_NAME` and
+ * `OTEL_RESOURCE_ATTRIBUTES`. Spans without a parent continue the trace in `TRACEPARENT`, so
+ * they show up under the CI job that ran lsp-cli.
+ *
+ * @param endpoint - OTLP/HTTP base URL (e.g. `http://localhost:4318`) or full traces URL
+ * @param logger - Logger for export failures
+ * @returns A tracer exporting via OTLP, or one that discards spans if no endpoint is configured
+ */
+export function createTracer(endpoint: string | undefined, logger: Logger): Tracer {
+    const env = process.env;
+    const tracesUrl = (base: string) =>
+        /\/v1\/traces\/?$/.test(base) ? base : `${base.replace(/\/$/, '')}/v1/traces`;
+    const url = endpoint
+        ? tracesUrl(endpoint)
+        : (env.OTEL_EXPORTER_OTLP_TRACES_ENDPOINT ??
+          (env.OTEL_EXPORTER_OTLP_ENDPOINT && tracesUrl(env.OTEL_EXPORTER_OTLP_ENDPOINT)));
+    if (!url) {
+        return new Tracer();
+    }
+
+    const resource = {
+        ...parseKeyValueList(env.OTEL_RESOURCE_ATTRIBUTES),
+        ...(env.OTEL_SERVICE_NAME && { 'service.name': env.OTEL_SERVICE_NAME })
+    };
+    logger.debug(`Exporting traces to ${url}`);
+    return new Tracer(
+        new OtlpExporter(url, logger, parseKeyValueList(env.OTEL_EXPORTER_OTLP_HEADERS), resource),
+        parseTraceparent(env.TRACEPARENT)
+    );
+}
@@ -0,0 +1,213 @@
+import type { SupportedLanguage, SymbolInfo } from './types';
+
+// Keywords introducing a type alias `<keyword> Name<Params> = Target`, per language
+const ALIAS_KEYWORDS: Record<SupportedLanguage, string[]> = {
+    rust: ['type'],
+    typescript: ['type'],
+    python: ['type'],
+    haxe: ['typedef'],
+    dart: ['typedef'],
+    csharp: ['using'],
+    cpp: ['using'],
+    c: [],
+    java: []
+};
+
+interface AliasDefinition {
+    symbol: SymbolInfo;
+    /** Names of the alias's generic parameters, e.g. `T` for `ModuleResult<T>` */
+    parameters: string[];
+}
+
+/**
+ * Sets `aliasOf` on type aliases to their target as written, with whitespace collapsed:
+ * `type ModuleResult<T> = Result<T, ModuleError>;` in Rust and TypeScript, Python's `type X = ...`
+ * and `X: TypeAlias = ...`, `typedef X = ...` in Haxe and Dart, `using X = ...` in C# and C++, and
+ * `typedef target Name;` in C and C++. Function pointer typedefs and typedefs of inline struct
+ * bodies are not aliases of a nameable type and are left out.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param lines - Source lines of the file
+ * @param language - Language of the file
+ */
+export function addAliasTargets(symbols: SymbolInfo[], lines: string[], language: SupportedLanguage): void {
+    const visit = (symbol: SymbolInfo) => {
+        // `range` leaves out doc comments above, which may mention `type Name` themselves
+        const text = lines
+            .slice(symbol.range.start.line, symbol.range.end.line + 1)
+            .join(' ')
+            .replace(/\s+/g, ' ');
+        const target = aliasTarget(text, symbol.name, language);
+        if (target) {
+            symbol.aliasOf = target;
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
+
+/**
+ * Sets `underlyingType` on type aliases: their target with every alias defined in the analyzed
+ * code expanded, recursively, substituting generic arguments, so `type Id = Handle<u32>` and
+ * `type Handle<T> = Arc<Mutex<T>>` give `Arc<Mutex<u32>>`. An alias name defined more than once
+ * is expanded to the definition in the same file, or not at all. Aliases referring to themselves,
+ * like `type Result<T> = std::result::Result<T, Error>`, stop the expansion.
+ *
+ * @param symbols - Symbols of the analyzed files, with `aliasOf` set; updated in place
+ */
+export function expandAliases(symbols: SymbolInfo[]): void {
+    const definitions = new Map<string, AliasDefinition[]>();
+    const collect = (symbol: SymbolInfo) => {
+        if (symbol.aliasOf) {
+            const named = definitions.get(symbol.name) ?? [];
+            named.push({ symbol, parameters: genericParameters(symbol) });
+            definitions.set(symbol.name, named);
+        }
+        symbol.children?.forEach(collect);
+    };
+    symbols.forEach(collect);
+
+    const lookup = (name: string, file: string) => {
+        const named = definitions.get(name) ?? [];
+        return named.find((definition) => definition.symbol.file === file) ?? (named.length === 1 ? named[0] : undefined);
+    };
+    const expand = (type: string, file: string, seen: Set<string>): string => {
+        let result = '';
+        let index = 0;
+        const reference = /(?:[A-Za-z_]\w*(?:::|\.))*([A-Za-z_]\w*)/g;
+        for (let match = reference.exec(type); match; match = reference.exec(type)) {
+            const definition = seen.has(match[1]) ? undefined : lookup(match[1], file);
+            if (!definition?.symbol.aliasOf) {
+                continue;
+            }
+            const { args, end } = typeArguments(type, match.index + match[0].length);
+            const target = substitute(definition.symbol.aliasOf, definition.parameters, args);
+            result += type.slice(index, match.index) + expand(target, definition.symbol.file, new Set([...seen, match[1]]));
+            index = end;
+            reference.lastIndex = end;
+        }
+        return result + type.slice(index);
+    };
+
+    for (const named of definitions.values()) {
+        for (const { symbol } of named) {
+            symbol.underlyingType = expand(symbol.aliasOf!, symbol.file, new Set([symbol.name]));
+        }
+    }
+}
+
+/** The text after `=` in an alias declaration, up to `;` at the top level */
+function aliasTarget(text: string, symbolName: string, language: SupportedLanguage): string | undefined {
+    const name = symbolName.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+    if ((language === 'c' || language === 'cpp') && /\btypedef\b/.test(text)) {
+        // `typedef unsigned long size_type;`, but not `typedef int (*callback)(int);` or `typedef struct {...} T;`
+        const typedef = new RegExp(`\\btypedef\\s+([^;{}()]+?)\\s*\\b${name}\\s*;`).exec(text);
+        return typedef?.[1].trim() || undefined;
+    }
+
+    const keywords = ALIAS_KEYWORDS[language];
+    const declaration =
+        (keywords.length > 0 && new RegExp(`\\b(?:${keywords.join('|')})\\s+${name}\\b`).exec(text)) ||
+        (language === 'python' && new RegExp(`^\\s*${name}\\s*:\\s*(?:typing\\.)?TypeAlias\\b`).exec(text));
+    if (!declaration) {
+        return undefined;
+    }
+    let index = declaration.index + declaration[0].length;
+    index = skipBalanced(text, index, language === 'python' ? '[' : '<');
+    const equals = /^\s*=(?!=)/.exec(text.slice(index));
+    if (!equals) {
+        return undefined;
+    }
+
+    let depth = 0;
+    let end = index + equals[0].length;
+    for (; end < text.length; end++) {
+        const char = text[end];
+        if ('([{<'.includes(char)) {
+            depth++;
+        } else if (')]}'.includes(char) || (char === '>' && text[end - 1] !== '=' && text[end - 1] !== '-')) {
+            depth--;
+        } else if (char === ';' && depth <= 0) {
+            break;
+        }
+    }
+    return text.slice(index + equals[0].length, end).trim() || undefined;
+}
+
+/** Index past a bracketed group starting at the first non-space character, or `index` if there is none */
+function skipBalanced(text: string, index: number, open: string): number {
+    const close = open === '<' ? '>' : ']';
+    const start = text.slice(index).search(/\S/);
+    if (start === -1 || text[index + start] !== open) {
+        return index;
+    }
+    let depth = 0;
+    for (let position = index + start; position < text.length; position++) {
+        if (text[position] === open) {
+            depth++;
+        } else if (text[position] === close && text[position - 1] !== '-' && --depth === 0) {
+            return position + 1;
+        }
+    }
+    return index;
+}
+
+/** Names of an alias's generic parameters, from `<T, E: Debug = X>` or Python's `[T]` after its name */
+function genericParameters(symbol: SymbolInfo): string[] {
+    const header = symbol.signature ?? symbol.preview;
+    const name = symbol.name.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+    const match = new RegExp(`\\b${name}\\s*[<[]`).exec(header);
+    if (!match) {
+        return [];
+    }
+    const open = match.index + match[0].length - 1;
+    const end = skipBalanced(header, open, header[open]);
+    return splitTopLevel(header.slice(open + 1, end - 1))
+        .map((parameter) => /^(?:const\s+)?('?[A-Za-z_]\w*)/.exec(parameter)?.[1])
+        .filter((parameter): parameter is string => parameter !== undefined);
+}
+
+/** Generic arguments following a reference at `index`, and the index past them */
+function typeArguments(type: string, index: number): { args: string[]; end: number } {
+    const open = type.slice(index).search(/\S/);
+    const char = type[index + open];
+    if (open === -1 || (char !== '<' && char !== '[')) {
+        return { args: [], end: index };
+    }
+    const end = skipBalanced(type, index, char);
+    return end === index ? { args: [], end } : { args: splitTopLevel(type.slice(index + open + 1, end - 1)), end };
+}
+
+/** Replaces the alias's parameters in its target by the arguments it is used with */
+function substitute(target: string, parameters: string[], args: string[]): string {
+    return parameters.reduce(
+        (result, parameter, position) =>
+            args[position] === undefined
+                ? result
+                : result.replace(new RegExp(`(?<![\\w'])${parameter}\\b`, 'g'), () => args[position]),
+        target
+    );
+}
+
+function splitTopLevel(text: string): string[] {
+    const parts: string[] = [];
+    let depth = 0;
+    let current = '';
+    for (let index = 0; index < text.length; index++) {
+        const char = text[index];
+        if ('([{<'.includes(char)) {
+            depth++;
+        } else if (')]}'.includes(char) || (char === '>' && text[index - 1] !== '-')) {
+            depth--;
+        } else if (char === ',' && depth === 0) {
+            parts.push(current.trim());
+            current = '';
+            continue;
+        }
+        current += char;
+    }
+    if (current.trim()) {
+        parts.push(current.trim());
+    }
+    return parts;
+}
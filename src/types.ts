@@ -2,9 +2,22 @@ export type SupportedLanguage = 'java' | 'cpp' | 'c' | 'csharp' | 'haxe' | 'type
 
 export interface Position {
     line: number;
+    /** Column in the output's `positionEncoding` */
     character: number;
+    /** Symbol ranges: column in UTF-16 code units */
+    utf16Character?: number;
+    /** Symbol ranges: column in UTF-8 bytes */
+    utf8Character?: number;
+    /** Symbol ranges: offset from the start of the file in bytes, in the file's encoding on disk */
+    byteOffset?: number;
 }
 
+/** Unit of `character` columns, as negotiated with the language server */
+export type PositionEncoding = 'utf-8' | 'utf-16';
+
+/** Encoding of a source file on disk; `utf-8-bom` is UTF-8 starting with a byte order mark */
+export type SourceEncoding = 'utf-8' | 'utf-8-bom' | 'utf-16le' | 'utf-16be' | 'shift_jis' | 'windows-1252';
+
 export interface Range {
     start: Position;
     end: Position;
@@ -12,21 +25,340 @@ export interface Range {
 
 export interface SymbolInfo {
     name: string;
+    /** Name including module and containers, in the language's notation, e.g. `crate::nested::ModuleStruct::new` */
+    qualifiedName?: string;
+    /** Stable id of the symbol, from its file and the kinds and names of it and its containers */
+    id?: string;
+    /** Id of the symbol this one is nested in; absent for top-level symbols */
+    containerId?: string;
     kind: string;
     file: string;
+    /** From the start of the name's line to the end of the definition */
     range: Range;
+    /** The whole definition as reported by the server, including doc comments and attributes for most servers */
+    fullRange?: Range;
+    /** The symbol's name; absent for servers reporting flat `SymbolInformation` */
+    selectionRange?: Range;
+    /** Jupyter notebooks: index of the code cell in the notebook's `cells`; `range` is relative to the cell */
+    cell?: number;
     preview: string;
+    /** Functions and types: the full declaration header with generics, bounds and where clauses */
+    signature?: string;
+    /** Fields, constants, properties and variables: the resolved type from hover, including inferred types */
+    type?: string;
+    /** Type aliases: the aliased type as written, e.g. `Result<T, ModuleError>` */
+    aliasOf?: string;
+    /** Type aliases: `aliasOf` with the aliases defined in the analyzed code expanded */
+    underlyingType?: string;
+    /** Access level from the declaration's modifiers, or the language's default where none is written */
+    visibility?: Visibility;
+    /** Functions, methods and constructors: `async`, `unsafe`, `const`, `extern` and generator flags that apply */
+    modifiers?: FunctionModifiers;
+    /** Items of `extern "C" { ... }` blocks: the block's ABI */
+    abi?: string;
+    /** Declared in a foreign block and defined outside the analyzed code, e.g. Rust FFI functions and statics */
+    foreign?: boolean;
+    /** Present when another symbol of the same file has the same name, e.g. overloads */
+    disambiguation?: DisambiguationInfo;
+    /** Doc comment or docstring with the comment markers stripped */
     documentation?: string;
+    /** The doc comment as written in the source, markers and indentation included */
+    rawDocumentation?: string;
+    /** The doc comment as markdown: markers and common indentation removed, JavaDoc tags and C# XML converted */
+    markdownDocumentation?: string;
     comments?: string[];
+    /** Regular (non-doc) comments directly above the declaration, from `--leading-comments` */
+    leadingComments?: string;
+    /** Source text of the full range, from `--include-body` */
+    body?: string;
+    /** Set when `body` was cut to `--include-body`'s line limit */
+    bodyTruncated?: boolean;
     supertypes?: string[];
+    /** Set when the server tags the symbol deprecated or a deprecation attribute or doc tag marks it */
+    deprecated?: DeprecationInfo;
+    /** Attributes, annotations or decorators on the declaration, e.g. `#[derive(...)]` or `@app.route(...)` */
+    attributes?: AttributeInfo[];
+    /** Rust: the macro invocation the symbol was generated by */
+    macro?: MacroOrigin;
+    /** Rust impl blocks and their items: the implementing type and trait */
+    impl?: ImplInfo;
+    /** Rust types: traits implemented for the type by impl blocks in the analyzed code */
+    traits?: string[];
+    /** Rust traits: types implementing the trait in the analyzed code */
+    implementors?: string[];
     children?: SymbolInfo[];
-    definition?: {
-        file: string;
-        range: Range;
-        preview?: string;
-    };
+    definition?: SourceLocation;
+    /** For re-exports like `pub use`, `from x import y` or `export { x } from`: where the item is defined */
+    reexportOf?: SourceLocation;
+    blame?: BlameInfo;
+    tokens?: TokenCounts;
+    /** Owning Cargo package (Rust) */
+    crate?: CrateInfo;
+    /** Cargo features referenced by the `cfg` attributes gating the symbol (Rust) */
+    features?: string[];
+    /** Third-party packages referenced in the symbol's source, from `--with-dependencies` */
+    dependencies?: string[];
+    /** Names of the `--feature-set` configurations the symbol was found in (Rust) */
+    configurations?: string[];
+    /** For symbols missing from some configuration: the `cfg` conditions on the symbol and its parents (Rust) */
+    cfg?: string;
+}
+
+/**
+ * Access levels across languages: `crate`, `super` and `restricted` are Rust's `pub(crate)`, `pub(super)`
+ * and `pub(in path)`; `package` is Java's package-private; `exported` and `local` distinguish
+ * TypeScript module members that are exported or not, and `local` also marks symbols nested in functions
+ */
+export type Visibility =
+    | 'public'
+    | 'protected'
+    | 'internal'
+    | 'protected internal'
+    | 'private protected'
+    | 'package'
+    | 'crate'
+    | 'super'
+    | 'restricted'
+    | 'private'
+    | 'exported'
+    | 'local';
+
+/**
+ * Function modifiers; only flags that apply are present. `const` covers Rust `const fn`, C++ `constexpr`
+ * and `consteval` and Dart `const` constructors; `extern` covers Rust and C++ `extern`, C# `extern`,
+ * Java `native`, Dart `external` and TypeScript `declare`; `generator` marks functions that yield,
+ * including C++ coroutines using `co_yield`, while those using `co_await` count as `async`
+ */
+export interface FunctionModifiers {
+    async?: boolean;
+    unsafe?: boolean;
+    const?: boolean;
+    extern?: boolean;
+    /** ABI of an `extern` function, e.g. `C` for Rust's `extern "C" fn` and `extern fn` */
+    abi?: string;
+    generator?: boolean;
+}
+
+/** What tells apart symbols of one file that share a name */
+export interface DisambiguationInfo {
+    /** Functions, methods and constructors: number of parameters, without receivers like `self` */
+    arity?: number;
+    /** First 8 hex digits of the SHA-256 of the declaration header, whitespace collapsed */
+    signatureHash: string;
+    /** Container declaring the symbol; `Trait for Type` or `Type` for Rust impl blocks */
+    owner?: string;
+}
+
+/** An attribute, annotation or decorator; `#[serde(rename_all = "camelCase")]` has name `serde` */
+export interface AttributeInfo {
+    /** Name as written, including any path, e.g. `derive`, `app.route` or `System.Obsolete` */
+    name: string;
+    /** Source text inside the parentheses, or after `=` for `#[name = value]` */
+    arguments?: string;
+}
+
+/** Deprecation of a symbol; both fields are absent if only the server's tag or a bare marker says so */
+export interface DeprecationInfo {
+    /** Version the symbol is deprecated since, e.g. from `#[deprecated(since = "1.2")]` */
+    since?: string;
+    /** Reason or replacement, e.g. from `note = "..."`, `[Obsolete("...")]` or `@deprecated Use bar()` */
+    message?: string;
+}
+
+/** A Rust macro invocation generating symbols, like `create_test_data!(TEST_VALUE, i32, 42);` */
+export interface MacroOrigin {
+    /** Macro name as written, including any path */
+    name: string;
+    /** From the macro name to the closing delimiter of its arguments */
+    invocation: Range;
+    /** The expanded source from rust-analyzer, with `--expand-macros` */
+    expansion?: string;
+}
+
+/** Header of a Rust impl block; `impl<T> Processor<T> for Wrapper<T>` has type `Wrapper` and trait `Processor` */
+export interface ImplInfo {
+    /** Implementing type, without path and generic arguments */
+    type: string;
+    /** Implemented trait, without path and generic arguments; absent for inherent impls */
+    trait?: string;
+    /** Header as written, up to the where clause */
+    signature: string;
+}
+
+export interface CrateInfo {
+    name: string;
+    version: string;
+    /** Target containing the symbol's file; `kind` is e.g. "lib", "bin", "test" or "example" */
+    target?: { name: string; kind: string };
+}
+
+/** A rust-analyzer feature configuration to extract symbols under, from `--feature-set` */
+export interface FeatureConfiguration {
+    name: string;
+    /** Features to enable, or `all` for `--all-features`; none besides the defaults if omitted */
+    features?: string[] | 'all';
+    noDefaultFeatures?: boolean;
+}
+
+export interface CargoPackageInfo {
+    name: string;
+    version: string;
+    manifestPath: string;
+    /** Feature name to the features and dependencies it enables */
+    features: Record<string, string[]>;
+    targets: Array<{ name: string; kinds: string[]; srcPath: string; requiredFeatures?: string[] }>;
+}
+
+/** Cargo workspace layout from `cargo metadata` */
+export interface CargoWorkspaceInfo {
+    workspaceRoot: string;
+    members: CargoPackageInfo[];
 }
 
+/** A third-party package and the code using it, from `--with-dependencies` */
+export interface DependencyUsage {
+    name: string;
+    /** Locked or installed version; the declared requirement if neither is available */
+    version?: string;
+    ecosystem: 'cargo' | 'npm';
+    /** Files importing the package, relative to the analyzed directory */
+    files: string[];
+    /** Number of symbols referencing the package, nested symbols included */
+    symbols: number;
+}
+
+/**
+ * What happens to doc comments directly below a definition and separated from the next one by a
+ * blank line: attach to the next symbol, attach to the symbol above, drop them, or report them
+ * as orphaned
+ */
+export type TrailingDocPolicy = 'next' | 'previous' | 'drop' | 'orphan';
+
+/** A doc comment not attached to any symbol, from `--trailing-docs orphan` */
+export interface OrphanedDoc {
+    file: string;
+    range: Range;
+    documentation: string;
+}
+
+/** Token counts of a symbol's parts, from `--count-tokens` */
+export interface TokenCounts {
+    signature: number;
+    documentation?: number;
+    /** Full source range, including nested symbols */
+    body: number;
+}
+
+/** Most recent commit touching a symbol's lines, from `git blame` */
+export interface BlameInfo {
+    author: string;
+    email: string;
+    commit: string;
+    /** Author date as ISO 8601 */
+    date: string;
+    /** Whole days between the author date and the analysis */
+    ageDays: number;
+}
+
+export interface SourceLocation {
+    file: string;
+    range: Range;
+    preview?: string;
+}
+
+export interface DiagnosticInfo {
+    file: string;
+    range: Range;
+    severity: 'error' | 'warning' | 'information' | 'hint';
+    message: string;
+    /** Tool that produced the diagnostic, e.g. "typescript" or "clang" */
+    source?: string;
+    code?: string;
+}
+
+export interface HoverInfo {
+    contents: string;
+    range?: Range;
+}
+
+export interface AnalysisOutput {
+    language: SupportedLanguage;
+    directory: string;
+    /** Unit of the `character` columns of all ranges */
+    positionEncoding?: PositionEncoding;
+    symbols: SymbolInfo[];
+    /** Diagnostics reported by the language server; only present when requested */
+    diagnostics?: DiagnosticInfo[];
+    /** Custom sections emitted by plugins, keyed by plugin name */
+    sections?: Record<string, unknown>;
+    /** Workspace members of Rust projects */
+    cargo?: CargoWorkspaceInfo;
+    /** Where the analyzed sources came from, for `lsp-cli analyze` targets other than local directories */
+    source?: AnalysisSource;
+    /** Third-party packages used by the analyzed code, from `--with-dependencies` */
+    dependencies?: DependencyUsage[];
+    /** Doc comments below definitions that no symbol received, from `--trailing-docs orphan` */
+    orphanedDocs?: OrphanedDoc[];
+    /** Feature configurations whose symbols were merged, from `--feature-set` (Rust) */
+    featureConfigurations?: FeatureConfiguration[];
+    /** Detected encodings of source files that are not plain UTF-8, keyed by absolute path */
+    fileEncodings?: Record<string, SourceEncoding>;
+    /** Other paths reaching an analyzed file through symlinks or hardlinks, keyed by the path symbols use */
+    fileAliases?: Record<string, string[]>;
+    /** Files and feature sets whose symbols are missing because their analysis failed; only present on failures */
+    errors?: ExtractionError[];
+    /** Who calls and references which function, from `--call-graph` */
+    edges?: CallGraphEdge[];
+}
+
+/** An end of a call graph edge: an extracted symbol, or code outside the output such as a library function */
+export interface CallGraphNode {
+    /** `id` of the symbol; absent for code outside the output */
+    id?: string;
+    name: string;
+    kind?: string;
+    file: string;
+    /** The symbol's name */
+    range: Range;
+}
+
+/** A call or other reference from one symbol to another, from `--call-graph` */
+export interface CallGraphEdge {
+    /** `call` for calls from the call hierarchy, `reference` for other uses, e.g. a function passed as a value */
+    kind: 'call' | 'reference';
+    from: CallGraphNode;
+    to: CallGraphNode;
+    /** Call sites or references in `from`'s file */
+    ranges: Range[];
+}
+
+/** A file, notebook or feature set that could not be analyzed; the rest of the run went on without it */
+export interface ExtractionError {
+    /** Absolute path of the file or notebook; absent when a whole feature set failed */
+    file?: string;
+    /** Feature set (`--feature-set`) the failure happened in */
+    configuration?: string;
+    /** Why the analysis failed, e.g. a request error or the language server crashing */
+    reason: string;
+    /** Last lines the language server wrote to stderr while the file was analyzed */
+    stderr?: string;
+}
+
+export type AnalysisSource =
+    | {
+          type: 'git';
+          url: string;
+          /** Requested branch, tag or commit */
+          ref?: string;
+          /** Commit that was analyzed */
+          commit: string;
+      }
+    | {
+          type: 'archive';
+          /** Absolute path or URL of the archive */
+          path: string;
+      };
+
 export interface ToolchainCheckResult {
     installed: boolean;
     message: string;
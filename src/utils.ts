@@ -1,16 +1,74 @@
 import { exec } from 'node:child_process';
-import { createWriteStream, existsSync, readdirSync, type Stats, statSync } from 'node:fs';
+import { randomBytes } from 'node:crypto';
+import {
+    closeSync,
+    createWriteStream,
+    existsSync,
+    fsyncSync,
+    openSync,
+    readdirSync,
+    readFileSync,
+    realpathSync,
+    renameSync,
+    rmSync,
+    type Stats,
+    statSync,
+    writeFileSync
+} from 'node:fs';
 import { get } from 'node:https';
-import { extname, join } from 'node:path';
+import { basename, dirname, extname, isAbsolute, join, relative, resolve, sep } from 'node:path';
 import { promisify } from 'node:util';
 import * as tar from 'tar';
 
 const StreamZip = require('node-stream-zip');
 
+import { getNotebookLanguage } from './notebooks';
 import type { ProjectFileCheckResult, SupportedLanguage, ToolchainCheckResult } from './types';
 
 const execAsync = promisify(exec);
 
+export const SUPPORTED_LANGUAGES: SupportedLanguage[] = [
+    'java',
+    'cpp',
+    'c',
+    'csharp',
+    'haxe',
+    'typescript',
+    'dart',
+    'rust',
+    'python'
+];
+
+export function isSupportedLanguage(language: string): language is SupportedLanguage {
+    return SUPPORTED_LANGUAGES.includes(language as SupportedLanguage);
+}
+
+/**
+ * Parses a listen address of the form "host:port" or ":port".
+ *
+ * @param address - Address as given on the command line, e.g. ":8080" or "0.0.0.0:8080"
+ * @returns Host (127.0.0.1 unless given, so other machines can only connect when asked for) and port
+ */
+export function parseListenAddress(address: string): { host: string; port: number } {
+    const separator = address.lastIndexOf(':');
+    const host = separator > 0 ? address.substring(0, separator).replace(/^\[(.*)\]$/, '$1') : '127.0.0.1';
+    const port = Number(separator >= 0 ? address.substring(separator + 1) : address);
+
+    if (!Number.isInteger(port) || port < 0 || port > 65535) {
+        throw new Error(`Invalid listen address '${address}', expected [host]:port`);
+    }
+
+    return { host, port };
+}
+
+/**
+ * @param host - Host name or IP address to listen on
+ * @returns Whether only this machine can connect to it
+ */
+export function isLoopbackHost(host: string): boolean {
+    return host === 'localhost' || host === '::1' || /^127\./.test(host);
+}
+
 export async function checkToolchain(language: SupportedLanguage): Promise<ToolchainCheckResult> {
     const platform = process.platform;
 
@@ -113,23 +171,24 @@ export async function checkToolchain(language: SupportedLanguage): Promise<Toolc
     }
 }
 
+/** Files marking a project of each language, as checked before an analysis */
+export const PROJECT_FILES: { [key in SupportedLanguage]: string[] } = {
+    java: ['pom.xml', 'build.gradle', 'build.gradle.kts', '.classpath'],
+    cpp: ['compile_commands.json', '.clangd', 'CMakeLists.txt'],
+    c: ['compile_commands.json', '.clangd', 'Makefile'],
+    csharp: ['.csproj', '.sln'],
+    haxe: ['build.hxml', 'haxe.json'],
+    typescript: ['tsconfig.json', 'jsconfig.json'],
+    dart: ['pubspec.yaml', 'analysis_options.yaml'],
+    rust: ['Cargo.toml'],
+    python: ['requirements.txt', 'pyproject.toml', 'setup.py', 'setup.cfg', 'Pipfile', 'environment.yml']
+};
+
 export async function checkProjectFiles(
     directory: string,
     language: SupportedLanguage
 ): Promise<ProjectFileCheckResult> {
-    const projectFiles: { [key in SupportedLanguage]: string[] } = {
-        java: ['pom.xml', 'build.gradle', 'build.gradle.kts', '.classpath'],
-        cpp: ['compile_commands.json', '.clangd', 'CMakeLists.txt'],
-        c: ['compile_commands.json', '.clangd', 'Makefile'],
-        csharp: ['.csproj', '.sln'],
-        haxe: ['build.hxml', 'haxe.json'],
-        typescript: ['tsconfig.json', 'jsconfig.json'],
-        dart: ['pubspec.yaml', 'analysis_options.yaml'],
-        rust: ['Cargo.toml'],
-        python: ['requirements.txt', 'pyproject.toml', 'setup.py', 'setup.cfg', 'Pipfile', 'environment.yml']
-    };
-
-    const required = projectFiles[language];
+    const required = PROJECT_FILES[language];
     const found: string[] = [];
 
     for (const file of required) {
@@ -165,14 +224,112 @@ export async function checkProjectFiles(
     };
 }
 
-export function getAllFiles(directory: string, extensions: string[]): string[] {
-    const files: string[] = [];
+/** File extensions analyzed per language */
+export const SOURCE_EXTENSIONS: { [key in SupportedLanguage]: string[] } = {
+    java: ['.java'],
+    cpp: ['.cpp', '.cxx', '.cc', '.hpp', '.hxx', '.hh', '.h'],
+    c: ['.c', '.h'],
+    csharp: ['.cs'],
+    haxe: ['.hx'],
+    dart: ['.dart'],
+    typescript: ['.ts', '.tsx', '.js'],
+    rust: ['.rs'],
+    python: ['.py', '.pyi']
+};
+
+/**
+ * Detects the languages of a directory by counting source files. Headers (`.h`) count towards C++
+ * if the directory has C++ sources, and towards C otherwise; Jupyter notebooks count towards their
+ * kernel's language.
+ *
+ * @param directory - Directory to scan, skipping build and dependency directories
+ * @param files - Files to count instead of scanning the directory, e.g. from `--files-from`
+ * @returns Languages with at least one source file, most files first
+ */
+export function detectLanguages(
+    directory: string,
+    files?: string[]
+): Array<{ language: SupportedLanguage; files: number }> {
+    const extensions = [...new Set(Object.values(SOURCE_EXTENSIONS).flat()), '.ipynb'];
+    const counts = new Map<string, number>();
+    const notebooks = new Map<SupportedLanguage, number>();
+    const candidates = files ? selectFiles(directory, files, extensions).files : getAllFiles(directory, extensions);
+    for (const file of candidates) {
+        const extension = extname(file).toLowerCase();
+        if (extension === '.ipynb') {
+            const language = file.includes('.ipynb_checkpoints') ? undefined : getNotebookLanguage(file);
+            if (language) {
+                notebooks.set(language, (notebooks.get(language) ?? 0) + 1);
+            }
+        } else {
+            counts.set(extension, (counts.get(extension) ?? 0) + 1);
+        }
+    }
+
+    const totals = SUPPORTED_LANGUAGES.map((language) => ({
+        language,
+        files: SOURCE_EXTENSIONS[language]
+            .filter((extension) => extension !== '.h')
+            .reduce((sum, extension) => sum + (counts.get(extension) ?? 0), notebooks.get(language) ?? 0)
+    }));
+    const headers = counts.get('.h') ?? 0;
+    const cpp = totals.find((entry) => entry.language === 'cpp')!;
+    (cpp.files > 0 ? cpp : totals.find((entry) => entry.language === 'c')!).files += headers;
+
+    return totals.filter((entry) => entry.files > 0).sort((a, b) => b.files - a.files);
+}
+
+/** Source files of a directory with each file listed once, however many links reach it */
+export interface DiscoveredFiles {
+    /** One path per distinct file, without symlinks below the directory where the file lies inside it */
+    files: string[];
+    /** Other paths reaching the same file through symlinks or hardlinks, keyed by its path in `files` */
+    aliases: Map<string, string[]>;
+    /** Files with the extensions that were left out: ignored, or listed but outside the directory or missing */
+    skipped: number;
+}
+
+/**
+ * Finds the files with the given extensions below a directory, following symlinks. Files reachable
+ * through several symlinks or hardlinks are listed once, under their canonical path: the real path
+ * rebased onto `directory` when it lies inside it, else the path it was first found at. Symlinks to
+ * a directory's own ancestors are not followed, so symlink cycles end; unresolved symlinks are skipped.
+ *
+ * @param directory - Directory to scan, skipping build and dependency directories
+ * @param extensions - Lowercase extensions including the dot, e.g. `.ts`
+ * @param ignore - Glob patterns of files and directories to skip, see {@link globToRegExp}
+ * @returns Canonical paths in discovery order, and the alias paths of files reached more than once
+ */
+export function discoverFiles(directory: string, extensions: string[], ignore: string[] = []): DiscoveredFiles {
+    const root = realpathSync(directory);
+    const ignored = ignore.map(globToRegExp);
+    // Paths per file identity (device and inode), in discovery order
+    const links = new Map<string, string[]>();
+    const ancestors = new Set<string>();
+    let skipped = 0;
 
     function scanDirectory(dir: string) {
-        const entries = readdirSync(dir);
+        let realDir: string;
+        try {
+            realDir = realpathSync(dir);
+        } catch (_error) {
+            return;
+        }
+        if (ancestors.has(realDir)) {
+            // Symlink back up the tree
+            return;
+        }
+        ancestors.add(realDir);
 
-        for (const entry of entries) {
+        for (const entry of readdirSync(dir)) {
             const fullPath = join(dir, entry);
+            const relativePath = relative(directory, fullPath).split(sep).join('/');
+            if (ignored.some((pattern) => pattern.test(relativePath))) {
+                if (extensions.includes(extname(entry).toLowerCase())) {
+                    skipped++;
+                }
+                continue;
+            }
 
             let stat: Stats;
             try {
@@ -190,24 +347,213 @@ export function getAllFiles(directory: string, extensions: string[]): string[] {
             } else if (stat.isFile()) {
                 const ext = extname(entry).toLowerCase();
                 if (extensions.includes(ext)) {
-                    files.push(fullPath);
+                    const identity = `${stat.dev}:${stat.ino}`;
+                    const paths = links.get(identity);
+                    if (!paths) {
+                        links.set(identity, [fullPath]);
+                    } else if (!paths.includes(fullPath)) {
+                        paths.push(fullPath);
+                    }
                 }
             }
         }
+        ancestors.delete(realDir);
     }
 
     scanDirectory(directory);
-    return files;
+
+    const canonical = (path: string) => {
+        const real = realpathSync(path);
+        const fromRoot = relative(root, real);
+        return fromRoot.startsWith('..') || isAbsolute(fromRoot) ? path : join(directory, fromRoot);
+    };
+    const files: string[] = [];
+    const aliases = new Map<string, string[]>();
+    for (const paths of links.values()) {
+        // Hardlinks have several real paths; prefer one that was found without going through a symlink
+        const file = paths.find((path) => canonical(path) === path) ?? canonical(paths[0]);
+        files.push(file);
+        const others = paths.filter((path) => path !== file);
+        if (others.length > 0) {
+            aliases.set(file, others);
+        }
+    }
+    return { files, aliases, skipped };
+}
+
+/**
+ * @param directory - Directory to scan, skipping build and dependency directories
+ * @param extensions - Lowercase extensions including the dot, e.g. `.ts`
+ * @param ignore - Glob patterns of files and directories to skip
+ * @returns Canonical paths of the matching files, each file once; see {@link discoverFiles}
+ */
+export function getAllFiles(directory: string, extensions: string[], ignore: string[] = []): string[] {
+    return discoverFiles(directory, extensions, ignore).files;
+}
+
+/**
+ * Reads a list of files, one path per line or NUL-separated like `git diff -z --name-only` prints it.
+ *
+ * @param source - File containing the list, or `-` for stdin
+ * @param base - Directory relative paths are resolved against
+ * @returns Absolute paths; blank lines are skipped
+ */
+export function readFileList(source: string, base = process.cwd()): string[] {
+    const text = readFileSync(source === '-' ? 0 : source, 'utf-8');
+    return text
+        .split(text.includes('\0') ? '\0' : /\r?\n/)
+        .map((line) => line.trim())
+        .filter((line) => line !== '')
+        .map((line) => resolve(base, line));
+}
+
+/**
+ * Picks the files to analyze from an explicit list, like {@link discoverFiles} does from a directory
+ * scan: files with the given extensions below the directory that aren't ignored, each file once
+ * under its canonical path. Paths that don't exist, e.g. files deleted in a diff, are skipped.
+ *
+ * @param directory - Analyzed directory; listed files outside it are skipped
+ * @param files - Absolute paths, as from {@link readFileList}
+ * @param extensions - Lowercase extensions including the dot, e.g. `.ts`
+ * @param ignore - Glob patterns of files and directories to skip, see {@link globToRegExp}
+ * @returns Canonical paths in list order, and the other listed paths of files listed more than once
+ */
+export function selectFiles(
+    directory: string,
+    files: string[],
+    extensions: string[],
+    ignore: string[] = []
+): DiscoveredFiles {
+    const root = realpathSync(directory);
+    const ignored = ignore.map(globToRegExp);
+    const links = new Map<string, string[]>();
+    let skipped = 0;
+    for (const file of files) {
+        if (!extensions.includes(extname(file).toLowerCase())) {
+            continue;
+        }
+        const relativePath = relative(directory, file).split(sep).join('/');
+        const outside = relativePath.startsWith('..') || isAbsolute(relativePath);
+        if (outside || ignored.some((pattern) => pattern.test(relativePath))) {
+            skipped++;
+            continue;
+        }
+        let stat: Stats;
+        try {
+            stat = statSync(file);
+        } catch (_error) {
+            skipped++;
+            continue;
+        }
+        if (!stat.isFile()) {
+            skipped++;
+            continue;
+        }
+        const identity = `${stat.dev}:${stat.ino}`;
+        const paths = links.get(identity);
+        if (!paths) {
+            links.set(identity, [file]);
+        } else if (!paths.includes(file)) {
+            paths.push(file);
+        }
+    }
+
+    const result: DiscoveredFiles = { files: [], aliases: new Map(), skipped };
+    for (const paths of links.values()) {
+        const fromRoot = relative(root, realpathSync(paths[0]));
+        const file = fromRoot.startsWith('..') || isAbsolute(fromRoot) ? paths[0] : join(directory, fromRoot);
+        result.files.push(file);
+        const others = paths.filter((path) => path !== file);
+        if (others.length > 0) {
+            result.aliases.set(file, others);
+        }
+    }
+    return result;
 }
 
-export async function downloadFile(url: string, destination: string): Promise<void> {
+/**
+ * Compiles a gitignore-style glob matched against paths relative to the analyzed directory, with
+ * forward slashes: `*` and `?` stay within a path segment, `**` spans segments, and `**\/` matches
+ * zero or more directories. Patterns without a slash, like `*.generated.ts` or `vendor`, match a
+ * file or directory name at any depth; a trailing slash is ignored.
+ *
+ * @param pattern - Glob pattern, e.g. `src/generated/**` or `*_test.go`
+ * @returns Regular expression matching the relative paths the pattern selects
+ */
+export function globToRegExp(pattern: string): RegExp {
+    const body = pattern.replace(/^\.?\//, '').replace(/\/$/, '');
+    let source = '';
+    for (let index = 0; index < body.length; index++) {
+        const char = body[index];
+        if (body.startsWith('**/', index)) {
+            source += '(?:.*/)?';
+            index += 2;
+        } else if (body.startsWith('**', index)) {
+            source += '.*';
+            index++;
+        } else if (char === '*') {
+            source += '[^/]*';
+        } else if (char === '?') {
+            source += '[^/]';
+        } else {
+            source += char.replace(/[.+^${}()|[\]\\]/g, '\\$&');
+        }
+    }
+    return new RegExp(`^${body.includes('/') ? '' : '(?:.*/)?'}${source}$`);
+}
+
+/**
+ * Writes an output file so that readers never see it half-written: the content goes to a temporary
+ * file next to it, which replaces the output only once it is complete and flushed to disk. If
+ * writing fails, the previous output is left as it was. `-` writes to stdout instead.
+ *
+ * @param path - Output file, or `-` for stdout
+ * @param content - Content to write; a final newline is added for stdout
+ */
+export async function writeOutput(path: string, content: string): Promise<void> {
+    if (path === '-') {
+        const text = content === '' || content.endsWith('\n') ? content : `${content}\n`;
+        // Resolves once the data is handed to the pipe, so an exit right after doesn't cut it off
+        return new Promise((resolve, reject) =>
+            process.stdout.write(text, (error) => (error ? reject(error) : resolve()))
+        );
+    }
+
+    const temporary = join(dirname(path), `.${basename(path)}.${process.pid}.${randomBytes(4).toString('hex')}.tmp`);
+    try {
+        const descriptor = openSync(temporary, 'w');
+        try {
+            writeFileSync(descriptor, content);
+            fsyncSync(descriptor);
+        } finally {
+            closeSync(descriptor);
+        }
+        renameSync(temporary, path);
+    } catch (error) {
+        rmSync(temporary, { force: true });
+        throw error;
+    }
+}
+
+/**
+ * Downloads a file, following redirects.
+ *
+ * @param url - URL to download
+ * @param destination - Path to write the file to
+ * @param onProgress - Called with the bytes received so far and the total from `Content-Length`, if sent
+ */
+export async function downloadFile(
+    url: string,
+    destination: string,
+    onProgress?: (received: number, total?: number) => void
+): Promise<void> {
     return new Promise((resolve, reject) => {
         const file = createWriteStream(destination);
 
         get(url, (response) => {
             if (response.statusCode === 302 || response.statusCode === 301) {
                 // Handle redirect
-                downloadFile(response.headers.location!, destination).then(resolve).catch(reject);
+                downloadFile(response.headers.location!, destination, onProgress).then(resolve).catch(reject);
                 return;
             }
 
@@ -216,6 +562,15 @@ export async function downloadFile(url: string, destination: string): Promise<vo
                 return;
             }
 
+            if (onProgress) {
+                const length = Number(response.headers['content-length']);
+                const total = length > 0 ? length : undefined;
+                let received = 0;
+                response.on('data', (chunk: Buffer) => {
+                    received += chunk.length;
+                    onProgress(received, total);
+                });
+            }
             response.pipe(file);
 
             file.on('finish', () => {
@@ -231,6 +586,56 @@ export async function downloadFile(url: string, destination: string): Promise<vo
     });
 }
 
+export interface RetryOptions {
+    /** Retries after the first attempt */
+    maxRetries: number;
+    /** Awaited before every attempt, e.g. for rate limiting */
+    beforeAttempt?: () => Promise<void>;
+    /** Called before waiting for a retry */
+    onRetry?: (reason: string, delayMs: number) => void;
+}
+
+/**
+ * Sends an HTTP request, retrying network errors, 429 and 5xx responses with exponential backoff
+ * (1s, 2s, 4s, ...) or the delay requested by the server's `Retry-After` header.
+ *
+ * @param url - Request URL
+ * @param init - Request options; the body must be reusable (string or buffer)
+ * @param options - Retry limit and hooks
+ * @returns The first response that isn't retried, or the last response once retries are exhausted
+ */
+export async function fetchWithRetry(url: string | URL, init: RequestInit, options: RetryOptions): Promise<Response> {
+    for (let attempt = 0; ; attempt++) {
+        await options.beforeAttempt?.();
+
+        let reason: string;
+        let retryAfterMs: number | undefined;
+        try {
+            const response = await fetch(url, init);
+            if (response.status !== 429 && response.status < 500) {
+                return response;
+            }
+            if (attempt >= options.maxRetries) {
+                return response;
+            }
+            reason = `HTTP ${response.status}`;
+            const retryAfter = Number(response.headers.get('retry-after'));
+            retryAfterMs = Number.isFinite(retryAfter) && retryAfter > 0 ? retryAfter * 1000 : undefined;
+            // Release the connection before waiting
+            await response.body?.cancel();
+        } catch (error) {
+            if (attempt >= options.maxRetries) {
+                throw error;
+            }
+            reason = error instanceof Error ? error.message : String(error);
+        }
+
+        const delay = retryAfterMs ?? 1000 * 2 ** attempt;
+        options.onRetry?.(reason, delay);
+        await new Promise((resolve) => setTimeout(resolve, delay));
+    }
+}
+
 export async function extractArchive(archivePath: string, destination: string): Promise<void> {
     const ext = extname(archivePath).toLowerCase();
 
@@ -238,7 +643,8 @@ export async function extractArchive(archivePath: string, destination: string):
         const zip = new StreamZip.async({ file: archivePath });
         await zip.extract(null, destination);
         await zip.close();
-    } else if (ext === '.gz' || ext === '.tgz') {
+    } else if (ext === '.gz' || ext === '.tgz' || ext === '.tar' || ext === '.crate') {
+        // crates.io .crate files are gzipped tarballs; tar detects the compression itself
         await tar.extract({
             file: archivePath,
             cwd: destination
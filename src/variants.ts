@@ -0,0 +1,161 @@
+import type { Position, PositionEncoding, Range, SupportedLanguage, SymbolInfo } from './types';
+
+// Variant payloads longer than this many lines are left alone
+const MAX_PAYLOAD_LINES = 50;
+
+interface PayloadField {
+    text: string;
+    start: { line: number; column: number };
+    end: { line: number; column: number };
+}
+
+/**
+ * Adds the payload of enum variants as `field` children: the fields of tuple variants named by their
+ * index (`Inactive(String)` gets field `0` of type `String`) and the named fields of struct variants
+ * (`Pending { timestamp: u64, priority: u8 }`) in Rust, and the arguments of Haxe enum constructors
+ * (`Pending(timestamp:Int)`). Variants the server already reports children for are left as they are.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param lines - Source lines of the file
+ * @param language - Language of the file
+ * @param encoding - Encoding of the server's `character` values, used for the fields' positions
+ */
+export function addVariantPayloads(
+    symbols: SymbolInfo[],
+    lines: string[],
+    language: SupportedLanguage,
+    encoding: PositionEncoding
+): void {
+    if (language !== 'rust' && language !== 'haxe') {
+        return;
+    }
+    const visit = (symbol: SymbolInfo) => {
+        if (symbol.kind === 'enumMember' && !symbol.children) {
+            const fields = parsePayload(lines, symbol).map((field, index) =>
+                toFieldSymbol(field, index, symbol, lines, language, encoding)
+            );
+            if (fields.length > 0) {
+                symbol.children = fields;
+            }
+        }
+        symbol.children?.forEach(visit);
+    };
+    symbols.forEach(visit);
+}
+
+/** The comma-separated parts inside the parentheses or braces following a variant's name */
+function parsePayload(lines: string[], variant: SymbolInfo): PayloadField[] {
+    const startLine = variant.range.start.line;
+    const nameColumn = lines[startLine]?.indexOf(variant.name) ?? -1;
+    if (nameColumn === -1) {
+        return [];
+    }
+
+    const fields: PayloadField[] = [];
+    let depth = 0;
+    let current: PayloadField | undefined;
+    const finish = () => {
+        if (current) {
+            fields.push(current);
+            current = undefined;
+        }
+    };
+
+    for (let line = startLine; line < lines.length && line - startLine < MAX_PAYLOAD_LINES; line++) {
+        const text = lines[line];
+        let column = line === startLine ? nameColumn + variant.name.length : 0;
+        for (; column < text.length; column++) {
+            const char = text[column];
+            if (text.startsWith('//', column)) {
+                break;
+            }
+            if (/\s/.test(char)) {
+                continue;
+            }
+            if (depth === 0) {
+                // Unit variants and explicit discriminants have no payload
+                if (char !== '(' && char !== '{') {
+                    return [];
+                }
+                depth = 1;
+                continue;
+            }
+            if (depth === 1 && char === '#' && text[column + 1] === '[') {
+                // Attributes on fields, e.g. `#[serde(rename = "ts")]`
+                column = skipAttribute(text, column);
+                continue;
+            }
+            if (char === '(' || char === '[' || char === '{' || char === '<') {
+                depth++;
+            } else if (char === ')' || char === ']' || char === '}' || (char === '>' && text[column - 1] !== '-')) {
+                depth--;
+                if (depth === 0) {
+                    finish();
+                    return fields;
+                }
+            } else if (depth === 1 && char === ',') {
+                finish();
+                continue;
+            }
+            if (!current) {
+                current = { text: '', start: { line, column }, end: { line, column } };
+            }
+            // Keep the text between two parts of the same field on different lines as one space
+            current.text += current.end.line !== line ? ` ${char}` : text.slice(current.end.column, column + 1);
+            current.end = { line, column: column + 1 };
+        }
+    }
+    return [];
+}
+
+function skipAttribute(text: string, start: number): number {
+    let depth = 0;
+    for (let column = start + 1; column < text.length; column++) {
+        if (text[column] === '[') {
+            depth++;
+        } else if (text[column] === ']' && --depth === 0) {
+            return column;
+        }
+    }
+    return text.length;
+}
+
+function toFieldSymbol(
+    field: PayloadField,
+    index: number,
+    variant: SymbolInfo,
+    lines: string[],
+    language: SupportedLanguage,
+    encoding: PositionEncoding
+): SymbolInfo {
+    const position = (line: number, column: number): Position => ({
+        line,
+        character: encoding === 'utf-8' ? Buffer.byteLength(lines[line].slice(0, column)) : column
+    });
+    const fullRange: Range = {
+        start: position(field.start.line, field.start.column),
+        end: position(field.end.line, field.end.column)
+    };
+
+    // `timestamp: u64`, Haxe `?timestamp:Int`; tuple fields are only a type
+    const named = /^(\??)([A-Za-z_]\w*)\s*:(?!:)\s*([\s\S]+)$/.exec(field.text);
+    const name = named ? named[2] : String(index);
+    const nameColumn = field.start.column + (named ? named[1].length : 0);
+    return {
+        name,
+        kind: 'field',
+        file: variant.file,
+        range: { start: { line: field.start.line, character: 0 }, end: fullRange.end },
+        fullRange,
+        selectionRange: named
+            ? {
+                  start: position(field.start.line, nameColumn),
+                  end: position(field.start.line, nameColumn + name.length)
+              }
+            : fullRange,
+        preview: lines[field.start.line].trim(),
+        type: (named ? named[3] : field.text).trim(),
+        // Fields of a variant are as visible as the variant itself
+        visibility: language === 'rust' ? variant.visibility : undefined
+    };
+}
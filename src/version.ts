@@ -0,0 +1,19 @@
+import { readFileSync } from 'node:fs';
+import { join } from 'node:path';
+
+// Set by esbuild from package.json; a bundle replaced by `self-update` has no matching package.json next to it
+declare const __LSP_CLI_VERSION__: string | undefined;
+
+/** Version of the running lsp-cli */
+export const VERSION: string = typeof __LSP_CLI_VERSION__ === 'string' ? __LSP_CLI_VERSION__ : packageVersion();
+
+/**
+ * @returns The version in the package.json above the source directory, for runs from a checkout
+ */
+function packageVersion(): string {
+    try {
+        return JSON.parse(readFileSync(join(__dirname, '..', 'package.json'), 'utf8')).version;
+    } catch (_error) {
+        return '0.0.0';
+    }
+}
@@ -0,0 +1,165 @@
+import type { SupportedLanguage, SymbolInfo, Visibility } from './types';
+
+// Kinds whose nested symbols are locals rather than members
+const CALLABLE_KINDS = new Set(['function', 'method', 'constructor']);
+
+// `pub(crate)`, `pub(super)`, `pub(self)` and `pub(in path)`
+const RUST_RESTRICTIONS: Record<string, Visibility> = {
+    crate: 'crate',
+    super: 'super',
+    self: 'private',
+    in: 'restricted'
+};
+
+/**
+ * Sets `visibility` on symbols and their children from the modifiers in their declarations,
+ * applying each language's defaults where none is written: Rust items are private unless `pub`,
+ * Java members package-private, C# members private and top-level types internal, C++ members
+ * follow the preceding access label.
+ *
+ * @param symbols - Symbols of one file; updated in place
+ * @param lines - Source lines of the file
+ * @param language - Language of the file
+ */
+export function addVisibility(symbols: SymbolInfo[], lines: string[], language: SupportedLanguage): void {
+    const visit = (symbol: SymbolInfo, parent?: SymbolInfo) => {
+        const visibility = visibilityOf(symbol, parent, lines, language);
+        // Macro-generated symbols already have theirs from the expansion
+        if (visibility && !symbol.visibility) {
+            symbol.visibility = visibility;
+        }
+        symbol.children?.forEach((child) => visit(child, symbol));
+    };
+    symbols.forEach((symbol) => visit(symbol));
+}
+
+function visibilityOf(
+    symbol: SymbolInfo,
+    parent: SymbolInfo | undefined,
+    lines: string[],
+    language: SupportedLanguage
+): Visibility | undefined {
+    const line = lines[symbol.range.start.line] ?? '';
+    // Modifiers come before the name; looking only there avoids matching parameter or type names
+    const nameIndex = line.indexOf(symbol.name);
+    const modifiers = nameIndex > 0 ? line.slice(0, nameIndex) : line;
+    const local = parent !== undefined && CALLABLE_KINDS.has(parent.kind);
+
+    switch (language) {
+        case 'rust':
+            return rustVisibility(symbol, parent, modifiers);
+        case 'java':
+            if (local) {
+                return 'local';
+            }
+            return (
+                keyword(modifiers, ['public', 'protected', 'private']) ??
+                // Interface and annotation members and enum constants are implicitly public
+                (parent?.kind === 'interface' || symbol.kind === 'enumMember' ? 'public' : 'package')
+            );
+        case 'csharp':
+            if (/\bprotected\s+internal\b|\binternal\s+protected\b/.test(modifiers)) {
+                return 'protected internal';
+            }
+            if (/\bprivate\s+protected\b|\bprotected\s+private\b/.test(modifiers)) {
+                return 'private protected';
+            }
+            if (local) {
+                return 'local';
+            }
+            return (
+                keyword(modifiers, ['public', 'internal', 'protected', 'private']) ??
+                (parent?.kind === 'interface' || symbol.kind === 'enumMember'
+                    ? 'public'
+                    : !parent || parent.kind === 'namespace'
+                      ? 'internal'
+                      : 'private')
+            );
+        case 'typescript':
+            if (local) {
+                return 'local';
+            }
+            if (parent && parent.kind !== 'module' && parent.kind !== 'namespace') {
+                // Class members; interface and object members are public
+                return symbol.name.startsWith('#')
+                    ? 'private'
+                    : (keyword(modifiers, ['private', 'protected', 'public']) ?? 'public');
+            }
+            return /\bexport\b/.test(modifiers) ? 'exported' : 'local';
+        case 'cpp':
+        case 'c':
+            if (local) {
+                return 'local';
+            }
+            if (parent && (parent.kind === 'class' || parent.kind === 'struct')) {
+                // Members of a `class` are private until the first access label, those of a `struct` public
+                const isClass = /\bclass\b/.test(lines[parent.range.start.line] ?? '');
+                return cppAccess(symbol, parent, lines) ?? (isClass ? 'private' : 'public');
+            }
+            return /\bstatic\b/.test(modifiers) ? 'local' : 'public';
+        case 'haxe':
+            if (local) {
+                return 'local';
+            }
+            return (
+                keyword(modifiers, ['public', 'private']) ??
+                (parent && parent.kind !== 'interface' && parent.kind !== 'enum' ? 'private' : 'public')
+            );
+        case 'python':
+            if (local) {
+                return 'local';
+            }
+            if (symbol.name.startsWith('__') && !symbol.name.endsWith('__')) {
+                return 'private';
+            }
+            return symbol.name.startsWith('_') && !symbol.name.endsWith('__') ? 'internal' : 'public';
+        case 'dart':
+            if (local) {
+                return 'local';
+            }
+            // Underscore names are private to the library
+            return symbol.name.startsWith('_') ? 'private' : 'public';
+        default:
+            return undefined;
+    }
+}
+
+function rustVisibility(symbol: SymbolInfo, parent: SymbolInfo | undefined, modifiers: string): Visibility | undefined {
+    // Impl blocks have no visibility of their own
+    if (/^(unsafe\s+)?impl\b/.test(symbol.name)) {
+        return undefined;
+    }
+    const restriction = /\bpub\s*\(\s*(crate|super|self|in\b)/.exec(modifiers)?.[1];
+    if (restriction) {
+        return RUST_RESTRICTIONS[restriction];
+    }
+    if (/\bpub\b/.test(modifiers)) {
+        return 'public';
+    }
+    if (parent && CALLABLE_KINDS.has(parent.kind)) {
+        return 'local';
+    }
+    // Trait items and enum variants are as visible as their trait or enum; trait impl items as the trait
+    if (parent && (parent.kind === 'interface' || parent.kind === 'enum')) {
+        return parent.visibility;
+    }
+    if (symbol.impl?.trait) {
+        return 'public';
+    }
+    return symbol.attributes?.some((attribute) => attribute.name === 'macro_export') ? 'public' : 'private';
+}
+
+/** The access label (`public:`, `protected:`, `private:`) in effect at a C++ member */
+function cppAccess(symbol: SymbolInfo, parent: SymbolInfo, lines: string[]): Visibility | undefined {
+    for (let line = symbol.range.start.line; line > parent.range.start.line; line--) {
+        const label = /^\s*(public|protected|private)\s*:(?!:)/.exec(lines[line] ?? '')?.[1];
+        if (label) {
+            return label as Visibility;
+        }
+    }
+    return undefined;
+}
+
+function keyword<T extends Visibility>(modifiers: string, keywords: T[]): T | undefined {
+    return keywords.find((word) => new RegExp(`\\b${word}\\b`).test(modifiers));
+}
@@ -0,0 +1,158 @@
+import { createHmac } from 'node:crypto';
+import { identifySymbols } from './ci';
+import type { Logger } from './logger';
+import type { Snapshot, SnapshotStore } from './snapshots';
+import type { AnalysisOutput, Range, SymbolInfo } from './types';
+import { fetchWithRetry } from './utils';
+
+/** A symbol in a webhook payload, without its children */
+export interface SymbolReference {
+    /** Workspace-relative file, enclosing symbols and kind, e.g. `src/user.ts:UserService.save (method)` */
+    id: string;
+    name: string;
+    kind: string;
+    file: string;
+    range: Range;
+    preview: string;
+}
+
+export interface SymbolDelta {
+    added: SymbolReference[];
+    removed: SymbolReference[];
+    /** Symbols whose declaration or documentation changed; moved symbols are not reported */
+    changed: SymbolReference[];
+}
+
+export interface WebhookPayload extends SymbolDelta {
+    event: 'snapshot.updated';
+    snapshotId: string;
+    /** Snapshot the delta is relative to; null for the first snapshot of a directory */
+    previousSnapshotId: string | null;
+    createdAt: string;
+    language: string;
+    directory: string;
+}
+
+/**
+ * Compares two analyses of the same directory symbol by symbol.
+ *
+ * @param previous - Earlier analysis; without it every symbol counts as added
+ * @param current - Newer analysis
+ */
+export function diffSymbols(previous: AnalysisOutput | undefined, current: AnalysisOutput): SymbolDelta {
+    const before = groupById(previous);
+    const after = groupById(current);
+    const delta: SymbolDelta = { added: [], removed: [], changed: [] };
+
+    for (const [id, symbols] of after) {
+        // Overloads share an id; pair them up in document order
+        const old = before.get(id) ?? [];
+        symbols.forEach((symbol, index) => {
+            if (index >= old.length) {
+                delta.added.push(reference(id, symbol));
+            } else if (fingerprint(symbol) !== fingerprint(old[index])) {
+                delta.changed.push(reference(id, symbol));
+            }
+        });
+    }
+    for (const [id, symbols] of before) {
+        const remaining = after.get(id)?.length ?? 0;
+        for (const symbol of symbols.slice(remaining)) {
+            delta.removed.push(reference(id, symbol));
+        }
+    }
+
+    return delta;
+}
+
+function groupById(output: AnalysisOutput | undefined): Map<string, SymbolInfo[]> {
+    const groups = new Map<string, SymbolInfo[]>();
+    for (const { id, symbol } of output ? identifySymbols(output) : []) {
+        groups.set(id, [...(groups.get(id) ?? []), symbol]);
+    }
+    return groups;
+}
+
+function fingerprint(symbol: SymbolInfo): string {
+    return JSON.stringify([symbol.preview, symbol.documentation ?? '', symbol.supertypes ?? []]);
+}
+
+function reference(id: string, symbol: SymbolInfo): SymbolReference {
+    const { name, kind, file, range, preview } = symbol;
+    return { id, name, kind, file, range, preview };
+}
+
+/**
+ * POSTs a delta payload to a set of URLs whenever a snapshot store receives a snapshot that adds,
+ * removes or changes symbols. With a secret, each request carries an
+ * `X-Lsp-Cli-Signature: sha256=<hex>` HMAC of the body.
+ */
+export class WebhookNotifier {
+    /**
+     * @param urls - Endpoints to notify
+     * @param logger - Logger for delivery failures
+     * @param secret - Key for the HMAC-SHA256 request signature
+     */
+    constructor(
+        private urls: string[],
+        private logger: Logger,
+        private secret?: string
+    ) {}
+
+    /**
+     * Notifies the webhooks about every snapshot added to the store from now on.
+     */
+    attach(snapshots: SnapshotStore): void {
+        snapshots.onAdd((snapshot, previous) => {
+            // Delivery runs in the background so analysis responses aren't delayed by slow receivers
+            void this.notify(snapshot, previous);
+        });
+    }
+
+    private async notify(snapshot: Snapshot, previous: Snapshot | undefined): Promise<void> {
+        const delta = diffSymbols(previous?.output, snapshot.output);
+        if (delta.added.length === 0 && delta.removed.length === 0 && delta.changed.length === 0) {
+            return;
+        }
+
+        const payload: WebhookPayload = {
+            event: 'snapshot.updated',
+            snapshotId: snapshot.id,
+            previousSnapshotId: previous?.id ?? null,
+            createdAt: snapshot.createdAt,
+            language: snapshot.output.language,
+            directory: snapshot.output.directory,
+            ...delta
+        };
+        const body = JSON.stringify(payload);
+        const headers: Record<string, string> = {
+            'Content-Type': 'application/json',
+            'X-Lsp-Cli-Event': payload.event
+        };
+        if (this.secret) {
+            headers['X-Lsp-Cli-Signature'] = `sha256=${createHmac('sha256', this.secret).update(body).digest('hex')}`;
+        }
+
+        await Promise.all(
+            this.urls.map(async (url) => {
+                try {
+                    const response = await fetchWithRetry(
+                        url,
+                        { method: 'POST', headers, body },
+                        {
+                            maxRetries: 3,
+                            onRetry: (reason, delay) =>
+                                this.logger.debug(`Webhook ${url} failed (${reason}), retrying in ${delay}ms`)
+                        }
+                    );
+                    if (!response.ok) {
+                        this.logger.warn(`Webhook ${url} returned HTTP ${response.status}`);
+                    }
+                } catch (error) {
+                    const message = error instanceof Error ? error.message : String(error);
+                    this.logger.warn(`Webhook ${url} failed: ${message}`);
+                }
+            })
+        );
+    }
+}
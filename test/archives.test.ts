@@ -0,0 +1,54 @@
+import { existsSync, mkdirSync, mkdtempSync, readFileSync, rmSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { extractArchive } from '../src/utils';
+
+const ARCHIVES = join(__dirname, 'fixtures', 'archives');
+
+describe('Archive extraction', () => {
+    let directory: string;
+    let destination: string;
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-archives-'));
+        destination = join(directory, 'source');
+        mkdirSync(destination);
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it.each(['project.zip', 'project.tar'])('extracts %s', async (archive) => {
+        await extractArchive(join(ARCHIVES, archive), destination);
+
+        expect(readFileSync(join(destination, 'project', 'src', 'lib.ts'), 'utf-8')).toContain('export function greet');
+    });
+
+    it('extracts a gzipped .crate from crates.io', async () => {
+        await extractArchive(join(ARCHIVES, 'demo-0.1.0.crate'), destination);
+
+        expect(readFileSync(join(destination, 'demo-0.1.0', 'Cargo.toml'), 'utf-8')).toContain('name = "demo"');
+        expect(existsSync(join(destination, 'demo-0.1.0', 'src', 'lib.rs'))).toBe(true);
+    });
+
+    it('rejects a zip with an entry outside the destination', async () => {
+        await expect(extractArchive(join(ARCHIVES, 'traversal.zip'), destination)).rejects.toThrow('Malicious entry');
+
+        expect(existsSync(join(directory, 'escaped.txt'))).toBe(false);
+    });
+
+    it('skips tar entries outside the destination and extracts the others', async () => {
+        await extractArchive(join(ARCHIVES, 'traversal.tar'), destination);
+
+        expect(existsSync(join(directory, 'escaped.txt'))).toBe(false);
+        expect(existsSync(join(destination, 'project', 'src', 'lib.ts'))).toBe(true);
+    });
+
+    it('refuses unknown formats', async () => {
+        await expect(extractArchive(join(ARCHIVES, 'project.rar'), destination)).rejects.toThrow(
+            'Unsupported archive format: .rar'
+        );
+    });
+});
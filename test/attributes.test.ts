@@ -0,0 +1,89 @@
+import { describe, expect, it } from 'vitest';
+import { extractAttributes } from '../src/attributes';
+import type { AttributeInfo, SupportedLanguage } from '../src/types';
+
+describe('Attributes', () => {
+    it.each<[string, SupportedLanguage, string, number, AttributeInfo[] | undefined]>([
+        [
+            'Rust derive and serde attributes',
+            'rust',
+            '#[derive(Debug, Clone)]\n#[serde(rename_all = "camelCase")]\npub struct User {',
+            2,
+            [
+                { name: 'derive', arguments: 'Debug, Clone' },
+                { name: 'serde', arguments: 'rename_all = "camelCase"' }
+            ]
+        ],
+        [
+            'a multi-line Rust attribute above a doc comment',
+            'rust',
+            '#[cfg_attr(\n    feature = "serde",\n    derive(Serialize)\n)]\n/// A kind.\npub enum Kind {',
+            5,
+            [{ name: 'cfg_attr', arguments: 'feature = "serde",\n    derive(Serialize)' }]
+        ],
+        [
+            'a Rust attribute with a bracket in a string',
+            'rust',
+            '#[error("missing ]")]\n#[doc = "An error"]\nstruct MissingBracket;',
+            2,
+            [
+                { name: 'error', arguments: '"missing ]"' },
+                { name: 'doc', arguments: '"An error"' }
+            ]
+        ],
+        ['a Rust attribute on the declaration line', 'rust', '#[inline] pub fn fast() {}', 0, [{ name: 'inline' }]],
+        ['no Rust attribute past a blank line', 'rust', '#[test]\nfn first() {}\n\nfn second() {}', 3, undefined],
+        [
+            'C# attribute lists',
+            'csharp',
+            '[Serializable, Obsolete("Use V2, not V1")]\n[DataContract]\npublic class User',
+            2,
+            [{ name: 'Serializable' }, { name: 'Obsolete', arguments: '"Use V2, not V1"' }, { name: 'DataContract' }]
+        ],
+        [
+            'C++ standard attributes',
+            'cpp',
+            '[[nodiscard, deprecated("use parse2")]] int parse(const char* text);',
+            0,
+            [{ name: 'nodiscard' }, { name: 'deprecated', arguments: '"use parse2"' }]
+        ],
+        [
+            'a GNU C attribute',
+            'c',
+            '__attribute__((noreturn, format(printf, 1, 2)))\nvoid die(const char *format, ...);',
+            1,
+            [{ name: 'noreturn' }, { name: 'format', arguments: 'printf, 1, 2' }]
+        ],
+        [
+            'Python decorators with a comment in between',
+            'python',
+            '@app.route("/users/<id>", methods=["GET"])\n# Needs a session\n@login_required\ndef get_user(id):',
+            3,
+            [{ name: 'app.route', arguments: '"/users/<id>", methods=["GET"]' }, { name: 'login_required' }]
+        ],
+        [
+            'Java annotations',
+            'java',
+            '@Override\n@SuppressWarnings({"unchecked", "rawtypes"})\npublic void run() {',
+            2,
+            [{ name: 'Override' }, { name: 'SuppressWarnings', arguments: '{"unchecked", "rawtypes"}' }]
+        ],
+        [
+            'a multi-line TypeScript decorator',
+            'typescript',
+            "@Component({\n    selector: 'app-root',\n})\nexport class AppComponent {",
+            3,
+            [{ name: 'Component', arguments: "{\n    selector: 'app-root',\n}" }]
+        ],
+        ['a Dart annotation', 'dart', '@override\nWidget build(BuildContext context) {', 1, [{ name: 'override' }]],
+        [
+            'Haxe metadata',
+            'haxe',
+            '@:keep\n@:native("Foo")\nclass Foo {',
+            2,
+            [{ name: ':keep' }, { name: ':native', arguments: '"Foo"' }]
+        ]
+    ])('reads %s', (_case, language, source, line, expected) => {
+        expect(extractAttributes(source.split('\n'), line, language)).toEqual(expected);
+    });
+});
@@ -0,0 +1,23 @@
+import { execSync } from 'node:child_process';
+import { join } from 'node:path';
+import { describe, expect, it } from 'vitest';
+
+/**
+ * @returns Whether a C compiler and make are installed to build the bindings
+ */
+function hasCompiler(): boolean {
+    try {
+        execSync('command -v cc && command -v make', { stdio: 'pipe' });
+        return true;
+    } catch (_error) {
+        return false;
+    }
+}
+
+describe.skipIf(!hasCompiler())('C bindings', () => {
+    it('build and pass their tests against a fake lsp-cli', () => {
+        const output = execSync('make -s test', { cwd: join(process.cwd(), 'bindings', 'c'), encoding: 'utf8' });
+
+        expect(output).toContain('All C binding tests passed');
+    });
+});
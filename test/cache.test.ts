@@ -0,0 +1,137 @@
+import { execSync } from 'node:child_process';
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { ANY_FILE, AnalysisCache, clearCache } from '../src/cache';
+import { Logger } from '../src/logger';
+import type { SymbolInfo } from '../src/types';
+import { findSymbol, readOutput } from './utils';
+
+describe('Analysis cache', () => {
+    const logger = new Logger();
+    const home = process.env.HOME;
+    let directory: string;
+    let file: string;
+    const symbol: SymbolInfo = {
+        name: 'main',
+        kind: 'variable',
+        file: '',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 22 } },
+        preview: 'export const main = 1;'
+    };
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-cache-'));
+        // The cache lives below the home directory
+        process.env.HOME = directory;
+        file = join(directory, 'main.ts');
+        writeFileSync(file, 'export const main = 1;\n');
+
+        const cache = AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger);
+        cache.lookup(file);
+        cache.store(file, { symbols: [{ ...symbol, file }], encoding: 'utf-8' });
+        cache.save('utf-16');
+    });
+
+    afterEach(() => {
+        process.env.HOME = home;
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('returns the symbols of unchanged files', () => {
+        const cache = AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger);
+
+        expect(cache.lookup(file)?.symbols.map((cached) => cached.name)).toEqual(['main']);
+        expect(cache.positionEncoding).toBe('utf-16');
+    });
+
+    it('misses files whose content changed', () => {
+        writeFileSync(file, 'export const main = 2;\n');
+
+        expect(AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger).lookup(file)).toBeUndefined();
+    });
+
+    it('keeps caches of other server versions apart', () => {
+        expect(AnalysisCache.open(directory, 'typescript', { server: '1.1.0' }, logger).lookup(file)).toBeUndefined();
+    });
+
+    it('misses files whose symbols took data from a changed file', () => {
+        const other = join(directory, 'other.ts');
+        writeFileSync(other, 'export const other = 1;\n');
+        const cache = AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger);
+        cache.store(file, { symbols: [{ ...symbol, file }], encoding: 'utf-8' }, [other]);
+        cache.save('utf-16');
+
+        expect(AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger).lookup(file)).toBeDefined();
+        writeFileSync(other, 'export const other = "changed";\n');
+        expect(AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger).lookup(file)).toBeUndefined();
+    });
+
+    it('misses files with data from unknown files once any analyzed file changed', () => {
+        const other = join(directory, 'other.ts');
+        writeFileSync(other, 'export const other = 1;\n');
+        const cache = AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger);
+        cache.scan([file, other]);
+        cache.store(file, { symbols: [{ ...symbol, file }], encoding: 'utf-8' }, [ANY_FILE]);
+        cache.save('utf-16');
+
+        const unchanged = AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger);
+        unchanged.scan([file, other]);
+        expect(unchanged.lookup(file)).toBeDefined();
+
+        writeFileSync(other, 'export const other = 2;\n');
+        const changed = AnalysisCache.open(directory, 'typescript', { server: '1.0.0' }, logger);
+        changed.scan([file, other]);
+        expect(changed.lookup(file)).toBeUndefined();
+    });
+});
+
+describe('Analysis cache across files', () => {
+    let directory: string;
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-cache-project-'));
+        writeFileSync(join(directory, 'tsconfig.json'), '{ "compilerOptions": { "strict": true } }\n');
+        writeFileSync(join(directory, 'b.ts'), 'export const value = 1;\n');
+        writeFileSync(join(directory, 'a.ts'), "import { value } from './b';\n\nexport const copy = value;\n");
+    });
+
+    afterEach(() => {
+        clearCache(directory);
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    /**
+     * Analyzes the project with the symbol cache.
+     *
+     * @param resolveTypes - Whether to pass --resolve-types
+     * @returns Resolved type of `copy` in a.ts
+     */
+    const typeOfCopy = (resolveTypes = true): string | undefined => {
+        const outputFile = join(directory, 'symbols.json');
+        const cliPath = join(process.cwd(), 'src', 'index.ts');
+        const flags = resolveTypes ? ' --resolve-types' : '';
+        execSync(`npx tsx "${cliPath}" "${directory}" typescript "${outputFile}"${flags}`, {
+            stdio: 'inherit',
+            cwd: process.cwd()
+        });
+        const type = findSymbol(readOutput(outputFile).symbols, 'copy')?.type;
+        rmSync(outputFile);
+        return type;
+    };
+
+    it('resolves the type of an unchanged file again when the file it comes from changed', () => {
+        expect(typeOfCopy()).toBe('number');
+
+        writeFileSync(join(directory, 'b.ts'), "export const value = 'text';\n");
+
+        expect(typeOfCopy()).toBe('string');
+    });
+
+    it('keeps symbols extracted with and without --resolve-types apart', () => {
+        expect(typeOfCopy(false)).toBeUndefined();
+        expect(typeOfCopy()).toBe('number');
+        expect(typeOfCopy(false)).toBeUndefined();
+    });
+});
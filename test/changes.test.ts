@@ -0,0 +1,123 @@
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join, resolve } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { compareVersions } from '../src/changes';
+import type { Session } from '../src/session';
+import type { DiagnosticInfo, SymbolInfo } from '../src/types';
+
+/**
+ * A session whose "server" sees the documents opened through it: every `function name(...)` line is a
+ * symbol, and a call `name()` without such a function in any open document is an error.
+ */
+class FakeSession {
+    readonly language = 'typescript';
+    /** Content of the open documents by absolute path */
+    readonly open = new Map<string, string>();
+
+    constructor(readonly directory: string) {}
+
+    /**
+     * @param file - Absolute path or path relative to the directory
+     * @param content - Content to open the document with
+     * @returns The functions declared in the content
+     */
+    async analyzeContent(file: string, content: string): Promise<SymbolInfo[]> {
+        const path = resolve(this.directory, file);
+        this.open.set(path, content);
+        return content.split('\n').flatMap((line, index) => {
+            const name = /^function (\w+)/.exec(line)?.[1];
+            const range = { start: { line: index, character: 0 }, end: { line: index, character: line.length } };
+            return name ? [{ name, kind: 'function', file: path, range, preview: line }] : [];
+        });
+    }
+
+    /** @param file - Absolute path or path relative to the directory */
+    async closeFile(file: string): Promise<void> {
+        this.open.delete(resolve(this.directory, file));
+    }
+
+    /** @returns An error for each call of a function no open document declares */
+    async diagnostics(): Promise<DiagnosticInfo[]> {
+        const declared = [...this.open.values()].join('\n');
+        return [...this.open].flatMap(([file, content]) =>
+            content.split('\n').flatMap((line, index) => {
+                const name = /^\s+(\w+)\(\);/.exec(line)?.[1];
+                if (!name || declared.includes(`function ${name}(`)) {
+                    return [];
+                }
+                const range = { start: { line: index, character: 4 }, end: { line: index, character: 4 } };
+                return [{ file, range, severity: 'error' as const, message: `Cannot find name '${name}'.` }];
+            })
+        );
+    }
+}
+
+const MAIN = 'function main() {\n    helper();\n}\n';
+const HELPER = 'function helper() {}\n';
+
+describe('Comparing versions', () => {
+    let directory: string;
+    let session: FakeSession;
+
+    /**
+     * @param files - Old and new content per file
+     * @returns The report of compareVersions against the fake session
+     */
+    const compare = (files: Parameters<typeof compareVersions>[1]) =>
+        compareVersions(session as unknown as Session, files);
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-changes-'));
+        mkdirSync(join(directory, 'src'));
+        writeFileSync(join(directory, 'src', 'main.ts'), MAIN);
+        writeFileSync(join(directory, 'src', 'helper.ts'), HELPER);
+        session = new FakeSession(directory);
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('reports new diagnostics of files given with ./ or as absolute paths', async () => {
+        const report = await compare([
+            { file: './src/main.ts', before: MAIN, after: `${MAIN}function run() {\n    missing();\n}\n` },
+            { file: join(directory, 'src', 'helper.ts'), before: HELPER, after: HELPER }
+        ]);
+
+        expect(report.newDiagnostics.map((diagnostic) => diagnostic.message)).toEqual(["Cannot find name 'missing'."]);
+        expect(report.addedSymbols).toEqual(['src/main.ts:run (function)']);
+    });
+
+    it("doesn't see a deleted file's old content and reopens it from disk afterwards", async () => {
+        const report = await compare([
+            { file: 'src/main.ts', before: MAIN, after: MAIN },
+            { file: 'src/helper.ts', before: HELPER }
+        ]);
+
+        expect(report.newDiagnostics).toMatchObject([
+            { file: join(directory, 'src', 'main.ts'), message: "Cannot find name 'helper'." }
+        ]);
+        expect(report.removedSymbols).toEqual(['src/helper.ts:helper (function)']);
+        expect(session.open.get(join(directory, 'src', 'helper.ts'))).toBe(HELPER);
+    });
+
+    it('closes added files that are not on disk', async () => {
+        const report = await compare([
+            { file: 'src/main.ts', before: MAIN, after: `${MAIN}function extra() {\n    added();\n}\n` },
+            { file: 'src/added.ts', after: 'function added() {}\n' }
+        ]);
+
+        expect(report.newDiagnostics).toEqual([]);
+        expect(report.addedSymbols).toEqual(['src/main.ts:extra (function)', 'src/added.ts:added (function)']);
+        expect([...session.open.keys()]).toEqual([join(directory, 'src', 'main.ts')]);
+    });
+
+    it('closes files deleted on disk that only the old version had', async () => {
+        rmSync(join(directory, 'src', 'helper.ts'));
+
+        await compare([{ file: 'src/helper.ts', before: HELPER }]);
+
+        expect(session.open.size).toBe(0);
+    });
+});
@@ -0,0 +1,152 @@
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { createChunks } from '../src/chunks';
+import type { AnalysisOutput, SymbolInfo } from '../src/types';
+
+const SOURCE = [
+    'export class UserService {',
+    '    save(user: User) {',
+    '        db.insert(user);',
+    '    }',
+    '',
+    '    load(id: string) {',
+    '        return db.find(id);',
+    '    }',
+    '}',
+    '',
+    'export function createUser(name: string) {',
+    '    const user = new User(name);',
+    '    user.validate();',
+    '    user.normalize();',
+    '    return user;',
+    '}',
+    ''
+].join('\n');
+
+/** Counts words, so budgets in the tests are easy to follow */
+const countWords = (text: string) => text.split(/\s+/).filter(Boolean).length;
+
+describe('Chunks', () => {
+    let directory: string;
+    let output: AnalysisOutput;
+
+    /**
+     * @param name - Symbol name
+     * @param kind - Symbol kind
+     * @param lines - First and last 0-based line
+     * @param children - Nested symbols
+     * @returns A symbol of src/user.ts with its first line as preview
+     */
+    const symbol = (name: string, kind: string, [start, end]: [number, number], children?: SymbolInfo[]) => ({
+        name,
+        kind,
+        file: join(directory, 'src', 'user.ts'),
+        range: { start: { line: start, character: 0 }, end: { line: end, character: 1 } },
+        preview: SOURCE.split('\n')[start].trim(),
+        children
+    });
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-chunks-'));
+        mkdirSync(join(directory, 'src'));
+        writeFileSync(join(directory, 'src', 'user.ts'), SOURCE);
+        output = {
+            language: 'typescript',
+            directory,
+            symbols: [
+                symbol('UserService', 'class', [0, 8], [
+                    symbol('save', 'method', [1, 3]),
+                    symbol('load', 'method', [5, 7])
+                ]),
+                { ...symbol('createUser', 'function', [10, 15]), documentation: 'Creates a valid user.' }
+            ]
+        };
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('outlines symbols with children and takes the full source of the others', () => {
+        const chunks = createChunks(output, { countTokens: countWords });
+
+        expect(chunks.map((chunk) => chunk.id)).toEqual([
+            'src/user.ts:UserService:1',
+            'src/user.ts:UserService.save:2',
+            'src/user.ts:UserService.load:6',
+            'src/user.ts:createUser:11'
+        ]);
+        expect(chunks[0].text).toBe(
+            'UserService (class) in src/user.ts\n\n' +
+                'export class UserService {\n    save(user: User) {\n    load(id: string) {'
+        );
+        expect(chunks[1]).toMatchObject({
+            file: 'src/user.ts',
+            range: { start: { line: 1, character: 0 }, end: { line: 3, character: 0 } },
+            symbols: [{ name: 'save', qualifiedName: 'UserService.save', kind: 'method' }],
+            signature: 'save(user: User) {',
+            text: 'UserService.save (method) in src/user.ts\n\n    save(user: User) {\n        db.insert(user);\n    }',
+            tokens: 9
+        });
+        expect(chunks[3]).toMatchObject({
+            documentation: 'Creates a valid user.',
+            text: expect.stringContaining('Creates a valid user.\n\nexport function createUser')
+        });
+    });
+
+    it('merges small symbols of a file up to the budget, spanning the lines of nested symbols', () => {
+        const chunks = createChunks(output, { maxTokens: 40, countTokens: countWords });
+
+        expect(chunks.map((chunk) => [chunk.id, chunk.symbols.map((entry) => entry.name)])).toEqual([
+            ['src/user.ts:UserService:1+2', ['UserService', 'save', 'load']],
+            ['src/user.ts:createUser:11', ['createUser']]
+        ]);
+        // The class ends after its last method
+        expect(chunks[0].range).toEqual({ start: { line: 0, character: 0 }, end: { line: 8, character: 0 } });
+        expect(chunks[0].text.split('\n\n---\n\n')).toHaveLength(3);
+        expect(chunks[0]).not.toHaveProperty('signature');
+        expect(chunks[0].tokens).toBe(countWords(chunks[0].text));
+    });
+
+    it('never merges symbols of different files', () => {
+        writeFileSync(join(directory, 'src', 'other.ts'), 'export const other = 1;\n');
+        output.symbols.push({ ...symbol('other', 'constant', [0, 0]), file: join(directory, 'src', 'other.ts') });
+
+        const chunks = createChunks(output, { maxTokens: 1000, countTokens: countWords });
+
+        expect(chunks.map((chunk) => chunk.file)).toEqual(['src/user.ts', 'src/other.ts']);
+    });
+
+    it('splits oversized symbols at line boundaries, repeating the header in every part', () => {
+        const chunks = createChunks(output, { maxTokens: 12, countTokens: countWords });
+        const parts = chunks.filter((chunk) => chunk.symbols[0].name === 'createUser');
+
+        expect(parts.map((part) => [part.id, part.part])).toEqual([
+            ['src/user.ts:createUser:11#1', 1],
+            ['src/user.ts:createUser:11#2', 2],
+            ['src/user.ts:createUser:11#3', 3],
+            ['src/user.ts:createUser:11#4', 4]
+        ]);
+        for (const part of parts) {
+            expect(part.text.startsWith('createUser (function) in src/user.ts\n')).toBe(true);
+            expect(part.tokens).toBeLessThanOrEqual(12);
+        }
+        // Every line of the symbol ends up in exactly one part
+        const header = 'createUser (function) in src/user.ts';
+        const body = parts.flatMap((part) => part.text.split('\n').slice(1));
+        expect([header, ...body].join('\n')).toBe(createChunks(output, { countTokens: countWords })[3].text);
+    });
+
+    it('keeps a single line over the budget as a part of its own', () => {
+        output.symbols = [symbol('createUser', 'function', [10, 10])];
+
+        const parts = createChunks(output, { maxTokens: 3, countTokens: countWords });
+
+        expect(parts.map((part) => part.text)).toEqual([
+            'createUser (function) in src/user.ts\n',
+            'createUser (function) in src/user.ts\nexport function createUser(name: string) {'
+        ]);
+    });
+});
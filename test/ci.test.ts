@@ -0,0 +1,162 @@
+import { describe, expect, it } from 'vitest';
+import { evaluateCi, findNewErrors, findRemovedSymbols, findUndocumented } from '../src/ci';
+import type { AnalysisOutput, DiagnosticInfo, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param extra - Documentation, children and other fields
+ * @returns A symbol in src/user.ts of the checkout in /base
+ */
+function symbol(name: string, kind: string, extra: Partial<SymbolInfo> = {}): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/base/src/user.ts',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } },
+        preview: '',
+        ...extra
+    };
+}
+
+/**
+ * @param file - File relative to the checkout
+ * @param message - Diagnostic message
+ * @param line - 0-based line
+ * @param severity - Severity, error by default
+ * @returns A diagnostic in the checkout in /base
+ */
+function diagnostic(
+    file: string,
+    message: string,
+    line: number,
+    severity: DiagnosticInfo['severity'] = 'error'
+): DiagnosticInfo {
+    return {
+        file: `/base/${file}`,
+        range: { start: { line, character: 2 }, end: { line, character: 8 } },
+        severity,
+        message
+    };
+}
+
+/**
+ * Moves an analysis of the checkout in /base to another directory, as a CI job analyzing the
+ * target branch in a separate worktree would see it.
+ *
+ * @param output - Analysis of /base
+ * @param directory - New checkout directory
+ * @returns The same analysis with all paths under the new directory
+ */
+function relocate(output: AnalysisOutput, directory: string): AnalysisOutput {
+    return JSON.parse(JSON.stringify(output).replaceAll('/base', directory));
+}
+
+const BASELINE: AnalysisOutput = {
+    language: 'typescript',
+    directory: '/base',
+    symbols: [
+        symbol('UserService', 'class', {
+            documentation: 'Stores users.',
+            children: [
+                symbol('save', 'method', { documentation: 'Saves a user.' }),
+                symbol('delete', 'method'),
+                symbol('cache', 'property')
+            ]
+        }),
+        symbol('createUser', 'function', { documentation: '  ' })
+    ],
+    diagnostics: [
+        diagnostic('src/user.ts', "Cannot find name 'db'.", 3),
+        diagnostic('src/user.ts', "Cannot find name 'db'.", 9),
+        diagnostic('src/user.ts', 'Unused variable.', 4, 'warning')
+    ]
+};
+
+describe('CI gate', () => {
+    it('measures doc coverage over documentable kinds only', () => {
+        const summary = evaluateCi(BASELINE, { minDocCoverage: 50 });
+
+        expect(findUndocumented(BASELINE).map((undocumented) => undocumented.name)).toEqual(['delete', 'createUser']);
+        expect(summary.stats).toEqual({
+            symbols: 5,
+            documentable: 4,
+            documented: 2,
+            docCoverage: 50,
+            errors: 2,
+            warnings: 1
+        });
+        expect(summary.checks).toEqual([{ name: 'docCoverage', value: 50, threshold: 50, passed: true }]);
+        expect(evaluateCi(BASELINE, { minDocCoverage: 50.1 }).passed).toBe(false);
+    });
+
+    it('counts a workspace without documentable symbols as fully documented', () => {
+        const output = { ...BASELINE, symbols: [symbol('VERSION', 'constant')] };
+
+        expect(evaluateCi(output, { minDocCoverage: 100 }).stats.docCoverage).toBe(100);
+    });
+
+    it('fails on too many errors and lists them with 1-based positions', () => {
+        const summary = evaluateCi(BASELINE, { maxErrors: 1 });
+
+        expect(summary.passed).toBe(false);
+        expect(summary.checks).toEqual([
+            {
+                name: 'errors',
+                value: 2,
+                threshold: 1,
+                passed: false,
+                details: ["src/user.ts:4:3: Cannot find name 'db'.", "src/user.ts:10:3: Cannot find name 'db'."]
+            }
+        ]);
+    });
+
+    it('passes without configured thresholds and skips baseline checks without a baseline', () => {
+        const summary = evaluateCi(BASELINE, { maxNewErrors: 0, maxRemovedSymbols: 0 });
+
+        expect(summary).toMatchObject({ passed: true, checks: [] });
+    });
+
+    it('finds new errors by file and message, ignoring moved lines and another checkout directory', () => {
+        const output = relocate(
+            {
+                ...BASELINE,
+                diagnostics: [
+                    diagnostic('src/user.ts', "Cannot find name 'db'.", 20),
+                    diagnostic('src/user.ts', "Cannot find name 'db'.", 21),
+                    diagnostic('src/user.ts', "Cannot find name 'db'.", 22),
+                    diagnostic('src/other.ts', "Cannot find name 'db'.", 3)
+                ]
+            },
+            '/head'
+        );
+
+        expect(findNewErrors(output, BASELINE).map((error) => [error.file, error.range.start.line])).toEqual([
+            ['/head/src/user.ts', 22],
+            ['/head/src/other.ts', 3]
+        ]);
+        const summary = evaluateCi(output, { maxNewErrors: 1 }, BASELINE);
+        expect(summary.checks[0]).toMatchObject({ name: 'newErrors', value: 2, threshold: 1, passed: false });
+    });
+
+    it('finds removed symbols by file, enclosing symbols and kind', () => {
+        const [service, createUser] = BASELINE.symbols;
+        const output = relocate(
+            {
+                ...BASELINE,
+                symbols: [
+                    { ...service, children: service.children?.filter((child) => child.name !== 'delete') },
+                    { ...createUser, kind: 'method' }
+                ]
+            },
+            '/head'
+        );
+
+        const removed = findRemovedSymbols(output, BASELINE).map(({ id }) => id);
+        expect(removed).toEqual(['src/user.ts:UserService.delete (method)', 'src/user.ts:createUser (function)']);
+        expect(evaluateCi(output, { maxRemovedSymbols: 0 }, BASELINE).checks).toEqual([
+            { name: 'removedSymbols', value: 2, threshold: 0, passed: false, details: removed }
+        ]);
+        expect(evaluateCi(relocate(BASELINE, '/head'), { maxRemovedSymbols: 0 }, BASELINE).passed).toBe(true);
+    });
+});
@@ -0,0 +1,183 @@
+import { Command, InvalidArgumentError } from 'commander';
+import { describe, expect, it, vi } from 'vitest';
+import { applyConfig, type ConfigTable, parseToml } from '../src/config';
+import { Logger } from '../src/logger';
+
+describe('TOML parser', () => {
+    it('parses tables, dotted keys, strings, numbers, booleans, arrays and inline tables', () => {
+        const text = [
+            '# Comment',
+            'title = "lsp-cli" # Trailing comment',
+            "literal = 'C:\\path\\n'",
+            'escapes = "tab\\tquote\\" \\u00e9 \\U0001F600 \\\\"',
+            'count = 1_000',
+            'negative = -42',
+            'float = 3.14',
+            'exponent = 1e3',
+            'zero = 0',
+            'enabled = true',
+            'list = [',
+            '    "a", \'b\',',
+            '    [1, 2],',
+            ']',
+            'inline = { a = 1, b.c = "d" }',
+            'site."key with spaces".x = false',
+            '',
+            '[profile.ci]',
+            'include-body = 0',
+            '',
+            '[profile]',
+            'name = "x"'
+        ].join('\n');
+
+        expect(parseToml(text, 'config.toml')).toEqual({
+            title: 'lsp-cli',
+            literal: 'C:\\path\\n',
+            escapes: 'tab\tquote" é 😀 \\',
+            count: 1000,
+            negative: -42,
+            float: 3.14,
+            exponent: 1000,
+            zero: 0,
+            enabled: true,
+            list: ['a', 'b', [1, 2]],
+            inline: { a: 1, b: { c: 'd' } },
+            site: { 'key with spaces': { x: false } },
+            profile: { ci: { 'include-body': 0 }, name: 'x' }
+        });
+    });
+
+    it('rejects invalid documents with the file and line', () => {
+        const invalid = (text: string, message: string) => {
+            expect(() => parseToml(text, 'config.toml')).toThrow(message);
+        };
+
+        invalid('[a]\nx = 1\n[a]\n', "config.toml:3: Table 'a' is defined twice");
+        invalid('x = 1\nx = 2\n', "config.toml:2: Duplicate key 'x'");
+        invalid('a = { x = 1 }\n[a]\n', "config.toml:2: Inline table 'a' cannot be extended");
+        invalid('a = { x = 1 }\na.y = 2\n', "config.toml:2: Inline table 'a' cannot be extended");
+        invalid('x = 1\nx.y = 2\n', "config.toml:2: 'x' is not a table");
+        invalid('x = 012', 'config.toml:1: Numbers must not have leading zeros');
+        invalid('x = 1__0', 'config.toml:1: Underscores in numbers must be between digits');
+        invalid('x = 1_', 'config.toml:1: Underscores in numbers must be between digits');
+        invalid('x = 1._5', 'config.toml:1: Underscores in numbers must be between digits');
+        invalid('x = "\\uD800"', 'config.toml:1: Escape \\uD800 is not a Unicode scalar value');
+        invalid('x = "\\U00110000"', 'config.toml:1: Escape \\U00110000 is not a Unicode scalar value');
+        invalid('x = "\\u12"', 'config.toml:1: Invalid escape \\u');
+        invalid('x = "\\q"', 'config.toml:1: Invalid escape \\q');
+        invalid('x = "open\ny = 1', 'config.toml:1: Unterminated string');
+        invalid('x = """a"""', 'config.toml:1: Multi-line strings are not supported');
+        invalid('[[a]]', 'config.toml:1: Arrays of tables are not supported');
+        invalid('x = 1 y = 2', 'config.toml:1: Expected a new line');
+        invalid('x = [1 2]', "config.toml:1: Expected ',' or ']' in array");
+        invalid('x = inf', 'config.toml:1: Expected a value');
+    });
+});
+
+describe('applyConfig', () => {
+    /**
+     * Parses the arguments with a small program shaped like lsp-cli's: root options and a `ci` subcommand.
+     *
+     * @param args - Command line arguments
+     * @returns The root command and the `ci` subcommand
+     */
+    const parse = (args: string[]) => {
+        const root = new Command('lsp-cli')
+            .exitOverride()
+            .option('--include-body <lines>', 'Body lines', (value: string) => {
+                if (!/^\d+$/.test(value)) {
+                    throw new InvalidArgumentError('Expected a number of lines');
+                }
+                return Number(value);
+            })
+            .option('--ignore <glob>', 'Ignored files', (value: string, previous: string[]) => [...previous, value], [])
+            .option('--no-cache', 'Analyze without the cache')
+            .option('--profile <name>', 'Profile to apply')
+            .action(() => {});
+        const ci = root
+            .command('ci')
+            .option('--fail-on <level>', 'Failing level')
+            .action(() => {});
+        root.parse(args, { from: 'user' });
+        return { root, ci };
+    };
+
+    /**
+     * @param config - Configuration to apply
+     * @param args - Command line arguments
+     * @returns The root and `ci` commands after applying the configuration to the command that ran
+     */
+    const run = (config: ConfigTable, args: string[] = []) => {
+        const commands = parse(args);
+        applyConfig(args.includes('ci') ? commands.ci : commands.root, config, new Logger({ quiet: 2 }));
+        return commands;
+    };
+
+    it('sets root options from top-level keys', () => {
+        const { root } = run({ 'include-body': 40, ignore: ['*.gen.ts', 'vendor'], cache: false });
+
+        expect(root.opts()).toMatchObject({ includeBody: 40, ignore: ['*.gen.ts', 'vendor'], cache: false });
+        expect(root.getOptionValueSource('includeBody')).toBe('config');
+    });
+
+    it('lets options on the command line win', () => {
+        const { root } = run({ 'include-body': 40, ignore: 'vendor' }, ['--include-body', '5', '--ignore', 'dist']);
+
+        expect(root.opts()).toMatchObject({ includeBody: 5, ignore: ['dist'] });
+    });
+
+    it('applies the subcommand table only to that subcommand', () => {
+        const config = { ci: { 'fail-on': 'error' } };
+
+        expect(run(config, ['ci']).ci.opts().failOn).toBe('error');
+        expect(run(config).ci.opts().failOn).toBeUndefined();
+    });
+
+    it('applies the profile over the base configuration, and its subcommand table last', () => {
+        const config = {
+            'include-body': 10,
+            ci: { 'fail-on': 'note' },
+            profile: { deep: { 'include-body': 80, 'fail-on': 'warning', ci: { 'fail-on': 'error' } } }
+        };
+
+        expect(run(config).root.opts().includeBody).toBe(10);
+        expect(run(config, ['--profile', 'deep']).root.opts().includeBody).toBe(80);
+        const { root, ci } = run(config, ['--profile', 'deep', 'ci']);
+        expect(root.opts().includeBody).toBe(80);
+        expect(ci.opts().failOn).toBe('error');
+        expect(run(config, ['--profile', 'deep', '--include-body', '3']).root.opts().includeBody).toBe(3);
+    });
+
+    it('rejects unknown profiles', () => {
+        expect(() => run({ profile: { deep: {} } }, ['--profile', 'fast'])).toThrow(
+            "Unknown profile 'fast' (defined: deep)"
+        );
+        expect(() => run({}, ['--profile', 'fast'])).toThrow("Unknown profile 'fast' (defined: none)");
+    });
+
+    it('warns about keys that match no option', () => {
+        const logger = new Logger({ quiet: 2 });
+        const warn = vi.spyOn(logger, 'warn');
+        const { ci } = parse(['ci']);
+
+        applyConfig(ci, { colour: true, ci: { verbose: 1 } }, logger);
+
+        expect(warn.mock.calls).toEqual([
+            ["Ignoring unknown configuration key 'colour'"],
+            ["Ignoring unknown configuration key 'verbose' in [ci]"]
+        ]);
+    });
+
+    it('rejects values of the wrong type', () => {
+        expect(() => run({ cache: 'no' })).toThrow("Configuration key 'cache' expects true or false");
+        expect(() => run({ 'include-body': true })).toThrow(
+            "Configuration key 'include-body' expects a value, not a boolean"
+        );
+        expect(() => run({ 'include-body': 'many' })).toThrow(
+            "Configuration key 'include-body': Expected a number of lines"
+        );
+        expect(() => run({ 'include-body': { lines: 1 } })).toThrow(
+            "Configuration key 'include-body' expects a value, not a table"
+        );
+    });
+});
@@ -0,0 +1,103 @@
+import { describe, expect, it } from 'vitest';
+import { addDeprecation } from '../src/deprecation';
+import type { SymbolInfo } from '../src/types';
+
+/**
+ * @param extra - Raw documentation, attributes and other fields
+ * @returns The deprecation addDeprecation finds for a function with these fields
+ */
+function deprecationOf(extra: Partial<SymbolInfo>): SymbolInfo['deprecated'] {
+    const symbol: SymbolInfo = {
+        name: 'old',
+        kind: 'function',
+        file: '/project/src/lib',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } },
+        preview: '',
+        ...extra
+    };
+    addDeprecation([symbol]);
+    return symbol.deprecated;
+}
+
+describe('Deprecation', () => {
+    it.each([
+        ['a JSDoc tag', '/**\n * Old.\n * @deprecated Use bar() instead.\n */', { message: 'Use bar() instead.' }],
+        [
+            'a JSDoc tag continued on the next lines',
+            '/**\n * @deprecated since 2.0, use\n * bar() instead.\n * @param x - Input\n */',
+            { since: '2.0', message: 'use bar() instead.' }
+        ],
+        [
+            'a JSDoc tag followed by another paragraph',
+            '/**\n * @deprecated Use bar().\n *\n * Computes the old way.\n */',
+            { message: 'Use bar().' }
+        ],
+        ['a one-line JSDoc tag', '/** @deprecated */', {}],
+        [
+            'a Doxygen command in Rust-style line comments',
+            '/// \\deprecated as of v1.4: use bar\n///\n/// Details.',
+            { since: '1.4', message: 'use bar' }
+        ],
+        [
+            'a Sphinx directive',
+            '"""Old function.\n\n    .. deprecated:: 1.2\n       Use :func:`bar`.\n\n    More text.\n    """',
+            { since: '1.2', message: 'Use :func:`bar`.' }
+        ],
+        ['a Sphinx directive with the message on its line', '"""\n.. deprecated:: 3.0 Gone soon.\n"""', {
+            since: '3.0',
+            message: 'Gone soon.'
+        }]
+    ])('reads %s', (_form, rawDocumentation, expected) => {
+        expect(deprecationOf({ rawDocumentation })).toEqual(expected);
+    });
+
+    it.each([
+        [
+            'Rust #[deprecated] with since and note',
+            { name: 'deprecated', arguments: 'since = "1.2.0", note = "use `bar` instead"' },
+            { since: '1.2.0', message: 'use `bar` instead' }
+        ],
+        ['Rust #[deprecated = "..."]', { name: 'deprecated', arguments: '"use bar"' }, { message: 'use bar' }],
+        ['a bare Rust #[deprecated]', { name: 'deprecated' }, {}],
+        ['C# [Obsolete]', { name: 'System.Obsolete', arguments: '"Use Bar", true' }, { message: 'Use Bar' }],
+        ['Java @Deprecated', { name: 'Deprecated', arguments: 'since = "9"' }, { since: '9' }],
+        [
+            'a Python decorator',
+            { name: 'typing_extensions.deprecated', arguments: "'Use bar'" },
+            { message: 'Use bar' }
+        ],
+        ['C++ [[gnu::deprecated]]', { name: 'gnu::deprecated', arguments: '"old"' }, { message: 'old' }]
+    ])('reads %s', (_form, attribute, expected) => {
+        expect(deprecationOf({ attributes: [attribute] })).toEqual(expected);
+    });
+
+    it("prefers the attribute's message and keeps the server's tag", () => {
+        expect(
+            deprecationOf({
+                attributes: [{ name: 'deprecated', arguments: 'note = "from the attribute"' }],
+                rawDocumentation: '/// @deprecated since 2.0 from the docs'
+            })
+        ).toEqual({ since: '2.0', message: 'from the attribute' });
+        expect(deprecationOf({ deprecated: {} })).toEqual({});
+        expect(deprecationOf({ rawDocumentation: '/// Not deprecated.', attributes: [{ name: 'inline' }] })).toBe(
+            undefined
+        );
+    });
+
+    it('completes nested symbols', () => {
+        const child: SymbolInfo = {
+            name: 'method',
+            kind: 'method',
+            file: '/project/src/Old.java',
+            range: { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } },
+            preview: '',
+            attributes: [{ name: 'Deprecated' }]
+        };
+        const parent: SymbolInfo = { ...child, name: 'Old', kind: 'class', attributes: [], children: [child] };
+
+        addDeprecation([parent]);
+
+        expect(parent.deprecated).toBeUndefined();
+        expect(child.deprecated).toEqual({});
+    });
+});
@@ -0,0 +1,180 @@
+import { mkdtempSync, readFileSync, rmSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+import type { Chunk } from '../src/chunks';
+import { type EmbeddingOptions, embedChunks, writeEmbeddings } from '../src/embeddings';
+import { Logger } from '../src/logger';
+
+/**
+ * @param text - Chunk text
+ * @returns A single-symbol chunk of src/user.ts
+ */
+function chunk(text: string): Chunk {
+    return {
+        id: `src/user.ts:${text}:0`,
+        language: 'typescript',
+        file: 'src/user.ts',
+        range: { start: { line: 0, character: 0 }, end: { line: 2, character: 1 } },
+        symbols: [{ name: text, qualifiedName: text, kind: 'function' }],
+        text,
+        tokens: 1
+    };
+}
+
+const CHUNKS = ['alpha', 'beta', 'gamma'].map(chunk);
+
+interface EmbeddingRequest {
+    url: string;
+    headers: Record<string, string>;
+    model: string;
+    input: string[];
+    at: number;
+}
+
+describe('Embeddings', () => {
+    const logger = new Logger({ quiet: 2 });
+    let requests: EmbeddingRequest[];
+    let directory: string;
+
+    /**
+     * Replaces fetch with an endpoint that answers each request with the next response given,
+     * and then with vectors `[index of the text in CHUNKS]`, listed in reverse order.
+     *
+     * @param responses - Responses for the first requests
+     */
+    const serve = (...responses: Response[]) => {
+        vi.stubGlobal('fetch', async (url: string, init: RequestInit) => {
+            const body = JSON.parse(String(init.body));
+            requests.push({ url, headers: init.headers as Record<string, string>, ...body, at: Date.now() });
+            const response = responses.shift();
+            if (response) {
+                return response;
+            }
+            const data = body.input.map((text: string, index: number) => ({
+                index,
+                embedding: [CHUNKS.findIndex((known) => known.text === text)]
+            }));
+            return new Response(JSON.stringify({ data: data.reverse() }));
+        });
+    };
+
+    /**
+     * Embeds CHUNKS with fake timers, so retry and rate limit waits pass instantly.
+     *
+     * @param options - Options besides the endpoint and model
+     * @returns The embedding result
+     */
+    const embed = async (options: Partial<EmbeddingOptions> = {}) => {
+        vi.useFakeTimers();
+        const result = embedChunks(CHUNKS, { endpoint: 'http://embed.test/v1/', model: 'nomic', ...options }, logger);
+        let done = false;
+        result.then(
+            () => {
+                done = true;
+            },
+            () => {
+                done = true;
+            }
+        );
+        while (!done) {
+            await vi.advanceTimersToNextTimerAsync();
+        }
+        return result;
+    };
+
+    beforeEach(() => {
+        requests = [];
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-embeddings-'));
+    });
+
+    afterEach(() => {
+        vi.useRealTimers();
+        vi.unstubAllGlobals();
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('embeds chunks in batches, matching vectors to inputs by index', async () => {
+        serve();
+
+        const embedded = await embed({ batchSize: 2, apiKey: 'secret' });
+
+        expect(embedded.map((embeddedChunk) => [embeddedChunk.id, embeddedChunk.embedding])).toEqual([
+            ['src/user.ts:alpha:0', [0]],
+            ['src/user.ts:beta:0', [1]],
+            ['src/user.ts:gamma:0', [2]]
+        ]);
+        expect(requests.map((request) => request.input)).toEqual([['alpha', 'beta'], ['gamma']]);
+        expect(requests[0]).toMatchObject({
+            url: 'http://embed.test/v1/embeddings',
+            model: 'nomic',
+            headers: { Authorization: 'Bearer secret' }
+        });
+    });
+
+    it('retries rate limits after Retry-After and server errors with backoff', async () => {
+        serve(
+            new Response('slow down', { status: 429, headers: { 'Retry-After': '3' } }),
+            new Response('', { status: 503 })
+        );
+
+        const embedded = await embed();
+
+        expect(embedded).toHaveLength(3);
+        expect(requests.map((request) => request.at - requests[0].at)).toEqual([0, 3000, 5000]);
+    });
+
+    it('fails on client errors and on a wrong number of vectors', async () => {
+        serve(new Response('model not found', { status: 404 }));
+        await expect(embed()).rejects.toThrow('Embedding endpoint returned HTTP 404: model not found');
+
+        serve(new Response(JSON.stringify({ data: [{ index: 0, embedding: [0] }] })));
+        await expect(embed()).rejects.toThrow('Embedding endpoint returned 1 vectors for 3 inputs');
+
+        serve(new Response('', { status: 500 }), new Response('', { status: 500 }));
+        await expect(embed({ maxRetries: 1 })).rejects.toThrow('Embedding endpoint returned HTTP 500');
+    });
+
+    it('spaces requests to stay within the rate limit', async () => {
+        serve();
+
+        await embed({ batchSize: 1, requestsPerMinute: 30 });
+
+        expect(requests.map((request) => request.at - requests[0].at)).toEqual([0, 2000, 4000]);
+    });
+
+    it('writes vectors with the model and dimensions to JSON', async () => {
+        const path = join(directory, 'vectors.json');
+
+        await writeEmbeddings(path, 'nomic', [{ ...CHUNKS[0], embedding: [0.5, -1] }]);
+
+        expect(JSON.parse(readFileSync(path, 'utf-8'))).toEqual({
+            model: 'nomic',
+            dimensions: 2,
+            chunks: [{ ...CHUNKS[0], embedding: [0.5, -1] }]
+        });
+    });
+
+    it('writes vectors as float32 blobs to SQLite where node:sqlite is available', async () => {
+        const path = join(directory, 'vectors.sqlite');
+        const embedded = [{ ...CHUNKS[0], embedding: [0.5, -1] }];
+        let sqlite: { DatabaseSync: new (path: string) => any };
+        try {
+            const specifier = 'node:sqlite';
+            sqlite = await import(specifier);
+        } catch (_error) {
+            await expect(writeEmbeddings(path, 'nomic', embedded)).rejects.toThrow('SQLite output requires Node.js');
+            return;
+        }
+
+        await writeEmbeddings(path, 'nomic', embedded);
+
+        const db = new sqlite.DatabaseSync(path);
+        const row = db.prepare('SELECT id, start_line, end_line, embedding FROM chunks').get();
+        const dimensions = db.prepare("SELECT value FROM metadata WHERE key = 'dimensions'").get();
+        db.close();
+        expect(row).toMatchObject({ id: 'src/user.ts:alpha:0', start_line: 0, end_line: 2 });
+        expect(Array.from(new Float32Array(new Uint8Array(row.embedding).buffer))).toEqual([0.5, -1]);
+        expect(dimensions.value).toBe('2');
+    });
+});
@@ -0,0 +1,97 @@
+import { describe, expect, it } from 'vitest';
+import { byteOrderMarkLength, decode, detectEncoding, encodedLength } from '../src/encoding';
+import { addPositionOffsets } from '../src/positions';
+import type { PositionEncoding, SourceEncoding, SymbolInfo } from '../src/types';
+
+// Astral-plane characters take two UTF-16 code units and four UTF-8 bytes
+const SOURCE = '// 😀 emoji\r\nconst 𝒳 = "𝄞";\r\nlet y = 1;\r\n';
+
+/**
+ * @param text - Decoded text
+ * @param encoding - Encoding of the file
+ * @param withByteOrderMark - Whether to start with the byte order mark, where the encoding has one
+ * @returns The bytes of the text in the encoding
+ */
+function encode(text: string, encoding: SourceEncoding, withByteOrderMark = true): Buffer {
+    const mark = (bytes: number[]) => Buffer.from(withByteOrderMark ? bytes : []);
+    switch (encoding) {
+        case 'utf-8-bom':
+            return Buffer.concat([mark([0xef, 0xbb, 0xbf]), Buffer.from(text)]);
+        case 'utf-16le':
+            return Buffer.concat([mark([0xff, 0xfe]), Buffer.from(text, 'utf16le')]);
+        case 'utf-16be':
+            return Buffer.concat([mark([0xfe, 0xff]), Buffer.from(text, 'utf16le').swap16()]);
+        default:
+            return Buffer.from(text);
+    }
+}
+
+describe('Source encodings', () => {
+    it.each<SourceEncoding>(['utf-8', 'utf-8-bom', 'utf-16le', 'utf-16be'])(
+        'detects and decodes %s, keeping CRLF line endings',
+        (encoding) => {
+            const buffer = encode(SOURCE, encoding);
+
+            expect(detectEncoding(buffer)).toBe(encoding);
+            expect(decode(buffer, encoding)).toBe(SOURCE);
+        }
+    );
+
+    it('detects Shift-JIS and Windows-1252 from invalid UTF-8', () => {
+        expect(detectEncoding(Buffer.from([0x82, 0xa0, 0x0d, 0x0a]))).toBe('shift_jis');
+        expect(detectEncoding(Buffer.from('caf\xe9\r\n', 'latin1'))).toBe('windows-1252');
+        expect(decode(Buffer.from('caf\xe9\r\n', 'latin1'), 'windows-1252')).toBe('café\r\n');
+    });
+
+    it.each<[SourceEncoding, number, number]>([
+        ['utf-8', 2, 0],
+        ['utf-8-bom', 2, 3],
+        ['utf-16le', 4, 2],
+        ['utf-16be', 4, 2]
+    ])('measures astral characters and CRLF in %s', (encoding, lineBreak, byteOrderMark) => {
+        expect(encodedLength('𝄞', encoding)).toBe(4);
+        expect(encodedLength('a😀\r\n', encoding)).toBe(encodedLength('a', encoding) + 4 + lineBreak);
+        expect(byteOrderMarkLength(encoding)).toBe(byteOrderMark);
+    });
+});
+
+describe('Position offsets', () => {
+    /**
+     * @param encoding - Encoding of the server's columns
+     * @returns The constant `𝒳` on the second line, with the columns the server reports in that encoding
+     */
+    const symbol = (encoding: PositionEncoding): SymbolInfo => {
+        // `const ` is 6 units in both encodings; `𝒳` and `𝄞` are 2 UTF-16 units and 4 UTF-8 bytes each
+        const [nameEnd, lineEnd] = encoding === 'utf-8' ? [10, 20] : [8, 16];
+        return {
+            name: '𝒳',
+            kind: 'constant',
+            file: '/work/main.ts',
+            range: { start: { line: 1, character: 0 }, end: { line: 1, character: lineEnd } },
+            selectionRange: { start: { line: 1, character: 6 }, end: { line: 1, character: nameEnd } },
+            preview: 'const 𝒳 = "𝄞";'
+        };
+    };
+
+    it.each<[SourceEncoding, PositionEncoding]>([
+        ['utf-8', 'utf-16'],
+        ['utf-8', 'utf-8'],
+        ['utf-8-bom', 'utf-16'],
+        ['utf-16le', 'utf-16'],
+        ['utf-16be', 'utf-8']
+    ])('maps columns of a CRLF %s file to byte offsets from %s positions', (fileEncoding, positionEncoding) => {
+        const buffer = encode(SOURCE, fileEncoding);
+        const symbols = [symbol(positionEncoding)];
+
+        addPositionOffsets(symbols, decode(buffer, fileEncoding).split('\n'), positionEncoding, fileEncoding);
+
+        const { range, selectionRange } = symbols[0];
+        const name = encode('𝒳', fileEncoding, false);
+        expect(selectionRange?.start).toMatchObject({ utf16Character: 6, utf8Character: 6 });
+        expect(selectionRange?.end).toMatchObject({ utf16Character: 8, utf8Character: 10 });
+        expect(selectionRange?.start.byteOffset).toBe(buffer.indexOf(name));
+        expect(selectionRange?.end.byteOffset).toBe(buffer.indexOf(name) + name.length);
+        // The range ends after `= "𝄞";`, right before the CRLF
+        expect(range.end.byteOffset).toBe(buffer.indexOf(encode('\r\n', fileEncoding, false), range.start.byteOffset));
+    });
+});
@@ -0,0 +1,101 @@
+import { spawnSync } from 'node:child_process';
+import { chmodSync, existsSync, mkdirSync, mkdtempSync, readFileSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { dirname, join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { ExitCode, ExitError, exitCodeOf } from '../src/exit-codes';
+
+describe('Exit codes', () => {
+    let directory: string;
+
+    /**
+     * Runs the CLI without the user's configuration.
+     *
+     * @param args - Command line arguments
+     * @param env - Additional environment variables
+     * @returns The exit code
+     */
+    const run = (args: string[], env: Record<string, string> = {}) => {
+        const cliPath = join(process.cwd(), 'src', 'index.ts');
+        const result = spawnSync('npx', ['tsx', cliPath, ...args], {
+            cwd: process.cwd(),
+            env: { ...process.env, XDG_CONFIG_HOME: directory, ...env },
+            encoding: 'utf-8'
+        });
+        return result.status;
+    };
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-exit-codes-'));
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('keeps the documented values', () => {
+        // Scripts depend on these; change the README table and never reuse a value when adding codes
+        expect(ExitCode).toEqual({
+            Success: 0,
+            Failure: 1,
+            InvalidArguments: 2,
+            ToolchainMissing: 3,
+            ServerInstallFailed: 4,
+            ServerCrashed: 5,
+            PartialExtraction: 6,
+            ThresholdViolation: 7,
+            CompletedWithErrors: 8
+        });
+    });
+
+    it('takes the code of an ExitError and 1 for other errors', () => {
+        expect(exitCodeOf(new ExitError('No JDK', ExitCode.ToolchainMissing))).toBe(3);
+        expect(exitCodeOf(new Error('EACCES'))).toBe(1);
+        expect(exitCodeOf('failed')).toBe(1);
+    });
+
+    it('exits with 2 for usage errors and invalid configuration, and 0 for help', () => {
+        expect(run(['--no-such-option'])).toBe(ExitCode.InvalidArguments);
+        expect(run([directory, 'cobol', '-'])).toBe(ExitCode.InvalidArguments);
+        expect(run([join(directory, 'missing'), 'typescript', '-'])).toBe(ExitCode.InvalidArguments);
+        expect(run([directory, 'typescript'])).toBe(ExitCode.InvalidArguments);
+
+        writeFileSync(join(directory, '.lsp-cli.toml'), 'include-body = 012\n');
+        expect(run([directory, 'typescript', '-'])).toBe(ExitCode.InvalidArguments);
+
+        expect(run(['--help'])).toBe(ExitCode.Success);
+    });
+
+    it('exits with 3 when the toolchain is missing', () => {
+        // Only node and npx on the PATH, so java can't be found
+        expect(run([directory, 'java', '-'], { PATH: dirname(process.execPath) })).toBe(ExitCode.ToolchainMissing);
+    });
+
+    // Root reads files whatever their mode
+    it.skipIf(process.getuid?.() === 0)(
+        'exits with 8 and lists files that failed in the output, or with 6 and no output with --fail-fast',
+        () => {
+            const workspace = join(directory, 'project');
+            const outputFile = join(directory, 'symbols.json');
+            mkdirSync(workspace);
+            writeFileSync(join(workspace, 'tsconfig.json'), '{}\n');
+            writeFileSync(join(workspace, 'readable.ts'), 'export function readable() {}\n');
+            writeFileSync(join(workspace, 'unreadable.ts'), 'export function unreadable() {}\n');
+            chmodSync(join(workspace, 'unreadable.ts'), 0o000);
+
+            expect(run([workspace, 'typescript', outputFile])).toBe(ExitCode.CompletedWithErrors);
+            const output = JSON.parse(readFileSync(outputFile, 'utf-8'));
+            expect(output.symbols.map((symbol: { name: string }) => symbol.name)).toEqual(['readable']);
+            expect(output.errors).toEqual([
+                expect.objectContaining({
+                    file: expect.stringMatching(/unreadable\.ts$/),
+                    reason: expect.stringContaining('EACCES')
+                })
+            ]);
+
+            rmSync(outputFile);
+            expect(run([workspace, 'typescript', outputFile, '--fail-fast'])).toBe(ExitCode.PartialExtraction);
+            expect(existsSync(outputFile)).toBe(false);
+        }
+    );
+});
@@ -0,0 +1,144 @@
+import { describe, expect, it } from 'vitest';
+import { addForeignItems } from '../src/ffi';
+import type { SupportedLanguage, SymbolInfo } from '../src/types';
+
+/**
+ * Tags the symbols of a source text with addForeignItems.
+ *
+ * @param source - File content
+ * @param language - Language of the file
+ * @param spans - Name, first and last line of each symbol
+ * @returns The abi and foreign flag per symbol name, for tagged symbols
+ */
+function foreignItems(
+    source: string,
+    language: SupportedLanguage,
+    spans: Array<[string, number, number]>
+): Record<string, Pick<SymbolInfo, 'abi' | 'foreign'>> {
+    const symbols: SymbolInfo[] = spans.map(([name, start, end]) => ({
+        name,
+        kind: 'function',
+        file: '/project/src/lib',
+        range: { start: { line: start, character: 0 }, end: { line: end, character: 0 } },
+        preview: ''
+    }));
+    addForeignItems(symbols, source.split('\n'), language);
+    return Object.fromEntries(
+        symbols
+            .filter((symbol) => symbol.abi !== undefined || symbol.foreign !== undefined)
+            .map(({ name, abi, foreign }) => [name, { abi, foreign }])
+    );
+}
+
+describe('Foreign items', () => {
+    it('tags the items of Rust extern blocks with their ABI', () => {
+        const source = [
+            'extern "C" {',
+            '    fn abs(input: i32) -> i32;',
+            '    static errno: i32;',
+            '}',
+            'unsafe extern "system" {',
+            '    fn GetTickCount() -> u32;',
+            '}',
+            'extern {',
+            '    fn puts(s: *const u8) -> i32;',
+            '}',
+            'fn local() {}'
+        ].join('\n');
+
+        expect(
+            foreignItems(source, 'rust', [
+                ['abs', 1, 1],
+                ['errno', 2, 2],
+                ['GetTickCount', 5, 5],
+                ['puts', 8, 8],
+                ['local', 10, 10]
+            ])
+        ).toEqual({
+            abs: { abi: 'C', foreign: true },
+            errno: { abi: 'C', foreign: true },
+            GetTickCount: { abi: 'system', foreign: true },
+            puts: { abi: 'C', foreign: true }
+        });
+    });
+
+    it('leaves Rust functions exported to C alone, also with the fn on the next line', () => {
+        const source = [
+            'extern "C" fn callback(value: i32) {',
+            '    println!("{value}");',
+            '}',
+            '#[no_mangle]',
+            'pub extern "C"',
+            'fn exported() {',
+            '    helper();',
+            '}',
+            'fn helper() {}'
+        ].join('\n');
+
+        expect(
+            foreignItems(source, 'rust', [
+                ['callback', 0, 2],
+                ['exported', 3, 7],
+                ['helper', 8, 8]
+            ])
+        ).toEqual({});
+        expect(foreignItems('extern "C"\nfn exported() {\n    helper();\n}', 'rust', [['exported', 1, 3]])).toEqual({});
+    });
+
+    it('ends a block at its closing brace, ignoring braces in strings and comments', () => {
+        const source = [
+            'extern "C" {',
+            '    // } not the end',
+            '    fn printf(format: *const u8, ...) -> i32; // "{"',
+            '}',
+            'fn after() { let _ = "}"; }'
+        ].join('\n');
+
+        expect(
+            foreignItems(source, 'rust', [
+                ['printf', 2, 2],
+                ['after', 4, 4]
+            ])
+        ).toEqual({ printf: { abi: 'C', foreign: true } });
+    });
+
+    it('marks only declarations in C and C++ linkage blocks as foreign', () => {
+        const source = [
+            '#ifdef __cplusplus',
+            'extern "C"',
+            '{',
+            '#endif',
+            'int parse(const char *text);',
+            'static inline int twice(int value) {',
+            '    return value * 2;',
+            '}',
+            '#ifdef __cplusplus',
+            '}',
+            '#endif',
+            'int outside(void);'
+        ].join('\n');
+
+        expect(
+            foreignItems(source, 'cpp', [
+                ['parse', 4, 4],
+                ['twice', 5, 7],
+                ['outside', 11, 11]
+            ])
+        ).toEqual({ parse: { abi: 'C', foreign: true }, twice: { abi: 'C', foreign: undefined } });
+    });
+
+    it('treats extern "C" before a single C++ declaration as no block', () => {
+        const source = ['extern "C"', 'int exported(void) {', '    return 0;', '}', 'int after(void);'].join('\n');
+
+        expect(
+            foreignItems(source, 'cpp', [
+                ['exported', 1, 3],
+                ['after', 4, 4]
+            ])
+        ).toEqual({});
+    });
+
+    it('ignores other languages', () => {
+        expect(foreignItems('extern "C" {\n    fn abs(input: i32) -> i32;\n}', 'java', [['abs', 1, 1]])).toEqual({});
+    });
+});
@@ -0,0 +1,53 @@
+import { linkSync, mkdirSync, mkdtempSync, rmSync, symlinkSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { discoverFiles } from '../src/utils';
+
+describe('File discovery', () => {
+    let directory: string;
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-discovery-'));
+        mkdirSync(join(directory, 'src'));
+        writeFileSync(join(directory, 'src', 'main.ts'), 'export const main = 1;\n');
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('lists a file reached through a symlinked directory once, under its real path', () => {
+        symlinkSync(join(directory, 'src'), join(directory, 'linked'));
+
+        const { files, aliases } = discoverFiles(directory, ['.ts']);
+
+        expect(files).toEqual([join(directory, 'src', 'main.ts')]);
+        expect(aliases.get(join(directory, 'src', 'main.ts'))).toEqual([join(directory, 'linked', 'main.ts')]);
+    });
+
+    it('lists hardlinked files once and records the other link as alias', () => {
+        linkSync(join(directory, 'src', 'main.ts'), join(directory, 'copy.ts'));
+
+        const { files, aliases } = discoverFiles(directory, ['.ts']);
+
+        expect(files).toHaveLength(1);
+        expect([files[0], ...(aliases.get(files[0]) ?? [])].sort()).toEqual(
+            [join(directory, 'copy.ts'), join(directory, 'src', 'main.ts')].sort()
+        );
+    });
+
+    it('stops at symlink cycles', () => {
+        symlinkSync(directory, join(directory, 'src', 'loop'));
+
+        const { files } = discoverFiles(directory, ['.ts']);
+
+        expect(files).toEqual([join(directory, 'src', 'main.ts')]);
+    });
+
+    it('skips unresolved symlinks', () => {
+        symlinkSync(join(directory, 'missing.ts'), join(directory, 'dangling.ts'));
+
+        expect(discoverFiles(directory, ['.ts']).files).toEqual([join(directory, 'src', 'main.ts')]);
+    });
+});
@@ -710,6 +710,31 @@ describe('Fixture-based LSP Tests', () => {
             }
         });
     });
+
+    describe('Rust', () => {
+        const rustFixture = join(FIXTURES_DIR, 'rust');
+        const outputFile = 'test-rust-call-graph.json';
+
+        afterEach(() => {
+            if (existsSync(outputFile)) {
+                execSync(`rm -f ${outputFile}`);
+            }
+        });
+
+        it('should extract calls across functions with --call-graph', () => {
+            runLSPCLI(rustFixture, 'rust', outputFile, ['--call-graph']);
+            const result = readOutput(outputFile);
+
+            expect(result.edges).toBeDefined();
+            const call = result.edges?.find(
+                (edge) =>
+                    edge.kind === 'call' && edge.from.name === 'get_helper_info' && edge.to.name === 'private_helper'
+            );
+            expect(call).toBeDefined();
+            expect(call?.from.file).toMatch(/nested[/\\]utils\.rs$/);
+            expect(call?.ranges.length).toBe(1);
+        });
+    });
 });
 
 // Helper functions
@@ -0,0 +1,167 @@
+import { connect, type IncomingHttpHeaders } from 'node:http2';
+import type { AddressInfo } from 'node:net';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { startGrpcServer } from '../src/grpc-server';
+import { Logger } from '../src/logger';
+import { ProtoMessage, ProtoWriter } from '../src/protobuf';
+import type { SessionPool } from '../src/session';
+import { SnapshotStore } from '../src/snapshots';
+import type { AnalysisOutput, Position, SourceLocation } from '../src/types';
+
+const OUTPUT: AnalysisOutput = {
+    language: 'typescript',
+    directory: '/project',
+    symbols: [
+        {
+            name: 'greet',
+            kind: 'function',
+            file: '/project/src/index.ts',
+            range: { start: { line: 6, character: 0 }, end: { line: 8, character: 1 } },
+            preview: 'export function greet() {',
+            visibility: 'exported'
+        }
+    ]
+};
+
+const LOCATION: SourceLocation = {
+    file: '/project/src/index.ts',
+    range: { start: { line: 6, character: 16 }, end: { line: 6, character: 21 } },
+    preview: 'export function greet() {'
+};
+
+interface CallResult {
+    messages: ProtoMessage[];
+    status: number;
+    message: string;
+}
+
+describe('gRPC server', () => {
+    const logger = new Logger({ quiet: 2 });
+    let references: Array<{ position: Position; includeDeclaration: boolean }>;
+    let close: () => Promise<void>;
+    let baseUrl: string;
+
+    const sessions = {
+        get: () => ({
+            analyze: async () => OUTPUT,
+            references: async (_file: string, position: Position, includeDeclaration: boolean) => {
+                references.push({ position, includeDeclaration });
+                return [LOCATION];
+            }
+        })
+    } as unknown as SessionPool;
+
+    /**
+     * Makes a call over plaintext HTTP/2 the way gRPC clients do.
+     *
+     * @param method - Method of the LspCli service
+     * @param request - Encoded request message
+     * @returns The response messages and the status from the trailers
+     */
+    const call = (method: string, request: Buffer) =>
+        new Promise<CallResult>((resolve, reject) => {
+            const client = connect(baseUrl);
+            const stream = client.request({
+                ':method': 'POST',
+                ':path': `/lspcli.v1.LspCli/${method}`,
+                'content-type': 'application/grpc+proto',
+                te: 'trailers'
+            });
+            const chunks: Buffer[] = [];
+            let trailers: IncomingHttpHeaders = {};
+            stream.on('data', (chunk: Buffer) => chunks.push(chunk));
+            stream.on('trailers', (received) => {
+                trailers = received;
+            });
+            stream.on('error', reject);
+            stream.on('end', () => {
+                client.close();
+                const body = Buffer.concat(chunks);
+                const messages: ProtoMessage[] = [];
+                // Length-prefixed frames: compression flag, 4-byte length, message
+                for (let offset = 0; offset < body.length; ) {
+                    const end = offset + 5 + body.readUInt32BE(offset + 1);
+                    messages.push(ProtoMessage.decode(body.subarray(offset + 5, end)));
+                    offset = end;
+                }
+                resolve({
+                    messages,
+                    status: Number(trailers['grpc-status']),
+                    message: decodeURIComponent(String(trailers['grpc-message'] ?? ''))
+                });
+            });
+
+            const header = Buffer.alloc(5);
+            header.writeUInt32BE(request.length, 1);
+            stream.end(Buffer.concat([header, request]));
+        });
+
+    /**
+     * @param position - Position to send, if any
+     * @returns A References request for src/index.ts
+     */
+    const positionRequest = (position?: Position) =>
+        new ProtoWriter()
+            .string(1, '/project')
+            .string(2, 'typescript')
+            .string(3, 'src/index.ts')
+            .message(4, position && new ProtoWriter().uint(1, position.line).uint(2, position.character));
+
+    beforeEach(async () => {
+        references = [];
+        const api = await startGrpcServer(':0', sessions, new SnapshotStore(), logger);
+        close = api.close;
+        const { address, port } = api.server.address() as AddressInfo;
+        baseUrl = `http://${address}:${port}`;
+    });
+
+    afterEach(async () => {
+        await close();
+    });
+
+    it('analyzes into a snapshot and streams its symbols', async () => {
+        const request = new ProtoWriter().string(1, '/project').string(2, 'typescript').finish();
+        const analyzed = await call('Analyze', request);
+        expect(analyzed.status).toBe(0);
+        const snapshot = analyzed.messages[0];
+        expect(snapshot.string(3)).toBe('typescript');
+        expect(snapshot.uint(5)).toBe(1);
+
+        const streamed = await call('StreamSymbols', new ProtoWriter().string(1, snapshot.string(1)).finish());
+        expect(streamed.status).toBe(0);
+        expect(streamed.messages.map((symbol) => symbol.string(1))).toEqual(['greet']);
+        expect(JSON.parse(streamed.messages[0].string(15))).toEqual({ visibility: 'exported' });
+    });
+
+    it('includes the declaration in references unless include_declaration is false', async () => {
+        const unset = await call('References', positionRequest({ line: 6, character: 16 }).finish());
+        // Field 5 set to false, which ProtoWriter leaves out as a proto3 default
+        const request = positionRequest({ line: 6, character: 16 }).finish();
+        await call('References', Buffer.concat([request, Buffer.from([0x28, 0x00])]));
+
+        expect(unset.status).toBe(0);
+        expect(unset.messages[0].message(1)?.string(1)).toBe(LOCATION.file);
+        expect(references).toEqual([
+            { position: { line: 6, character: 16 }, includeDeclaration: true },
+            { position: { line: 6, character: 16 }, includeDeclaration: false }
+        ]);
+    });
+
+    it('accepts position 0:0', async () => {
+        const result = await call('References', positionRequest({ line: 0, character: 0 }).finish());
+
+        expect(result.status).toBe(0);
+        expect(references[0].position).toEqual({ line: 0, character: 0 });
+    });
+
+    it('rejects a missing position and unknown snapshots and methods', async () => {
+        const missing = await call('References', positionRequest().finish());
+        expect(missing).toMatchObject({ status: 3, message: "Missing required field 'position'" });
+        expect(references).toEqual([]);
+
+        const unknown = await call('QuerySymbols', new ProtoWriter().string(1, 'unknown').finish());
+        expect(unknown).toMatchObject({ status: 5, message: "Snapshot 'unknown' not found" });
+
+        expect((await call('Rename', Buffer.alloc(0))).status).toBe(12);
+    });
+});
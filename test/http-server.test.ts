@@ -0,0 +1,188 @@
+import type { AddressInfo } from 'node:net';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { startHttpServer } from '../src/http-server';
+import { Logger } from '../src/logger';
+import type { SessionPool } from '../src/session';
+import { SnapshotStore } from '../src/snapshots';
+import type { AnalysisOutput, Position, SourceLocation } from '../src/types';
+
+const OUTPUT: AnalysisOutput = {
+    language: 'typescript',
+    directory: '/project',
+    symbols: [
+        {
+            name: 'Greeter',
+            kind: 'class',
+            file: '/project/src/index.ts',
+            range: { start: { line: 0, character: 0 }, end: { line: 4, character: 1 } },
+            preview: 'export class Greeter {'
+        },
+        {
+            name: 'greet',
+            kind: 'function',
+            file: '/project/src/index.ts',
+            range: { start: { line: 6, character: 0 }, end: { line: 8, character: 1 } },
+            preview: 'export function greet() {'
+        }
+    ]
+};
+
+const LOCATION: SourceLocation = {
+    file: '/project/src/index.ts',
+    range: { start: { line: 0, character: 13 }, end: { line: 0, character: 20 } }
+};
+
+/** Stands in for the language server sessions, recording the position requests */
+class FakeSessions {
+    readonly requests: Array<{ method: string; file: string; position: Position; includeDeclaration?: boolean }> = [];
+
+    get() {
+        return {
+            analyze: async () => OUTPUT,
+            definition: async (file: string, position: Position) => {
+                this.requests.push({ method: 'definition', file, position });
+                return [LOCATION];
+            },
+            hover: async (file: string, position: Position) => {
+                this.requests.push({ method: 'hover', file, position });
+                return undefined;
+            },
+            references: async (file: string, position: Position, includeDeclaration: boolean) => {
+                this.requests.push({ method: 'references', file, position, includeDeclaration });
+                return [LOCATION];
+            }
+        };
+    }
+}
+
+describe('HTTP server', () => {
+    const logger = new Logger({ quiet: 2 });
+    let sessions: FakeSessions;
+    let snapshots: SnapshotStore;
+    let close: (() => Promise<void>) | undefined;
+    let baseUrl: string;
+
+    /**
+     * Starts the server on a free loopback port.
+     *
+     * @param token - Bearer token the server requires, if any
+     */
+    const start = async (token?: string) => {
+        const api = await startHttpServer(':0', sessions as unknown as SessionPool, snapshots, logger, token);
+        close = api.close;
+        const { address, port } = api.server.address() as AddressInfo;
+        baseUrl = `http://${address}:${port}`;
+    };
+
+    /**
+     * @param path - Request path
+     * @param body - JSON body, sent with POST
+     * @returns The response
+     */
+    const post = (path: string, body: unknown) =>
+        fetch(`${baseUrl}${path}`, { method: 'POST', body: JSON.stringify(body) });
+
+    const position = { directory: '/project', language: 'typescript', file: 'src/index.ts', line: 0, character: 13 };
+
+    beforeEach(() => {
+        sessions = new FakeSessions();
+        snapshots = new SnapshotStore();
+        close = undefined;
+    });
+
+    afterEach(async () => {
+        await close?.();
+    });
+
+    it('listens on loopback when the address has no host', async () => {
+        await start();
+
+        expect(baseUrl).toMatch(/^http:\/\/127\.0\.0\.1:\d+$/);
+        expect(await (await fetch(`${baseUrl}/health`)).json()).toEqual({ status: 'ok' });
+    });
+
+    it('stores an analysis as a snapshot that can be read, queried and deleted', async () => {
+        await start();
+
+        const analyzed = await post('/analyze', { directory: '/project', language: 'typescript' });
+        expect(analyzed.status).toBe(201);
+        const id = analyzed.headers.get('X-Snapshot-Id');
+        expect(analyzed.headers.get('Location')).toBe(`/snapshots/${id}`);
+        expect(await analyzed.json()).toEqual(OUTPUT);
+
+        const list = await (await fetch(`${baseUrl}/snapshots`)).json();
+        expect(list).toMatchObject([{ id, language: 'typescript', directory: '/project', symbolCount: 2 }]);
+        expect(await (await fetch(`${baseUrl}/snapshots/${id}`)).json()).toEqual(OUTPUT);
+
+        const query = await (await fetch(`${baseUrl}/snapshots/${id}/symbols?kind=function`)).json();
+        expect(query.symbols.map((symbol: { name: string }) => symbol.name)).toEqual(['greet']);
+
+        expect((await fetch(`${baseUrl}/snapshots/${id}`, { method: 'DELETE' })).status).toBe(204);
+        expect((await fetch(`${baseUrl}/snapshots/${id}`)).status).toBe(404);
+        expect((await fetch(`${baseUrl}/snapshots/${id}`, { method: 'DELETE' })).status).toBe(404);
+    });
+
+    it('answers position requests', async () => {
+        await start();
+
+        expect(await (await post('/definition', position)).json()).toEqual({ locations: [LOCATION] });
+        expect(await (await post('/hover', position)).json()).toEqual({ hover: null });
+        await post('/references', { ...position, includeDeclaration: false });
+
+        expect(sessions.requests).toEqual([
+            { method: 'definition', file: 'src/index.ts', position: { line: 0, character: 13 } },
+            { method: 'hover', file: 'src/index.ts', position: { line: 0, character: 13 } },
+            {
+                method: 'references',
+                file: 'src/index.ts',
+                position: { line: 0, character: 13 },
+                includeDeclaration: false
+            }
+        ]);
+    });
+
+    it('rejects invalid requests with 4xx errors', async () => {
+        await start();
+
+        const invalid = async (response: Response, status: number, error: RegExp) => {
+            expect(response.status).toBe(status);
+            expect((await response.json()).error).toMatch(error);
+        };
+
+        await invalid(await post('/definition', { ...position, line: -1 }), 400, /non-negative integers/);
+        await invalid(await post('/definition', { ...position, file: undefined }), 400, /'file'/);
+        await invalid(await post('/analyze', { directory: '/project', language: 'cobol' }), 400, /Unsupported/);
+        await invalid(await post('/compare', { ...position, files: 'a.ts' }), 400, /'files' must be an array/);
+        await invalid(
+            await fetch(`${baseUrl}/analyze`, { method: 'POST', body: '[1, 2' }),
+            400,
+            /must be a JSON object/
+        );
+        await invalid(await fetch(`${baseUrl}/snapshots/unknown`), 404, /not found/);
+        await invalid(await fetch(`${baseUrl}/unknown`), 404, /No route for GET \/unknown/);
+    });
+
+    it('requires the bearer token when started with one', async () => {
+        await start('secret');
+
+        expect((await fetch(`${baseUrl}/health`)).status).toBe(200);
+        const denied = await fetch(`${baseUrl}/snapshots`);
+        expect(denied.status).toBe(401);
+        expect(denied.headers.get('WWW-Authenticate')).toBe('Bearer');
+        const wrong = await fetch(`${baseUrl}/snapshots`, { headers: { Authorization: 'Bearer guess' } });
+        expect(wrong.status).toBe(401);
+        const allowed = await fetch(`${baseUrl}/snapshots`, { headers: { Authorization: 'Bearer secret' } });
+        expect(allowed.status).toBe(200);
+    });
+
+    it('keeps only the newest snapshots up to the limit', () => {
+        snapshots = new SnapshotStore(2);
+        const first = snapshots.add(OUTPUT);
+        const second = snapshots.add(OUTPUT);
+        const third = snapshots.add(OUTPUT);
+
+        expect(snapshots.get(first.id)).toBeUndefined();
+        expect(snapshots.list().map((summary) => summary.id)).toEqual([second.id, third.id]);
+        expect(snapshots.latest('/project', 'typescript')).toBe(third);
+    });
+});
@@ -0,0 +1,95 @@
+import { describe, expect, it } from 'vitest';
+import { linkImplementations, parseImplHeader } from '../src/impls';
+import type { ImplInfo, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param extra - Impl information and children
+ * @returns A symbol of src/lib.rs
+ */
+function symbol(name: string, kind: string, extra: Partial<SymbolInfo> = {}): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/work/src/lib.rs',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 1 } },
+        preview: '',
+        ...extra
+    };
+}
+
+describe('Rust impl blocks', () => {
+    it.each<[string, string, ImplInfo | undefined]>([
+        ['an inherent impl', 'impl User {', { type: 'User', signature: 'impl User' }],
+        [
+            'a generic trait impl with a where clause',
+            'impl<T: Clone> Processor<T> for Wrapper<T> where T: Send {',
+            { type: 'Wrapper', trait: 'Processor', signature: 'impl<T: Clone> Processor<T> for Wrapper<T>' }
+        ],
+        [
+            'a trait impl with a multi-line where clause',
+            'impl<T> fmt::Display for Tree<T>\nwhere\n    T: fmt::Display,\n{',
+            { type: 'Tree', trait: 'Display', signature: 'impl<T> fmt::Display for Tree<T>' }
+        ],
+        [
+            'an unsafe impl',
+            'unsafe impl Send for Handle {}',
+            { type: 'Handle', trait: 'Send', signature: 'unsafe impl Send for Handle' }
+        ],
+        [
+            'a negative impl',
+            'impl !Sync for Handle {}',
+            { type: 'Handle', trait: 'Sync', signature: 'impl !Sync for Handle' }
+        ],
+        [
+            'a higher-ranked bound inside the trait arguments',
+            "impl Visitor<for<'a> fn(&'a str)> for Printer {",
+            { type: 'Printer', trait: 'Visitor', signature: "impl Visitor<for<'a> fn(&'a str)> for Printer" }
+        ],
+        [
+            'an impl for a mutable reference',
+            "impl<'a> Iterator for &'a mut Lines {",
+            { type: 'Lines', trait: 'Iterator', signature: "impl<'a> Iterator for &'a mut Lines" }
+        ],
+        ['a struct', 'struct User {', undefined],
+        ['a function named like impl', 'fn impl_detail() {}', undefined]
+    ])('parses %s', (_case, source, expected) => {
+        expect(parseImplHeader(source.split('\n'), 0)).toEqual(expected);
+    });
+
+    it('links types and traits through trait impls, ignoring inherent impls', () => {
+        const user = symbol('User', 'struct');
+        const shape = symbol('Shape', 'enum');
+        const display = symbol('Display', 'interface');
+        const clone = symbol('Clone', 'interface');
+        const symbols = [
+            symbol('shapes', 'module', {
+                children: [
+                    shape,
+                    symbol('impl Display for Shape', 'object', {
+                        impl: { type: 'Shape', trait: 'Display', signature: 'impl Display for Shape' }
+                    })
+                ]
+            }),
+            user,
+            display,
+            clone,
+            symbol('impl Display for User', 'object', {
+                impl: { type: 'User', trait: 'Display', signature: 'impl Display for User' }
+            }),
+            symbol('impl Clone for User', 'object', {
+                impl: { type: 'User', trait: 'Clone', signature: 'impl Clone for User' }
+            }),
+            symbol('impl User', 'object', { impl: { type: 'User', signature: 'impl User' } })
+        ];
+
+        linkImplementations(symbols);
+
+        expect(user.traits).toEqual(['Clone', 'Display']);
+        expect(shape.traits).toEqual(['Display']);
+        expect(display.implementors).toEqual(['Shape', 'User']);
+        expect(clone.implementors).toEqual(['User']);
+        expect(symbols[0].traits).toBeUndefined();
+    });
+});
@@ -0,0 +1,80 @@
+import { describe, expect, it } from 'vitest';
+import { findRustClosures, isLocalItem } from '../src/locals';
+import type { SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param start - 0-based first line of the function
+ * @param end - 0-based last line of the function
+ * @param children - Nested functions
+ * @returns A function spanning the lines
+ */
+function fn(name: string, start: number, end: number, children?: SymbolInfo[]): SymbolInfo {
+    return {
+        name,
+        kind: 'function',
+        file: '/work/src/lib.rs',
+        range: { start: { line: start, character: 0 }, end: { line: end, character: 1 } },
+        preview: '',
+        children
+    };
+}
+
+describe('Local items', () => {
+    it.each<[string, string, string, string, boolean]>([
+        ['a nested function', 'function', 'helper', 'fn helper() {}', true],
+        ['a local struct', 'struct', 'Point', 'struct Point { x: i32 }', true],
+        ['a Rust closure', 'variable', 'add', 'let add = |a, b| a + b;', true],
+        ['a Rust move closure', 'variable', 'task', 'let task = move || run();', true],
+        ['a Python lambda', 'variable', 'square', 'square = lambda x: x * x', true],
+        ['a TypeScript arrow function', 'variable', 'sum', 'const sum = (a: number, b: number) => a + b;', true],
+        ['an async arrow function', 'variable', 'load', 'const load = async () => fetch(url);', true],
+        ['a Java lambda', 'variable', 'twice', 'Function<Integer, Integer> twice = x -> x * 2;', true],
+        ['a function expression', 'variable', 'handler', 'var handler = function (event) {};', true],
+        ['a C++ lambda', 'variable', 'compare', 'auto compare = [&](int a, int b) { return a < b; };', true],
+        ['a plain variable', 'variable', 'count', 'let count = items.len();', false],
+        ['a comparison', 'variable', 'same', 'let same = a == b;', false],
+        ['a local constant', 'constant', 'LIMIT', 'const LIMIT: usize = 10;', false]
+    ])('classifies %s', (_case, kind, name, line, expected) => {
+        expect(isLocalItem(kind, name, line)).toBe(expected);
+    });
+
+    it('finds Rust closures bound in a function body, skipping nested functions', () => {
+        const lines = [
+            'fn main() {',
+            '    let mut double = |x: i32| {',
+            '        x * 2 // twice',
+            '    };',
+            '    let count = 1;',
+            '    fn inner() {',
+            '        let hidden = || 1;',
+            '    }',
+            '}'
+        ];
+        const main = fn('main', 0, 8, [fn('inner', 5, 7)]);
+
+        const closures = findRustClosures(main, lines, 'utf-16');
+
+        expect(closures.map((closure) => closure.name)).toEqual(['double']);
+        expect(closures[0]).toMatchObject({
+            kind: 'variable',
+            range: { start: { line: 1, character: 0 }, end: { line: 3, character: 6 } },
+            fullRange: { start: { line: 1, character: 4 }, end: { line: 3, character: 6 } },
+            selectionRange: { start: { line: 1, character: 12 }, end: { line: 1, character: 18 } },
+            preview: 'let mut double = |x: i32| {'
+        });
+    });
+
+    it('positions closures in UTF-8 when the server counts bytes', () => {
+        const lines = ['fn main() {', '    let greet = move || "hé";', '}'];
+
+        const [closure] = findRustClosures(fn('main', 0, 2), lines, 'utf-8');
+
+        expect(closure.name).toBe('greet');
+        expect(closure.selectionRange).toEqual({
+            start: { line: 1, character: 8 },
+            end: { line: 1, character: 13 }
+        });
+        expect(closure.range.end).toEqual({ line: 1, character: 30 });
+    });
+});
@@ -0,0 +1,214 @@
+import { mkdtempSync, readFileSync, rmSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import chalk from 'chalk';
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+import { Logger, parseLogFilter } from '../src/logger';
+
+describe('Structured logging', () => {
+    const colorLevel = chalk.level;
+    let stdout: string[];
+    let stderr: string[];
+    let directory: string;
+
+    /** @returns The JSON records written to stdout */
+    const records = (lines = stdout) => lines.map((line) => JSON.parse(line));
+
+    beforeEach(() => {
+        // Colors would depend on the terminal running the tests
+        chalk.level = 0;
+        stdout = [];
+        stderr = [];
+        vi.spyOn(process.stdout, 'write').mockImplementation((chunk) => {
+            stdout.push(String(chunk));
+            return true;
+        });
+        vi.spyOn(process.stderr, 'write').mockImplementation((chunk) => {
+            stderr.push(String(chunk));
+            return true;
+        });
+        vi.spyOn(console, 'log').mockImplementation((...args) => stdout.push(`${args.join(' ')}\n`));
+        vi.spyOn(console, 'error').mockImplementation((...args) => stderr.push(`${args.join(' ')}\n`));
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-logger-'));
+    });
+
+    afterEach(() => {
+        chalk.level = colorLevel;
+        vi.useRealTimers();
+        vi.restoreAllMocks();
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('writes one JSON record per line with the time, level, target and event fields', () => {
+        const logger = new Logger({ format: 'json' });
+
+        logger.info('Analyzing /project');
+        logger.scoped('extractor').event('file.finished', { file: 'src/a.ts', symbols: 3 }, 'info');
+        logger.event('file.started', { file: 'src/b.ts' });
+        logger.error('Request failed', 'timeout after 30s');
+
+        expect(records()).toEqual([
+            { time: expect.stringMatching(/^\d{4}-\d\d-\d\dT/), level: 'info', message: 'Analyzing /project' },
+            {
+                time: expect.any(String),
+                target: 'extractor',
+                level: 'info',
+                event: 'file.finished',
+                file: 'src/a.ts',
+                symbols: 3
+            }
+        ]);
+        expect(records(stderr)).toEqual([
+            { time: expect.any(String), level: 'error', message: 'Request failed', details: 'timeout after 30s' }
+        ]);
+    });
+
+    it('shows events in pretty output only with debug messages', () => {
+        new Logger().event('server.restarted', { language: 'java', attempt: 2 }, 'warn');
+        expect(stdout).toEqual([]);
+
+        new Logger({ verbose: true }).scoped('transport').event('server.restarted', { language: 'java', attempt: 2 });
+        expect(stdout).toEqual(['[EVENT] transport: server.restarted language=java attempt=2\n']);
+    });
+
+    it('appends pretty lines without colors to the log file', () => {
+        chalk.level = 1;
+        const file = join(directory, 'lsp-cli.log');
+        const logger = new Logger({ file, progress: false });
+
+        logger.warn('Slow language server');
+        logger.error('Request failed', 'timeout after 30s');
+
+        expect(stdout[0]).toContain('\x1b[');
+        const logged = readFileSync(file, 'utf-8');
+        expect(logged).toMatch(/^⚠ Slow language server\n✗ Request failed\s*\n\s+timeout after 30s\n$/);
+    });
+
+    it('appends the same JSON records to the log file', () => {
+        const file = join(directory, 'lsp-cli.log');
+        const logger = new Logger({ format: 'json', stderr: true, file });
+
+        logger.warn('Slow language server');
+
+        expect(stdout).toEqual([]);
+        expect(readFileSync(file, 'utf-8')).toBe(stderr.join(''));
+        expect(records(stderr)).toEqual([{ time: expect.any(String), level: 'warn', message: 'Slow language server' }]);
+    });
+
+    it('records stages as events and for the run summary', () => {
+        vi.useFakeTimers();
+        const logger = new Logger({ format: 'json' });
+
+        logger.stage('Extracting symbols', 3);
+        vi.advanceTimersByTime(1500);
+        logger.scoped('extractor').warn('No symbols in src/empty.ts');
+        logger.stageDone('3 files');
+        logger.stage('Resolving types');
+        logger.stageFailed('server crashed');
+
+        expect(records()).toMatchObject([
+            { level: 'info', event: 'stage.started', stage: 'Extracting symbols', total: 3 },
+            { target: 'extractor', level: 'warn', message: 'No symbols in src/empty.ts' },
+            {
+                level: 'info',
+                event: 'stage.finished',
+                stage: 'Extracting symbols',
+                durationMs: 1500,
+                summary: '3 files'
+            },
+            { level: 'info', event: 'stage.started', stage: 'Resolving types' },
+            { level: 'error', event: 'stage.failed', stage: 'Resolving types', durationMs: 0, reason: 'server crashed' }
+        ]);
+        expect(records(stderr)).toEqual([]);
+        expect(logger.getRunStats()).toEqual({
+            warnings: 1,
+            errors: 0,
+            phases: [
+                { name: 'Extracting symbols', durationMs: 1500 },
+                { name: 'Resolving types', durationMs: 0 }
+            ]
+        });
+    });
+
+    it('writes nothing when silent but still counts warnings and errors', () => {
+        const logger = new Logger({ format: 'json', silent: true, file: join(directory, 'lsp-cli.log') });
+
+        logger.warn('Slow language server');
+        logger.error('Request failed');
+
+        expect([...stdout, ...stderr]).toEqual([]);
+        expect(logger.getRunStats()).toMatchObject({ warnings: 1, errors: 1 });
+    });
+});
+
+describe('Log levels and filters', () => {
+    /**
+     * @param logger - Logger to check
+     * @returns The levels the logger shows
+     */
+    const shown = (logger: Logger) =>
+        (['trace', 'debug', 'info', 'warn', 'error'] as const).filter((level) => logger.enabled(level));
+
+    afterEach(() => {
+        vi.restoreAllMocks();
+    });
+
+    it('shows info and above by default, more with -v and -vv, less with -q and -qq', () => {
+        expect(shown(new Logger())).toEqual(['info', 'warn', 'error']);
+        expect(shown(new Logger({ verbose: true }))).toEqual(['debug', 'info', 'warn', 'error']);
+        expect(shown(new Logger({ verbose: 1 }))).toEqual(['debug', 'info', 'warn', 'error']);
+        expect(shown(new Logger({ verbose: 2 }))).toEqual(['trace', 'debug', 'info', 'warn', 'error']);
+        expect(shown(new Logger({ verbose: 5 }))).toEqual(['trace', 'debug', 'info', 'warn', 'error']);
+        expect(shown(new Logger({ quiet: 1 }))).toEqual(['warn', 'error']);
+        expect(shown(new Logger({ quiet: 2 }))).toEqual(['error']);
+        // Errors are never hidden by -q
+        expect(shown(new Logger({ quiet: 5 }))).toEqual(['error']);
+    });
+
+    it('parses env-filter directives', () => {
+        expect(parseLogFilter('warn,transport=trace,extractor=debug')).toEqual({
+            default: 'warn',
+            targets: { transport: 'trace', extractor: 'debug' }
+        });
+        expect(parseLogFilter(' Info , transport.stderr = OFF ,, extractor')).toEqual({
+            default: 'info',
+            targets: { 'transport.stderr': 'off', extractor: 'trace' }
+        });
+        expect(() => parseLogFilter('transport=loud')).toThrow(
+            "Unknown log level 'loud' (expected trace, debug, info, warn, error, off)"
+        );
+    });
+
+    it('applies the most specific directive of a target and its parents', () => {
+        const logger = new Logger({ filter: parseLogFilter('warn,transport=trace,transport.stderr=off') });
+
+        expect(shown(logger)).toEqual(['warn', 'error']);
+        expect(shown(logger.scoped('transport'))).toEqual(['trace', 'debug', 'info', 'warn', 'error']);
+        expect(shown(logger.scoped('transport.messages'))).toEqual(['trace', 'debug', 'info', 'warn', 'error']);
+        expect(shown(logger.scoped('transport.stderr'))).toEqual([]);
+        // Only dotted children are covered, not targets that merely start with the same name
+        expect(shown(logger.scoped('transporter'))).toEqual(['warn', 'error']);
+    });
+
+    it('uses -v and -q for targets without a directive unless the filter sets a default level', () => {
+        const quiet = new Logger({ quiet: 1, filter: parseLogFilter('extractor=debug') });
+        expect(shown(quiet)).toEqual(['warn', 'error']);
+        expect(shown(quiet.scoped('extractor'))).toEqual(['debug', 'info', 'warn', 'error']);
+
+        expect(shown(new Logger({ verbose: 2, filter: parseLogFilter('error') }))).toEqual(['error']);
+    });
+
+    it('prefixes debug and trace lines of scoped loggers with the target', () => {
+        const log = vi.spyOn(console, 'log').mockImplementation(() => {});
+        const logger = new Logger({ filter: parseLogFilter('transport=trace'), progress: false });
+
+        logger.trace('hidden');
+        logger.scoped('transport').trace('--> textDocument/definition');
+
+        expect(log).toHaveBeenCalledTimes(1);
+        expect(log.mock.calls[0].slice(1).map(String)).toEqual([
+            expect.stringContaining('transport:'),
+            '--> textDocument/definition'
+        ]);
+    });
+});
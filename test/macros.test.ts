@@ -0,0 +1,117 @@
+import { describe, expect, it } from 'vitest';
+import { attributeMacroSymbols, findMacroInvocations, symbolsFromExpansion } from '../src/macros';
+import type { SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param start - 0-based first line of the symbol
+ * @param end - 0-based last line of the symbol
+ * @param character - Column the symbol's selection starts at
+ * @returns A symbol spanning the lines
+ */
+function symbol(name: string, kind: string, start: number, end = start, character = 0): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/work/src/lib.rs',
+        range: { start: { line: start, character: 0 }, end: { line: end, character: 1 } },
+        selectionRange: { start: { line: start, character }, end: { line: start, character: character + 1 } },
+        preview: ''
+    };
+}
+
+describe('Rust macro invocations', () => {
+    it.each<[string, string[], SymbolInfo[], Array<[string, number, number]>]>([
+        ['a top-level invocation', ['create_test_data!(TEST_VALUE, i32, 42);'], [], [['create_test_data', 0, 0]]],
+        ['a path invocation', ['    serde::forward! { Item }'], [], [['serde::forward', 0, 11]]],
+        ['a macro_rules definition', ['macro_rules! log {', '    () => {};', '}'], [], []],
+        [
+            'an invocation in a function body',
+            ['fn main() {', '    println!("hi");', '}'],
+            [symbol('main', 'function', 0, 2)],
+            []
+        ],
+        [
+            'an invocation in an impl block',
+            ['impl User {', '    getter!(name);', '}'],
+            [symbol('impl User', 'object', 0, 2)],
+            [['getter', 1, 4]]
+        ],
+        ['an unclosed invocation', ['broken!(', 'fn next() {}'], [], []],
+        [
+            'a multi-line invocation with delimiters in strings',
+            ['routes! {', '    get("/)") => index,', '}', 'other!();'],
+            [],
+            [
+                ['routes', 0, 0],
+                ['other', 3, 0]
+            ]
+        ]
+    ])('finds %s', (_case, lines, symbols, expected) => {
+        const invocations = findMacroInvocations(lines, symbols);
+
+        expect(invocations.map((invocation) => [invocation.name, invocation.line, invocation.column])).toEqual(
+            expected
+        );
+    });
+
+    it('spans the invocation from the name to the closing delimiter', () => {
+        const [invocation] = findMacroInvocations(['  define! {', '    a: u8', '  } // done'], []);
+
+        expect(invocation.start).toEqual({ line: 0, column: 2 });
+        expect(invocation.end).toEqual({ line: 2, column: 3 });
+    });
+
+    it('attributes symbols inside an invocation to it', () => {
+        const lines = ['make_struct! {', '    Point', '}', 'struct Plain;'];
+        const [invocation] = findMacroInvocations(lines, []);
+        const point = symbol('Point', 'struct', 1, 1, 4);
+        const plain = symbol('Plain', 'struct', 3, 3, 7);
+
+        const attributed = attributeMacroSymbols([point, plain], [invocation], lines, 'utf-16');
+
+        expect(point.macro).toEqual({
+            name: 'make_struct',
+            invocation: { start: { line: 0, character: 0 }, end: { line: 2, character: 1 } }
+        });
+        expect(plain.macro).toBeUndefined();
+        expect(attributed.get(invocation)).toEqual([point]);
+    });
+
+    it('creates symbols for the top-level items of an expansion', () => {
+        const lines = ['// Test data', 'create_test_data!(TEST_VALUE, i32, 42);'];
+        const [invocation] = findMacroInvocations(lines, []);
+        const expansion = [
+            'pub const TEST_VALUE: i32 = 42;',
+            '#[allow(dead_code)]',
+            'pub(crate) fn generated_helper() -> i32 {',
+            '    fn nested() {}',
+            '    TEST_VALUE',
+            '}',
+            'struct Hidden;'
+        ].join('\n');
+
+        const symbols = symbolsFromExpansion(expansion, invocation, '/work/src/lib.rs', lines, 'utf-8', true);
+
+        expect(symbols.map((entry) => [entry.name, entry.kind, entry.visibility])).toEqual([
+            ['TEST_VALUE', 'constant', 'public'],
+            ['generated_helper', 'function', 'crate'],
+            ['Hidden', 'struct', 'private']
+        ]);
+        // Named in the arguments, or located at the macro name when the macro makes the name up
+        expect(symbols[0].selectionRange).toEqual({
+            start: { line: 1, character: 18 },
+            end: { line: 1, character: 28 }
+        });
+        expect(symbols[1].selectionRange).toEqual({
+            start: { line: 1, character: 0 },
+            end: { line: 1, character: 16 }
+        });
+        expect(symbols[0].macro).toEqual({
+            name: 'create_test_data',
+            invocation: { start: { line: 1, character: 0 }, end: { line: 1, character: 38 } },
+            expansion
+        });
+    });
+});
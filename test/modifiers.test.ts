@@ -0,0 +1,175 @@
+import { describe, expect, it } from 'vitest';
+import { addFunctionModifiers } from '../src/modifiers';
+import type { FunctionModifiers, SupportedLanguage, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param lines - Source lines of the symbol; the first is its preview
+ * @param start - 0-based first line of the symbol
+ * @param children - Nested symbols
+ * @returns A symbol spanning the lines
+ */
+function symbol(name: string, kind: string, lines: string[], start = 0, children?: SymbolInfo[]): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/work/source',
+        range: { start: { line: start, character: 0 }, end: { line: start + lines.length - 1, character: 1 } },
+        preview: lines[0].trim(),
+        children
+    };
+}
+
+describe('Function modifiers', () => {
+    it.each<[string, SupportedLanguage, string, string, string[], FunctionModifiers | undefined]>([
+        [
+            'an async unsafe Rust function',
+            'rust',
+            'read',
+            'function',
+            ['pub async unsafe fn read() {}'],
+            { async: true, unsafe: true }
+        ],
+        ['a const Rust function', 'rust', 'size', 'function', ['pub const fn size() -> usize {}'], { const: true }],
+        [
+            'a Rust function with an ABI',
+            'rust',
+            'callback',
+            'function',
+            ['pub extern "system" fn callback() {}'],
+            { extern: true, abi: 'system' }
+        ],
+        [
+            'a Rust extern function without ABI',
+            'rust',
+            'legacy',
+            'function',
+            ['extern fn legacy() {}'],
+            { extern: true, abi: 'C' }
+        ],
+        ['a plain Rust function', 'rust', 'plain', 'function', ['fn plain() {}'], undefined],
+        [
+            'an async TypeScript generator',
+            'typescript',
+            'stream',
+            'function',
+            ['export async function* stream() {}'],
+            { async: true, generator: true }
+        ],
+        ['a TypeScript generator method', 'typescript', 'items', 'method', ['*items() {}'], { generator: true }],
+        [
+            'a declared TypeScript function',
+            'typescript',
+            'native',
+            'function',
+            ['declare function native(): void;'],
+            { extern: true }
+        ],
+        [
+            'an async Python generator',
+            'python',
+            'fetch',
+            'function',
+            ['async def fetch():', '    yield 1'],
+            { async: true, generator: true }
+        ],
+        [
+            'a C# extern method',
+            'csharp',
+            'Beep',
+            'method',
+            ['public static extern int Beep(int frequency);'],
+            { extern: true }
+        ],
+        ['an async C# method', 'csharp', 'Load', 'method', ['public async Task Load() {}'], { async: true }],
+        [
+            'a C# iterator',
+            'csharp',
+            'Numbers',
+            'method',
+            ['public IEnumerable<int> Numbers() {', '    yield return 1;', '}'],
+            { generator: true }
+        ],
+        ['an unsafe C# method', 'csharp', 'Copy', 'method', ['unsafe void Copy(byte* source) {}'], { unsafe: true }],
+        ['a native Java method', 'java', 'poll', 'method', ['public native void poll();'], { extern: true }],
+        ['a constexpr C++ function', 'cpp', 'square', 'function', ['constexpr int square(int x) {}'], { const: true }],
+        [
+            'an extern "C" C++ function',
+            'cpp',
+            'init',
+            'function',
+            ['extern "C" void init();'],
+            { extern: true, abi: 'C' }
+        ],
+        ['an extern C function', 'c', 'shutdown', 'function', ['extern void shutdown(void);'], { extern: true }],
+        [
+            'a C++ coroutine',
+            'cpp',
+            'run',
+            'function',
+            ['Task run() {', '    co_await sleep();', '    co_yield 1;', '}'],
+            { async: true, generator: true }
+        ],
+        ['an async Dart method', 'dart', 'load', 'method', ['Future<void> load() async {}'], { async: true }],
+        [
+            'an async Dart generator',
+            'dart',
+            'count',
+            'method',
+            ['Stream<int> count() async* {}'],
+            { async: true, generator: true }
+        ],
+        [
+            'a sync Dart generator',
+            'dart',
+            'naturals',
+            'function',
+            ['Iterable<int> naturals() sync* {}'],
+            { generator: true }
+        ],
+        [
+            'an external Dart function',
+            'dart',
+            'log',
+            'function',
+            ['external void log(String message);'],
+            { extern: true }
+        ],
+        ['a const Dart constructor', 'dart', 'Point', 'constructor', ['const Point(this.x);'], { const: true }],
+        ['an extern Haxe function', 'haxe', 'trace', 'function', ['extern function trace():Void;'], { extern: true }],
+        [
+            'a field, which has no modifiers',
+            'rust',
+            'handler',
+            'field',
+            ['pub handler: unsafe extern "C" fn()'],
+            undefined
+        ]
+    ])('reads %s', (_case, language, name, kind, lines, expected) => {
+        const symbols = [symbol(name, kind, lines)];
+
+        addFunctionModifiers(symbols, lines, language);
+
+        expect(symbols[0].modifiers).toEqual(expected);
+    });
+
+    it('prefers the signature over the preview', () => {
+        const lines = ['pub', 'async fn spread() {}'];
+        const symbols = [{ ...symbol('spread', 'function', lines), signature: 'pub async fn spread()' }];
+
+        addFunctionModifiers(symbols, lines, 'rust');
+
+        expect(symbols[0].modifiers).toEqual({ async: true });
+    });
+
+    it('ignores yields in nested functions and comments', () => {
+        const lines = ['def outer():', '    def inner():', '        yield 1', '    return inner  # no yield here'];
+        const symbols = [symbol('outer', 'function', lines, 0, [symbol('inner', 'function', lines.slice(1, 3), 1)])];
+
+        addFunctionModifiers(symbols, lines, 'python');
+
+        expect(symbols[0].modifiers).toBeUndefined();
+        expect(symbols[0].children?.[0].modifiers).toEqual({ generator: true });
+    });
+});
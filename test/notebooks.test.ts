@@ -0,0 +1,132 @@
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { getNotebookLanguage, mapNotebookSymbols, readNotebook } from '../src/notebooks';
+import type { SymbolInfo } from '../src/types';
+
+const NOTEBOOK = {
+    metadata: { kernelspec: { name: 'python3', language: 'python' } },
+    cells: [
+        { cell_type: 'code', source: ['import pandas as pd\n', '%matplotlib inline'] },
+        { cell_type: 'markdown', source: ['# Loading'] },
+        {
+            cell_type: 'code',
+            source: 'class Loader:\n    def load(self, path):\n        !ls {path}\n        return pd.read_csv(path)'
+        },
+        { cell_type: 'code', source: ['def main():\n', '    Loader().load("data.csv")\n'] }
+    ]
+};
+
+describe('Notebooks', () => {
+    let directory: string;
+    let notebookPath: string;
+
+    /**
+     * @param name - Symbol name
+     * @param kind - Symbol kind
+     * @param start - First line in the virtual document
+     * @param end - Last line in the virtual document
+     * @param children - Nested symbols
+     * @returns A symbol of the virtual document
+     */
+    const symbol = (name: string, kind: string, start: number, end: number, children?: SymbolInfo[]): SymbolInfo => ({
+        name,
+        kind,
+        file: `${notebookPath}.py`,
+        range: { start: { line: start, character: 0 }, end: { line: end, character: 4 } },
+        selectionRange: { start: { line: start, character: 4 }, end: { line: start, character: 4 + name.length } },
+        preview: '',
+        children
+    });
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-notebooks-'));
+        notebookPath = join(directory, 'analysis.ipynb');
+        writeFileSync(notebookPath, JSON.stringify(NOTEBOOK));
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('takes the language from the kernel', () => {
+        expect(getNotebookLanguage(notebookPath)).toBe('python');
+        writeFileSync(notebookPath, JSON.stringify({ ...NOTEBOOK, metadata: { language_info: { name: 'R' } } }));
+        expect(getNotebookLanguage(notebookPath)).toBeUndefined();
+    });
+
+    it('concatenates the code cells and comments out magics and shell escapes', () => {
+        const document = readNotebook(notebookPath);
+
+        expect(document?.content.split('\n')).toEqual([
+            'import pandas as pd',
+            '# %matplotlib inline',
+            '',
+            'class Loader:',
+            '    def load(self, path):',
+            '#         !ls {path}',
+            '        return pd.read_csv(path)',
+            '',
+            'def main():',
+            '    Loader().load("data.csv")',
+            '',
+            ''
+        ]);
+        // Indices count the markdown cell, as in the notebook JSON
+        expect(document?.cells).toEqual([
+            { index: 0, startLine: 0, lineCount: 2 },
+            { index: 2, startLine: 3, lineCount: 4 },
+            { index: 3, startLine: 8, lineCount: 3 }
+        ]);
+    });
+
+    it('maps symbols to the notebook file and to lines of their cell', () => {
+        const document = readNotebook(notebookPath);
+        if (!document) {
+            throw new Error('Notebook not read');
+        }
+        const symbols = [
+            symbol('Loader', 'class', 3, 6, [symbol('load', 'method', 4, 6)]),
+            symbol('main', 'function', 8, 9)
+        ];
+
+        mapNotebookSymbols(symbols, document, notebookPath);
+
+        expect(symbols).toMatchObject([
+            {
+                name: 'Loader',
+                file: notebookPath,
+                cell: 2,
+                range: { start: { line: 0, character: 0 }, end: { line: 3, character: 4 } },
+                selectionRange: { start: { line: 0, character: 4 }, end: { line: 0, character: 10 } },
+                children: [
+                    {
+                        name: 'load',
+                        file: notebookPath,
+                        cell: 2,
+                        range: { start: { line: 1, character: 0 }, end: { line: 3, character: 4 } }
+                    }
+                ]
+            },
+            {
+                name: 'main',
+                file: notebookPath,
+                cell: 3,
+                range: { start: { line: 0, character: 0 }, end: { line: 1, character: 4 } }
+            }
+        ]);
+    });
+
+    it('clamps ranges reaching into the separator to the last line of the cell', () => {
+        const document = readNotebook(notebookPath);
+        if (!document) {
+            throw new Error('Notebook not read');
+        }
+        const symbols = [symbol('main', 'function', 8, 11)];
+
+        mapNotebookSymbols(symbols, document, notebookPath);
+
+        expect(symbols[0].range).toEqual({ start: { line: 0, character: 0 }, end: { line: 2, character: 0 } });
+    });
+});
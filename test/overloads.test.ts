@@ -0,0 +1,84 @@
+import { describe, expect, it } from 'vitest';
+import { addDisambiguation } from '../src/overloads';
+import type { SupportedLanguage, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param signature - Declaration header
+ * @param extra - Other properties, e.g. `children` or `impl`
+ * @returns A symbol with the signature
+ */
+function symbol(name: string, kind: string, signature?: string, extra: Partial<SymbolInfo> = {}): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/work/source',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 1 } },
+        preview: signature ?? '',
+        signature,
+        ...extra
+    };
+}
+
+describe('Overload disambiguation', () => {
+    it.each<[string, SupportedLanguage, string, number | undefined]>([
+        ['a Rust method with a receiver', 'rust', 'pub fn update(&mut self, canvas: &mut Canvas, scale: f32)', 2],
+        ['a Rust method with a lifetime receiver', 'rust', "fn update(&'a self) -> &'a str", 0],
+        ['Rust generics and closures', 'rust', 'fn update<F: Fn(u8) -> u8>(items: Vec<u8>, f: F) -> Vec<u8>', 2],
+        ['a Python method', 'python', 'def update(self, key, value=None):', 2],
+        ['a Python class method', 'python', 'def update(cls):', 0],
+        ['a C function taking void', 'c', 'int update(void)', 0],
+        ['C++ template arguments', 'cpp', 'void update(std::map<int, std::string> values, int count)', 2],
+        ['a Java method', 'java', 'public void update(String key, List<Integer> values)', 2],
+        ['TypeScript callbacks', 'typescript', 'update(handler: (a: number, b: number) => void): void', 1],
+        ['a header without parameters', 'typescript', 'update', undefined]
+    ])('counts the parameters of %s', (_case, language, signature, expected) => {
+        const symbols = [symbol('update', 'method', signature), symbol('update', 'method', 'update()')];
+
+        addDisambiguation(symbols, language);
+
+        expect(symbols[0].disambiguation?.arity).toBe(expected);
+    });
+
+    it('names the owner of same-named methods after their impl block', () => {
+        const trait = { type: 'Rectangle', trait: 'Drawable', signature: 'impl Drawable for Rectangle' };
+        const inherent = { type: 'Rectangle', signature: 'impl Rectangle' };
+        const symbols = [
+            symbol('impl Drawable for Rectangle', 'object', undefined, {
+                impl: trait,
+                children: [symbol('draw', 'method', 'fn draw(&self)')]
+            }),
+            symbol('impl Rectangle', 'object', undefined, {
+                impl: inherent,
+                children: [symbol('draw', 'method', 'fn draw(&self, color: Color)')]
+            }),
+            symbol('Canvas', 'class', undefined, { children: [symbol('draw', 'method', 'fn draw(&self)')] })
+        ];
+
+        addDisambiguation(symbols, 'rust');
+
+        const draws = symbols.map((entry) => entry.children?.[0].disambiguation);
+        expect(draws.map((entry) => [entry?.owner, entry?.arity])).toEqual([
+            ['Drawable for Rectangle', 0],
+            ['Rectangle', 1],
+            ['Canvas', 0]
+        ]);
+        expect(draws[0]?.signatureHash).toMatch(/^[0-9a-f]{8}$/);
+        expect(draws[0]?.signatureHash).toBe(draws[2]?.signatureHash);
+        expect(draws[0]?.signatureHash).not.toBe(draws[1]?.signatureHash);
+    });
+
+    it('leaves unique names and non-callables without arity', () => {
+        const symbols = [
+            symbol('unique', 'function', 'fn unique(a: u8)'),
+            symbol('Mode', 'enum', 'enum Mode'),
+            symbol('Mode', 'module', 'mod Mode')
+        ];
+
+        addDisambiguation(symbols, 'rust');
+
+        expect(symbols[0].disambiguation).toBeUndefined();
+        expect(symbols[1].disambiguation).toEqual({ signatureHash: expect.stringMatching(/^[0-9a-f]{8}$/) });
+    });
+});
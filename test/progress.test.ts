@@ -0,0 +1,135 @@
+import chalk from 'chalk';
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+import { ProgressDisplay } from '../src/progress';
+
+const CLEAR_LINE = '\r\x1b[2K';
+
+/**
+ * @param isTTY - Whether the stream is a terminal
+ * @param columns - Terminal width
+ * @returns A stream recording what is written to it
+ */
+function fakeStream(isTTY: boolean, columns = 80) {
+    const writes: string[] = [];
+    const stream = {
+        isTTY,
+        columns,
+        write: (chunk: string) => {
+            writes.push(chunk);
+            return true;
+        }
+    } as unknown as NodeJS.WriteStream;
+    return { stream, writes };
+}
+
+describe('Progress display', () => {
+    // Colors would depend on the terminal running the tests
+    const colorLevel = chalk.level;
+
+    beforeEach(() => {
+        chalk.level = 0;
+        vi.useFakeTimers();
+    });
+
+    afterEach(() => {
+        chalk.level = colorLevel;
+        vi.useRealTimers();
+    });
+
+    it('logs stage starts, every tenth of a counted stage with an ETA and stage ends without a terminal', () => {
+        const { stream, writes } = fakeStream(false);
+        const display = new ProgressDisplay({ stream });
+
+        display.start('Extracting symbols', 20);
+        for (let file = 1; file <= 20; file++) {
+            vi.advanceTimersByTime(1000);
+            display.update(file, undefined, `src/${file}.ts`);
+        }
+        vi.advanceTimersByTime(45000);
+        display.finish('20 files');
+
+        expect(writes).toHaveLength(12);
+        expect(writes.slice(0, 3)).toEqual([
+            '→ Extracting symbols (20)\n',
+            '  Extracting symbols 10% (2/20) ETA 18s\n',
+            '  Extracting symbols 20% (4/20) ETA 16s\n'
+        ]);
+        expect(writes.slice(-2)).toEqual([
+            '  Extracting symbols 100% (20/20)\n',
+            '✓ Extracting symbols: 20 files (1m05s)\n'
+        ]);
+    });
+
+    it('redraws one line in place on a terminal, at most every 100ms unless a stage completes', () => {
+        const { stream, writes } = fakeStream(true);
+        const display = new ProgressDisplay({ stream });
+        const last = () => writes[writes.length - 1];
+
+        display.start('Indexing');
+        expect(last()).toBe(`${CLEAR_LINE}⠙ Indexing 0s`);
+        vi.advanceTimersByTime(100);
+        display.setActivity('Loading workspace', 42.4);
+        expect(last()).toBe(`${CLEAR_LINE}⠹ Indexing 0s · Loading workspace 42%`);
+        vi.advanceTimersByTime(50);
+        display.setActivity(undefined);
+        expect(writes).toHaveLength(2);
+        // The spinner keeps moving while nothing is reported
+        vi.advanceTimersByTime(50);
+        expect(last()).toBe(`${CLEAR_LINE}⠸ Indexing 0s`);
+
+        display.start('Extracting symbols', 4);
+        expect(last()).toBe(`${CLEAR_LINE}Extracting symbols ${'░'.repeat(20)} 0% (0/4)`);
+        // 100ms after the last redraw by the spinner timer
+        vi.advanceTimersByTime(1100);
+        display.update(1, undefined, 'src/a.ts');
+        const bar = `${'█'.repeat(5)}${'░'.repeat(15)}`;
+        expect(last()).toBe(`${CLEAR_LINE}Extracting symbols ${bar} 25% (1/4) ETA 3s · src/a.ts`);
+        vi.advanceTimersByTime(10);
+        display.update(4);
+        expect(last()).toBe(`${CLEAR_LINE}Extracting symbols ${'█'.repeat(20)} 100% (4/4)`);
+
+        writes.length = 0;
+        display.suspend(() => stream.write('log line\n'));
+        display.finish('4 files');
+
+        expect(writes).toEqual([
+            CLEAR_LINE,
+            'log line\n',
+            `${CLEAR_LINE}Extracting symbols ${'█'.repeat(20)} 100% (4/4)`,
+            CLEAR_LINE,
+            '✓ Extracting symbols: 4 files (1s)\n'
+        ]);
+        expect(vi.getTimerCount()).toBe(0);
+    });
+
+    it('cuts the live line to the terminal width', () => {
+        const { stream, writes } = fakeStream(true, 20);
+
+        new ProgressDisplay({ stream }).start('A very long stage name');
+
+        expect(writes).toEqual([`${CLEAR_LINE}⠙ A very long stage`]);
+    });
+
+    it('reports failed stages', () => {
+        const { stream, writes } = fakeStream(false);
+        const display = new ProgressDisplay({ stream });
+
+        display.start('Installing typescript-language-server');
+        display.fail('npm exited with code 1');
+
+        expect(writes[1]).toBe('✗ Installing typescript-language-server: npm exited with code 1 (0s)\n');
+    });
+
+    it('writes nothing when disabled', () => {
+        const { stream, writes } = fakeStream(true);
+        const display = new ProgressDisplay({ stream, enabled: false });
+
+        display.start('Extracting symbols', 2);
+        display.update(1);
+        display.setActivity('Indexing');
+        display.finish();
+
+        expect(writes).toEqual([]);
+        expect(vi.getTimerCount()).toBe(0);
+    });
+});
@@ -0,0 +1,73 @@
+import { describe, expect, it } from 'vitest';
+import { ProtoMessage, ProtoWriter } from '../src/protobuf';
+
+describe('Protobuf codec', () => {
+    it('round-trips strings, integers, bools and nested messages', () => {
+        const encoded = new ProtoWriter()
+            .string(1, 'héllo')
+            .uint(2, 300)
+            .uint(3, 2 ** 40)
+            .bool(4, true)
+            .message(5, new ProtoWriter().uint(1, 7).string(2, 'nested'))
+            .finish();
+
+        const message = ProtoMessage.decode(encoded);
+
+        expect(message.string(1)).toBe('héllo');
+        expect(message.uint(2)).toBe(300);
+        expect(message.uint(3)).toBe(2 ** 40);
+        expect(message.bool(4)).toBe(true);
+        expect(message.message(5)?.uint(1)).toBe(7);
+        expect(message.message(5)?.string(2)).toBe('nested');
+    });
+
+    it('omits default values, which decode to the defaults', () => {
+        const encoded = new ProtoWriter().string(1, '').uint(2, 0).bool(3, false).message(4, undefined).finish();
+
+        const message = ProtoMessage.decode(encoded);
+
+        expect(encoded.length).toBe(0);
+        expect(message.string(1)).toBe('');
+        expect(message.uint(2)).toBe(0);
+        expect(message.bool(3)).toBe(false);
+        expect(message.message(4)).toBeUndefined();
+        expect(message.has(3)).toBe(false);
+    });
+
+    it('tells an explicit false of an optional field from an unset one', () => {
+        // Field 5, varint, value 0, as clients encode `optional bool` set to false
+        const message = ProtoMessage.decode(Buffer.from([0x28, 0x00]));
+
+        expect(message.has(5)).toBe(true);
+        expect(message.bool(5)).toBe(false);
+    });
+
+    it('encodes an empty nested message, so a 0:0 position is still sent', () => {
+        const message = ProtoMessage.decode(new ProtoWriter().message(4, new ProtoWriter().uint(1, 0)).finish());
+
+        expect(message.message(4)?.uint(1)).toBe(0);
+    });
+
+    it('takes the last value of a field sent several times', () => {
+        const encoded = Buffer.concat([
+            new ProtoWriter().string(1, 'first').finish(),
+            new ProtoWriter().string(1, 'last').finish()
+        ]);
+
+        expect(ProtoMessage.decode(encoded).string(1)).toBe('last');
+    });
+
+    it('skips fixed-width fields of other schemas', () => {
+        const fixed64 = Buffer.from([0x09, 1, 2, 3, 4, 5, 6, 7, 8]);
+        const fixed32 = Buffer.from([0x15, 1, 2, 3, 4]);
+        const encoded = Buffer.concat([fixed64, fixed32, new ProtoWriter().string(3, 'kept').finish()]);
+
+        expect(ProtoMessage.decode(encoded).string(3)).toBe('kept');
+    });
+
+    it('rejects truncated and unsupported input', () => {
+        expect(() => ProtoMessage.decode(Buffer.from([0x08, 0x80]))).toThrow(/Truncated varint/);
+        expect(() => ProtoMessage.decode(Buffer.from([0x0a, 0x05, 0x61]))).toThrow(/Truncated length-delimited/);
+        expect(() => ProtoMessage.decode(Buffer.from([0x0b]))).toThrow(/Unsupported wire type 3/);
+    });
+});
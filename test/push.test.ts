@@ -0,0 +1,183 @@
+import { execFileSync } from 'node:child_process';
+import { mkdtempSync, rmSync } from 'node:fs';
+import { createServer, type IncomingHttpHeaders, type Server } from 'node:http';
+import type { AddressInfo } from 'node:net';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { gunzipSync } from 'node:zlib';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { Logger } from '../src/logger';
+import {
+    createSnapshotPayload,
+    type DeltaSnapshotPayload,
+    getSnapshotSource,
+    pushSnapshot,
+    snapshotId
+} from '../src/push';
+import type { AnalysisOutput, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param file - File relative to /project
+ * @param preview - Declaration line
+ * @returns A function symbol
+ */
+function symbol(name: string, file: string, preview = `function ${name}() {`): SymbolInfo {
+    return {
+        name,
+        kind: 'function',
+        file: `/project/${file}`,
+        range: { start: { line: 0, character: 0 }, end: { line: 2, character: 1 } },
+        preview
+    };
+}
+
+const BASE: AnalysisOutput = {
+    language: 'typescript',
+    directory: '/project',
+    symbols: [symbol('a', 'src/a.ts'), symbol('b', 'src/b.ts'), symbol('old', 'src/old.ts')]
+};
+
+describe('Push', () => {
+    const logger = new Logger({ quiet: 2 });
+    let received: Array<{ headers: IncomingHttpHeaders; body: Buffer }>;
+    let answer: { status: number; body: string };
+    let server: Server;
+    let endpoint: string;
+
+    beforeEach(async () => {
+        received = [];
+        answer = { status: 201, body: '{"stored":true}' };
+        server = createServer((request, response) => {
+            const chunks: Buffer[] = [];
+            request.on('data', (chunk: Buffer) => chunks.push(chunk));
+            request.on('end', () => {
+                received.push({ headers: request.headers, body: Buffer.concat(chunks) });
+                response.writeHead(answer.status).end(answer.body);
+            });
+        });
+        await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+        endpoint = `http://127.0.0.1:${(server.address() as AddressInfo).port}/snapshots`;
+    });
+
+    afterEach(async () => {
+        await new Promise((resolve) => server.close(resolve));
+    });
+
+    it('builds a full payload identified by the hash of the output', () => {
+        const payload = createSnapshotPayload(BASE, { commit: 'abc123' });
+
+        expect(payload).toMatchObject({
+            format: 'lsp-cli-snapshot',
+            version: 1,
+            kind: 'full',
+            id: snapshotId(BASE),
+            language: 'typescript',
+            directory: '/project',
+            source: { commit: 'abc123' },
+            output: BASE
+        });
+        expect(snapshotId(BASE)).toMatch(/^[0-9a-f]{64}$/);
+        expect(snapshotId({ ...BASE, symbols: BASE.symbols.slice(1) })).not.toBe(snapshotId(BASE));
+    });
+
+    it('builds a delta of the changed, added and removed files against the base', () => {
+        const output: AnalysisOutput = {
+            ...BASE,
+            symbols: [
+                symbol('a', 'src/a.ts'),
+                symbol('b', 'src/b.ts', 'function b(value: number) {'),
+                symbol('c', 'src/b.ts'),
+                symbol('created', 'src/new.ts')
+            ]
+        };
+
+        const payload = createSnapshotPayload(output, {}, BASE) as DeltaSnapshotPayload;
+
+        expect(payload).toMatchObject({ kind: 'delta', id: snapshotId(output), baseId: snapshotId(BASE) });
+        expect(payload.changedFiles).toEqual({
+            'src/b.ts': [output.symbols[1], output.symbols[2]],
+            'src/new.ts': [output.symbols[3]]
+        });
+        expect(payload.removedFiles).toEqual(['src/old.ts']);
+    });
+
+    it('uploads the payload gzipped with the token and extra headers', async () => {
+        const payload = createSnapshotPayload(BASE, {});
+
+        const options = { endpoint, token: 'secret', headers: { 'X-Team': 'search' } };
+        const result = await pushSnapshot(payload, options, logger);
+
+        expect(result).toEqual({ stored: true });
+        expect(received[0].headers).toMatchObject({
+            'content-type': 'application/json',
+            'content-encoding': 'gzip',
+            authorization: 'Bearer secret',
+            'x-team': 'search'
+        });
+        expect(JSON.parse(gunzipSync(received[0].body).toString())).toEqual(payload);
+    });
+
+    it('uploads plain JSON without compression and returns non-JSON answers as text', async () => {
+        answer = { status: 200, body: 'stored' };
+        const payload = createSnapshotPayload(BASE, {});
+
+        expect(await pushSnapshot(payload, { endpoint, compress: false }, logger)).toBe('stored');
+        expect(received[0].headers['content-encoding']).toBeUndefined();
+        expect(received[0].headers.authorization).toBeUndefined();
+        expect(JSON.parse(received[0].body.toString())).toEqual(payload);
+    });
+
+    it('fails on error responses, hinting at a missing base for deltas', async () => {
+        answer = { status: 409, body: 'unknown base' };
+        const delta = createSnapshotPayload(BASE, {}, BASE) as DeltaSnapshotPayload;
+
+        await expect(pushSnapshot(delta, { endpoint }, logger)).rejects.toThrow(
+            `Endpoint returned HTTP 409 (the endpoint may not have base snapshot ${delta.baseId}; ` +
+                'push without --since): unknown base'
+        );
+        await expect(pushSnapshot(createSnapshotPayload(BASE, {}), { endpoint }, logger)).rejects.toThrow(
+            'Endpoint returned HTTP 409: unknown base'
+        );
+    });
+});
+
+describe('Snapshot source', () => {
+    let directory: string;
+
+    /**
+     * @param args - Arguments of a git command run in the test repository
+     * @returns Its trimmed output
+     */
+    const git = (...args: string[]) => execFileSync('git', args, { cwd: directory, encoding: 'utf8' }).trim();
+    const commit = () =>
+        git('-c', 'user.name=Test', '-c', 'user.email=test@example.com', 'commit', '-q', '--allow-empty', '-m', 'Init');
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-push-'));
+        git('init', '-q', '-b', 'main');
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('reads the origin, commit and branch of the repository', async () => {
+        git('remote', 'add', 'origin', 'https://example.com/project.git');
+        commit();
+
+        expect(await getSnapshotSource(directory)).toEqual({
+            repository: 'https://example.com/project.git',
+            commit: git('rev-parse', 'HEAD'),
+            branch: 'main'
+        });
+    });
+
+    it('leaves out what the repository lacks', async () => {
+        expect(await getSnapshotSource(directory)).toEqual({ branch: 'main' });
+
+        commit();
+        git('checkout', '-q', '--detach');
+        expect(await getSnapshotSource(directory)).toEqual({ commit: git('rev-parse', 'HEAD') });
+    });
+});
@@ -0,0 +1,27 @@
+import { execSync } from 'node:child_process';
+import { join } from 'node:path';
+import { describe, expect, it } from 'vitest';
+
+/**
+ * @returns Whether python3 is installed to run the bindings' tests
+ */
+function hasPython(): boolean {
+    try {
+        execSync('command -v python3', { stdio: 'pipe' });
+        return true;
+    } catch (_error) {
+        return false;
+    }
+}
+
+describe.skipIf(!hasPython())('Python bindings', () => {
+    it('pass their tests against a fake lsp-cli', () => {
+        // unittest reports on stderr
+        const output = execSync('python3 -m unittest discover -s tests 2>&1', {
+            cwd: join(process.cwd(), 'bindings', 'python'),
+            encoding: 'utf8'
+        });
+
+        expect(output).toMatch(/^OK$/m);
+    });
+});
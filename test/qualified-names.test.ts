@@ -0,0 +1,182 @@
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { dirname, join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { addQualifiedNames } from '../src/qualified-names';
+import type { SupportedLanguage, SymbolInfo } from '../src/types';
+
+// Content of the project files the module paths are derived from
+const PROJECT_FILES: Record<string, string> = {
+    'Cargo.toml': '[package]\nname = "shapes"\n',
+    'pubspec.yaml': 'name: app\n'
+};
+
+describe('Qualified names', () => {
+    let directory: string;
+
+    /**
+     * @param name - Symbol name
+     * @param kind - Symbol kind
+     * @param children - Nested symbols
+     * @param extra - Other properties, e.g. `impl`
+     * @returns A symbol on the first line
+     */
+    const symbol = (name: string, kind: string, children?: SymbolInfo[], extra: Partial<SymbolInfo> = {}) => ({
+        name,
+        kind,
+        file: '',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 1 } },
+        preview: '',
+        children,
+        ...extra
+    });
+
+    /**
+     * @param symbols - Symbols with qualified names
+     * @returns The qualified names of the symbols and their children, depth first
+     */
+    const qualifiedNames = (symbols: SymbolInfo[]): Array<string | undefined> =>
+        symbols.flatMap((entry) => [entry.qualifiedName, ...qualifiedNames(entry.children ?? [])]);
+
+    /**
+     * Creates files in the workspace, with the content of project files where they are one.
+     *
+     * @param paths - Paths relative to the workspace
+     */
+    const createFiles = (paths: string[]) => {
+        for (const path of paths) {
+            mkdirSync(dirname(join(directory, path)), { recursive: true });
+            writeFileSync(join(directory, path), PROJECT_FILES[path.split('/').pop() ?? ''] ?? '');
+        }
+    };
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-qualified-names-'));
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it.each<[string, SupportedLanguage, string[], string, string[], string[]]>([
+        ['a Rust library root', 'rust', ['Cargo.toml'], 'src/lib.rs', [], ['crate::Item', 'crate::Item::run']],
+        [
+            'a Rust mod.rs',
+            'rust',
+            ['Cargo.toml'],
+            'src/nested/mod.rs',
+            [],
+            ['crate::nested::Item', 'crate::nested::Item::run']
+        ],
+        [
+            'a nested Rust module',
+            'rust',
+            ['Cargo.toml'],
+            'src/nested/deep.rs',
+            [],
+            ['crate::nested::deep::Item', 'crate::nested::deep::Item::run']
+        ],
+        ['a Rust binary', 'rust', ['Cargo.toml'], 'src/bin/tool.rs', [], ['crate::Item', 'crate::Item::run']],
+        [
+            'a module of a Rust binary directory',
+            'rust',
+            ['Cargo.toml', 'src/bin/tool/main.rs'],
+            'src/bin/tool/cli.rs',
+            [],
+            ['crate::cli::Item', 'crate::cli::Item::run']
+        ],
+        ['a Rust integration test', 'rust', ['Cargo.toml'], 'tests/api.rs', [], ['crate::Item', 'crate::Item::run']],
+        [
+            'a module in a Python package',
+            'python',
+            ['pkg/__init__.py', 'pkg/sub/__init__.py'],
+            'pkg/sub/module.py',
+            [],
+            ['pkg.sub.module.Item', 'pkg.sub.module.Item.run']
+        ],
+        ['a Python package', 'python', ['pkg/__init__.py'], 'pkg/__init__.py', [], ['pkg.Item', 'pkg.Item.run']],
+        ['a Python script', 'python', [], 'script.py', [], ['script.Item', 'script.Item.run']],
+        [
+            'a Java class in a package',
+            'java',
+            [],
+            'src/Item.java',
+            ['// Items', 'package com.example.service;', '', 'class Item {'],
+            ['com.example.service.Item', 'com.example.service.Item.run']
+        ],
+        ['a Java class in the default package', 'java', [], 'Item.java', ['class Item {'], ['Item', 'Item.run']],
+        [
+            'a Haxe class in a package',
+            'haxe',
+            [],
+            'src/game/Item.hx',
+            ['package game;'],
+            ['game.Item', 'game.Item.run']
+        ],
+        [
+            'a TypeScript module',
+            'typescript',
+            [],
+            'src/models/user.ts',
+            [],
+            ['"src/models/user".Item', '"src/models/user".Item.run']
+        ],
+        [
+            'a TypeScript declaration file',
+            'typescript',
+            [],
+            'types/api.d.ts',
+            [],
+            ['"types/api".Item', '"types/api".Item.run']
+        ],
+        [
+            'a Dart library',
+            'dart',
+            ['pubspec.yaml'],
+            'lib/src/user.dart',
+            [],
+            ['package:app/src/user.dart.Item', 'package:app/src/user.dart.Item.run']
+        ],
+        [
+            'a Dart file outside lib',
+            'dart',
+            ['pubspec.yaml'],
+            'bin/main.dart',
+            [],
+            ['bin/main.dart.Item', 'bin/main.dart.Item.run']
+        ],
+        ['C# members', 'csharp', [], 'Item.cs', [], ['Item', 'Item.run']],
+        ['C++ members', 'cpp', [], 'item.cpp', [], ['Item', 'Item::run']],
+        ['C struct fields', 'c', [], 'item.c', [], ['Item', 'Item.run']]
+    ])('names %s', (_case, language, files, file, lines, expected) => {
+        createFiles([...files, file]);
+        const symbols = [symbol('Item', 'class', [symbol('run', 'method')])];
+
+        addQualifiedNames(symbols, join(directory, file), lines, language, directory);
+
+        expect(qualifiedNames(symbols)).toEqual(expected);
+    });
+
+    it('names Rust impl blocks after their type and trait', () => {
+        createFiles(['Cargo.toml', 'src/shapes.rs']);
+        const symbols = [
+            symbol('Rectangle', 'struct'),
+            symbol('impl Rectangle', 'object', [symbol('new', 'function')], {
+                impl: { type: 'Rectangle', signature: 'impl Rectangle' }
+            }),
+            symbol('impl Drawable for Rectangle', 'object', [symbol('draw', 'method')], {
+                impl: { type: 'Rectangle', trait: 'Drawable', signature: 'impl Drawable for Rectangle' }
+            })
+        ];
+
+        addQualifiedNames(symbols, join(directory, 'src', 'shapes.rs'), [], 'rust', directory);
+
+        expect(qualifiedNames(symbols)).toEqual([
+            'crate::shapes::Rectangle',
+            'crate::shapes::Rectangle',
+            'crate::shapes::Rectangle::new',
+            '<crate::shapes::Rectangle as Drawable>',
+            '<crate::shapes::Rectangle as Drawable>::draw'
+        ]);
+    });
+});
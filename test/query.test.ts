@@ -0,0 +1,76 @@
+import { describe, expect, it } from 'vitest';
+import { filterSymbolsByKind, querySymbols } from '../src/query';
+import type { SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name, also used as its id
+ * @param kind - Symbol kind
+ * @param children - Nested symbols, which get this symbol as their container
+ * @returns A symbol in /project/src/user.ts
+ */
+function symbol(name: string, kind: string, children?: SymbolInfo[]): SymbolInfo {
+    return {
+        name,
+        id: name,
+        kind,
+        file: '/project/src/user.ts',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } },
+        preview: '',
+        children: children?.map((child) => ({ ...child, containerId: name }))
+    };
+}
+
+const TREE = [
+    symbol('users', 'module', [
+        symbol('User', 'class', [symbol('name', 'property'), symbol('greet', 'method', [symbol('helper', 'function')])])
+    ]),
+    symbol('main', 'function')
+];
+
+/**
+ * @param symbols - Symbol tree
+ * @returns The name, container and children of each symbol, for comparing trees
+ */
+function outline(symbols: SymbolInfo[]): unknown[] {
+    return symbols.map((found) => [
+        found.name,
+        found.containerId,
+        ...(found.children ? [outline(found.children)] : [])
+    ]);
+}
+
+describe('Symbol queries', () => {
+    it('finds nested symbols by name, kind and file suffix', () => {
+        expect(querySymbols(TREE, { kind: 'method' }).map((found) => found.name)).toEqual(['greet']);
+        expect(querySymbols(TREE, { name: 'helper', file: './src/user.ts' })).toHaveLength(1);
+        expect(querySymbols(TREE, { name: 'helper', file: 'other.ts' })).toEqual([]);
+        expect(querySymbols(TREE, { file: 'user.ts' })).toEqual(TREE);
+    });
+
+    it('keeps the children of kept symbols and their containers', () => {
+        expect(outline(filterSymbolsByKind(TREE, ['module', 'class', 'method']))).toEqual([
+            ['users', undefined, [['User', 'users', [['greet', 'User']]]]]
+        ]);
+    });
+
+    it('points children of dropped symbols at the nearest kept ancestor', () => {
+        expect(outline(filterSymbolsByKind(TREE, ['module', 'method', 'function']))).toEqual([
+            ['users', undefined, [['greet', 'users', [['helper', 'greet']]]]],
+            ['main', undefined]
+        ]);
+    });
+
+    it('leaves out the container of children lifted to the top level', () => {
+        const filtered = filterSymbolsByKind(TREE, ['class', 'function']);
+
+        expect(outline(filtered)).toEqual([
+            ['User', undefined, [['helper', 'User']]],
+            ['main', undefined]
+        ]);
+        expect(filtered[0]).not.toHaveProperty('containerId');
+        expect(outline(filterSymbolsByKind(TREE, ['function']))).toEqual([
+            ['helper', undefined],
+            ['main', undefined]
+        ]);
+    });
+});
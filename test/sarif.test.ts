@@ -0,0 +1,125 @@
+import { describe, expect, it } from 'vitest';
+import { evaluateCi } from '../src/ci';
+import { ciToSarif, diagnosticsToSarif } from '../src/sarif';
+import type { AnalysisOutput, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param file - Absolute path
+ * @returns A symbol on the third line of the file
+ */
+function symbol(name: string, kind: string, file: string): SymbolInfo {
+    return {
+        name,
+        kind,
+        file,
+        range: { start: { line: 2, character: 0 }, end: { line: 4, character: 1 } },
+        preview: ''
+    };
+}
+
+const OUTPUT: AnalysisOutput = {
+    language: 'typescript',
+    directory: '/work/my project',
+    symbols: [symbol('save', 'function', '/work/my project/src/user service.ts')],
+    diagnostics: [
+        {
+            file: '/work/my project/src/user service.ts',
+            range: { start: { line: 3, character: 4 }, end: { line: 3, character: 6 } },
+            severity: 'error',
+            message: "Cannot find name 'db'.",
+            source: 'typescript',
+            code: '2304'
+        },
+        {
+            file: '/work/my project/src/index.ts',
+            range: { start: { line: 0, character: 0 }, end: { line: 0, character: 5 } },
+            severity: 'hint',
+            message: 'Unused import.'
+        }
+    ]
+};
+
+describe('SARIF output', () => {
+    it('writes a SARIF 2.1.0 log with one lsp-cli run and a rule per diagnostic source and code', () => {
+        const log = diagnosticsToSarif(OUTPUT);
+
+        expect(log.version).toBe('2.1.0');
+        expect(log.$schema).toBe('https://json.schemastore.org/sarif-2.1.0.json');
+        expect(log.runs).toHaveLength(1);
+        expect(log.runs[0].tool.driver).toEqual({
+            name: 'lsp-cli',
+            rules: [
+                { id: 'lsp', shortDescription: { text: 'Diagnostic lsp' } },
+                { id: 'typescript/2304', shortDescription: { text: 'Diagnostic typescript/2304' } }
+            ]
+        });
+        expect(log.runs[0].results.map((result) => [result.ruleId, result.level])).toEqual([
+            ['typescript/2304', 'error'],
+            ['lsp', 'note']
+        ]);
+    });
+
+    it('locates results relative to the workspace with 1-based regions', () => {
+        const run = diagnosticsToSarif(OUTPUT).runs[0];
+
+        expect(run.originalUriBaseIds).toEqual({ SRCROOT: { uri: 'file:///work/my%20project/' } });
+        expect(run.results[0].locations).toEqual([
+            {
+                physicalLocation: {
+                    artifactLocation: { uri: 'src/user%20service.ts', uriBaseId: 'SRCROOT' },
+                    region: { startLine: 4, startColumn: 5, endLine: 4, endColumn: 7 }
+                }
+            }
+        ]);
+    });
+
+    it('adds undocumented and removed symbols from CI checks under lsp-cli rules', () => {
+        const baseline: AnalysisOutput = {
+            ...OUTPUT,
+            directory: '/base',
+            symbols: [
+                symbol('save', 'function', '/base/src/user service.ts'),
+                symbol('load', 'function', '/base/src/a.ts')
+            ]
+        };
+        const summary = evaluateCi(OUTPUT, { minDocCoverage: 100, maxRemovedSymbols: 5 }, baseline);
+
+        const run = ciToSarif(summary, OUTPUT, baseline).runs[0];
+
+        expect(run.tool.driver.rules.map((rule) => rule.id)).toEqual([
+            'lsp',
+            'typescript/2304',
+            'lsp-cli/undocumented-symbol',
+            'lsp-cli/removed-symbol'
+        ]);
+        expect(run.results.slice(2)).toEqual([
+            {
+                ruleId: 'lsp-cli/undocumented-symbol',
+                level: 'warning',
+                message: { text: "function 'save' has no documentation" },
+                locations: [
+                    {
+                        physicalLocation: {
+                            artifactLocation: { uri: 'src/user%20service.ts', uriBaseId: 'SRCROOT' },
+                            region: { startLine: 3, startColumn: 1, endLine: 5, endColumn: 2 }
+                        }
+                    }
+                ]
+            },
+            {
+                ruleId: 'lsp-cli/removed-symbol',
+                level: 'note',
+                message: { text: "function 'load' was removed" },
+                locations: [{ physicalLocation: { artifactLocation: { uri: 'src/a.ts', uriBaseId: 'SRCROOT' } } }]
+            }
+        ]);
+    });
+
+    it('leaves out the lsp-cli rules without results', () => {
+        const run = ciToSarif(evaluateCi(OUTPUT, { maxErrors: 0 }), OUTPUT).runs[0];
+
+        expect(run.tool.driver.rules.map((rule) => rule.id)).toEqual(['lsp', 'typescript/2304']);
+    });
+});
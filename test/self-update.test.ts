@@ -0,0 +1,139 @@
+import { createHash, generateKeyPairSync, sign } from 'node:crypto';
+import { chmodSync, mkdtempSync, readFileSync, rmSync, statSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it, vi } from 'vitest';
+import { Logger } from '../src/logger';
+import { compareSemver, type SelfUpdateOptions, selfUpdate, verifyChecksum } from '../src/self-update';
+
+const OLD_BUNDLE = '#!/usr/bin/env node\nconsole.log("1.0.0");\n';
+const NEW_BUNDLE = Buffer.from('#!/usr/bin/env node\nconsole.log("2.0.0");\n');
+
+describe('Self-update', () => {
+    const logger = new Logger({ quiet: 2 });
+    const { publicKey, privateKey } = generateKeyPairSync('ed25519');
+    const publicPem = publicKey.export({ type: 'spki', format: 'pem' }).toString();
+    let directory: string;
+    let executable: string;
+
+    /**
+     * Serves one release v2.0.0 with the given assets in place of GitHub.
+     *
+     * @param assets - Asset content by name
+     * @returns Names of the assets downloaded
+     */
+    const serveRelease = (assets: Record<string, Buffer>): string[] => {
+        const downloaded: string[] = [];
+        vi.stubGlobal('fetch', async (url: string) => {
+            if (url.startsWith('https://api.github.com/')) {
+                const release = {
+                    tag_name: 'v2.0.0',
+                    draft: false,
+                    prerelease: false,
+                    assets: Object.keys(assets).map((name) => ({
+                        name,
+                        browser_download_url: `https://downloads.test/${name}`
+                    }))
+                };
+                return new Response(JSON.stringify([release]));
+            }
+            const name = url.split('/').pop() ?? '';
+            downloaded.push(name);
+            return assets[name] ? new Response(assets[name]) : new Response('', { status: 404 });
+        });
+        return downloaded;
+    };
+
+    /**
+     * @param bundle - Bundle the checksums are computed of
+     * @returns The checksums file and its signature by the test key
+     */
+    const signedChecksums = (bundle: Buffer) => {
+        const checksums = Buffer.from(`${createHash('sha256').update(bundle).digest('hex')}  lsp-cli.js\n`);
+        return { SHA256SUMS: checksums, 'SHA256SUMS.sig': sign(null, checksums, privateKey) };
+    };
+
+    /** Options updating the test executable from 1.0.0, verified with the test key */
+    const options = (overrides: Partial<SelfUpdateOptions> = {}): SelfUpdateOptions => ({
+        repository: 'owner/lsp-cli',
+        channel: 'stable',
+        executable,
+        currentVersion: '1.0.0',
+        publicKey: publicPem,
+        ...overrides
+    });
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-self-update-'));
+        executable = join(directory, 'lsp-cli.js');
+        writeFileSync(executable, OLD_BUNDLE);
+        chmodSync(executable, 0o755);
+    });
+
+    afterEach(() => {
+        vi.unstubAllGlobals();
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('orders versions with prereleases before their release', () => {
+        expect(compareSemver('1.10.0', '1.9.0')).toBeGreaterThan(0);
+        expect(compareSemver('1.3.0-rc.1', '1.3.0')).toBeLessThan(0);
+        expect(compareSemver('1.3.0-rc.2', '1.3.0-rc.10')).toBeLessThan(0);
+        expect(compareSemver('v1.2.3', '1.2.3')).toBe(0);
+    });
+
+    it('finds the checksum listed for an asset', () => {
+        const checksums = `${createHash('sha256').update(NEW_BUNDLE).digest('hex')} *dist/lsp-cli.js\n`;
+
+        expect(() => verifyChecksum(NEW_BUNDLE, checksums, 'lsp-cli.js')).not.toThrow();
+        expect(() => verifyChecksum(Buffer.from('other'), checksums, 'lsp-cli.js')).toThrow(/Checksum mismatch/);
+        expect(() => verifyChecksum(NEW_BUNDLE, checksums, 'other.js')).toThrow(/no checksum/);
+    });
+
+    it('replaces the executable with a signed release, keeping its permissions', async () => {
+        serveRelease({ 'lsp-cli.js': NEW_BUNDLE, ...signedChecksums(NEW_BUNDLE) });
+
+        const result = await selfUpdate(options(), logger);
+
+        expect(result).toMatchObject({ current: '1.0.0', target: '2.0.0', updated: true });
+        expect(readFileSync(executable)).toEqual(NEW_BUNDLE);
+        expect(statSync(executable).mode & 0o777).toBe(0o755);
+    });
+
+    it('refuses a bundle that does not match the checksums', async () => {
+        serveRelease({ 'lsp-cli.js': Buffer.from('#!/bin/sh\necho tampered\n'), ...signedChecksums(NEW_BUNDLE) });
+
+        await expect(selfUpdate(options(), logger)).rejects.toThrow(/Checksum mismatch/);
+        expect(readFileSync(executable, 'utf8')).toBe(OLD_BUNDLE);
+    });
+
+    it('refuses checksums signed by another key', async () => {
+        const { SHA256SUMS } = signedChecksums(NEW_BUNDLE);
+        const other = generateKeyPairSync('ed25519').privateKey;
+        serveRelease({ 'lsp-cli.js': NEW_BUNDLE, SHA256SUMS, 'SHA256SUMS.sig': sign(null, SHA256SUMS, other) });
+
+        await expect(selfUpdate(options(), logger)).rejects.toThrow(/Invalid signature/);
+        expect(readFileSync(executable, 'utf8')).toBe(OLD_BUNDLE);
+    });
+
+    it('refuses unsigned releases unless the signature check is skipped', async () => {
+        const { SHA256SUMS } = signedChecksums(NEW_BUNDLE);
+        serveRelease({ 'lsp-cli.js': NEW_BUNDLE, SHA256SUMS });
+
+        await expect(selfUpdate(options(), logger)).rejects.toThrow(/has no SHA256SUMS\.sig/);
+        expect(readFileSync(executable, 'utf8')).toBe(OLD_BUNDLE);
+
+        await selfUpdate(options({ publicKey: undefined, skipSignature: true }), logger);
+        expect(readFileSync(executable)).toEqual(NEW_BUNDLE);
+    });
+
+    it('only reports an available update with check', async () => {
+        const downloaded = serveRelease({ 'lsp-cli.js': NEW_BUNDLE, ...signedChecksums(NEW_BUNDLE) });
+
+        const result = await selfUpdate(options({ check: true }), logger);
+
+        expect(result).toMatchObject({ target: '2.0.0', updated: false });
+        expect(downloaded).toEqual([]);
+        expect(readFileSync(executable, 'utf8')).toBe(OLD_BUNDLE);
+    });
+});
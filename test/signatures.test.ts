@@ -0,0 +1,140 @@
+import { describe, expect, it } from 'vitest';
+import { extractSignature, signatureFromHover, typeFromHover } from '../src/signatures';
+import type { SupportedLanguage } from '../src/types';
+
+describe('Declaration signatures', () => {
+    it.each<[string, SupportedLanguage, string, number, string | undefined]>([
+        [
+            'a Rust impl with a multi-line where clause',
+            'rust',
+            [
+                'impl<T, U, V> ComplexGeneric<T, U, V>',
+                'where',
+                '    T: Clone + Send + Sync, // shared across threads',
+                '    U: Default,',
+                '{',
+                '    fn new() -> Self {}'
+            ].join('\n'),
+            0,
+            'impl<T, U, V> ComplexGeneric<T, U, V> where T: Clone + Send + Sync, U: Default'
+        ],
+        [
+            'a Rust function with nested generics, lifetimes and a closure bound',
+            'rust',
+            [
+                "pub fn apply<'a, F, R>(items: &'a [Vec<Option<R>>], f: F) -> HashMap<String, Vec<R>>",
+                'where',
+                '    F: Fn(&R) -> bool,',
+                '{'
+            ].join('\n'),
+            0,
+            "pub fn apply<'a, F, R>(items: &'a [Vec<Option<R>>], f: F) -> HashMap<String, Vec<R>> " +
+                'where F: Fn(&R) -> bool'
+        ],
+        [
+            'a Rust struct with default type parameters',
+            'rust',
+            'pub struct Cache<K = String, V = Vec<u8>, const N: usize = 16> {\n    entries: Vec<(K, V)>,\n}',
+            0,
+            'pub struct Cache<K = String, V = Vec<u8>, const N: usize = 16>'
+        ],
+        [
+            'a Rust trait method without a body',
+            'rust',
+            '    fn visit<V: Visitor<Item = Self>>(&self, visitor: &mut V) -> Result<(), V::Error>;',
+            0,
+            'fn visit<V: Visitor<Item = Self>>(&self, visitor: &mut V) -> Result<(), V::Error>'
+        ],
+        [
+            'a TypeScript function with a multi-line parameter list and an arrow type',
+            'typescript',
+            [
+                'export function subscribe<T extends Record<string, unknown> = {}>(',
+                '    topic: string,',
+                '    handler: (event: T) => void /* called once per event */',
+                '): () => void {'
+            ].join('\n'),
+            0,
+            'export function subscribe<T extends Record<string, unknown> = {}>( topic: string, ' +
+                'handler: (event: T) => void ): () => void'
+        ],
+        [
+            'a TypeScript class with braces in a string default',
+            'typescript',
+            "class Template<T = '{'> extends Base<Map<string, T[]>> implements Render {",
+            0,
+            "class Template<T = '{'> extends Base<Map<string, T[]>> implements Render"
+        ],
+        [
+            'a Java generic method',
+            'java',
+            '    public static <T extends Comparable<? super T>> T max(List<? extends T> items) {',
+            0,
+            'public static <T extends Comparable<? super T>> T max(List<? extends T> items)'
+        ],
+        [
+            'a C++ function with the template line above',
+            'cpp',
+            'template <typename T, typename A = std::allocator<T>>\nstd::vector<T, A> copy(const T* from, size_t n);',
+            1,
+            'template <typename T, typename A = std::allocator<T>> std::vector<T, A> copy(const T* from, size_t n)'
+        ],
+        [
+            'a Python function with a multi-line parameter list',
+            'python',
+            [
+                'def fetch(',
+                '    url: str,  # absolute',
+                '    headers: dict[str, list[str]] | None = None,',
+                ') -> tuple[int, bytes]:',
+                '    pass'
+            ].join('\n'),
+            0,
+            'def fetch( url: str, headers: dict[str, list[str]] | None = None, ) -> tuple[int, bytes]'
+        ],
+        [
+            'a Python class with a keyword argument containing a colon',
+            'python',
+            "class Config(Base, metaclass=Meta, doc='a: b'):",
+            0,
+            "class Config(Base, metaclass=Meta, doc='a: b')"
+        ],
+        ['a header that never ends', 'rust', `fn endless(\n${'    a: u8,\n'.repeat(40)}`, 0, undefined]
+    ])('reads %s', (_case, language, source, line, expected) => {
+        expect(extractSignature(source.split('\n'), line, language)).toBe(expected);
+    });
+
+    it('reads the declaration from the hover code block naming the symbol', () => {
+        const hover = [
+            '```rust',
+            'my_crate::storage',
+            '```',
+            '',
+            '```rust',
+            'pub fn load<T>(path: &Path) -> Result<T>',
+            'where',
+            '    T: DeserializeOwned,',
+            '```',
+            '',
+            'Loads a value.'
+        ].join('\n');
+
+        expect(signatureFromHover(hover, 'load', 'rust')).toBe(
+            'pub fn load<T>(path: &Path) -> Result<T> where T: DeserializeOwned'
+        );
+        expect(signatureFromHover(hover, 'save', 'rust')).toBeUndefined();
+    });
+
+    it.each([
+        ['GLOBAL_COUNTER', '```rust\nstatic GLOBAL_COUNTER: AtomicUsize = AtomicUsize::new(0)\n```', 'AtomicUsize'],
+        ['name', '```typescript\n(property) User.name?: string\n```', 'string'],
+        ['lookup', '```typescript\nconst lookup: Map<string, number[]> = new Map()\n```', 'Map<string, number[]>'],
+        ['count', '```haxe\nvar count:Int\n```', 'Int'],
+        ['CACHE', '```java\nprivate static final Map<String, List<Integer>> CACHE\n```', 'Map<String, List<Integer>>'],
+        ['Name', '```csharp\n(field) string Config.Name\n```', 'string'],
+        ['size', '### field `size`\n\n---\nType: `std::size_t`\n', 'std::size_t'],
+        ['other', '```rust\nfn helper()\n```', undefined]
+    ])('reads the type of %s from hover', (name, hover, expected) => {
+        expect(typeFromHover(hover, name)).toBe(expected);
+    });
+});
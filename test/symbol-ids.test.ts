@@ -0,0 +1,80 @@
+import { createHash } from 'node:crypto';
+import { describe, expect, it } from 'vitest';
+import { addSymbolIds } from '../src/symbol-ids';
+import type { SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param children - Nested symbols
+ * @returns A symbol on the first line
+ */
+function symbol(name: string, kind: string, children?: SymbolInfo[]): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/work/src/lib.rs',
+        range: { start: { line: 0, character: 0 }, end: { line: 0, character: 1 } },
+        preview: '',
+        children
+    };
+}
+
+/**
+ * @returns A class with a field and two overloads of a method, next to a function
+ */
+function tree(): SymbolInfo[] {
+    return [
+        symbol('Store', 'class', [symbol('size', 'field'), symbol('load', 'method'), symbol('load', 'method')]),
+        symbol('load', 'function')
+    ];
+}
+
+describe('Symbol ids', () => {
+    it('derives the id from the relative path and the kinds and names of the containers', () => {
+        const symbols = [symbol('Store', 'class', [symbol('load', 'method')])];
+
+        addSymbolIds(symbols, '/work/src/lib.rs', '/work');
+
+        const hash = (path: string) => createHash('sha256').update(path).digest('hex').slice(0, 16);
+        expect(symbols[0].id).toBe(hash('src/lib.rs\0class:Store'));
+        expect(symbols[0].children?.[0].id).toBe(hash('src/lib.rs\0class:Store\0method:load'));
+    });
+
+    it('links children to their container', () => {
+        const symbols = tree();
+
+        addSymbolIds(symbols, '/work/src/lib.rs', '/work');
+
+        expect(symbols[0].containerId).toBeUndefined();
+        expect(symbols[0].children?.map((child) => child.containerId)).toEqual([
+            symbols[0].id,
+            symbols[0].id,
+            symbols[0].id
+        ]);
+    });
+
+    it('tells apart overloads and same-named symbols of other kinds', () => {
+        const symbols = tree();
+
+        addSymbolIds(symbols, '/work/src/lib.rs', '/work');
+
+        const ids = [symbols[0], ...(symbols[0].children ?? []), symbols[1]].map((entry) => entry.id);
+        expect(new Set(ids).size).toBe(ids.length);
+        expect(ids.every((id) => /^[0-9a-f]{16}$/.test(id ?? ''))).toBe(true);
+    });
+
+    it.each<[string, string, string, boolean]>([
+        ['another run', '/work/src/lib.rs', '/work', true],
+        ['the workspace at another location', '/elsewhere/src/lib.rs', '/elsewhere', true],
+        ['another file', '/work/src/main.rs', '/work', false]
+    ])('compares the ids of %s', (_case, filePath, workspaceRoot, same) => {
+        const first = tree();
+        const second = tree();
+
+        addSymbolIds(first, '/work/src/lib.rs', '/work');
+        addSymbolIds(second, filePath, workspaceRoot);
+
+        expect(second[0].children?.[2].id === first[0].children?.[2].id).toBe(same);
+    });
+});
@@ -0,0 +1,92 @@
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { Logger } from '../src/logger';
+import { addTokenCounts, BpeTokenizer, estimateTokenizer, loadTokenizer } from '../src/tokenizer';
+import type { SymbolInfo } from '../src/types';
+
+// Word pieces with an optional leading space, as in the tiktoken encodings
+const PATTERN = ' ?\\p{L}+|\\s+';
+
+/** Writes a `.tiktoken` ranks file of the given tokens, ranked in order */
+function writeRanks(path: string, tokens: string[]): void {
+    writeFileSync(path, tokens.map((token, rank) => `${Buffer.from(token).toString('base64')} ${rank}\n`).join(''));
+}
+
+describe('Tokenizer', () => {
+    const logger = new Logger({ quiet: 2 });
+    let directory: string;
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-tokenizer-'));
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('merges the lowest-ranked pair first, as byte-pair encoding does', () => {
+        const tokenizer = new BpeTokenizer(
+            'test',
+            new Map(['a', 'b', 'c', ' ', 'ab', 'bc', 'abc'].map((token, rank) => [token, rank])),
+            PATTERN
+        );
+
+        // a b c a b -> ab c a b -> ab c ab -> abc ab; "bc" is never formed as "ab" ranks lower
+        expect(tokenizer.count('abcab')).toBe(2);
+        expect(tokenizer.count('abc')).toBe(1);
+        // "abc" and " ab", where the space has no merge
+        expect(tokenizer.count('abc ab')).toBe(3);
+        // Bytes without a rank count as one token each; é is two bytes in UTF-8
+        expect(tokenizer.count('é')).toBe(2);
+        expect(tokenizer.count('')).toBe(0);
+    });
+
+    it('reads ranks files and picks the pre-tokenization by encoding name', async () => {
+        const custom = join(directory, 'custom.tiktoken');
+        const p50k = join(directory, 'p50k_base.tiktoken');
+        writeRanks(custom, ['a', 'b', 'ab', ' ']);
+        writeRanks(p50k, ['a', 'b', 'ab', ' ']);
+
+        const tokenizer = await loadTokenizer(custom, logger);
+        expect(tokenizer.name).toBe('cl100k_base');
+        expect(tokenizer.count('ab ab')).toBe(3);
+        expect((await loadTokenizer(p50k, logger)).name).toBe('p50k_base');
+    });
+
+    it('estimates 4 characters per token and rejects unknown tokenizers', async () => {
+        expect(await loadTokenizer('estimate', logger)).toBe(estimateTokenizer);
+        expect(estimateTokenizer.count('function')).toBe(2);
+        expect(estimateTokenizer.count('fn')).toBe(1);
+        await expect(loadTokenizer('llama-3', logger)).rejects.toThrow("Unknown tokenizer 'llama-3'");
+    });
+
+    it('counts signature, documentation and body per symbol, children inside their parent', () => {
+        const file = join(directory, 'user.ts');
+        writeFileSync(file, ['class User {', '    save() {', '        return 1;', '    }', '}'].join('\n'));
+        const save: SymbolInfo = {
+            name: 'save',
+            kind: 'method',
+            file,
+            range: { start: { line: 1, character: 4 }, end: { line: 3, character: 5 } },
+            preview: 'save() {'
+        };
+        const user: SymbolInfo = {
+            name: 'User',
+            kind: 'class',
+            file,
+            range: { start: { line: 0, character: 0 }, end: { line: 4, character: 1 } },
+            preview: 'class User {',
+            documentation: 'A user.',
+            children: [save]
+        };
+
+        addTokenCounts([user], estimateTokenizer);
+
+        // 'class User {\n    save() {\n        return 1;\n    }\n}' is 51 characters
+        expect(user.tokens).toEqual({ signature: 3, documentation: 2, body: 13 });
+        // '    save() {\n        return 1;\n    }' is 36 characters
+        expect(save.tokens).toEqual({ signature: 2, body: 9 });
+    });
+});
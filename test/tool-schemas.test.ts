@@ -0,0 +1,154 @@
+import { spawnSync } from 'node:child_process';
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { formatToolSchemas, generateToolDefinitions } from '../src/tool-schemas';
+import type { AnalysisOutput, SymbolInfo } from '../src/types';
+
+const SOURCE = [
+    'export class ReportService {',
+    '    /**',
+    '     * Renders a report.',
+    '     *',
+    '     * @param title - Heading of the report',
+    '     * @param pages - Page numbers to include',
+    '     */',
+    "    render(title: string, pages: number[], format: 'pdf' | 'html' = 'pdf', draft?: boolean): string {",
+    '        return title;',
+    '    }',
+    '',
+    '    private cleanup(force: boolean): void {}',
+    '}',
+    '',
+    'export function archive(path: string, keep: number | undefined) {}',
+    ''
+].join('\n');
+
+describe('Tool schemas', () => {
+    let directory: string;
+    let output: AnalysisOutput;
+
+    /**
+     * @param name - Symbol name
+     * @param kind - Symbol kind
+     * @param lines - First and last 0-based line
+     * @param extra - Documentation and children
+     * @returns A symbol of report.ts with its first line as preview
+     */
+    const symbol = (name: string, kind: string, [start, end]: [number, number], extra: Partial<SymbolInfo> = {}) => ({
+        name,
+        kind,
+        file: join(directory, 'report.ts'),
+        range: { start: { line: start, character: 0 }, end: { line: end, character: 1 } },
+        preview: SOURCE.split('\n')[start].trim(),
+        ...extra
+    });
+
+    /**
+     * Runs the `tools` command on the analysis.
+     *
+     * @param args - Options after the analysis file
+     * @returns Exit code and standard output
+     */
+    const run = (args: string[]) => {
+        const analysisFile = join(directory, 'analysis.json');
+        writeFileSync(analysisFile, JSON.stringify(output));
+        const cliPath = join(process.cwd(), 'src', 'index.ts');
+        const result = spawnSync('npx', ['tsx', cliPath, 'tools', analysisFile, ...args], {
+            cwd: process.cwd(),
+            env: { ...process.env, XDG_CONFIG_HOME: directory },
+            encoding: 'utf-8'
+        });
+        return { status: result.status, stdout: result.stdout };
+    };
+
+    beforeEach(() => {
+        directory = mkdtempSync(join(tmpdir(), 'lsp-cli-tool-schemas-'));
+        writeFileSync(join(directory, 'report.ts'), SOURCE);
+        output = {
+            language: 'typescript',
+            directory,
+            symbols: [
+                symbol('ReportService', 'class', [0, 12], {
+                    children: [
+                        symbol('render', 'method', [7, 9], {
+                            documentation:
+                                'Renders a report.\n\n@param title - Heading of the report\n' +
+                                '@param pages - Page numbers to include'
+                        }),
+                        symbol('cleanup', 'method', [11, 11])
+                    ]
+                }),
+                symbol('archive', 'function', [14, 14])
+            ]
+        };
+    });
+
+    afterEach(() => {
+        rmSync(directory, { recursive: true, force: true });
+    });
+
+    it('derives one property per declared parameter, requiring those without default or null', () => {
+        const definitions = generateToolDefinitions(output);
+
+        expect(definitions.map((definition) => definition.name)).toEqual(['ReportService_render', 'archive']);
+        expect(definitions[0]).toEqual({
+            name: 'ReportService_render',
+            description: 'Renders a report.',
+            parameters: {
+                type: 'object',
+                properties: {
+                    title: { type: 'string', description: 'Heading of the report' },
+                    pages: { type: 'array', items: { type: 'number' }, description: 'Page numbers to include' },
+                    format: { type: 'string', enum: ['pdf', 'html'] },
+                    draft: { type: 'boolean' }
+                },
+                required: ['title', 'pages']
+            },
+            source: { file: join(directory, 'report.ts'), line: 8, qualifiedName: 'ReportService.render' }
+        });
+        expect(definitions[1].parameters).toEqual({
+            type: 'object',
+            properties: { path: { type: 'string' }, keep: { type: 'number' } },
+            required: ['path']
+        });
+    });
+
+    it('selects functions by qualified name patterns', () => {
+        const names = (include?: string[], exclude?: string[]) =>
+            generateToolDefinitions(output, { include, exclude }).map((definition) => definition.name);
+
+        expect(names(['ReportService.*'])).toEqual(['ReportService_render']);
+        expect(names(undefined, ['*.render'])).toEqual(['archive']);
+        expect(names(['*'], ['arch*'])).toEqual(['ReportService_render']);
+    });
+
+    it('wraps the definitions in the OpenAI and Anthropic request formats', () => {
+        const [definition] = generateToolDefinitions(output, { include: ['archive'] });
+        const { name, description, parameters } = definition;
+
+        expect(formatToolSchemas([definition], 'openai')).toEqual([
+            { type: 'function', function: { name, description, parameters } }
+        ]);
+        expect(formatToolSchemas([definition], 'anthropic')).toEqual([{ name, description, input_schema: parameters }]);
+    });
+
+    it('has a command option for every generator option', () => {
+        const help = run(['--help']).stdout;
+        const options = help.slice(help.indexOf('Options:')).match(/--[\w-]+/g);
+
+        // --format picks the formatToolSchemas format; --include and --exclude are the ToolSchemaOptions
+        expect(options).toEqual(['--format', '--include', '--exclude', '--help']);
+    });
+
+    it('passes the command options on to the generator', () => {
+        const result = run(['--format', 'anthropic', '--include', '*', '--exclude', 'arch*', '--include', 'archive']);
+
+        expect(result.status).toBe(0);
+        const definitions = generateToolDefinitions(output, { include: ['*', 'archive'], exclude: ['arch*'] });
+        expect(JSON.parse(result.stdout)).toEqual(formatToolSchemas(definitions, 'anthropic'));
+        expect(JSON.parse(run([]).stdout)).toEqual(formatToolSchemas(generateToolDefinitions(output), 'openai'));
+        expect(run(['--format', 'gemini']).status).toBe(2);
+    });
+});
@@ -0,0 +1,111 @@
+import { describe, expect, it } from 'vitest';
+import { addAliasTargets, expandAliases } from '../src/type-aliases';
+import type { SupportedLanguage, SymbolInfo } from '../src/types';
+
+/**
+ * @param name - Symbol name
+ * @param lines - Source lines of the symbol; the first is its preview
+ * @param file - Path of the file declaring it
+ * @returns A type alias spanning the lines
+ */
+function alias(name: string, lines: string[], file = '/work/source'): SymbolInfo {
+    return {
+        name,
+        kind: 'typeParameter',
+        file,
+        range: { start: { line: 0, character: 0 }, end: { line: lines.length - 1, character: 1 } },
+        preview: lines[0].trim()
+    };
+}
+
+/**
+ * @param name - Symbol name
+ * @param header - Declaration of the alias, with its generic parameters
+ * @param aliasOf - Target the alias was read as
+ * @param file - Path of the file declaring it
+ * @returns A type alias with `aliasOf` set
+ */
+function target(name: string, header: string, aliasOf: string, file = '/work/source'): SymbolInfo {
+    return { ...alias(name, [header], file), aliasOf };
+}
+
+describe('Type aliases', () => {
+    it.each<[string, SupportedLanguage, string, string[], string | undefined]>([
+        [
+            'a generic Rust alias',
+            'rust',
+            'ModuleResult',
+            ['pub type ModuleResult<T> = Result<T, ModuleError>;'],
+            'Result<T, ModuleError>'
+        ],
+        [
+            'a multi-line TypeScript alias',
+            'typescript',
+            'Handler',
+            ['export type Handler<T = unknown> =', '    (event: T) => Promise<void>;'],
+            '(event: T) => Promise<void>'
+        ],
+        ['a Python type statement', 'python', 'Pair', ['type Pair[T] = tuple[T, T]'], 'tuple[T, T]'],
+        ['a Python TypeAlias', 'python', 'Json', ['Json: TypeAlias = dict[str, "Json"]'], 'dict[str, "Json"]'],
+        ['a Haxe typedef', 'haxe', 'Point', ['typedef Point = { x:Int, y:Int };'], '{ x:Int, y:Int }'],
+        ['a Dart typedef', 'dart', 'Mapper', ['typedef Mapper<T> = T Function(T);'], 'T Function(T)'],
+        [
+            'a C# using alias',
+            'csharp',
+            'Ids',
+            ['using Ids = System.Collections.Generic.List<int>;'],
+            'System.Collections.Generic.List<int>'
+        ],
+        ['a C++ using alias', 'cpp', 'Map', ['template <class V> using Map = std::map<int, V>;'], 'std::map<int, V>'],
+        ['a C typedef', 'c', 'size_type', ['typedef unsigned long size_type;'], 'unsigned long'],
+        ['a C function pointer typedef', 'c', 'callback', ['typedef int (*callback)(int);'], undefined],
+        ['a C typedef of a struct body', 'c', 'Point', ['typedef struct { int x; } Point;'], undefined],
+        ['a Rust struct', 'rust', 'Config', ['pub struct Config;'], undefined],
+        ['a Java class', 'java', 'List', ['class List {}'], undefined]
+    ])('reads %s', (_case, language, name, lines, expected) => {
+        const symbols = [alias(name, lines)];
+
+        addAliasTargets(symbols, lines, language);
+
+        expect(symbols[0].aliasOf).toBe(expected);
+    });
+
+    it('expands aliases recursively, substituting generic arguments', () => {
+        const symbols = [
+            target('Id', 'type Id = Handle<u32>;', 'Handle<u32>'),
+            target('Handle', 'type Handle<T> = Arc<Mutex<T>>;', 'Arc<Mutex<T>>'),
+            target('Pairs', 'type Pairs<K, V> = Vec<(K, Handle<V>)>;', 'Vec<(K, Handle<V>)>')
+        ];
+
+        expandAliases(symbols);
+
+        expect(symbols.map((symbol) => symbol.underlyingType)).toEqual([
+            'Arc<Mutex<u32>>',
+            'Arc<Mutex<T>>',
+            'Vec<(K, Arc<Mutex<V>>)>'
+        ]);
+    });
+
+    it('stops at aliases referring to themselves', () => {
+        const symbols = [
+            target('Result', 'type Result<T> = std::result::Result<T, Error>;', 'std::result::Result<T, Error>')
+        ];
+
+        expandAliases(symbols);
+
+        expect(symbols[0].underlyingType).toBe('std::result::Result<T, Error>');
+    });
+
+    it('prefers the definition in the same file and skips ambiguous names', () => {
+        const symbols = [
+            target('Id', 'type Id = u32;', 'u32', '/work/a.rs'),
+            target('Id', 'type Id = u64;', 'u64', '/work/b.rs'),
+            target('Key', 'type Key = Id;', 'Id', '/work/a.rs'),
+            target('Other', 'type Other = Id;', 'Id', '/work/c.rs')
+        ];
+
+        expandAliases(symbols);
+
+        expect(symbols.map((symbol) => symbol.underlyingType)).toEqual(['u32', 'u64', 'u32', 'Id']);
+    });
+});
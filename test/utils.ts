@@ -2,15 +2,16 @@ import { execSync } from 'node:child_process';
 import { readFileSync } from 'node:fs';
 import { homedir } from 'node:os';
 import { join } from 'node:path';
-import type { SymbolInfo } from '../src/types';
+import type { CallGraphEdge, SymbolInfo } from '../src/types';
 
 export interface ExtractedSymbols {
     language: string;
     directory: string;
     symbols: SymbolInfo[];
+    edges?: CallGraphEdge[];
 }
 
-export function runLSPCLI(directory: string, language: string, outputFile: string): void {
+export function runLSPCLI(directory: string, language: string, outputFile: string, args: string[] = []): void {
     // Clear parso cache before Python tests to prevent cache poisoning
     if (language === 'python') {
         const parsoCachePath = join(homedir(), '.cache', 'parso');
@@ -22,7 +23,8 @@ export function runLSPCLI(directory: string, language: string, outputFile: strin
     }
 
     const cliPath = join(process.cwd(), 'src', 'index.ts');
-    execSync(`npx tsx "${cliPath}" "${directory}" ${language} "${outputFile}"`, {
+    // Cached symbols from an earlier run would hide changes to the extraction
+    execSync(`npx tsx "${cliPath}" "${directory}" ${language} "${outputFile}" --no-symbol-cache ${args.join(' ')}`, {
         stdio: 'inherit',
         cwd: process.cwd()
     });
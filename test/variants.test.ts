@@ -0,0 +1,122 @@
+import { describe, expect, it } from 'vitest';
+import type { SupportedLanguage, SymbolInfo } from '../src/types';
+import { addVariantPayloads } from '../src/variants';
+
+/**
+ * @param name - Variant name
+ * @param line - 0-based line of the variant
+ * @param extra - Other properties, e.g. `children` or `visibility`
+ * @returns An enum member starting at the line
+ */
+function variant(name: string, line: number, extra: Partial<SymbolInfo> = {}): SymbolInfo {
+    return {
+        name,
+        kind: 'enumMember',
+        file: '/work/source',
+        range: { start: { line, character: 0 }, end: { line, character: 1 } },
+        preview: '',
+        ...extra
+    };
+}
+
+/**
+ * @param symbol - Variant with payload fields
+ * @returns Name and type of the variant's fields
+ */
+function fields(symbol: SymbolInfo): Array<[string, string | undefined]> {
+    return (symbol.children ?? []).map((child) => [child.name, child.type]);
+}
+
+describe('Enum variant payloads', () => {
+    it.each<[string, SupportedLanguage, string[], Array<[string, string | undefined]>]>([
+        [
+            'a Rust tuple variant',
+            'rust',
+            ['    Inactive(String, u32),'],
+            [
+                ['0', 'String'],
+                ['1', 'u32']
+            ]
+        ],
+        [
+            'a Rust struct variant',
+            'rust',
+            ['    Pending { timestamp: u64, priority: u8 },'],
+            [
+                ['timestamp', 'u64'],
+                ['priority', 'u8']
+            ]
+        ],
+        [
+            'nested generics and function pointers',
+            'rust',
+            ['    Callback(HashMap<String, Vec<u8>>, fn(u8) -> bool),'],
+            [
+                ['0', 'HashMap<String, Vec<u8>>'],
+                ['1', 'fn(u8) -> bool']
+            ]
+        ],
+        [
+            'a multi-line struct variant with attributes and comments',
+            'rust',
+            [
+                '    Pending {',
+                '        #[serde(rename = "ts")]',
+                '        timestamp: u64, // seconds',
+                '        priority:',
+                '            u8,',
+                '    },'
+            ],
+            [
+                ['timestamp', 'u64'],
+                ['priority', 'u8']
+            ]
+        ],
+        ['a Rust unit variant', 'rust', ['    Active,'], []],
+        ['a Rust variant with a discriminant', 'rust', ['    Active = 1,'], []],
+        [
+            'a Haxe enum constructor',
+            'haxe',
+            ['    Pending(timestamp:Int, ?reason:String);'],
+            [
+                ['timestamp', 'Int'],
+                ['reason', 'String']
+            ]
+        ],
+        ['a variant of another language', 'typescript', ['    Pending(number),'], []]
+    ])('reads %s', (_case, language, lines, expected) => {
+        const symbols = [variant(lines[0].trim().match(/^\w+/)?.[0] ?? '', 0)];
+
+        addVariantPayloads(symbols, lines, language, 'utf-16');
+
+        expect(fields(symbols[0])).toEqual(expected);
+    });
+
+    it('positions the fields in the encoding of the server', () => {
+        const lines = ['    Named(é, id: u8),'];
+        const utf16 = [variant('Named', 0)];
+        const utf8 = [variant('Named', 0)];
+
+        addVariantPayloads(utf16, lines, 'rust', 'utf-16');
+        addVariantPayloads(utf8, lines, 'rust', 'utf-8');
+
+        expect(utf16[0].children?.[1].selectionRange).toEqual({
+            start: { line: 0, character: 13 },
+            end: { line: 0, character: 15 }
+        });
+        expect(utf8[0].children?.[1].selectionRange).toEqual({
+            start: { line: 0, character: 14 },
+            end: { line: 0, character: 16 }
+        });
+    });
+
+    it('gives the fields the visibility of the variant and keeps reported children', () => {
+        const reported = variant('Reported', 1, { children: [variant('existing', 1)] });
+        const symbols = [variant('Inactive', 0, { visibility: 'public' }), reported];
+
+        addVariantPayloads(symbols, ['    Inactive(String),', '    Reported(u8),'], 'rust', 'utf-16');
+
+        expect(symbols[0].children?.[0]).toMatchObject({ name: '0', kind: 'field', visibility: 'public' });
+        expect(reported.children?.map((child) => child.name)).toEqual(['existing']);
+    });
+});
@@ -0,0 +1,350 @@
+import { describe, expect, it } from 'vitest';
+import type { SupportedLanguage, SymbolInfo, Visibility } from '../src/types';
+import { addVisibility } from '../src/visibility';
+
+/**
+ * @param name - Symbol name
+ * @param kind - Symbol kind
+ * @param line - 0-based line of the declaration
+ * @param children - Nested symbols
+ * @param extra - Other properties, e.g. `impl` or `attributes`
+ * @returns A symbol starting at the line
+ */
+function symbol(
+    name: string,
+    kind: string,
+    line: number,
+    children?: SymbolInfo[],
+    extra: Partial<SymbolInfo> = {}
+): SymbolInfo {
+    return {
+        name,
+        kind,
+        file: '/work/source',
+        range: { start: { line, character: 0 }, end: { line, character: 1 } },
+        preview: '',
+        children,
+        ...extra
+    };
+}
+
+/**
+ * @param symbols - Symbols with visibility
+ * @returns Name and visibility of the symbols and their children, depth first
+ */
+function flatten(symbols: SymbolInfo[]): Array<[string, Visibility | undefined]> {
+    return symbols.flatMap((entry) => [
+        [entry.name, entry.visibility] as [string, Visibility | undefined],
+        ...flatten(entry.children ?? [])
+    ]);
+}
+
+const RUST_IMPL = { type: 'Config', trait: 'Store', signature: 'impl Store for Config' };
+
+describe('Visibility', () => {
+    it.each<[SupportedLanguage, string[], SymbolInfo[], Array<[string, Visibility | undefined]>]>([
+        [
+            'rust',
+            [
+                'pub struct Config {',
+                '    pub(crate) path: String,',
+                '    name: String,',
+                '}',
+                'pub(super) fn helper() {',
+                '    let local = 1;',
+                '}',
+                'pub(in crate::a) fn scoped() {}',
+                'fn private_fn() {}',
+                'pub trait Store {',
+                '    fn load(&self);',
+                '}',
+                'enum Mode {',
+                '    Fast,',
+                '}',
+                'impl Store for Config {',
+                '    fn load(&self) {}',
+                '}',
+                '#[macro_export]',
+                'macro_rules! log {}'
+            ],
+            [
+                symbol('Config', 'struct', 0, [symbol('path', 'field', 1), symbol('name', 'field', 2)]),
+                symbol('helper', 'function', 4, [symbol('local', 'variable', 5)]),
+                symbol('scoped', 'function', 7),
+                symbol('private_fn', 'function', 8),
+                symbol('Store', 'interface', 9, [symbol('load', 'method', 10)]),
+                symbol('Mode', 'enum', 12, [symbol('Fast', 'enumMember', 13)]),
+                symbol(
+                    'impl Store for Config',
+                    'object',
+                    15,
+                    [symbol('load', 'method', 16, undefined, { impl: RUST_IMPL })],
+                    { impl: RUST_IMPL }
+                ),
+                symbol('log', 'function', 19, undefined, { attributes: [{ name: 'macro_export' }] })
+            ],
+            [
+                ['Config', 'public'],
+                ['path', 'crate'],
+                ['name', 'private'],
+                ['helper', 'super'],
+                ['local', 'local'],
+                ['scoped', 'restricted'],
+                ['private_fn', 'private'],
+                ['Store', 'public'],
+                ['load', 'public'],
+                ['Mode', 'private'],
+                ['Fast', 'private'],
+                ['impl Store for Config', undefined],
+                ['load', 'public'],
+                ['log', 'public']
+            ]
+        ],
+        [
+            'java',
+            [
+                'public class User {',
+                '    private String name;',
+                '    int age;',
+                '    protected void save() {',
+                '        Runnable task = null;',
+                '    }',
+                '    enum Role { ADMIN }',
+                '}',
+                'interface Repo {',
+                '    void find();',
+                '}'
+            ],
+            [
+                symbol('User', 'class', 0, [
+                    symbol('name', 'field', 1),
+                    symbol('age', 'field', 2),
+                    symbol('save', 'method', 3, [symbol('task', 'variable', 4)]),
+                    symbol('Role', 'enum', 6, [symbol('ADMIN', 'enumMember', 6)])
+                ]),
+                symbol('Repo', 'interface', 8, [symbol('find', 'method', 9)])
+            ],
+            [
+                ['User', 'public'],
+                ['name', 'private'],
+                ['age', 'package'],
+                ['save', 'protected'],
+                ['task', 'local'],
+                ['Role', 'package'],
+                ['ADMIN', 'public'],
+                ['Repo', 'package'],
+                ['find', 'public']
+            ]
+        ],
+        [
+            'csharp',
+            [
+                'namespace App {',
+                '    public class Service {',
+                '        protected internal int count;',
+                '        private protected void Reset() {}',
+                '        string name;',
+                '        internal static void Log() {}',
+                '    }',
+                '    class Helper {}',
+                '    interface IStore {',
+                '        void Save();',
+                '    }',
+                '}'
+            ],
+            [
+                symbol('App', 'namespace', 0, [
+                    symbol('Service', 'class', 1, [
+                        symbol('count', 'field', 2),
+                        symbol('Reset', 'method', 3),
+                        symbol('name', 'field', 4),
+                        symbol('Log', 'method', 5)
+                    ]),
+                    symbol('Helper', 'class', 7),
+                    symbol('IStore', 'interface', 8, [symbol('Save', 'method', 9)])
+                ])
+            ],
+            [
+                ['App', 'internal'],
+                ['Service', 'public'],
+                ['count', 'protected internal'],
+                ['Reset', 'private protected'],
+                ['name', 'private'],
+                ['Log', 'internal'],
+                ['Helper', 'internal'],
+                ['IStore', 'internal'],
+                ['Save', 'public']
+            ]
+        ],
+        [
+            'typescript',
+            [
+                'export class Store {',
+                '    #cache = new Map();',
+                '    private load() {',
+                '        const result = 1;',
+                '    }',
+                '    protected save() {}',
+                '    size = 0;',
+                '}',
+                'function helper() {}',
+                'export namespace Api {',
+                '    export const version = 1;',
+                '    const secret = 2;',
+                '}'
+            ],
+            [
+                symbol('Store', 'class', 0, [
+                    symbol('#cache', 'property', 1),
+                    symbol('load', 'method', 2, [symbol('result', 'variable', 3)]),
+                    symbol('save', 'method', 5),
+                    symbol('size', 'property', 6)
+                ]),
+                symbol('helper', 'function', 8),
+                symbol('Api', 'namespace', 9, [symbol('version', 'variable', 10), symbol('secret', 'variable', 11)])
+            ],
+            [
+                ['Store', 'exported'],
+                ['#cache', 'private'],
+                ['load', 'private'],
+                ['result', 'local'],
+                ['save', 'protected'],
+                ['size', 'public'],
+                ['helper', 'local'],
+                ['Api', 'exported'],
+                ['version', 'exported'],
+                ['secret', 'local']
+            ]
+        ],
+        [
+            'cpp',
+            [
+                'class Widget {',
+                '    int id;',
+                'public:',
+                '    void draw();',
+                'protected:',
+                '    int size;',
+                '};',
+                'struct Point {',
+                '    int x;',
+                '};',
+                'static int counter;',
+                'int main() {',
+                '    int local = 0;',
+                '}'
+            ],
+            [
+                symbol('Widget', 'class', 0, [
+                    symbol('id', 'field', 1),
+                    symbol('draw', 'method', 3),
+                    symbol('size', 'field', 5)
+                ]),
+                symbol('Point', 'struct', 7, [symbol('x', 'field', 8)]),
+                symbol('counter', 'variable', 10),
+                symbol('main', 'function', 11, [symbol('local', 'variable', 12)])
+            ],
+            [
+                ['Widget', 'public'],
+                ['id', 'private'],
+                ['draw', 'public'],
+                ['size', 'protected'],
+                ['Point', 'public'],
+                ['x', 'public'],
+                ['counter', 'local'],
+                ['main', 'public'],
+                ['local', 'local']
+            ]
+        ],
+        [
+            'python',
+            [
+                'class Account:',
+                '    def __init__(self):',
+                '        self._balance = 0',
+                '    def __secret(self):',
+                '        pass',
+                '    def _internal(self):',
+                '        token = 1'
+            ],
+            [
+                symbol('Account', 'class', 0, [
+                    symbol('__init__', 'method', 1),
+                    symbol('__secret', 'method', 3),
+                    symbol('_internal', 'method', 5, [symbol('token', 'variable', 6)])
+                ])
+            ],
+            [
+                ['Account', 'public'],
+                ['__init__', 'public'],
+                ['__secret', 'private'],
+                ['_internal', 'internal'],
+                ['token', 'local']
+            ]
+        ],
+        [
+            'haxe',
+            [
+                'class Game {',
+                '    var score:Int;',
+                '    public function start() {}',
+                '}',
+                'interface Playable {',
+                '    function play():Void;',
+                '}',
+                'function main() {}'
+            ],
+            [
+                symbol('Game', 'class', 0, [symbol('score', 'field', 1), symbol('start', 'method', 2)]),
+                symbol('Playable', 'interface', 4, [symbol('play', 'method', 5)]),
+                symbol('main', 'function', 7)
+            ],
+            [
+                ['Game', 'public'],
+                ['score', 'private'],
+                ['start', 'public'],
+                ['Playable', 'public'],
+                ['play', 'public'],
+                ['main', 'public']
+            ]
+        ],
+        [
+            'dart',
+            [
+                'class _Cache {}',
+                'class Repo {',
+                '  void _reset() {}',
+                '  void load() {',
+                '    var item = 1;',
+                '  }',
+                '}'
+            ],
+            [
+                symbol('_Cache', 'class', 0),
+                symbol('Repo', 'class', 1, [
+                    symbol('_reset', 'method', 2),
+                    symbol('load', 'method', 3, [symbol('item', 'variable', 4)])
+                ])
+            ],
+            [
+                ['_Cache', 'private'],
+                ['Repo', 'public'],
+                ['_reset', 'private'],
+                ['load', 'public'],
+                ['item', 'local']
+            ]
+        ]
+    ])('applies the rules and defaults of %s', (language, lines, symbols, expected) => {
+        addVisibility(symbols, lines, language);
+
+        expect(flatten(symbols)).toEqual(expected);
+    });
+
+    it('keeps the visibility of macro-generated symbols', () => {
+        const symbols = [symbol('generated', 'function', 0, undefined, { visibility: 'public' })];
+
+        addVisibility(symbols, ['fn generated() {}'], 'rust');
+
+        expect(symbols[0].visibility).toBe('public');
+    });
+});